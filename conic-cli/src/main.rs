@@ -1,18 +1,761 @@
+use std::process::ExitCode;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use conic_core::frame::dmt::read_dmt_csv;
+use conic_core::frame::qa::RangeCheck;
+use conic_core::kernel::capabilities::capabilities;
+use conic_core::kernel::config::ERROR_INDICATORS;
+use conic_core::kernel::remote::strip_query;
+use conic_core::kernel::sink::sink_for;
+use conic_core::math::verify::check_correlations;
 use conic_core::prelude::*;
+use conic_core::report;
+use conic_core::report::markdown::MarkdownRows;
 
-fn main() -> Result<(), CoreError> {
-    let err_indicators = [-9999.0, -8888.0, -7777.0];
+mod repl;
 
-    let data = read_csv("data/sh23-101.csv")?
-        .adjust_depth(Some(0.125), None)?
-        .replace_rows(&err_indicators, &f64::NAN)?
-        .remove_rows(&[f64::NAN])?;
+/// CPTu data processing tool.
+#[derive(Parser)]
+#[command(name = "conic", disable_version_flag = true)]
+struct Cli {
+    /// Print version information.
+    #[arg(short = 'V', long)]
+    version: bool,
 
-    let out_data = data
-        .add_stress_cols(None, None, None)?
-        .add_behavior_cols(None, None)?;
+    /// With `--version`, also list the optional capabilities of this build.
+    #[arg(long, requires = "version")]
+    capabilities: bool,
 
-    println!("{:?}", out_data.head(Some(8)));
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Cleans a sounding and computes stress and behavior parameters.
+    Process {
+        /// Input file (CSV, optionally `.gz` or `.zip`).
+        input: String,
+
+        /// Output file (`.csv`, `.las` or `.parquet`). Prints a preview if
+        /// omitted.
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Directory or object store URL (`s3://bucket/prefix`, `https://…`)
+        /// receiving the processed sounding as `<sounding ID>.csv`.
+        #[arg(long)]
+        sink: Option<String>,
+
+        /// SQLite database the processed sounding is appended to.
+        #[arg(long)]
+        sqlite: Option<String>,
+
+        /// Table used with `--sqlite`.
+        #[arg(long, default_value = "soundings", requires = "sqlite")]
+        table: String,
+
+        /// CSV file the depth-discretized E' and M profiles are written to.
+        #[arg(long)]
+        modulus_profile: Option<String>,
+
+        /// Depth interval (m) used with `--modulus-profile`.
+        #[arg(long, default_value_t = 0.5, requires = "modulus_profile")]
+        interval: f64,
+
+        /// Write ASCII column names (e.g. `sigv_tot`) instead of the
+        /// configured names.
+        #[arg(long)]
+        ascii_headers: bool,
+
+        /// Write a second CSV header row with the unit of each column.
+        #[arg(long)]
+        units_row: bool,
+
+        /// Directory receiving one file per SBT layer plus a `layers.csv`
+        /// summary.
+        #[arg(long)]
+        by_layer: Option<String>,
+
+        /// File format used with `--by-layer`.
+        #[arg(
+            long,
+            value_enum,
+            default_value_t = LayerFormat::Csv,
+            requires = "by_layer"
+        )]
+        layer_format: LayerFormat,
+
+        /// CSV file receiving the records dropped while cleaning, each with
+        /// the reason of its removal.
+        #[arg(long)]
+        dropped: Option<String>,
+
+        /// Derived columns to compute. Defaults to `selection` from the
+        /// configuration.
+        #[arg(long, value_enum)]
+        select: Option<Selection>,
+
+        /// Comma-separated derived columns to compute instead of a
+        /// selection, by configuration key (e.g. `qt,ic,n60`) or name.
+        #[arg(long, value_delimiter = ',', conflicts_with = "select")]
+        columns: Vec<String>,
+    },
+
+    /// Processes several soundings in one run, each written as
+    /// `<sounding ID>.csv`.
+    Batch {
+        /// Input files or object store URLs (CSV, optionally `.gz` or
+        /// `.zip`).
+        #[arg(required = true)]
+        inputs: Vec<String>,
+
+        /// Directory or object store URL (`s3://bucket/prefix`, `https://…`)
+        /// receiving the processed soundings.
+        #[arg(short, long, default_value = ".")]
+        output: String,
+
+        /// Derived columns to compute. Defaults to `selection` from the
+        /// configuration.
+        #[arg(long, value_enum)]
+        select: Option<Selection>,
+    },
+
+    /// Processes a sounding and renders a report.
+    Report {
+        /// Input file (CSV, optionally `.gz` or `.zip`).
+        input: String,
+
+        /// Report format.
+        #[arg(short, long, value_enum, default_value_t = ReportFormat::Html)]
+        format: ReportFormat,
+
+        /// Output file. Defaults to the sounding ID with the format extension.
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// With `--format markdown`, summarize depth bins of this size (m)
+        /// instead of SBT layers.
+        #[arg(long)]
+        bin: Option<f64>,
+    },
+
+    /// Lists the SBT zone transitions of a sounding.
+    Zones {
+        /// Input file (CSV, optionally `.gz` or `.zip`).
+        input: String,
+
+        /// Output CSV file. Prints the event table if omitted.
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+
+    /// Exports sounding locations and key results, such as the LPI, the LSN
+    /// and the reconsolidation settlement, as GeoJSON.
+    Geojson {
+        /// Input files (CSV, optionally `.gz` or `.zip`).
+        #[arg(required = true)]
+        inputs: Vec<String>,
+
+        /// CSV file with `id`, `longitude` and `latitude` columns, matched
+        /// against the sounding IDs derived from the input file names.
+        #[arg(short, long)]
+        locations: String,
+
+        /// Output file.
+        #[arg(short, long, default_value = "soundings.geojson")]
+        output: String,
+    },
+
+    /// Starts an interactive session to process a sounding step by step.
+    Repl {
+        /// Job file whose commands are replayed before the session starts.
+        #[arg(long)]
+        job: Option<String>,
+    },
+
+    /// Pairs a DMT sounding with a processed CPTu sounding.
+    Dmt {
+        /// CPTu input file.
+        cpt: String,
+
+        /// DMT CSV file.
+        dmt: String,
+
+        /// Depth window (m) of CPT records averaged at each DMT depth.
+        #[arg(short, long, default_value_t = 0.2)]
+        window: f64,
+
+        /// Output CSV file. Prints the comparison table if omitted.
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+
+    /// Checks every correlation against values hand-calculated from its
+    /// equations and prints a pass/fail table.
+    CheckCorrelations,
+
+    /// Checks a sounding against the physical limits of `[qa.parameters]`
+    /// and prints the records failing each check.
+    Validate {
+        /// Input file (CSV, optionally `.gz` or `.zip`).
+        input: String,
+
+        /// Output CSV file receiving the records with their range flags.
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum LayerFormat {
+    Csv,
+    Las,
+    Parquet,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Selection {
+    Minimal,
+    Standard,
+    Full,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ReportFormat {
+    Html,
+    Pdf,
+    Markdown,
+}
+
+fn main() -> ExitCode {
+    // report errors with their Display message rather than Debug output
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<(), CoreError> {
+    #[cfg(feature = "profiling")]
+    let _flame_guard = init_profiling()?;
+
+    let cli = Cli::parse();
+
+    if cli.version {
+        print_version(cli.capabilities);
+        return Ok(());
+    }
+
+    match cli.command {
+        Some(Command::Process {
+            input, output, sink, sqlite, table, modulus_profile, interval,
+            ascii_headers, units_row, by_layer, layer_format, dropped, select,
+            columns
+        }) => {
+            let options = WriteOptions {
+                ascii_headers: ascii_headers.then_some(true),
+                units_row: units_row.then_some(true),
+                ..Default::default()
+            };
+            let extras = ExtraOutputs {
+                sink: sink.as_deref(),
+                sqlite: sqlite.as_deref().map(|path| (path, table.as_str())),
+                modulus_profile: modulus_profile
+                    .as_deref()
+                    .map(|path| (path, interval)),
+                by_layer: by_layer.as_deref().map(|dir| (dir, layer_format)),
+                dropped: dropped.as_deref(),
+            };
+            let selection = match select {
+                _ if !columns.is_empty() => Some(OutputSelection::Columns(columns)),
+                select => select.map(output_selection),
+            };
+            process(&input, output.as_deref(), selection.as_ref(), &extras, &options)
+        }
+        Some(Command::Batch { inputs, output, select }) => {
+            let selection = select.map(output_selection);
+            process_batch(&inputs, &output, selection.as_ref())
+        }
+        Some(Command::Report { input, format, output, bin }) => {
+            write_report(&input, format, output.as_deref(), bin)
+        }
+        Some(Command::Zones { input, output }) => {
+            zone_changes(&input, output.as_deref())
+        }
+        Some(Command::Geojson { inputs, locations, output }) => {
+            export_geojson(&inputs, &locations, &output)
+        }
+        Some(Command::Repl { job }) => repl::run(job.as_deref()),
+        Some(Command::Dmt { cpt, dmt, window, output }) => {
+            compare_dmt(&cpt, &dmt, window, output.as_deref())
+        }
+        Some(Command::CheckCorrelations) => print_checks(),
+        Some(Command::Validate { input, output }) => {
+            validate(&input, output.as_deref())
+        }
+        None => {
+            Cli::command().print_help()?;
+            Ok(())
+        }
+    }
+}
+
+fn print_version(with_capabilities: bool) {
+    println!("conic {}", env!("CARGO_PKG_VERSION"));
+
+    if !with_capabilities {
+        return;
+    }
+
+    for capability in capabilities() {
+        let status = if capability.available { "yes" } else { "no" };
+        println!("  {:<10} {:<4} {}", capability.name, status, capability.detail);
+    }
+}
+
+/// Runs the standard cleaning and computation pipeline on a sounding.
+///
+/// Computes the derived columns of `selection`, or of the configured
+/// selection if None.
+fn run_pipeline(
+    input: &str,
+    selection: Option<&OutputSelection>,
+) -> Result<ConicDataFrame, CoreError> {
+    let data = read_auto(input)?;
+    for warning in data.check_units()? {
+        eprintln!("Warning: {}", warning);
+    }
+
+    data.retain_dropped()
+        .clean_standard()?
+        .add_derived_cols(selection)
+}
+
+fn output_selection(select: Selection) -> OutputSelection {
+    match select {
+        Selection::Minimal => OutputSelection::Minimal,
+        Selection::Standard => OutputSelection::Standard,
+        Selection::Full => OutputSelection::Full,
+    }
+}
+
+/// Outputs of `process` written besides the main output file.
+struct ExtraOutputs<'a> {
+    /// Directory or object store URL.
+    sink: Option<&'a str>,
+    /// Database path and table.
+    sqlite: Option<(&'a str, &'a str)>,
+    /// Profile path and depth interval.
+    modulus_profile: Option<(&'a str, f64)>,
+    /// Layer directory and file format.
+    by_layer: Option<(&'a str, LayerFormat)>,
+    /// Path of the dropped records.
+    dropped: Option<&'a str>,
+}
+
+fn process(
+    input: &str,
+    output: Option<&str>,
+    selection: Option<&OutputSelection>,
+    extras: &ExtraOutputs,
+    options: &WriteOptions,
+) -> Result<(), CoreError> {
+    let out_data = run_pipeline(input, selection)?;
+
+    if let Some(location) = extras.sink {
+        let name = format!("{}.csv", sounding_id(input));
+        sink_for(location)?.write_frame(&name, &out_data, options)?;
+        println!("Wrote '{}' to '{}'", name, location);
+    }
+
+    if let Some(path) = extras.dropped {
+        out_data.write_dropped(path, options)?;
+        let rows = out_data.dropped_rows().map_or(0, |rows| rows.height());
+        println!("Wrote {} dropped records to '{}'", rows, path);
+    }
+
+    if let Some((dir, format)) = extras.by_layer {
+        let format = export_format(format)?;
+        let layers = out_data.write_by_layer(dir, format, None, options)?;
+        println!("Wrote {} layer files to '{}'", layers, dir);
+    }
+
+    if let Some((path, interval)) = extras.modulus_profile {
+        let profile = ConicDataFrame::with_metadata(
+            out_data.modulus_profile(interval)?,
+            out_data.metadata().clone(),
+        );
+        // the profile has its own depth intervals, so never bin it
+        let profile_options = WriteOptions { depth_bin: Some(0.0), ..options.clone() };
+        profile.write_csv(path, &profile_options)?;
+    }
+
+    if let Some((db_path, table)) = extras.sqlite {
+        let rows = to_sqlite(&out_data, db_path, table, &sounding_id(input), options)?;
+        println!("Appended {} rows to '{}' in '{}'", rows, table, db_path);
+    }
+
+    match output {
+        Some(path) if path.ends_with(".parquet") => {
+            write_parquet(&out_data, path, options)
+        }
+        Some(path) if path.ends_with(".las") => {
+            out_data.write_las(path, &sounding_id(input), options)
+        }
+        Some(path) => out_data.write_csv(path, options),
+        None => {
+            println!("{:?}", out_data.head(Some(8)));
+            Ok(())
+        }
+    }
+}
+
+/// Processes every input into `location`, reporting the inputs that fail
+/// without stopping the run.
+fn process_batch(
+    inputs: &[String],
+    location: &str,
+    selection: Option<&OutputSelection>,
+) -> Result<(), CoreError> {
+    let sink = sink_for(location)?;
+    let options = WriteOptions::default();
+
+    let mut failed = Vec::new();
+    for input in inputs {
+        let name = format!("{}.csv", sounding_id(input));
+        let written = run_pipeline(input, selection)
+            .and_then(|data| sink.write_frame(&name, &data, &options));
+        match written {
+            Ok(()) => println!("Wrote '{}' to '{}'", name, location),
+            Err(err) => {
+                eprintln!("Error: '{}': {}", input, err);
+                failed.push(input.as_str());
+            }
+        }
+    }
+
+    if !failed.is_empty() {
+        return Err(CoreError::InvalidData(format!(
+            "{} of {} inputs failed: {}",
+            failed.len(),
+            inputs.len(),
+            failed.join(", ")
+        )));
+    }
+    println!("Processed {} soundings", inputs.len());
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "parquet")]
+fn write_parquet(
+    data: &ConicDataFrame,
+    path: &str,
+    options: &WriteOptions,
+) -> Result<(), CoreError> {
+    data.write_parquet(path, options)
+}
+
+#[cfg(not(feature = "parquet"))]
+fn write_parquet(
+    _data: &ConicDataFrame,
+    path: &str,
+    _options: &WriteOptions,
+) -> Result<(), CoreError> {
+    Err(CoreError::InvalidData(format!(
+        "Cannot write '{}': this build has no Parquet support \
+         (see `conic --version --capabilities`)",
+        path
+    )))
+}
+
+fn export_format(format: LayerFormat) -> Result<ExportFormat, CoreError> {
+    match format {
+        LayerFormat::Csv => Ok(ExportFormat::Csv),
+        LayerFormat::Las => Ok(ExportFormat::Las),
+        #[cfg(feature = "parquet")]
+        LayerFormat::Parquet => Ok(ExportFormat::Parquet),
+        #[cfg(not(feature = "parquet"))]
+        LayerFormat::Parquet => Err(CoreError::InvalidData(
+            "Cannot write Parquet layer files: this build has no Parquet support \
+             (see `conic --version --capabilities`)"
+                .to_string()
+        )),
+    }
+}
+
+fn write_report(
+    input: &str,
+    format: ReportFormat,
+    output: Option<&str>,
+    bin: Option<f64>,
+) -> Result<(), CoreError> {
+    let out_data = run_pipeline(input, Some(&OutputSelection::Full))?;
+    let id = sounding_id(input);
+
+    let extension = match format {
+        ReportFormat::Html => "html",
+        ReportFormat::Pdf => "pdf",
+        ReportFormat::Markdown => "md",
+    };
+    let path = output
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{}.{}", id, extension));
+
+    match format {
+        ReportFormat::Html => report::html::write_html(&out_data, &path, &id)?,
+        ReportFormat::Pdf => write_pdf(&out_data, &path, &id)?,
+        ReportFormat::Markdown => {
+            let rows = match bin {
+                Some(step) => MarkdownRows::DepthBins(step),
+                None => MarkdownRows::Layers,
+            };
+            report::markdown::write_markdown(&out_data, &path, rows)?
+        }
+    }
+
+    println!("Wrote report to '{}'", path);
+    Ok(())
+}
+
+#[cfg(feature = "pdf")]
+fn write_pdf(data: &ConicDataFrame, path: &str, id: &str) -> Result<(), CoreError> {
+    report::pdf::write_pdf(data, path, id)
+}
+
+#[cfg(not(feature = "pdf"))]
+fn write_pdf(_data: &ConicDataFrame, path: &str, _id: &str) -> Result<(), CoreError> {
+    Err(CoreError::InvalidData(format!(
+        "Cannot write '{}': this build has no PDF support \
+         (see `conic --version --capabilities`)",
+        path
+    )))
+}
+
+fn zone_changes(input: &str, output: Option<&str>) -> Result<(), CoreError> {
+    let out_data = run_pipeline(input, Some(&OutputSelection::Full))?;
+    let changes = ConicDataFrame::with_metadata(
+        out_data.zone_changes()?,
+        out_data.metadata().clone(),
+    );
+
+    match output {
+        Some(path) => changes.write_csv(path, &WriteOptions::default()),
+        None => {
+            println!("{:?}", changes.inner());
+            Ok(())
+        }
+    }
+}
+
+fn export_geojson(
+    inputs: &[String],
+    locations: &str,
+    output: &str,
+) -> Result<(), CoreError> {
+    let locations = report::geojson::read_locations(locations)?;
+
+    let mut soundings = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        let id = sounding_id(input);
+        let mut data = run_pipeline(input, Some(&OutputSelection::Full))?;
+        match locations.get(&id) {
+            Some(&location) => data = data.with_location(location),
+            None => eprintln!("Warning: no location for sounding '{}'", id),
+        }
+        soundings.push((id, data));
+    }
+
+    let soundings: Vec<(&str, &ConicDataFrame)> = soundings
+        .iter()
+        .map(|(id, data)| (id.as_str(), data))
+        .collect();
+    report::geojson::write_geojson(&soundings, output)?;
+
+    for (id, data) in &soundings {
+        println!(
+            "  {:<20} LPI {:.1}  LSN {:.1}  settlement {:.1} mm",
+            id,
+            data.liquefaction_potential()?,
+            data.liquefaction_severity()?,
+            data.liquefaction_settlement()?
+        );
+    }
+    println!("Wrote {} soundings to '{}'", soundings.len(), output);
+    Ok(())
+}
+
+fn compare_dmt(
+    cpt: &str,
+    dmt: &str,
+    window: f64,
+    output: Option<&str>,
+) -> Result<(), CoreError> {
+    let cpt_data = run_pipeline(cpt, Some(&OutputSelection::Full))?;
+    let dmt_data = read_dmt_csv(dmt)?;
+
+    let comparison = ConicDataFrame::with_metadata(
+        cpt_data.align_dmt(&dmt_data, window)?,
+        cpt_data.metadata().clone(),
+    );
+
+    match output {
+        Some(path) => comparison.write_csv(path, &WriteOptions::default()),
+        None => {
+            println!("{:?}", comparison.inner());
+            Ok(())
+        }
+    }
+}
+
+// formats a checked value, in scientific notation when too small for six
+// decimal places
+fn format_value(value: f64) -> String {
+    if value != 0.0 && value.abs() < 1e-3 {
+        format!("{:.4e}", value)
+    } else {
+        format!("{:.6}", value)
+    }
+}
+
+/// Prints the correlation checks, failing if any does not pass.
+fn print_checks() -> Result<(), CoreError> {
+    let checks = check_correlations()?;
+
+    println!(
+        "{:<20} {:<17} {:<26} {:>12} {:>12}  Result",
+        "Quantity", "Case", "Reference", "Expected", "Computed"
+    );
+    for check in &checks {
+        println!(
+            "{:<20} {:<17} {:<26} {:>12} {:>12}  {}",
+            check.quantity,
+            check.case,
+            check.reference,
+            format_value(check.expected),
+            format_value(check.computed),
+            if check.passed() { "pass" } else { "FAIL" }
+        );
+    }
+
+    let failed = checks.iter().filter(|check| !check.passed()).count();
+    if failed > 0 {
+        return Err(CoreError::InvalidData(format!(
+            "{} of {} correlation checks failed",
+            failed,
+            checks.len()
+        )));
+    }
+    println!("All {} correlation checks passed", checks.len());
+
+    Ok(())
+}
+
+/// Prints the range check summary of a sounding, failing if any record is
+/// outside the physical limits.
+///
+/// Error indicators are replaced with NaN first, so they are not reported as
+/// negative readings.
+fn validate(input: &str, output: Option<&str>) -> Result<(), CoreError> {
+    let data = read_auto(input)?
+        .replace_rows(&ERROR_INDICATORS, &f64::NAN, None)?
+        .validate_ranges(None)?;
+    let summary = data.range_summary()?;
+
+    println!("{:<4} {:<26} {:>8} {:>12}", "Bit", "Check", "Records", "First (m)");
+    for violations in &summary {
+        let first = violations
+            .first_depth
+            .map_or_else(|| "-".to_string(), |depth| format!("{:.3}", depth));
+        println!(
+            "{:<4} {:<26} {:>8} {:>12}",
+            violations.check.bit(),
+            violations.check.description(),
+            violations.count,
+            first
+        );
+    }
+
+    if let Some(path) = output {
+        data.write_csv(path, &WriteOptions::default())?;
+        println!("Wrote range flags to '{}'", path);
+    }
+
+    let failed: Vec<&str> = summary
+        .iter()
+        .filter(|violations| violations.count > 0)
+        .map(|violations| violations.check.description())
+        .collect();
+    if !failed.is_empty() {
+        return Err(CoreError::InvalidData(format!(
+            "Records outside physical limits: {}",
+            failed.join(", ")
+        )));
+    }
+    println!(
+        "All {} records passed {} range checks",
+        data.height(),
+        RangeCheck::ALL.len()
+    );
+
+    Ok(())
+}
+
+/// Derives a sounding identifier from the input file name, ignoring the
+/// query of a URL.
+fn sounding_id(input: &str) -> String {
+    let input = strip_query(input);
+    let file_name = std::path::Path::new(input)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| input.to_string());
+
+    match file_name.split_once('.') {
+        Some((stem, _)) => stem.to_string(),
+        None => file_name,
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn to_sqlite(
+    data: &ConicDataFrame,
+    db_path: &str,
+    table: &str,
+    sounding_id: &str,
+    options: &WriteOptions,
+) -> Result<usize, CoreError> {
+    data.to_sqlite(db_path, table, sounding_id, options)
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn to_sqlite(
+    _data: &ConicDataFrame,
+    db_path: &str,
+    _table: &str,
+    _sounding_id: &str,
+    _options: &WriteOptions,
+) -> Result<usize, CoreError> {
+    Err(CoreError::InvalidData(format!(
+        "Cannot write '{}': this build has no SQLite support \
+         (see `conic --version --capabilities`)",
+        db_path
+    )))
+}
+
+/// Records pipeline spans into `conic.folded`, a folded-stack file that can
+/// be rendered with `inferno-flamegraph`. Spans are flushed when the returned
+/// guard is dropped.
+#[cfg(feature = "profiling")]
+fn init_profiling() -> Result<impl Drop, CoreError> {
+    use tracing_subscriber::prelude::*;
+
+    let (flame_layer, guard) = tracing_flame::FlameLayer::with_file("conic.folded")
+        .map_err(|err| std::io::Error::other(err.to_string()))?;
+
+    tracing_subscriber::registry().with(flame_layer).init();
+
+    Ok(guard)
+}
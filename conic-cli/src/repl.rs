@@ -0,0 +1,588 @@
+use std::io::{self, BufRead, Write};
+use conic_core::frame::clean::RodChangeOptions;
+use conic_core::frame::fix::{Baseline, ZeroReadings};
+use conic_core::kernel::config::{ERROR_INDICATORS, SG_ORDER, TRIM_RATIO};
+use conic_core::design::settlement::{
+    Footing, consolidation_settlement, schmertmann_settlement
+};
+use conic_core::math::sbt::sbt_layers;
+use conic_core::math::liquefaction::{CsrOptions, TriggeringMethod};
+use conic_core::math::thin_layer::ThinLayerOptions;
+use conic_core::prelude::*;
+use conic_core::report;
+
+const HELP: &str = "\
+Commands:
+  load <file>                 read a sounding (starts a new job)
+  trim [min] [max]            keep records within a depth range (`auto`
+                              leaves an end open)
+  trim-empty [tolerance]      drop empty records at the top and bottom
+  refusal [capacity] [action] detect the refusal at the end of the push;
+                              action: detect, truncate
+  resample <spacing>          interpolate onto a regular depth grid
+  downsample <interval> [aggregation]
+                              aggregate records into depth intervals;
+                              aggregation: mean, median
+  baseline <channel> <before> <after> ...
+                              remove linear zero drift of fs and/or u2
+                              from zero readings before and after the push
+  seating [length] [action]   drop the seating records of each push start;
+                              action: remove, mask
+  rod-changes [action]        detect rod change dips; action: flag,
+                              interpolate
+  fill-gaps [spacing]         insert NaN rows in depth gaps (before adjust)
+  merge-pushes [strategy]     merge pushes restarted at a shallower depth;
+                              strategy: keep_second, average
+  monotonic [policy]          handle decreasing depths; policy: error, drop,
+                              sort
+  dedup [strategy]            collapse repeated depths; strategy: keep_first,
+                              keep_last, average
+  adjust [start] [spacing]    adjust depths (`auto` infers a value)
+  clean [indicators...]       replace error indicators (default
+                              error_indicators) and drop incomplete records
+  interpolate [max_gap] [indicators...]
+                              interpolate short runs of error indicators
+                              (default error_indicators)
+  despike [window] [sigmas]   remove isolated qc, fs and u2 spikes
+  flag [indicators...]        flag error indicators (default
+                              error_indicators), spikes and out-of-range records
+                              without changing any value
+  qa                          flag clogging, desaturation and saturation loss
+  smooth [rolling] [kernel]   compute stresses with qt and fs smoothed over
+                              1, 3 or 5 samples; kernel: mean, median,
+                              trimmed_mean, winsorized_mean, savitzky_golay
+  gamma                       estimate γ from qt and Rf (after smooth) and
+                              recompute the stresses from it with the
+                              configured smoothing
+  classify [method]           compute behavior, robertson (default, SBTn) or
+                              schneider zones, group, Qtn,cs and FC
+                              columns, list SBT layers
+  isbt                        add the non-normalized ISBT and its SBT zone
+  correlate                   add N60, E', M, mv, Cc/(1+e0), Vs, G0, su,
+                              St, φ', Dr, ψ, σ'p, OCR, K0, k and cv columns
+  thin-layer [diameter]       correct qt of thin stiff layers (after classify);
+                              cone diameter in mm
+  liquefaction [pga] [mw] [method]
+                              add CSR and the CRR7.5 and FS liq of method:
+                              robertson_wride (default, with the liquefiable
+                              and thin layer flags, εv, the settlement,
+                              the LPI and the LSN) or
+                              boulanger_idriss (after classify, optionally
+                              thin-layer)
+  lateral-spread slope <percent> | face <height> <distance>
+                              add γmax and estimate the lateral displacement
+                              of sloping ground or near a free face, in m
+                              (after correlate and liquefaction)
+  settlement fill <pressure> | footing <pressure> <width> <length> [depth]
+                              1D consolidation settlement per SBT layer of a
+                              fill or of a footing (kPa, m) below its center
+                              (after correlate)
+  schmertmann <pressure> <width> <length> [depth] [years]
+                              settlement of a footing on sand (kPa, m) by
+                              the strain influence method over qt
+  show [rows]                 print the first rows (default 8)
+  plot <file.html>            write the HTML report
+  write <file>                write the data (.csv, .las or .parquet)
+  undo                        revert the last step
+  history                     list the steps of the current job
+  save-job <file>             save the steps as a job file
+  help                        show this help
+  quit                        leave the session";
+
+/// Outcome of a REPL command.
+enum Flow {
+    Continue,
+    Quit,
+}
+
+/// Interactive session on one sounding.
+///
+/// Every step that changes the data or writes a file is kept in the
+/// history, which `save-job` writes as a job file: one command per line,
+/// replayable with `conic repl --job <file>`.
+#[derive(Default)]
+struct Session {
+    id: String,
+    // data after each step, the last one being the current state
+    states: Vec<ConicDataFrame>,
+    history: Vec<String>,
+}
+
+/// Runs the REPL, first replaying the commands of `job` if given.
+///
+/// Errors in a job abort the session; errors of interactive commands are
+/// printed and the session continues.
+pub(crate) fn run(job: Option<&str>) -> Result<(), CoreError> {
+    let mut session = Session::default();
+
+    if let Some(path) = job {
+        for line in std::fs::read_to_string(path)?.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            println!("conic> {}", line);
+            if let Flow::Quit = session.execute(line)? {
+                return Ok(());
+            }
+        }
+    }
+
+    println!("conic {} (type `help` for commands)", env!("CARGO_PKG_VERSION"));
+    let stdin = io::stdin();
+
+    loop {
+        print!("conic> ");
+        io::stdout().flush()?;
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            println!();
+            return Ok(());
+        }
+
+        match session.execute(line.trim()) {
+            Ok(Flow::Continue) => {}
+            Ok(Flow::Quit) => return Ok(()),
+            Err(err) => eprintln!("Error: {}", err),
+        }
+    }
+}
+
+impl Session {
+    fn execute(&mut self, line: &str) -> Result<Flow, CoreError> {
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else { return Ok(Flow::Continue) };
+        let args: Vec<&str> = words.collect();
+
+        match command {
+            "load" => {
+                let [path] = args[..] else { return Err(usage("load <file>")) };
+                self.id = crate::sounding_id(path);
+                self.states = vec![read_auto(path)?];
+                self.history = vec![line.to_string()];
+                println!("Loaded '{}' ({} records)", self.id, self.current()?.height());
+                for warning in self.current()?.check_units()? {
+                    println!("Warning: {}", warning);
+                }
+            }
+            "trim" => {
+                let min_depth = parse_auto(args.first())?;
+                let max_depth = parse_auto(args.get(1))?;
+                self.step(line, |data| data.trim_depth(min_depth, max_depth))?;
+            }
+            "trim-empty" => {
+                let tolerance = args.first().map(|arg| parse(arg)).transpose()?;
+                self.step(line, |data| data.trim_empty_rows(tolerance))?;
+            }
+            "refusal" => {
+                let capacity = parse_auto(args.first())?;
+                let action = args
+                    .get(1)
+                    .map(|name| Refusal::from_config(name))
+                    .transpose()?;
+                self.step(line, |data| data.detect_refusal(capacity, None, action))?;
+            }
+            "resample" => {
+                let [spacing] = args[..] else {
+                    return Err(usage("resample <spacing>"));
+                };
+                let spacing = parse(spacing)?;
+                self.step(line, |data| data.resample(spacing))?;
+            }
+            "downsample" => {
+                let Some(interval) = args.first() else {
+                    return Err(usage("downsample <interval> [aggregation]"));
+                };
+                let interval = parse(interval)?;
+                let aggregation = args
+                    .get(1)
+                    .map(|name| Aggregation::from_config(name))
+                    .transpose()?;
+                self.step(line, |data| data.downsample(interval, aggregation))?;
+            }
+            "baseline" => {
+                let syntax = "baseline <fs|u2> <before> <after> \
+                              [<fs|u2> <before> <after>]";
+                if args.is_empty() || !args.len().is_multiple_of(3) {
+                    return Err(usage(syntax));
+                }
+                let mut baseline = Baseline::default();
+                for entry in args.chunks(3) {
+                    let readings = ZeroReadings {
+                        before: parse(entry[1])?,
+                        after: parse(entry[2])?,
+                    };
+                    match entry[0] {
+                        "fs" => baseline.fs = Some(readings),
+                        "u2" => baseline.u2 = Some(readings),
+                        _ => return Err(usage(syntax)),
+                    }
+                }
+                self.step(line, |data| data.baseline_correct(&baseline))?;
+            }
+            "seating" => {
+                let length = parse_auto(args.first())?;
+                let action = args
+                    .get(1)
+                    .map(|name| SeatingArtifacts::from_config(name))
+                    .transpose()?;
+                self.step(line, |data| {
+                    data.remove_seating_artifacts(length, None, action)
+                })?;
+            }
+            "rod-changes" => {
+                let options = RodChangeOptions {
+                    action: args
+                        .first()
+                        .map(|name| RodChanges::from_config(name))
+                        .transpose()?,
+                    ..Default::default()
+                };
+                self.step(line, |data| data.clean_rod_changes(&options))?;
+            }
+            "fill-gaps" => {
+                let spacing = parse_auto(args.first())?;
+                self.step(line, |data| data.fill_depth_gaps(spacing))?;
+            }
+            "merge-pushes" => {
+                let strategy = args
+                    .first()
+                    .map(|name| Repush::from_config(name))
+                    .transpose()?;
+                self.step(line, |data| data.merge_repushes(strategy))?;
+            }
+            "monotonic" => {
+                let policy = args
+                    .first()
+                    .map(|name| NonMonotonicDepth::from_config(name))
+                    .transpose()?;
+                self.step(line, |data| data.ensure_monotonic_depth(policy))?;
+            }
+            "dedup" => {
+                let strategy = args
+                    .first()
+                    .map(|name| DuplicateDepths::from_config(name))
+                    .transpose()?;
+                let rows = self.current()?.height();
+                self.step(line, |data| data.resolve_duplicate_depths(strategy))?;
+                println!("Collapsed {} records", rows - self.current()?.height());
+            }
+            "adjust" => {
+                let start = parse_auto(args.first())?;
+                let spacing = parse_auto(args.get(1))?;
+                self.step(line, |data| data.adjust_depth(start, spacing))?;
+            }
+            "clean" => {
+                let indicators: Vec<f64> = if args.is_empty() {
+                    ERROR_INDICATORS.clone()
+                } else {
+                    args.iter().map(|arg| parse(arg)).collect::<Result<_, _>>()?
+                };
+                self.step(line, |data| {
+                    data.replace_rows(&indicators, &f64::NAN, None)?
+                        .drop_nan(None)
+                })?;
+            }
+            "interpolate" => {
+                let max_gap = args.first().map(|arg| parse(arg)).transpose()?;
+                let indicators: Vec<f64> = if args.len() < 2 {
+                    ERROR_INDICATORS.clone()
+                } else {
+                    args[1..].iter().map(|arg| parse(arg)).collect::<Result<_, _>>()?
+                };
+                self.step(line, |data| data.interpolate_rows(&indicators, max_gap))?;
+            }
+            "despike" => {
+                let window = args.first().map(|arg| parse(arg)).transpose()?;
+                let n_sigmas = args.get(1).map(|arg| parse(arg)).transpose()?;
+                self.step(line, |data| data.despike(window, n_sigmas))?;
+            }
+            "flag" => {
+                let indicators: Vec<f64> = if args.is_empty() {
+                    ERROR_INDICATORS.clone()
+                } else {
+                    args.iter().map(|arg| parse(arg)).collect::<Result<_, _>>()?
+                };
+                self.step(line, |data| {
+                    data.flag_indicators(&indicators, None)?
+                        .flag_spikes(None, None)?
+                        .validate_ranges(None)
+                })?;
+            }
+            "qa" => {
+                self.step(line, |data| {
+                    data.flag_clogging(None, None, None, None)?
+                        .flag_desaturation(None, None, None)?
+                        .flag_saturation_loss(None, None, None)
+                })?;
+            }
+            "smooth" => {
+                let rolling = args.first().map(|arg| parse(arg)).transpose()?;
+                let smoothing = args
+                    .get(1)
+                    .map(|name| Smoothing::from_config(name, *TRIM_RATIO, *SG_ORDER))
+                    .transpose()?;
+                self.step(line, |data| {
+                    data.add_stress_cols(None, None, rolling, smoothing, None)?
+                        .add_pore_pressure_cols()
+                })?;
+            }
+            "classify" => {
+                let method = match args.first() {
+                    Some(name) => ClassificationMethod::from_config(name)?,
+                    None => ClassificationMethod::Robertson,
+                };
+                self.step(line, |data| {
+                    data.add_behavior_cols(None, None, None, None, None)?
+                        .add_classification_cols(method)?
+                        .add_group_col()?
+                        .add_description_col()?
+                        .add_clean_sand_cols()?
+                        .add_fines_col(None)
+                })?;
+                for layer in sbt_layers(self.current()?)? {
+                    println!(
+                        "  {:>8.3} - {:>8.3} m  Ic {:.2}  {}",
+                        layer.top, layer.bottom, layer.mean_ic, layer.zone
+                    );
+                }
+            }
+            "gamma" => {
+                let profile = Some(UnitWeight::Profile);
+                self.step(line, |data| {
+                    data.add_gamma_col()?
+                        .add_stress_cols(None, profile, None, None, None)?
+                        .add_pore_pressure_cols()
+                })?;
+            }
+            "isbt" => {
+                self.step(line, |data| data.add_isbt_cols())?;
+            }
+            "correlate" => {
+                self.step(line, |data| {
+                    data.add_spt_cols()?
+                        .add_modulus_cols()?
+                        .add_constrained_modulus_col()?
+                        .add_compressibility_cols()?
+                        .add_vs_cols()?
+                        .add_su_col(None)?
+                        .add_sensitivity_col()?
+                        .add_phi_col(None)?
+                        .add_dr_col()?
+                        .add_psi_col()?
+                        .add_ocr_cols(None)?
+                        .add_k0_col()?
+                        .add_permeability_col()?
+                        .add_cv_col()
+                })?;
+            }
+            "thin-layer" => {
+                let options = ThinLayerOptions {
+                    cone_diameter: parse_auto(args.first())?,
+                    ..Default::default()
+                };
+                self.step(line, |data| data.thin_layer_correction(&options))?;
+            }
+            "liquefaction" => {
+                let options = CsrOptions {
+                    pga: parse_auto(args.first())?,
+                    magnitude: parse_auto(args.get(1))?,
+                    ..Default::default()
+                };
+                let method = match args.get(2) {
+                    Some(name) => TriggeringMethod::from_config(name)?,
+                    None => TriggeringMethod::RobertsonWride,
+                };
+                self.step(line, |data| {
+                    let data = data.add_triggering_cols(method, &options)?;
+                    match method {
+                        TriggeringMethod::RobertsonWride => {
+                            data.add_volumetric_strain_col()?.add_settlement_col()
+                        }
+                        TriggeringMethod::BoulangerIdriss => Ok(data),
+                    }
+                })?;
+                if method == TriggeringMethod::RobertsonWride {
+                    let data = self.current()?;
+                    println!(
+                        "  LPI {:.1}, LSN {:.1}, settlement {:.1} mm",
+                        data.liquefaction_potential()?,
+                        data.liquefaction_severity()?,
+                        data.liquefaction_settlement()?
+                    );
+                }
+            }
+            "lateral-spread" => {
+                let syntax = "lateral-spread slope <percent> | face <height> \
+                              <distance>";
+                let geometry = match args[..] {
+                    ["slope", slope] => GroundGeometry::Slope(parse(slope)?),
+                    ["face", height, distance] => GroundGeometry::FreeFace {
+                        height: parse(height)?,
+                        distance: parse(distance)?,
+                    },
+                    _ => return Err(usage(syntax)),
+                };
+                self.step(line, |data| data.add_shear_strain_col())?;
+                let estimate = self.current()?.lateral_displacement(geometry)?;
+                println!(
+                    "  LDI {:.3} m, lateral displacement {:.3} m",
+                    estimate.ldi, estimate.displacement
+                );
+            }
+            "settlement" => {
+                let syntax = "settlement fill <pressure> | footing <pressure> \
+                              <width> <length> [depth]";
+                let load = match args[..] {
+                    ["fill", pressure] => LoadDistribution::Uniform(parse(pressure)?),
+                    ["footing", pressure, width, length, ..] if args.len() <= 5 => {
+                        LoadDistribution::Footing(Footing {
+                            pressure: parse(pressure)?,
+                            width: parse(width)?,
+                            length: parse(length)?,
+                            depth: parse_auto(args.get(4))?.unwrap_or(0.0),
+                        })
+                    }
+                    _ => return Err(usage(syntax)),
+                };
+                let settlement = consolidation_settlement(self.current()?, load)?;
+                for layer in &settlement.layers {
+                    println!(
+                        "  {:>8.3} - {:>8.3} m  Δσ {:>7.1} kPa  {:>7.1} mm  {}",
+                        layer.top, layer.bottom, layer.stress_increase,
+                        layer.settlement, layer.zone
+                    );
+                }
+                println!("  Total {:.1} mm", settlement.total);
+            }
+            "schmertmann" => {
+                let syntax = "schmertmann <pressure> <width> <length> [depth] [years]";
+                let [pressure, width, length, ..] = args[..] else {
+                    return Err(usage(syntax));
+                };
+                if args.len() > 5 {
+                    return Err(usage(syntax));
+                }
+                let footing = Footing {
+                    pressure: parse(pressure)?,
+                    width: parse(width)?,
+                    length: parse(length)?,
+                    depth: parse_auto(args.get(3))?.unwrap_or(0.0),
+                };
+                let years = parse_auto(args.get(4))?;
+                let estimate = schmertmann_settlement(self.current()?, footing, years)?;
+                println!(
+                    "  C1 {:.3}, C2 {:.3}, Izp {:.3}, settlement {:.1} mm",
+                    estimate.c1,
+                    estimate.c2,
+                    estimate.peak_influence,
+                    estimate.settlement
+                );
+            }
+            "show" => {
+                let rows = args.first().map(|arg| parse(arg)).transpose()?;
+                println!("{:?}", self.current()?.head(Some(rows.unwrap_or(8))));
+            }
+            "plot" => {
+                let [path] = args[..] else { return Err(usage("plot <file.html>")) };
+                report::html::write_html(self.current()?, path, &self.id)?;
+                self.history.push(line.to_string());
+                println!("Wrote report to '{}'", path);
+            }
+            "write" => {
+                let [path] = args[..] else { return Err(usage("write <file>")) };
+                let data = self.current()?;
+                let options = WriteOptions::default();
+                if path.ends_with(".las") {
+                    data.write_las(path, &self.id, &options)?;
+                } else if path.ends_with(".parquet") {
+                    crate::write_parquet(data, path, &options)?;
+                } else {
+                    data.write_csv(path, &options)?;
+                }
+                self.history.push(line.to_string());
+                println!("Wrote '{}'", path);
+            }
+            "undo" => {
+                if self.states.len() < 2 {
+                    return Err(CoreError::InvalidData("Nothing to undo".to_string()));
+                }
+                self.states.pop();
+                // outputs written after the step are undone with it
+                while let Some(last) = self.history.pop() {
+                    if !is_output(&last) {
+                        println!("Undid '{}'", last);
+                        break;
+                    }
+                }
+            }
+            "history" => {
+                for (i, step) in self.history.iter().enumerate() {
+                    println!("{:>3}  {}", i + 1, step);
+                }
+            }
+            "save-job" => {
+                let [path] = args[..] else { return Err(usage("save-job <file>")) };
+                let mut job = format!("# conic {} job\n", env!("CARGO_PKG_VERSION"));
+                for step in &self.history {
+                    job.push_str(step);
+                    job.push('\n');
+                }
+                std::fs::write(path, job)?;
+                println!("Saved {} steps to '{}'", self.history.len(), path);
+            }
+            "help" => println!("{}", HELP),
+            "quit" | "exit" => return Ok(Flow::Quit),
+            other => {
+                return Err(CoreError::InvalidData(format!(
+                    "Unknown command '{}' (type `help` for commands)",
+                    other
+                )));
+            }
+        }
+
+        Ok(Flow::Continue)
+    }
+
+    /// Applies a step to the current data and records it in the history.
+    fn step<F>(&mut self, line: &str, apply: F) -> Result<(), CoreError>
+    where
+        F: FnOnce(ConicDataFrame) -> Result<ConicDataFrame, CoreError>,
+    {
+        let data = apply(self.current()?.clone())?;
+        println!("{} records, {} columns", data.height(), data.width());
+
+        self.states.push(data);
+        self.history.push(line.to_string());
+        Ok(())
+    }
+
+    fn current(&self) -> Result<&ConicDataFrame, CoreError> {
+        self.states.last().ok_or_else(|| {
+            CoreError::InvalidData("No sounding loaded (use `load <file>`)".to_string())
+        })
+    }
+}
+
+// commands that write files without changing the data
+fn is_output(line: &str) -> bool {
+    line.starts_with("plot ") || line.starts_with("write ")
+}
+
+fn parse<T: std::str::FromStr>(arg: &str) -> Result<T, CoreError> {
+    arg.parse()
+        .map_err(|_| CoreError::InvalidData(format!("Invalid argument '{}'", arg)))
+}
+
+// parses an optional numeric argument, where `auto` means None
+fn parse_auto(arg: Option<&&str>) -> Result<Option<f64>, CoreError> {
+    match arg {
+        None | Some(&"auto") => Ok(None),
+        Some(arg) => parse(arg).map(Some),
+    }
+}
+
+fn usage(syntax: &str) -> CoreError {
+    CoreError::InvalidData(format!("Usage: {}", syntax))
+}
@@ -0,0 +1,27 @@
+use std::path::Path;
+use std::process::Command;
+
+// embeds the commit the crate is built from as CONIC_GIT_HASH
+fn main() {
+    let git_hash = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=CONIC_GIT_HASH={}", git_hash);
+
+    // rebuild when HEAD moves, either to another branch or to a new commit
+    println!("cargo:rerun-if-changed=build.rs");
+    let head = Path::new("../.git/HEAD");
+    if head.exists() {
+        println!("cargo:rerun-if-changed=../.git/HEAD");
+        if let Ok(content) = std::fs::read_to_string(head)
+            && let Some(reference) = content.trim().strip_prefix("ref: ")
+        {
+            println!("cargo:rerun-if-changed=../.git/{}", reference);
+        }
+    }
+}
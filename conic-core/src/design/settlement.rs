@@ -0,0 +1,345 @@
+use std::f64::consts::PI;
+use crate::kernel::{ConicDataFrame, CoreError};
+use crate::kernel::config::{COL_M_MOD, COL_MV, COL_QT, COL_SIGV_EFF, CREEP_YEARS};
+use crate::math::basic::record_thicknesses;
+use crate::math::sbt::{SbtZone, sbt_layers};
+use crate::report::{column_values, depth_values};
+
+/// Flexible rectangular footing applying a uniform bearing pressure.
+///
+/// The pressure and depth must not be negative, the width must be positive
+/// and the length at least the width.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Footing {
+    /// Bearing pressure q (kPa).
+    pub pressure: f64,
+    /// Width B (m), the shorter side.
+    pub width: f64,
+    /// Length L (m).
+    pub length: f64,
+    /// Depth of the base D (m).
+    pub depth: f64,
+}
+
+impl Footing {
+    // checks that the pressure and depth are not negative and the sides are
+    // positive
+    fn validate(self) -> Result<(), CoreError> {
+        let Self { pressure, width, length, depth } = self;
+        let valid = [pressure, width, length, depth]
+            .iter()
+            .all(|value| value.is_finite())
+            && pressure >= 0.0
+            && width > 0.0
+            && length >= width
+            && depth >= 0.0;
+        if !valid {
+            return Err(CoreError::InvalidData(format!(
+                "Invalid footing: {:?}. The pressure and depth must be >= 0, the \
+                 width > 0 and the length at least the width",
+                self
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Distribution of the stress applied at the ground surface.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoadDistribution {
+    /// Fill of wide extent applying a uniform pressure (kPa) at every
+    /// depth.
+    Uniform(f64),
+    /// Footing whose stress increase below its center follows Boussinesq.
+    Footing(Footing),
+}
+
+impl LoadDistribution {
+    /// Returns the vertical stress increase Δσ (kPa) at a depth (m).
+    ///
+    /// Below a footing, `Δσ = 4 q I(B / 2z', L / 2z')` with the corner
+    /// influence factor I of Newmark (1935) and z' the depth below the base;
+    /// Δσ is the full pressure at the base and 0 above it.
+    pub fn stress_increase(self, depth: f64) -> f64 {
+        match self {
+            Self::Uniform(pressure) => pressure,
+            Self::Footing(footing) => {
+                let z = depth - footing.depth;
+                if z < 0.0 {
+                    0.0
+                } else if z == 0.0 {
+                    footing.pressure
+                } else {
+                    let (m, n) = (footing.width / 2.0 / z, footing.length / 2.0 / z);
+                    4.0 * footing.pressure * corner_influence(m, n)
+                }
+            }
+        }
+    }
+
+    // depth (m) above which no stress is applied
+    fn base(self) -> f64 {
+        match self {
+            Self::Uniform(_) => f64::NEG_INFINITY,
+            Self::Footing(footing) => footing.depth,
+        }
+    }
+}
+
+// influence factor below the corner of a uniformly loaded rectangle of sides
+// m z and n z (Newmark, 1935)
+fn corner_influence(m: f64, n: f64) -> f64 {
+    let (m2, n2) = (m * m, n * n);
+    let root = (m2 + n2 + 1.0).sqrt();
+    let first = 2.0 * m * n * root / (m2 + n2 + m2 * n2 + 1.0) * (m2 + n2 + 2.0)
+        / (m2 + n2 + 1.0);
+    // atan2 keeps the angle within 0-π where the denominator is negative
+    let second = (2.0 * m * n * root).atan2(m2 + n2 + 1.0 - m2 * n2);
+
+    (first + second) / (4.0 * PI)
+}
+
+/// 1D consolidation settlement of an SBT layer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerSettlement {
+    /// Depth of the first record of the layer (m).
+    pub top: f64,
+    /// Depth of the last record of the layer (m).
+    pub bottom: f64,
+    pub zone: SbtZone,
+    /// Mean stress increase Δσ over the layer (kPa).
+    pub stress_increase: f64,
+    /// Settlement of the layer (mm).
+    pub settlement: f64,
+}
+
+/// 1D consolidation settlement of a sounding under a load.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settlement {
+    /// Settlement of each SBT layer (see `sbt_layers`), from the top.
+    pub layers: Vec<LayerSettlement>,
+    /// Sum of the settlement of the layers (mm).
+    pub total: f64,
+}
+
+/// Computes the 1D consolidation settlement of a processed sounding under
+/// `load`.
+///
+/// Integrates `s = Σ mv Δσ h` over the records, with mv from the column of
+/// `add_compressibility_cols`, or 1 / M if only the column of
+/// `add_constrained_modulus_col` is present, and h the thickness each record
+/// represents; records with a NaN mv or Δσ do not contribute. Records are
+/// grouped into the SBT layers of `sbt_layers`, so the Ic column of
+/// `add_behavior_cols` is also required.
+///
+/// # Errors
+///
+/// Returns `CoreError::InvalidData` if neither the mv nor the M column is
+/// present, if the pressure is negative, or if the footing is invalid (see
+/// `Footing`).
+pub fn consolidation_settlement(
+    data: &ConicDataFrame,
+    load: LoadDistribution,
+) -> Result<Settlement, CoreError> {
+    match load {
+        LoadDistribution::Uniform(pressure) => {
+            if pressure.is_nan() || pressure < 0.0 || pressure.is_infinite() {
+                return Err(CoreError::InvalidData(format!(
+                    "Invalid fill pressure: {}. Must be >= 0",
+                    pressure
+                )));
+            }
+        }
+        LoadDistribution::Footing(footing) => footing.validate()?,
+    }
+
+    let depth = depth_values(data)?;
+    let mv = match column_values(data, *COL_MV)? {
+        Some(mv) => mv,
+        None => column_values(data, *COL_M_MOD)?
+            .ok_or_else(|| {
+                CoreError::InvalidData(format!(
+                    "Cannot compute settlement: missing columns '{}' and '{}'",
+                    *COL_MV, *COL_M_MOD
+                ))
+            })?
+            .into_iter()
+            // mv = 1 / M
+            .map(|modulus| if modulus > 0.0 { 1.0 / modulus } else { f64::NAN })
+            .collect(),
+    };
+    let thickness = record_thicknesses(&depth);
+
+    let mut layers = Vec::new();
+    for layer in sbt_layers(data)? {
+        let (mut settlement, mut stress_sum, mut thickness_sum) = (0.0, 0.0, 0.0);
+        for i in 0..depth.len() {
+            let z = depth[i];
+            if z < layer.top || z > layer.bottom || z < load.base() {
+                continue;
+            }
+            let stress = load.stress_increase(z);
+            if !(mv[i].is_finite() && stress.is_finite() && thickness[i].is_finite()) {
+                continue;
+            }
+            // Δσ in kPa and mv in 1/MPa give a strain in thousandths, so a
+            // thickness in m gives mm
+            settlement += mv[i] * stress * thickness[i];
+            stress_sum += stress * thickness[i];
+            thickness_sum += thickness[i];
+        }
+
+        let stress_increase = if thickness_sum > 0.0 {
+            stress_sum / thickness_sum
+        } else {
+            f64::NAN
+        };
+        layers.push(LayerSettlement {
+            top: layer.top,
+            bottom: layer.bottom,
+            zone: layer.zone,
+            stress_increase,
+            settlement,
+        });
+    }
+    let total = layers.iter().map(|layer| layer.settlement).sum();
+
+    Ok(Settlement { layers, total })
+}
+
+/// Settlement of a footing on sand by the strain influence method.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SchmertmannSettlement {
+    /// Embedment correction factor C1.
+    pub c1: f64,
+    /// Creep correction factor C2.
+    pub c2: f64,
+    /// Peak strain influence factor Izp.
+    pub peak_influence: f64,
+    /// Settlement of the footing (mm).
+    pub settlement: f64,
+}
+
+/// Computes the settlement of a footing on sand with the strain influence
+/// method of Schmertmann et al. (1978) over the qt profile.
+///
+/// `s = C1 C2 Δp Σ Iz h / E`, with the net pressure `Δp = q - σ'v0` at the
+/// base, `C1 = 1 - 0.5 σ'v0 / Δp` (at least 0.5),
+/// `C2 = 1 + 0.2 log10(t / 0.1)` with t in years (default `creep_years` from
+/// the configuration), and h the thickness each record represents. Iz rises
+/// from 0.1 at the base to `Izp = 0.5 + 0.1 (Δp / σ'vp)^0.5` at B/2 below it
+/// and vanishes at 2B for square footings (L/B = 1), and rises from 0.2 to
+/// Izp at B and vanishes at 4B for strip footings (L/B of 10 or more);
+/// `E = 2.5 qt` and `3.5 qt` respectively. Intermediate shapes are
+/// interpolated on L/B. Records with a NaN or non-positive qt do not
+/// contribute, and a sounding ending above the influence depth
+/// underestimates the settlement. Requires the columns added by
+/// `add_stress_cols`.
+///
+/// # Errors
+///
+/// Returns `CoreError::InvalidData` if the footing is invalid (see
+/// `Footing`), t is below 0.1 years, σ'v cannot be interpolated at the base
+/// or at the peak of Iz, or the bearing pressure does not exceed σ'v0.
+pub fn schmertmann_settlement(
+    data: &ConicDataFrame,
+    footing: Footing,
+    years: Option<f64>,
+) -> Result<SchmertmannSettlement, CoreError> {
+    footing.validate()?;
+    let years = years.unwrap_or(*CREEP_YEARS);
+    if years.is_nan() || years < 0.1 || years.is_infinite() {
+        return Err(CoreError::InvalidData(format!(
+            "Invalid creep time: {} years. Must be >= 0.1",
+            years
+        )));
+    }
+
+    let missing = |name: &str| {
+        CoreError::InvalidData(format!(
+            "Cannot compute settlement: missing column '{}'",
+            name
+        ))
+    };
+    let depth = depth_values(data)?;
+    let qt = column_values(data, *COL_QT)?.ok_or_else(|| missing(*COL_QT))?;
+    let sigv_eff = column_values(data, *COL_SIGV_EFF)?
+        .ok_or_else(|| missing(*COL_SIGV_EFF))?;
+
+    // strain influence diagram, from square (0) to strip (1) footings
+    let shape = ((footing.length / footing.width).min(10.0) - 1.0) / 9.0;
+    let base_influence = 0.1 + 0.1 * shape;
+    let peak_depth = footing.width * (0.5 + 0.5 * shape);
+    let max_depth = footing.width * (2.0 + 2.0 * shape);
+    let modulus_factor = 2.5 + 1.0 * shape;
+
+    let sigv_base = interpolate(&depth, &sigv_eff, footing.depth);
+    let sigv_peak = interpolate(&depth, &sigv_eff, footing.depth + peak_depth);
+    if sigv_base.is_nan() || sigv_peak.is_nan() || sigv_peak <= 0.0 {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot interpolate σ'v at the base ({} m) and below it ({} m)",
+            footing.depth,
+            footing.depth + peak_depth
+        )));
+    }
+    let net_pressure = footing.pressure - sigv_base;
+    if net_pressure <= 0.0 {
+        return Err(CoreError::InvalidData(format!(
+            "Bearing pressure {} kPa does not exceed σ'v0 of {:.1} kPa at the base",
+            footing.pressure, sigv_base
+        )));
+    }
+
+    let c1 = (1.0 - 0.5 * sigv_base / net_pressure).max(0.5);
+    let c2 = 1.0 + 0.2 * (years / 0.1).log10();
+    let peak_influence = 0.5 + 0.1 * (net_pressure / sigv_peak).sqrt();
+    let influence = |z: f64| {
+        if z <= peak_depth {
+            base_influence + (peak_influence - base_influence) * z / peak_depth
+        } else if z < max_depth {
+            peak_influence * (max_depth - z) / (max_depth - peak_depth)
+        } else {
+            0.0
+        }
+    };
+
+    let thickness = record_thicknesses(&depth);
+    let sum: f64 = (0..depth.len())
+        .filter(|&i| {
+            let z = depth[i] - footing.depth;
+            z > 0.0 && z < max_depth && qt[i] > 0.0 && thickness[i].is_finite()
+        })
+        // E = factor * qt in MPa
+        .map(|i| {
+            let z = depth[i] - footing.depth;
+            influence(z) * thickness[i] / (modulus_factor * qt[i])
+        })
+        .sum();
+
+    // Δp in kPa over E in MPa gives thousandths, so a thickness in m gives mm
+    let settlement = c1 * c2 * net_pressure * sum;
+
+    Ok(SchmertmannSettlement { c1, c2, peak_influence, settlement })
+}
+
+// interpolates linearly a profile at a depth, from 0 at the ground surface
+// above the first record; NaN below the last record
+fn interpolate(depth: &[f64], values: &[f64], z: f64) -> f64 {
+    let mut above = (0.0, 0.0);
+    for (&depth, &value) in depth.iter().zip(values) {
+        if depth.is_nan() || value.is_nan() {
+            continue;
+        }
+        if depth >= z {
+            let (top, top_value) = above;
+            if depth == top {
+                return value;
+            }
+            return top_value + (value - top_value) * (z - top) / (depth - top);
+        }
+        above = (depth, value);
+    }
+
+    f64::NAN
+}
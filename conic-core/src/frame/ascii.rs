@@ -0,0 +1,77 @@
+use crate::kernel::config::column_keys;
+
+/// Returns the ASCII alias of a column name.
+///
+/// Configured columns map to their configuration key (e.g. `σv_tot (kPa)`
+/// to `sigv_tot`). Other names are transliterated: common Greek letters are
+/// spelled out, remaining characters other than ASCII letters and digits
+/// become underscores, and the result is lowercased (e.g. `qt [rolling]` to
+/// `qt_rolling`).
+pub fn ascii_alias(name: &str) -> String {
+    if let Some((key, _)) = column_keys().into_iter().find(|(_, col)| *col == name) {
+        return key.to_string();
+    }
+
+    let mut alias = String::with_capacity(name.len());
+    for ch in name.chars() {
+        let spelled = match greek_spelling(ch) {
+            Some(spelled) => spelled,
+            None if ch.is_ascii_alphanumeric() => {
+                alias.push(ch.to_ascii_lowercase());
+                continue;
+            }
+            None => "_",
+        };
+        // collapse runs of separators
+        if spelled == "_" && (alias.is_empty() || alias.ends_with('_')) {
+            continue;
+        }
+        alias.push_str(spelled);
+    }
+
+    alias.trim_end_matches('_').to_string()
+}
+
+/// Returns the ASCII aliases of a list of column names.
+///
+/// Aliases that collide are disambiguated with a numeric suffix, so the
+/// result can always be used as a header row.
+pub fn ascii_aliases<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut aliases: Vec<String> = Vec::new();
+
+    for name in names {
+        let base = ascii_alias(name);
+        let base = if base.is_empty() { "column".to_string() } else { base };
+
+        let mut alias = base.clone();
+        let mut suffix = 2;
+        while aliases.contains(&alias) {
+            alias = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        aliases.push(alias);
+    }
+
+    aliases
+}
+
+/// Returns the spelled-out name of common Greek letters used in headers.
+pub(crate) fn greek_spelling(ch: char) -> Option<&'static str> {
+    let spelled = match ch {
+        'α' => "alpha",
+        'β' => "beta",
+        'γ' => "gamma",
+        'δ' | 'Δ' => "delta",
+        'ε' => "eps",
+        'μ' => "mu",
+        'ν' => "nu",
+        'ρ' => "rho",
+        'σ' => "sig",
+        'τ' => "tau",
+        'φ' => "phi",
+        'ψ' => "psi",
+        _ => return None,
+    };
+
+    Some(spelled)
+}
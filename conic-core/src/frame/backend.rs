@@ -0,0 +1,135 @@
+use std::sync::{LazyLock, PoisonError, RwLock};
+use crate::kernel::{ConicDataFrame, CoreError};
+use crate::kernel::remote::strip_query;
+use super::read::read_csv;
+
+/// Reader for a sounding file format.
+///
+/// Implement this trait to plug readers for proprietary rig formats into
+/// `read_auto` without forking the crate, then add them with
+/// `register_reader`.
+pub trait ReaderBackend: Send + Sync {
+    /// Short name of the format, e.g. `csv`.
+    fn name(&self) -> &str;
+
+    /// Returns true if this backend can read `source`, usually decided from
+    /// the file extension.
+    fn can_read(&self, source: &str) -> bool;
+
+    /// Reads `source` into a `ConicDataFrame`.
+    fn read(&self, source: &str) -> Result<ConicDataFrame, CoreError>;
+}
+
+/// Built-in backend for CSV files, plain or compressed.
+pub struct CsvBackend;
+
+impl ReaderBackend for CsvBackend {
+    fn name(&self) -> &str {
+        "csv"
+    }
+
+    fn can_read(&self, source: &str) -> bool {
+        let source = strip_query(source).to_ascii_lowercase();
+        [".csv", ".csv.gz", ".zip"]
+            .iter()
+            .any(|extension| source.ends_with(extension))
+    }
+
+    fn read(&self, source: &str) -> Result<ConicDataFrame, CoreError> {
+        read_csv(source)
+    }
+}
+
+/// Ordered collection of reader backends.
+///
+/// Backends registered later take precedence over earlier ones, so a custom
+/// backend can override a built-in one for the same extension.
+pub struct ReaderRegistry {
+    backends: Vec<Box<dyn ReaderBackend>>,
+}
+
+impl ReaderRegistry {
+    /// Creates a registry without backends.
+    pub fn empty() -> Self {
+        Self { backends: Vec::new() }
+    }
+
+    /// Adds a backend to the registry.
+    pub fn register(&mut self, backend: impl ReaderBackend + 'static) {
+        self.backends.push(Box::new(backend));
+    }
+
+    /// Returns the backend that reads `source`, if any.
+    pub fn backend_for(&self, source: &str) -> Option<&dyn ReaderBackend> {
+        self.backends
+            .iter()
+            .rev()
+            .find(|backend| backend.can_read(source))
+            .map(|backend| backend.as_ref())
+    }
+
+    /// Returns the names of the registered backends.
+    pub fn names(&self) -> Vec<&str> {
+        self.backends.iter().map(|backend| backend.name()).collect()
+    }
+
+    /// Reads `source` with the first backend that accepts it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if no backend accepts `source`.
+    pub fn read(&self, source: &str) -> Result<ConicDataFrame, CoreError> {
+        match self.backend_for(source) {
+            Some(backend) => backend.read(source),
+            None => Err(CoreError::InvalidData(format!(
+                "No reader backend accepts '{}'. Registered backends: {:?}",
+                source,
+                self.names()
+            ))),
+        }
+    }
+}
+
+impl Default for ReaderRegistry {
+    /// Creates a registry with the built-in backends.
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register(CsvBackend);
+        registry
+    }
+}
+
+/// Registry used by `read_auto`.
+static REGISTRY: LazyLock<RwLock<ReaderRegistry>> =
+    LazyLock::new(|| RwLock::new(ReaderRegistry::default()));
+
+/// Adds a backend to the registry used by `read_auto`.
+pub fn register_reader(backend: impl ReaderBackend + 'static) {
+    REGISTRY
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .register(backend);
+}
+
+/// Reads a sounding with the registered backend matching `source`.
+pub fn read_auto(source: &str) -> Result<ConicDataFrame, CoreError> {
+    REGISTRY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .read(source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_backend_ignores_url_queries() {
+        assert!(CsvBackend.can_read("data/CPT-01.CSV"));
+        assert!(CsvBackend.can_read(
+            "https://bucket.s3.amazonaws.com/CPT-01.csv?X-Amz-Signature=abc"
+        ));
+        assert!(CsvBackend.can_read("s3://bucket/CPT-01.csv.gz#latest"));
+        assert!(!CsvBackend.can_read("https://host/CPT-01.xlsx?name=a.csv"));
+    }
+}
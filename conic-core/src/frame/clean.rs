@@ -1,77 +1,1037 @@
 use polars::prelude::*;
 use crate::kernel::CoreError;
-use crate::kernel::config::COL_DEPTH;
+use crate::kernel::profile::profile_scope;
+use crate::kernel::config::{
+    COL_DEPTH, COL_QC, COL_FS, COL_U2, COL_U0, COL_DROP_REASON, COL_INTERPOLATED,
+    COL_ROD_CHANGE, COL_INDICATOR, INTERP_MAX_GAP, COLUMN_INDICATORS, SEATING_LENGTH,
+    SEATING_QC, SEATING_ARTIFACTS, ROD_LENGTH, ROD_DIP_LENGTH, ROD_DIP_RATIO,
+    ROD_TOLERANCE, ROD_CHANGES
+};
 
 
 pub(crate) fn remove_rows(
     data: DataFrame,
     indicators: &[f64],
+    columns: Option<&[&str]>,
 ) -> Result<DataFrame, CoreError> {
-    let indicators = Series::from_vec(
-        "indicators".into(),
-        indicators.to_vec(),
-    );
-    let indicators = lit(indicators).implode();
+    profile_scope!("remove_rows");
 
-    let mask_expr: Vec<Expr> = data
-        .get_column_names()
-        .into_iter()
-        .map(|name| {
-            let name = name.as_str();
-            col(name).is_in(indicators.clone(), false).not()
-        })
+    let out_data = remove_rows_lazy(data.lazy(), indicators, columns)?.collect()?;
+
+    Ok(out_data)
+}
+
+pub(crate) fn remove_rows_lazy(
+    mut data: LazyFrame,
+    indicators: &[f64],
+    columns: Option<&[&str]>,
+) -> Result<LazyFrame, CoreError> {
+    let schema = data.collect_schema()?;
+    let mask_expr: Vec<Expr> = scoped_columns(&schema, columns)?
+        .iter()
+        .map(|name| is_indicator(name, indicators).not())
         .collect();
     let mask_expr = all_horizontal(mask_expr)?;
 
+    Ok(data.filter(mask_expr))
+}
+
+/// Returns the rows `remove_rows` would drop, with a reason column naming
+/// the first column holding an indicator and its value, e.g.
+/// `remove_rows: qc (MPa) = -9999`.
+pub(crate) fn dropped_rows(
+    data: &DataFrame,
+    indicators: &[f64],
+    columns: Option<&[&str]>,
+) -> Result<DataFrame, CoreError> {
+    let column_names = scoped_columns(data.schema(), columns)?;
+    let column_names: Vec<&str> =
+        column_names.iter().map(|name| name.as_str()).collect();
+
+    let matches: Vec<Expr> = column_names
+        .iter()
+        .map(|name| is_indicator(name, indicators))
+        .collect();
+
+    // the reason names the first matching column, checked in column order
+    let mut reason_expr = lit(NULL).cast(DataType::String);
+    for (name, matched) in column_names.iter().zip(&matches).rev() {
+        let reason = lit(format!("remove_rows: {} = ", name))
+            + col(*name).cast(DataType::String);
+        reason_expr = when(matched.clone()).then(reason).otherwise(reason_expr);
+    }
+
     let out_data = data
+        .clone()
         .lazy()
-        .filter(mask_expr)
+        .filter(any_horizontal(matches)?)
+        .with_column(reason_expr.alias(*COL_DROP_REASON))
         .collect()?;
 
     Ok(out_data)
 }
 
+/// Flags the rows `remove_rows` would drop without changing any value.
+///
+/// Adds a boolean indicator column, true where any inspected column (every
+/// float column, or `columns`) holds one of `indicators` or a value
+/// configured for it in `[input.indicators]`, so the rows can be reviewed
+/// before they are removed or replaced.
+pub(crate) fn flag_indicators(
+    data: DataFrame,
+    indicators: &[f64],
+    columns: Option<&[&str]>,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("flag_indicators");
+
+    let matches: Vec<Expr> = scoped_columns(data.schema(), columns)?
+        .iter()
+        .map(|name| is_indicator(name, indicators))
+        .collect();
+
+    let out_data = data
+        .lazy()
+        .with_column(any_horizontal(matches)?.alias(*COL_INDICATOR))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+pub(crate) fn drop_nan(
+    data: DataFrame,
+    subset: Option<&[&str]>,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("drop_nan");
+
+    let out_data = drop_nan_lazy(data.lazy(), subset)?.collect()?;
+
+    Ok(out_data)
+}
+
+pub(crate) fn drop_nan_lazy(
+    mut data: LazyFrame,
+    subset: Option<&[&str]>,
+) -> Result<LazyFrame, CoreError> {
+    let column_names = column_names(&mut data)?;
+    let mask_expr: Vec<Expr> = nan_columns(&column_names, subset)?
+        .iter()
+        .map(|name| is_missing(name).not())
+        .collect();
+    let mask_expr = all_horizontal(mask_expr)?;
+
+    Ok(data.filter(mask_expr))
+}
+
+/// Returns the rows `drop_nan` would drop, with a reason column naming
+/// `step` and the first missing column, e.g. `drop_nan: qc (MPa) is NaN`.
+pub(crate) fn nan_rows(
+    data: &DataFrame,
+    subset: Option<&[&str]>,
+    step: &str,
+) -> Result<DataFrame, CoreError> {
+    let column_names = nan_columns(&data.get_column_names_owned(), subset)?;
+
+    let matches: Vec<Expr> = column_names.iter().map(|name| is_missing(name)).collect();
+
+    // the reason names the first missing column, checked in subset order
+    let mut reason_expr = lit(NULL).cast(DataType::String);
+    for (name, matched) in column_names.iter().zip(&matches).rev() {
+        let reason = lit(format!("{}: {} is NaN", step, name));
+        reason_expr = when(matched.clone()).then(reason).otherwise(reason_expr);
+    }
+
+    let out_data = data
+        .clone()
+        .lazy()
+        .filter(any_horizontal(matches)?)
+        .with_column(reason_expr.alias(*COL_DROP_REASON))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Handling of NaN or null inputs of the derived column steps.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NanPolicy {
+    /// Compute the records anyway; their outputs are NaN.
+    Propagate,
+    /// Remove the records before computing.
+    SkipRow,
+    /// Abort at the first record, naming its depth.
+    Error,
+}
+
+impl NanPolicy {
+    /// Builds a policy from its configuration name.
+    ///
+    /// Accepted names: `propagate`, `skip_row`, `error`.
+    pub fn from_config(name: &str) -> Result<Self, CoreError> {
+        match name {
+            "propagate" => Ok(Self::Propagate),
+            "skip_row" => Ok(Self::SkipRow),
+            "error" => Ok(Self::Error),
+            _ => Err(CoreError::InvalidConfig(format!(
+                "Invalid nan_policy parameter: '{}'. Must be propagate, skip_row \
+                 or error",
+                name
+            ))),
+        }
+    }
+}
+
+/// Fails on the first record with a NaN or null value in `columns`.
+///
+/// # Errors
+///
+/// Returns `CoreError::InvalidData` naming `step`, the column and the depth
+/// of the record, or if a column does not exist.
+pub(crate) fn check_nan(
+    data: &DataFrame,
+    columns: &[&str],
+    step: &str,
+) -> Result<(), CoreError> {
+    let missing = nan_rows(data, Some(columns), step)?;
+    if missing.height() == 0 {
+        return Ok(());
+    }
+
+    let depth = missing.column(*COL_DEPTH)?.f64()?.into_iter().next().flatten();
+    let reason = missing.column(*COL_DROP_REASON)?.str()?.into_iter().next().flatten();
+    Err(CoreError::InvalidData(format!(
+        "Cannot compute {} at depth {} m",
+        reason.unwrap_or(step),
+        depth.map_or("NaN".to_string(), |depth| depth.to_string())
+    )))
+}
+
+/// Evaluates a boolean predicate on every row; null results count as false.
+pub(crate) fn predicate_mask(
+    data: &DataFrame,
+    predicate: Expr,
+) -> Result<BooleanChunked, CoreError> {
+    let mask = data
+        .clone()
+        .lazy()
+        .select([predicate.fill_null(lit(false)).alias("predicate")])
+        .collect()?;
+
+    Ok(mask.column("predicate")?.bool()?.clone())
+}
+
+/// Returns the rows selected by `mask`, with a reason column set to
+/// `reason`, e.g. `remove_rows_where: predicate`.
+pub(crate) fn masked_rows(
+    data: &DataFrame,
+    mask: &BooleanChunked,
+    reason: &str,
+) -> Result<DataFrame, CoreError> {
+    check_mask(data, mask)?;
+
+    let out_data = data
+        .filter(mask)?
+        .lazy()
+        .with_column(lit(reason).alias(*COL_DROP_REASON))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Removes the rows selected by `mask`; null mask values keep their row.
+pub(crate) fn remove_masked_rows(
+    data: DataFrame,
+    mask: &BooleanChunked,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("remove_rows_where");
+
+    check_mask(&data, mask)?;
+
+    Ok(data.filter(&!mask.fill_null_with_values(false)?)?)
+}
+
+fn check_mask(data: &DataFrame, mask: &BooleanChunked) -> Result<(), CoreError> {
+    if mask.len() != data.height() {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot remove rows: predicate has {} values for {} rows",
+            mask.len(),
+            data.height()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Clamps the values of `column` to `[min, max]`; a None limit leaves that
+/// end open. NaN values are kept. Returns the data with the number of
+/// clipped values.
+pub(crate) fn clip(
+    data: DataFrame,
+    column: &str,
+    min: Option<f64>,
+    max: Option<f64>,
+) -> Result<(DataFrame, usize), CoreError> {
+    profile_scope!("clip");
+
+    if min.is_some_and(f64::is_nan) || max.is_some_and(f64::is_nan) {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot clip '{}': limits must not be NaN",
+            column
+        )));
+    }
+    if let (Some(min), Some(max)) = (min, max)
+        && min > max
+    {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot clip '{}': min {} is greater than max {}",
+            column, min, max
+        )));
+    }
+
+    let values = data.column(column)?.f64()?;
+    let clipped = values
+        .into_iter()
+        .flatten()
+        .filter(|&value| {
+            min.is_some_and(|min| value < min) || max.is_some_and(|max| value > max)
+        })
+        .count();
+
+    let mut expr = col(column);
+    if let Some(min) = min {
+        expr = when(col(column).lt(lit(min))).then(lit(min)).otherwise(expr);
+    }
+    if let Some(max) = max {
+        expr = when(col(column).gt(lit(max))).then(lit(max)).otherwise(expr);
+    }
+
+    let out_data = data.lazy().with_column(expr.alias(column)).collect()?;
+
+    Ok((out_data, clipped))
+}
+
+/// Appends rows to a table, filling columns missing on either side with
+/// nulls.
+pub(crate) fn append_rows(
+    data: DataFrame,
+    rows: DataFrame,
+) -> Result<DataFrame, CoreError> {
+    if data.width() == 0 {
+        return Ok(rows);
+    }
+
+    Ok(polars::functions::concat_df_diagonal(&[data, rows])?)
+}
+
+/// Value written into one column of the rows flagged by `replace_rows`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Replacement {
+    /// A fixed value, e.g. NaN.
+    Value(f64),
+    /// The hydrostatic pressure u0 of the row, e.g. for u2.
+    Hydrostatic,
+    /// The original value of the row.
+    Keep,
+}
+
+impl std::fmt::Display for Replacement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Value(value) => write!(f, "{}", value),
+            Self::Hydrostatic => write!(f, "hydrostatic"),
+            Self::Keep => write!(f, "keep"),
+        }
+    }
+}
+
+/// Replaces the float values of rows holding an indicator value with
+/// `replace_value`, except depth and the columns listed in `replacements`,
+/// which get their own replacement. Non-float columns are left unchanged.
 pub(crate) fn replace_rows(
     data: DataFrame,
     indicators: &[f64],
     replace_value: &f64,
+    replacements: &[(&str, Replacement)],
+    columns: Option<&[&str]>,
 ) -> Result<DataFrame, CoreError> {
-    let indicators = Series::from_vec(
-        "indicators".into(),
-        indicators.to_vec(),
-    );
-    let indicators = lit(indicators).implode();
+    profile_scope!("replace_rows");
 
-    let mask_expr: Vec<Expr> = data
-        .get_column_names()
-        .into_iter()
-        .map(|name| {
+    let out_data = replace_rows_lazy(
+        data.lazy(),
+        indicators,
+        replace_value,
+        replacements,
+        columns
+    )?
+        .collect()?;
+
+    Ok(out_data)
+}
+
+pub(crate) fn replace_rows_lazy(
+    mut data: LazyFrame,
+    indicators: &[f64],
+    replace_value: &f64,
+    replacements: &[(&str, Replacement)],
+    columns: Option<&[&str]>,
+) -> Result<LazyFrame, CoreError> {
+    let schema = data.collect_schema()?;
+    let column_names: Vec<PlSmallStr> = schema.iter_names_cloned().collect();
+
+    if let Some((name, _)) = replacements
+        .iter()
+        .find(|(name, _)| !column_names.iter().any(|column| column == name))
+    {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot replace rows: unknown column '{}' in the replacements",
+            name
+        )));
+    }
+
+    let mask_expr: Vec<Expr> = scoped_columns(&schema, columns)?
+        .iter()
+        .map(|name| is_indicator(name, indicators))
+        .collect();
+    let mask_expr = any_horizontal(mask_expr)?;
+
+    // flag, zone and text columns hold no measurements to replace
+    if let Some((name, _)) = replacements.iter().find(|(name, replacement)| {
+        *replacement != Replacement::Keep
+            && schema.get(name).is_some_and(|dtype| !dtype.is_float())
+    }) {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot replace rows: column '{}' is not a float column",
+            name
+        )));
+    }
+
+    let transform_expr: Vec<Expr> = schema
+        .iter()
+        .map(|(name, dtype)| {
             let name = name.as_str();
-            col(name).is_in(indicators.clone(), false)
+            if name == *COL_DEPTH || !dtype.is_float() {
+                return col(name);
+            }
+            let replacement = replacements
+                .iter()
+                .find(|(column, _)| *column == name)
+                .map_or(Replacement::Value(*replace_value), |(_, value)| *value);
+            let value = match replacement {
+                Replacement::Value(value) => lit(value),
+                Replacement::Hydrostatic => col(*COL_U0),
+                Replacement::Keep => return col(name),
+            };
+            when(mask_expr.clone())
+                .then(value)
+                .otherwise(col(name))
+                .alias(name)
         })
         .collect();
-    let mask_expr = any_horizontal(mask_expr)?;
 
-    let transform_expr: Vec<Expr> = data
-        .get_column_names()
+    Ok(data.select(transform_expr))
+}
+
+/// Linearly interpolates qc, fs and u2 across runs of indicator values.
+///
+/// Each column is interpolated against depth over runs of at most `max_gap`
+/// consecutive indicator values bounded by valid records on both sides;
+/// longer runs and runs at either end of the sounding are left unchanged.
+/// Rows with an interpolated value are flagged in the interpolated column.
+pub(crate) fn interpolate_rows(
+    data: DataFrame,
+    indicators: &[f64],
+    max_gap: Option<usize>,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("interpolate_rows");
+
+    let max_gap = max_gap.unwrap_or(*INTERP_MAX_GAP);
+
+    let depth: Vec<f64> = data
+        .column(*COL_DEPTH)?
+        .f64()?
         .into_iter()
-        .map(|name| {
-            let name = name.as_str();
-            if name == *COL_DEPTH {
-                col(name)
-            } else {
-                when(mask_expr.clone())
-                    .then(lit(*replace_value))
-                    .otherwise(col(name))
-                    .alias(name)
+        .map(|z| z.unwrap_or(f64::NAN))
+        .collect();
+
+    // rows interpolated by an earlier call stay flagged
+    let mut interpolated: Vec<bool> = match data.column(*COL_INTERPOLATED) {
+        Ok(column) => column
+            .bool()?
+            .into_iter()
+            .map(|flag| flag == Some(true))
+            .collect(),
+        Err(_) => vec![false; data.height()],
+    };
+
+    let mut filled = Vec::with_capacity(3);
+    for name in [*COL_QC, *COL_FS, *COL_U2] {
+        let column_indicators = column_indicators(name, indicators);
+        let is_indicator = |value: f64| {
+            column_indicators
+                .iter()
+                .any(|&ind| value == ind || (value.is_nan() && ind.is_nan()))
+        };
+
+        let mut values: Vec<f64> = data
+            .column(name)?
+            .f64()?
+            .into_iter()
+            .map(|value| value.unwrap_or(f64::NAN))
+            .collect();
+
+        let mut start = 0;
+        while start < values.len() {
+            if !is_indicator(values[start]) {
+                start += 1;
+                continue;
             }
+            let end = (start..values.len())
+                .find(|&i| !is_indicator(values[i]))
+                .unwrap_or(values.len());
+
+            // runs need a valid record above and below, at finite depths
+            let bounded = start > 0 && end < values.len() && end - start <= max_gap;
+            if bounded && values[start - 1].is_finite() && values[end].is_finite() {
+                let (z0, v0) = (depth[start - 1], values[start - 1]);
+                let (z1, v1) = (depth[end], values[end]);
+                for i in start..end {
+                    let ratio = (depth[i] - z0) / (z1 - z0);
+                    if ratio.is_finite() {
+                        values[i] = v0 + ratio * (v1 - v0);
+                        interpolated[i] = true;
+                    }
+                }
+            }
+            start = end;
+        }
+
+        filled.push(Series::new(name.into(), values));
+    }
+    filled.push(Series::new((*COL_INTERPOLATED).into(), interpolated));
+
+    let out_data = data
+        .lazy()
+        .with_columns(filled.into_iter().map(lit).collect::<Vec<_>>())
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Handling of the seating records at the start of each push, where qc and
+/// fs ramp up from zero as the cone is loaded again.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SeatingArtifacts {
+    /// Drop the seating records.
+    Remove,
+    /// Keep the seating records with NaN qc, fs and u2.
+    Mask,
+}
+
+impl SeatingArtifacts {
+    /// Builds a handling from its configuration name.
+    ///
+    /// Accepted names: `remove`, `mask`.
+    pub fn from_config(name: &str) -> Result<Self, CoreError> {
+        match name {
+            "remove" => Ok(Self::Remove),
+            "mask" => Ok(Self::Mask),
+            _ => Err(CoreError::InvalidConfig(format!(
+                "Invalid seating_artifacts parameter: '{}'. Must be remove or \
+                 mask",
+                name
+            ))),
+        }
+    }
+}
+
+/// Returns whether each record belongs to the seating of a push.
+///
+/// Pushes start at the first record and after every run of records with qc
+/// at or below `zero_qc` (e.g. rod changes and pre-drilled intervals). The
+/// near-zero records and the records less than `length` meters below the
+/// start of each push are flagged.
+fn seating_mask(
+    data: &DataFrame,
+    length: f64,
+    zero_qc: f64,
+) -> Result<BooleanChunked, CoreError> {
+    let depth = data.column(*COL_DEPTH)?.f64()?;
+    let qc = data.column(*COL_QC)?.f64()?;
+
+    // depth of the current push start, set by the first record after a run
+    let mut push_start: Option<f64> = None;
+    let mut at_start = true;
+
+    let mask = depth
+        .into_iter()
+        .zip(qc)
+        .map(|(depth, qc)| {
+            let depth = depth.unwrap_or(f64::NAN);
+            if qc.is_some_and(|qc| qc <= zero_qc) {
+                at_start = true;
+                return true;
+            }
+            if at_start && !depth.is_nan() {
+                push_start = Some(depth);
+                at_start = false;
+            }
+            push_start.is_some_and(|start| depth < start + length)
         })
         .collect();
 
+    Ok(mask)
+}
+
+/// Returns the records `remove_seating_artifacts` flags as seating, with a
+/// reason column, e.g. `remove_seating_artifacts: push start`.
+pub(crate) fn seating_rows(
+    data: &DataFrame,
+    length: Option<f64>,
+    zero_qc: Option<f64>,
+) -> Result<DataFrame, CoreError> {
+    let mask = seating_mask(
+        data,
+        length.unwrap_or(*SEATING_LENGTH),
+        zero_qc.unwrap_or(*SEATING_QC)
+    )?;
+
     let out_data = data
+        .filter(&mask)?
         .lazy()
-        .select(transform_expr)
+        .with_column(
+            lit("remove_seating_artifacts: push start").alias(*COL_DROP_REASON)
+        )
         .collect()?;
 
     Ok(out_data)
 }
+
+/// Removes or masks the seating records of each push (see `seating_mask`),
+/// whose ramping qc and fs would bias rolling averages and layer
+/// statistics. Parameters default to `seating_length`, `seating_qc` and
+/// `seating_artifacts` from the configuration.
+pub(crate) fn remove_seating_artifacts(
+    data: DataFrame,
+    length: Option<f64>,
+    zero_qc: Option<f64>,
+    action: Option<SeatingArtifacts>,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("remove_seating_artifacts");
+
+    let length = length.unwrap_or(*SEATING_LENGTH);
+    let zero_qc = zero_qc.unwrap_or(*SEATING_QC);
+    if length.is_nan() || length < 0.0 || zero_qc.is_nan() {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot remove seating artifacts: length must be >= 0 and zero qc \
+             a number, got {} and {}",
+            length, zero_qc
+        )));
+    }
+
+    let mask = seating_mask(&data, length, zero_qc)?;
+
+    let out_data = match action.unwrap_or(*SEATING_ARTIFACTS) {
+        SeatingArtifacts::Remove => data.filter(&!mask)?,
+        SeatingArtifacts::Mask => {
+            let masked: Vec<Expr> = [*COL_QC, *COL_FS, *COL_U2]
+                .into_iter()
+                .map(|name| {
+                    when(lit(mask.clone().into_series()))
+                        .then(lit(f64::NAN))
+                        .otherwise(col(name))
+                        .alias(name)
+                })
+                .collect();
+            data.lazy().with_columns(masked).collect()?
+        }
+    };
+
+    Ok(out_data)
+}
+
+/// Handling of the dips detected by `clean_rod_changes`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RodChanges {
+    /// Flag the dip records in the rod change column only.
+    Flag,
+    /// Flag the dip records and interpolate qc, fs and u2 across them.
+    Interpolate,
+}
+
+impl RodChanges {
+    /// Builds a handling from its configuration name.
+    ///
+    /// Accepted names: `flag`, `interpolate`.
+    pub fn from_config(name: &str) -> Result<Self, CoreError> {
+        match name {
+            "flag" => Ok(Self::Flag),
+            "interpolate" => Ok(Self::Interpolate),
+            _ => Err(CoreError::InvalidConfig(format!(
+                "Invalid rod_changes parameter: '{}'. Must be flag or \
+                 interpolate",
+                name
+            ))),
+        }
+    }
+}
+
+/// Options of the rod change dip detection; unset fields default to the
+/// `rod_*` parameters of the configuration.
+#[derive(Debug, Clone, Default)]
+pub struct RodChangeOptions {
+    /// Length of the push rods (m), i.e. the expected dip spacing.
+    pub rod_length: Option<f64>,
+    /// Longest dip (m); longer low-qc runs are soil layers.
+    pub max_length: Option<f64>,
+    /// Dip records have qc below this fraction of the local median qc.
+    pub ratio: Option<f64>,
+    /// Largest offset (m) of a dip from the rod length grid.
+    pub tolerance: Option<f64>,
+    /// Whether dips are only flagged or also interpolated.
+    pub action: Option<RodChanges>,
+}
+
+/// Returns the runs of records, as `(start, end)` index ranges, of the qc
+/// dips caused by rod changes.
+///
+/// A dip is a run of records with qc below `ratio` times the median qc of
+/// the records within twice `max_length` above and below, at most
+/// `max_length` thick and bounded by valid records. Dips are kept when
+/// their tops fall on a grid of `rod_length` spacing, within `tolerance`,
+/// shared by at least one other dip; the grid offset is the one matching
+/// the most dips.
+fn rod_change_dips(
+    depth: &[f64],
+    qc: &[f64],
+    rod_length: f64,
+    max_length: f64,
+    ratio: f64,
+    tolerance: f64,
+) -> Vec<(usize, usize)> {
+    let half_window = 2.0 * max_length;
+    let mut low = vec![false; qc.len()];
+    let mut first = 0;
+    let mut last = 0;
+    for i in 0..qc.len() {
+        if !qc[i].is_finite() || !depth[i].is_finite() {
+            continue;
+        }
+        // window of records within half_window of the record
+        while first < i
+            && (depth[first].is_nan() || depth[first] < depth[i] - half_window)
+        {
+            first += 1;
+        }
+        while last + 1 < qc.len() && depth[last + 1] <= depth[i] + half_window {
+            last += 1;
+        }
+        let mut window: Vec<f64> = qc[first..=last.max(i)]
+            .iter()
+            .copied()
+            .filter(|value| value.is_finite())
+            .collect();
+        window.sort_by(f64::total_cmp);
+        let median = window[window.len() / 2];
+        low[i] = qc[i] < ratio * median;
+    }
+
+    let mut candidates = Vec::new();
+    let mut start = 0;
+    while start < qc.len() {
+        if !low[start] {
+            start += 1;
+            continue;
+        }
+        let end = (start..qc.len()).find(|&i| !low[i]).unwrap_or(qc.len());
+        let bounded = start > 0
+            && end < qc.len()
+            && qc[start - 1].is_finite()
+            && qc[end].is_finite();
+        if bounded && depth[end - 1] - depth[start] <= max_length {
+            candidates.push((start, end));
+        }
+        start = end;
+    }
+
+    // distance of a depth difference to the nearest multiple of rod_length
+    let off_grid = |offset: f64| {
+        let remainder = offset.rem_euclid(rod_length);
+        remainder.min(rod_length - remainder)
+    };
+    let on_grid = |(start, _): &(usize, usize), top: f64| {
+        off_grid(depth[*start] - top) <= tolerance
+    };
+
+    let best = candidates
+        .iter()
+        .map(|(start, _)| {
+            let top = depth[*start];
+            let support = candidates.iter().filter(|dip| on_grid(dip, top)).count();
+            (top, support)
+        })
+        .max_by_key(|(_, support)| *support);
+
+    match best {
+        Some((top, support)) if support >= 2 => candidates
+            .into_iter()
+            .filter(|dip| on_grid(dip, top))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Detects the short qc and u2 dips left by rod changes and flags them in
+/// the rod change column, interpolating qc, fs and u2 linearly against
+/// depth across them with `RodChanges::Interpolate` (see
+/// `rod_change_dips`). Returns the data with the number of dips.
+pub(crate) fn clean_rod_changes(
+    data: DataFrame,
+    options: &RodChangeOptions,
+) -> Result<(DataFrame, usize), CoreError> {
+    profile_scope!("clean_rod_changes");
+
+    let rod_length = options.rod_length.unwrap_or(*ROD_LENGTH);
+    let max_length = options.max_length.unwrap_or(*ROD_DIP_LENGTH);
+    let ratio = options.ratio.unwrap_or(*ROD_DIP_RATIO);
+    let tolerance = options.tolerance.unwrap_or(*ROD_TOLERANCE);
+    let action = options.action.unwrap_or(*ROD_CHANGES);
+
+    let positive = [rod_length, max_length, tolerance];
+    if positive.iter().any(|value| value.is_nan() || *value <= 0.0)
+        || !(ratio > 0.0 && ratio < 1.0)
+    {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot detect rod changes: rod length {}, dip length {} and \
+             tolerance {} must be positive and the ratio {} in (0, 1)",
+            rod_length, max_length, tolerance, ratio
+        )));
+    }
+
+    let to_vec = |name: &str| -> Result<Vec<f64>, CoreError> {
+        Ok(data
+            .column(name)?
+            .f64()?
+            .into_iter()
+            .map(|value| value.unwrap_or(f64::NAN))
+            .collect())
+    };
+    let depth = to_vec(*COL_DEPTH)?;
+    let dips = rod_change_dips(
+        &depth,
+        &to_vec(*COL_QC)?,
+        rod_length,
+        max_length,
+        ratio,
+        tolerance
+    );
+
+    let mut flagged = vec![false; data.height()];
+    for &(start, end) in &dips {
+        flagged[start..end].fill(true);
+    }
+    let mut columns = vec![Series::new((*COL_ROD_CHANGE).into(), flagged)];
+
+    if action == RodChanges::Interpolate {
+        for name in [*COL_QC, *COL_FS, *COL_U2] {
+            let mut values = to_vec(name)?;
+            for &(start, end) in &dips {
+                let (z0, v0) = (depth[start - 1], values[start - 1]);
+                let (z1, v1) = (depth[end], values[end]);
+                for i in start..end {
+                    values[i] = v0 + (depth[i] - z0) / (z1 - z0) * (v1 - v0);
+                }
+            }
+            columns.push(Series::new(name.into(), values));
+        }
+    }
+
+    let out_data = data
+        .lazy()
+        .with_columns(columns.into_iter().map(lit).collect::<Vec<_>>())
+        .collect()?;
+
+    Ok((out_data, dips.len()))
+}
+
+/// Returns the indicator values checked in a column: `indicators` plus
+/// those configured for the column in `[input.indicators]`.
+pub(crate) fn column_indicators(name: &str, indicators: &[f64]) -> Vec<f64> {
+    let mut values = indicators.to_vec();
+    if let Some((_, configured)) = COLUMN_INDICATORS
+        .iter()
+        .find(|(column, _)| *column == name)
+    {
+        values.extend(configured);
+    }
+
+    values
+}
+
+/// Returns an expression testing whether a column holds one of its
+/// indicator values (see `column_indicators`).
+fn is_indicator(name: &str, indicators: &[f64]) -> Expr {
+    let indicators = Series::from_vec(
+        "indicators".into(),
+        column_indicators(name, indicators),
+    );
+
+    col(name).is_in(lit(indicators).implode(), false)
+}
+
+/// Returns the columns inspected for indicators: `columns` if given,
+/// otherwise every float column, so the flag columns added by earlier steps
+/// are skipped.
+fn scoped_columns(
+    schema: &Schema,
+    columns: Option<&[&str]>,
+) -> Result<Vec<PlSmallStr>, CoreError> {
+    let Some(columns) = columns else {
+        return Ok(schema
+            .iter()
+            .filter(|(_, dtype)| dtype.is_float())
+            .map(|(name, _)| name.clone())
+            .collect());
+    };
+
+    columns
+        .iter()
+        .map(|&name| match schema.get_full(name) {
+            Some((_, column, dtype)) if dtype.is_primitive_numeric() => {
+                Ok(column.clone())
+            }
+            Some(_) => Err(CoreError::InvalidData(format!(
+                "Cannot match indicators: column '{}' is not numeric",
+                name
+            ))),
+            None => Err(CoreError::InvalidData(format!(
+                "Cannot match indicators: unknown column '{}'",
+                name
+            ))),
+        })
+        .collect()
+}
+
+// NaN or null values are both missing measurements
+fn is_missing(name: &str) -> Expr {
+    col(name).is_null().or(col(name).is_nan())
+}
+
+/// Returns the columns inspected by `drop_nan`: `subset` if given,
+/// otherwise the required input columns.
+fn nan_columns(
+    column_names: &[PlSmallStr],
+    subset: Option<&[&str]>,
+) -> Result<Vec<PlSmallStr>, CoreError> {
+    let required = [*COL_DEPTH, *COL_QC, *COL_FS, *COL_U2];
+
+    subset
+        .unwrap_or(&required)
+        .iter()
+        .map(|&name| {
+            column_names
+                .iter()
+                .find(|column| column.as_str() == name)
+                .cloned()
+                .ok_or_else(|| {
+                    CoreError::InvalidData(format!(
+                        "Cannot drop NaN rows: unknown column '{}'",
+                        name
+                    ))
+                })
+        })
+        .collect()
+}
+
+/// Returns the column names of a lazy frame from its resolved schema.
+fn column_names(data: &mut LazyFrame) -> Result<Vec<PlSmallStr>, CoreError> {
+    Ok(data.collect_schema()?.iter_names_cloned().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sounding() -> DataFrame {
+        DataFrame::new(3, vec![
+            Column::new((*COL_DEPTH).into(), [0.02, 0.04, 0.06]),
+            Column::new((*COL_QC).into(), [1.0, -9999.0, 3.0]),
+            Column::new((*COL_FS).into(), [10.0, 20.0, 30.0]),
+            Column::new("reviewed (?)".into(), [false, true, false]),
+        ])
+        .expect("columns should have the same length")
+    }
+
+    fn values(data: &DataFrame, name: &str) -> Vec<f64> {
+        data.column(name)
+            .and_then(|column| column.f64().cloned())
+            .expect("column should be a float column")
+            .into_iter()
+            .map(|value| value.unwrap_or(f64::NAN))
+            .collect()
+    }
+
+    #[test]
+    fn indicators_skip_boolean_columns() {
+        let removed = remove_rows(sounding(), &[-9999.0], None)
+            .expect("rows should be removed");
+        assert_eq!(values(&removed, *COL_QC), [1.0, 3.0]);
+
+        let dropped = dropped_rows(&sounding(), &[-9999.0], None)
+            .expect("dropped rows should be listed");
+        assert_eq!(values(&dropped, *COL_DEPTH), [0.04]);
+
+        let replaced = replace_rows(sounding(), &[-9999.0], &f64::NAN, &[], None)
+            .expect("rows should be replaced");
+        assert!(values(&replaced, *COL_FS)[1].is_nan());
+    }
+
+    #[test]
+    fn indicators_reject_listed_boolean_columns() {
+        let result = remove_rows(sounding(), &[-9999.0], Some(&["reviewed (?)"]));
+
+        assert!(matches!(result, Err(CoreError::InvalidData(_))));
+    }
+
+    #[test]
+    fn flagged_rows_can_be_removed_or_replaced() {
+        let data = DataFrame::new(5, vec![
+            Column::new((*COL_DEPTH).into(), [0.02, 0.04, 0.06, 0.08, 0.10]),
+            Column::new((*COL_QC).into(), [1.0, -9999.0, 3.0, 4.0, 5.0]),
+            Column::new((*COL_FS).into(), [10.0, 20.0, 30.0, 40.0, 50.0]),
+            Column::new((*COL_U2).into(), [5.0, 6.0, -9999.0, 8.0, 9.0]),
+        ])
+        .expect("columns should have the same length");
+
+        let flagged = flag_indicators(data, &[-9999.0], None)
+            .and_then(|data| {
+                crate::frame::smooth::flag_spikes(data, Some(3), None)
+            })
+            .expect("rows should be flagged");
+        let interpolated = interpolate_rows(flagged.clone(), &[-9999.0], Some(1))
+            .expect("gaps should be interpolated");
+
+        let removed = remove_rows(flagged.clone(), &[-9999.0], None)
+            .expect("flagged rows should be removed");
+        assert_eq!(values(&removed, *COL_QC), [1.0, 4.0, 5.0]);
+
+        let replaced = replace_rows(interpolated, &[-9999.0], &f64::NAN, &[], None)
+            .expect("flagged rows should be replaced");
+        assert_eq!(replaced.height(), 5);
+        assert_eq!(
+            replaced.column(*COL_INDICATOR).map(|column| column.dtype()).ok(),
+            Some(&DataType::Boolean)
+        );
+    }
+
+    #[test]
+    fn replacements_reject_boolean_columns() {
+        let replacements = [("reviewed (?)", Replacement::Value(0.0))];
+        let result = replace_rows(
+            sounding(),
+            &[-9999.0],
+            &f64::NAN,
+            &replacements,
+            None
+        );
+
+        assert!(matches!(result, Err(CoreError::InvalidData(_))));
+    }
+}
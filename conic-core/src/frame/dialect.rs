@@ -0,0 +1,241 @@
+use std::fmt;
+use crate::kernel::config::input_dialect;
+
+/// Number of leading bytes inspected when guessing the dialect of a file.
+pub const SNIFF_BYTES: usize = 1024;
+
+/// Field delimiters considered by `sniff_dialect`, in order of preference.
+const DELIMITERS: [u8; 4] = [b';', b'\t', b'|', b','];
+
+/// Character encoding of a CSV source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Latin1,
+}
+
+impl Encoding {
+    /// Parses an encoding label such as `utf-8` or `latin-1`.
+    pub fn parse(label: &str) -> Option<Self> {
+        match label.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "utf-8" | "utf8" => Some(Self::Utf8),
+            "latin-1" | "latin1" | "iso-8859-1" => Some(Self::Latin1),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Utf8 => write!(f, "utf-8"),
+            Self::Latin1 => write!(f, "latin-1"),
+        }
+    }
+}
+
+/// Layout of a CSV source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvDialect {
+    /// Field delimiter.
+    pub delimiter: u8,
+    /// True if numbers use a decimal comma (e.g. `0,614`).
+    pub decimal_comma: bool,
+    /// Zero-based line index of the header row; lines above it (instrument
+    /// preambles, project notes) are skipped.
+    pub header_row: usize,
+    pub encoding: Encoding,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            decimal_comma: false,
+            header_row: 0,
+            encoding: Encoding::Utf8,
+        }
+    }
+}
+
+impl CsvDialect {
+    /// Returns the dialect as processing step parameters.
+    pub(crate) fn parameters(&self) -> Vec<(&'static str, String)> {
+        let delimiter = match self.delimiter {
+            b'\t' => "'\\t'".to_string(),
+            other => format!("'{}'", other as char),
+        };
+        let decimal = if self.decimal_comma { "','" } else { "'.'" };
+
+        vec![
+            ("delimiter", delimiter),
+            ("decimal", decimal.to_string()),
+            ("header_row", self.header_row.to_string()),
+            ("encoding", self.encoding.to_string()),
+        ]
+    }
+}
+
+/// Guesses the dialect of a CSV source from its first `SNIFF_BYTES` bytes.
+///
+/// - Encoding: Latin-1 if the sample is not valid UTF-8 (ignoring a
+///   character cut at the end of the sample).
+/// - Delimiter: the candidate splitting most sample lines into the same
+///   number of fields; ties prefer `;`, tab and `|` over `,`, since a comma
+///   delimiter is ambiguous with decimal commas.
+/// - Header row: the first line with that number of fields, skipping
+///   preambles and `#` comments.
+/// - Decimal mark: comma if the delimiter is not a comma and data fields
+///   hold numbers such as `0,614`.
+///
+/// Settings of the `[input.dialect]` configuration take precedence over the
+/// guessed ones, which fall back to the defaults (`,`, `.`, first row,
+/// UTF-8) when the sample is inconclusive.
+pub fn sniff_dialect(bytes: &[u8]) -> CsvDialect {
+    let sample = &bytes[..bytes.len().min(SNIFF_BYTES)];
+    let explicit = input_dialect();
+
+    let encoding = match std::str::from_utf8(sample) {
+        Ok(_) => Encoding::Utf8,
+        // the sample may end in the middle of a multi-byte character
+        Err(err) if err.error_len().is_none() => Encoding::Utf8,
+        Err(_) => Encoding::Latin1,
+    };
+    let encoding = explicit
+        .encoding
+        .as_deref()
+        .and_then(Encoding::parse)
+        .unwrap_or(encoding);
+
+    let text = decode(sample, encoding);
+    let mut lines: Vec<&str> = text.lines().collect();
+    // the last line is usually cut by the sample limit
+    if sample.len() == SNIFF_BYTES && lines.len() > 1 {
+        lines.pop();
+    }
+
+    let guessed = DELIMITERS
+        .iter()
+        .filter_map(|&delimiter| {
+            let (fields, lines) = modal_field_count(&lines, delimiter)?;
+            Some((delimiter, fields, lines))
+        })
+        // max_by_key returns the last maximum, so reverse to prefer the first
+        .rev()
+        .max_by_key(|&(_, fields, lines)| (lines, fields));
+
+    let delimiter = explicit
+        .delimiter
+        .as_deref()
+        .and_then(parse_delimiter)
+        .unwrap_or_else(|| guessed.map_or(b',', |(delimiter, _, _)| delimiter));
+    let fields = guessed
+        .filter(|&(guessed, _, _)| guessed == delimiter)
+        .map(|(_, fields, _)| fields);
+
+    let header_row = match explicit.header_row {
+        Some(row) => row,
+        None => fields
+            .and_then(|fields| {
+                lines.iter().position(|line| {
+                    !line.starts_with('#') && split(line, delimiter).count() == fields
+                })
+            })
+            .unwrap_or(0),
+    };
+
+    let decimal_comma = match explicit.decimal.as_deref() {
+        Some(",") => true,
+        Some(_) => false,
+        None => {
+            delimiter != b','
+                && lines
+                    .iter()
+                    .skip(header_row + 1)
+                    .flat_map(|line| split(line, delimiter))
+                    .any(is_decimal_comma)
+        }
+    };
+
+    CsvDialect { delimiter, decimal_comma, header_row, encoding }
+}
+
+/// Converts a CSV source to UTF-8 text starting at its header row.
+///
+/// A UTF-8 byte order mark is removed, so the first header is matched
+/// against the configured names.
+pub(crate) fn normalize(bytes: &[u8], dialect: &CsvDialect) -> Vec<u8> {
+    let text = decode(bytes, dialect.encoding);
+    let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+
+    if dialect.header_row == 0 {
+        return text.as_bytes().to_vec();
+    }
+
+    text.split_inclusive('\n')
+        .skip(dialect.header_row)
+        .collect::<String>()
+        .into_bytes()
+}
+
+fn decode(bytes: &[u8], encoding: Encoding) -> String {
+    match encoding {
+        Encoding::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+        // Latin-1 code points map one-to-one to the first 256 chars
+        Encoding::Latin1 => bytes.iter().map(|&byte| byte as char).collect(),
+    }
+}
+
+// most frequent field count above 1 and the number of lines having it
+fn modal_field_count(lines: &[&str], delimiter: u8) -> Option<(usize, usize)> {
+    let mut counts: Vec<(usize, usize)> = Vec::new();
+
+    for line in lines.iter().filter(|line| !line.starts_with('#')) {
+        let fields = split(line, delimiter).count();
+        if fields < 2 {
+            continue;
+        }
+        match counts.iter_mut().find(|(count, _)| *count == fields) {
+            Some((_, lines)) => *lines += 1,
+            None => counts.push((fields, 1)),
+        }
+    }
+
+    counts.into_iter().max_by_key(|&(fields, lines)| (lines, fields))
+}
+
+// splits a line on the delimiter, ignoring delimiters inside quotes
+fn split(line: &str, delimiter: u8) -> impl Iterator<Item = &str> {
+    let delimiter = delimiter as char;
+    let mut in_quotes = false;
+
+    line.split(move |ch: char| {
+        if ch == '"' {
+            in_quotes = !in_quotes;
+        }
+        ch == delimiter && !in_quotes
+    })
+}
+
+fn is_decimal_comma(field: &str) -> bool {
+    let field = field.trim().trim_start_matches(['-', '+']);
+
+    match field.split_once(',') {
+        Some((int, frac)) => {
+            !int.is_empty()
+                && !frac.is_empty()
+                && int.chars().all(|ch| ch.is_ascii_digit())
+                && frac.chars().all(|ch| ch.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+/// Parses a delimiter label: a single ASCII character, or `\t`/`tab`.
+pub(crate) fn parse_delimiter(label: &str) -> Option<u8> {
+    match label {
+        "\\t" | "\t" | "tab" => Some(b'\t'),
+        label if label.len() == 1 && label.is_ascii() => Some(label.as_bytes()[0]),
+        _ => None,
+    }
+}
@@ -0,0 +1,134 @@
+use polars::prelude::*;
+use crate::kernel::CoreError;
+use crate::kernel::profile::profile_scope;
+use super::read::schema_mismatch;
+use crate::kernel::config::{
+    COL_DEPTH, COL_QT, COL_FS, COL_U2, COL_QTN, COL_IC,
+    COL_DMT_DEPTH, COL_DMT_ID, COL_DMT_KD, COL_DMT_ED,
+};
+
+const COL_ED_QT: &str = "ED/qt (adim.)";
+
+/// Reads a DMT (flat dilatometer) sounding from a CSV file.
+///
+/// Required columns: Depth, ID, KD, ED (names from `[input.dmt_columns]`).
+/// All columns are cast to `Float64`.
+pub fn read_dmt_csv(file_path: &str) -> Result<DataFrame, CoreError> {
+    profile_scope!("read_dmt_csv");
+
+    let required_columns = [*COL_DMT_DEPTH, *COL_DMT_ID, *COL_DMT_KD, *COL_DMT_ED];
+
+    let raw_data = CsvReadOptions::default()
+        .with_has_header(true)
+        .try_into_reader_with_file_path(Some(file_path.into()))?
+        .finish()
+        .map_err(|err| {
+            CoreError::InvalidData(format!(
+                "Failed to read DMT file '{}': {}",
+                file_path, err
+            ))
+        })?;
+
+    let column_names: Vec<String> = raw_data
+        .get_column_names()
+        .into_iter()
+        .map(|name| name.to_string())
+        .collect();
+
+    let required_keys = ["depth", "id", "kd", "ed"];
+    let missing = required_columns
+        .iter()
+        .zip(required_keys)
+        .find(|(col, _)| !column_names.iter().any(|name| name == **col));
+    if let Some((&missing, key)) = missing {
+        return Err(schema_mismatch(
+            &format!("input.dmt_columns.{}", key),
+            &[missing.to_string()],
+            &column_names,
+        ));
+    }
+
+    let cast_exprs: Vec<Expr> = required_columns
+        .iter()
+        .map(|&col_name| col(col_name).cast(DataType::Float64))
+        .collect();
+
+    let out_data = raw_data
+        .lazy()
+        .select(cast_exprs)
+        .sort([*COL_DMT_DEPTH], Default::default())
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Builds a side-by-side CPT–DMT comparison table at the DMT depths.
+///
+/// Each DMT reading is paired with the mean of the CPT records lying within
+/// `window / 2` of its depth. CPT columns missing from `cpt` (e.g. Ic before
+/// `add_behavior_cols`) are skipped. Adds the `ED/qt` ratio used in standard
+/// CPT–DMT comparisons when qt is available.
+pub(crate) fn align_dmt(
+    cpt: &DataFrame,
+    dmt: &DataFrame,
+    window: f64,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("align_dmt");
+
+    if window.is_nan() || window <= 0.0 {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot align DMT data: window must be positive, got {}",
+            window
+        )));
+    }
+
+    let cpt_depth = cpt.column(*COL_DEPTH)?.f64()?;
+    let cpt_depth: Vec<f64> = cpt_depth
+        .into_iter()
+        .map(|depth| depth.unwrap_or(f64::NAN))
+        .collect();
+    let dmt_depth = dmt.column(*COL_DMT_DEPTH)?.f64()?;
+
+    let cpt_cols: Vec<&str> = [*COL_QT, *COL_FS, *COL_U2, *COL_QTN, *COL_IC]
+        .into_iter()
+        .filter(|name| cpt.column(name).is_ok())
+        .collect();
+
+    let mut aligned_columns = Vec::with_capacity(cpt_cols.len());
+
+    for &name in &cpt_cols {
+        let values = cpt.column(name)?.f64()?;
+
+        let aligned: Vec<f64> = dmt_depth
+            .into_iter()
+            .map(|depth| {
+                let Some(depth) = depth else { return f64::NAN };
+
+                let (sum, count) = cpt_depth
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, cpt_z)| (*cpt_z - depth).abs() <= window / 2.0)
+                    .filter_map(|(i, _)| values.get(i))
+                    .filter(|value| !value.is_nan())
+                    .fold((0.0, 0usize), |(sum, count), value| {
+                        (sum + value, count + 1)
+                    });
+
+                if count == 0 { f64::NAN } else { sum / count as f64 }
+            })
+            .collect();
+
+        aligned_columns.push(lit(Series::new(name.into(), aligned)));
+    }
+
+    let mut out_data = dmt.clone().lazy().with_columns(aligned_columns);
+
+    if cpt_cols.contains(&*COL_QT) {
+        // ED in MPa, qt in MPa
+        out_data = out_data.with_column(
+            (col(*COL_DMT_ED) / col(*COL_QT)).alias(COL_ED_QT)
+        );
+    }
+
+    Ok(out_data.collect()?)
+}
@@ -1,13 +1,22 @@
+use std::collections::HashMap;
+use std::ops::Bound;
 use polars::prelude::*;
 use polars::series::ops::NullBehavior;
 use crate::kernel::CoreError;
-use crate::kernel::config::COL_DEPTH;
+use crate::kernel::profile::profile_scope;
+use crate::kernel::config::{
+    COL_DEPTH, COL_QC, COL_FS, COL_U2, COL_DROP_REASON, COL_INCLINATION,
+    COL_PUSH_DEPTH, DUPLICATE_DEPTHS, NON_MONOTONIC_DEPTH, EMPTY_TOLERANCE,
+    REFUSAL_QC, REFUSAL_RATIO, REFUSAL, AGGREGATION, REPUSH
+};
 
 pub(crate) fn adjust_depth(
     data: DataFrame,
     start_depth: Option<f64>,
     spacing: Option<f64>,
 ) -> Result<DataFrame, CoreError> {
+    profile_scope!("adjust_depth");
+
     let n_rows = data.height();
 
     if n_rows == 0 {
@@ -83,3 +92,1177 @@ pub(crate) fn adjust_depth(
 
     Ok(out_data)
 }
+
+pub(crate) fn bin_depth(data: DataFrame, step: f64) -> Result<DataFrame, CoreError> {
+    if step.is_nan() || step <= 0.0 {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot bin depth: step must be positive, got {}",
+            step
+        )));
+    }
+
+    // snap to the nearest multiple of step, then round to 3 decimal places so
+    // equal bins produce bit-identical keys
+    let binned_depth = data
+        .column(*COL_DEPTH)?
+        .f64()?
+        .apply_values(|depth| {
+            let snapped = (depth / step).round() * step;
+            (snapped * 1000.0).round() / 1000.0
+        })
+        .into_series();
+
+    let out_data = data
+        .lazy()
+        .with_column(lit(binned_depth).alias(*COL_DEPTH))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Aggregation of the records within each interval of `downsample`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aggregation {
+    /// Mean of the finite values.
+    Mean,
+    /// Median of the finite values, robust to spikes within an interval.
+    Median,
+}
+
+impl Aggregation {
+    /// Builds an aggregation from its configuration name.
+    ///
+    /// Accepted names: `mean`, `median`.
+    pub fn from_config(name: &str) -> Result<Self, CoreError> {
+        match name {
+            "mean" => Ok(Self::Mean),
+            "median" => Ok(Self::Median),
+            _ => Err(CoreError::InvalidConfig(format!(
+                "Invalid aggregation parameter: '{}'. Must be mean or median",
+                name
+            ))),
+        }
+    }
+}
+
+/// Aggregates the records into depth intervals of `interval` meters.
+///
+/// Intervals are bounded by multiples of `interval`, and each interval with
+/// records becomes one record at its midpoint depth (rounded to 3 decimal
+/// places). Float columns are aggregated with `aggregation` (default
+/// `aggregation` from the configuration) ignoring NaN values; other columns
+/// keep the value of the first record. Records with a NaN depth are dropped.
+pub(crate) fn downsample(
+    data: DataFrame,
+    interval: f64,
+    aggregation: Option<Aggregation>,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("downsample");
+
+    if interval.is_nan() || interval <= 0.0 {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot downsample: interval must be positive, got {}",
+            interval
+        )));
+    }
+
+    let aggregation = aggregation.unwrap_or(*AGGREGATION);
+
+    let midpoint_depth = data
+        .column(*COL_DEPTH)?
+        .f64()?
+        .apply_values(|depth| {
+            let midpoint = ((depth / interval).floor() + 0.5) * interval;
+            (midpoint * 1000.0).round() / 1000.0
+        })
+        .into_series();
+
+    let aggregations: Vec<Expr> = data
+        .schema()
+        .iter()
+        .filter(|(name, _)| name.as_str() != *COL_DEPTH)
+        .map(|(name, dtype)| {
+            let column = col(name.clone());
+            if !dtype.is_float() {
+                return column.first();
+            }
+            // NaN values would otherwise spread to the whole interval
+            let finite = column.fill_nan(lit(NULL));
+            let aggregated = match aggregation {
+                Aggregation::Mean => finite.mean(),
+                Aggregation::Median => finite.median(),
+            };
+            aggregated.fill_null(lit(f64::NAN))
+        })
+        .collect();
+
+    let out_data = data
+        .lazy()
+        .with_column(lit(midpoint_depth).alias(*COL_DEPTH))
+        .filter(col(*COL_DEPTH).is_not_nan())
+        .group_by_stable([col(*COL_DEPTH)])
+        .agg(aggregations)
+        .sort([*COL_DEPTH], Default::default())
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Keeps the records with depths within `[min_depth, max_depth]`; a None
+/// limit leaves that end open. Records with a NaN depth are dropped.
+pub(crate) fn trim_depth(
+    data: DataFrame,
+    min_depth: Option<f64>,
+    max_depth: Option<f64>,
+) -> Result<DataFrame, CoreError> {
+    if let (Some(min_depth), Some(max_depth)) = (min_depth, max_depth)
+        && min_depth > max_depth
+    {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot trim depth: minimum {} is greater than maximum {}",
+            min_depth, max_depth
+        )));
+    }
+    if min_depth.is_some_and(f64::is_nan) || max_depth.is_some_and(f64::is_nan) {
+        return Err(CoreError::InvalidData(
+            "Cannot trim depth: depth limits must not be NaN".to_string()
+        ));
+    }
+
+    let min_depth = min_depth.unwrap_or(f64::NEG_INFINITY);
+    let max_depth = max_depth.unwrap_or(f64::INFINITY);
+
+    let out_data = data
+        .lazy()
+        .filter(
+            col(*COL_DEPTH)
+                .gt_eq(lit(min_depth))
+                .and(col(*COL_DEPTH).lt_eq(lit(max_depth)))
+        )
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Keeps the records with depths within `start` and `end`, each included,
+/// excluded or unbounded. Records with a NaN depth are dropped.
+pub(crate) fn slice_by_depth(
+    data: DataFrame,
+    start: Bound<f64>,
+    end: Bound<f64>,
+) -> Result<DataFrame, CoreError> {
+    let limit = |bound: Bound<f64>| match bound {
+        Bound::Included(depth) | Bound::Excluded(depth) => Some(depth),
+        Bound::Unbounded => None,
+    };
+    if limit(start).is_some_and(f64::is_nan) || limit(end).is_some_and(f64::is_nan) {
+        return Err(CoreError::InvalidData(
+            "Cannot slice by depth: depth limits must not be NaN".to_string()
+        ));
+    }
+    if let (Some(start), Some(end)) = (limit(start), limit(end))
+        && start > end
+    {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot slice by depth: start {} is greater than end {}",
+            start, end
+        )));
+    }
+
+    let depth = || col(*COL_DEPTH);
+    let above_start = match start {
+        Bound::Included(start) => depth().gt_eq(lit(start)),
+        Bound::Excluded(start) => depth().gt(lit(start)),
+        Bound::Unbounded => depth().is_not_nan(),
+    };
+    let below_end = match end {
+        Bound::Included(end) => depth().lt_eq(lit(end)),
+        Bound::Excluded(end) => depth().lt(lit(end)),
+        Bound::Unbounded => depth().is_not_nan(),
+    };
+
+    let out_data = data
+        .lazy()
+        .filter(above_start.and(below_end))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Returns whether each record belongs to the leading or trailing run of
+/// empty records, whose qc and fs are both NaN or within `tolerance` of 0.
+fn empty_edge_mask(
+    data: &DataFrame,
+    tolerance: f64,
+) -> Result<BooleanChunked, CoreError> {
+    let is_empty = |value: Option<f64>| {
+        value.is_none_or(|value| value.is_nan() || value.abs() <= tolerance)
+    };
+    let empty: Vec<bool> = data
+        .column(*COL_QC)?
+        .f64()?
+        .into_iter()
+        .zip(data.column(*COL_FS)?.f64()?)
+        .map(|(qc, fs)| is_empty(qc) && is_empty(fs))
+        .collect();
+
+    let leading = empty.iter().take_while(|&&empty| empty).count();
+    let trailing = empty[leading..].iter().rev().take_while(|&&empty| empty).count();
+    let n_rows = empty.len();
+
+    Ok((0..n_rows)
+        .map(|i| i < leading || i >= n_rows - trailing)
+        .collect())
+}
+
+/// Returns the records `trim_empty_rows` removes, with a reason column,
+/// e.g. `trim_empty_rows: empty record`.
+pub(crate) fn empty_edge_rows(
+    data: &DataFrame,
+    tolerance: Option<f64>,
+) -> Result<DataFrame, CoreError> {
+    let mask = empty_edge_mask(data, tolerance.unwrap_or(*EMPTY_TOLERANCE))?;
+
+    let out_data = data
+        .filter(&mask)?
+        .lazy()
+        .with_column(lit("trim_empty_rows: empty record").alias(*COL_DROP_REASON))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Removes the runs of empty records at the top and bottom of a sounding,
+/// e.g. zero-filled rows logged before ground contact and after extraction.
+///
+/// A record is empty when qc and fs are both NaN or within `tolerance`
+/// (default `empty_tolerance` from the configuration) of 0. Empty records
+/// between valid ones are kept.
+pub(crate) fn trim_empty_rows(
+    data: DataFrame,
+    tolerance: Option<f64>,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("trim_empty_rows");
+
+    let tolerance = tolerance.unwrap_or(*EMPTY_TOLERANCE);
+    if tolerance.is_nan() || tolerance < 0.0 {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot trim empty rows: tolerance must be >= 0, got {}",
+            tolerance
+        )));
+    }
+
+    let mask = empty_edge_mask(&data, tolerance)?;
+
+    Ok(data.filter(&!mask)?)
+}
+
+/// Handling of the refusal records at the end of a sounding, where qc climbs
+/// to the capacity of the cone and the push is stopped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Refusal {
+    /// Only detect the termination depth.
+    Detect,
+    /// Drop the refusal records.
+    Truncate,
+}
+
+impl Refusal {
+    /// Builds a handling from its configuration name.
+    ///
+    /// Accepted names: `detect`, `truncate`.
+    pub fn from_config(name: &str) -> Result<Self, CoreError> {
+        match name {
+            "detect" => Ok(Self::Detect),
+            "truncate" => Ok(Self::Truncate),
+            _ => Err(CoreError::InvalidConfig(format!(
+                "Invalid refusal parameter: '{}'. Must be detect or truncate",
+                name
+            ))),
+        }
+    }
+}
+
+/// Returns whether each record belongs to the refusal at the end of the
+/// sounding: the trailing run of records with qc at or above `ratio` times
+/// `capacity`, provided qc reaches `capacity` within it. NaN qc values
+/// neither start nor break the run.
+fn refusal_mask(
+    data: &DataFrame,
+    capacity: f64,
+    ratio: f64,
+) -> Result<BooleanChunked, CoreError> {
+    let qc: Vec<Option<f64>> = data
+        .column(*COL_QC)?
+        .f64()?
+        .into_iter()
+        .map(|qc| qc.filter(|qc| !qc.is_nan()))
+        .collect();
+    let n_rows = qc.len();
+
+    let run = qc
+        .iter()
+        .rev()
+        .take_while(|qc| qc.is_none_or(|qc| qc >= ratio * capacity))
+        .count();
+    // leading NaN values of the run belong to the test above it
+    let start = (n_rows - run..n_rows)
+        .find(|&i| qc[i].is_some())
+        .unwrap_or(n_rows);
+    let refusal = qc[start..].iter().flatten().any(|&qc| qc >= capacity);
+
+    Ok((0..n_rows).map(|i| refusal && i >= start).collect())
+}
+
+/// Returns the records `detect_refusal` flags as refusal, with a reason
+/// column, e.g. `detect_refusal: refusal`.
+pub(crate) fn refusal_rows(
+    data: &DataFrame,
+    capacity: Option<f64>,
+    ratio: Option<f64>,
+) -> Result<DataFrame, CoreError> {
+    let mask = refusal_mask(
+        data,
+        capacity.unwrap_or(*REFUSAL_QC),
+        ratio.unwrap_or(*REFUSAL_RATIO)
+    )?;
+
+    let out_data = data
+        .filter(&mask)?
+        .lazy()
+        .with_column(lit("detect_refusal: refusal").alias(*COL_DROP_REASON))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Detects the refusal at the end of a sounding (see `refusal_mask`) and,
+/// with `Refusal::Truncate`, drops its records, whose qc reflects the
+/// capacity of the cone rather than the soil. Parameters default to
+/// `refusal_qc`, `refusal_ratio` and `refusal` from the configuration.
+pub(crate) fn detect_refusal(
+    data: DataFrame,
+    capacity: Option<f64>,
+    ratio: Option<f64>,
+    action: Option<Refusal>,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("detect_refusal");
+
+    let capacity = capacity.unwrap_or(*REFUSAL_QC);
+    let ratio = ratio.unwrap_or(*REFUSAL_RATIO);
+    if capacity.is_nan() || capacity <= 0.0 || !(ratio > 0.0 && ratio <= 1.0) {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot detect refusal: capacity must be positive and ratio in \
+             (0, 1], got {} and {}",
+            capacity, ratio
+        )));
+    }
+
+    let out_data = match action.unwrap_or(*REFUSAL) {
+        Refusal::Detect => data,
+        Refusal::Truncate => {
+            let mask = refusal_mask(&data, capacity, ratio)?;
+            data.filter(&!mask)?
+        }
+    };
+
+    Ok(out_data)
+}
+
+/// Interpolates every channel onto a regular depth grid of `spacing`.
+///
+/// The grid covers the multiples of `spacing` between the first and last
+/// depths, with depths rounded to 3 decimal places. Float columns are
+/// interpolated linearly between the records bracketing each grid depth
+/// (NaN if either is NaN); other columns take the value of the nearest
+/// record. Records with a NaN depth are ignored.
+pub(crate) fn resample(data: DataFrame, spacing: f64) -> Result<DataFrame, CoreError> {
+    profile_scope!("resample");
+
+    if spacing.is_nan() || spacing <= 0.0 {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot resample: spacing must be positive, got {}",
+            spacing
+        )));
+    }
+
+    let data = data
+        .lazy()
+        .filter(col(*COL_DEPTH).is_not_nan())
+        .collect()?;
+    let depth: Vec<f64> = data
+        .column(*COL_DEPTH)?
+        .f64()?
+        .into_no_null_iter()
+        .collect();
+
+    let (Some(&first), Some(&last)) = (depth.first(), depth.last()) else {
+        return Err(CoreError::InvalidData(
+            "Cannot resample: no records with a valid depth".to_string()
+        ));
+    };
+    if depth.windows(2).any(|pair| pair[1] < pair[0]) {
+        return Err(CoreError::InvalidData(
+            "Cannot resample: depth must not decrease (see \
+             `ensure_monotonic_depth`)".to_string()
+        ));
+    }
+
+    let round = |depth: f64| (depth * 1000.0).round() / 1000.0;
+    // tolerance so depths on the grid are not skipped by rounding errors
+    let start = (first / spacing - 1e-9).ceil() as i64;
+    let grid: Vec<f64> = (start..)
+        .map(|i| round(i as f64 * spacing))
+        .take_while(|&z| z <= last + 1e-9)
+        .collect();
+
+    // record above (or at) each grid depth and interpolation weight of the
+    // record below it
+    let mut lower = 0;
+    let brackets: Vec<(usize, f64)> = grid
+        .iter()
+        .map(|&z| {
+            while lower + 1 < depth.len() && depth[lower + 1] <= z {
+                lower += 1;
+            }
+            let upper = (lower + 1).min(depth.len() - 1);
+            let span = depth[upper] - depth[lower];
+            let weight = if span > 0.0 {
+                ((z - depth[lower]) / span).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            (lower, weight)
+        })
+        .collect();
+
+    let nearest: Vec<IdxSize> = brackets
+        .iter()
+        .map(|&(lower, weight)| {
+            let index = if weight > 0.5 { lower + 1 } else { lower };
+            index as IdxSize
+        })
+        .collect();
+    let mut out_data = data.take(&IdxCa::from_vec("nearest".into(), nearest))?;
+
+    let float_names: Vec<PlSmallStr> = data
+        .schema()
+        .iter()
+        .filter(|(name, dtype)| dtype.is_float() && name.as_str() != *COL_DEPTH)
+        .map(|(name, _)| name.clone())
+        .collect();
+    for name in float_names {
+        let values: Vec<f64> = data
+            .column(&name)?
+            .f64()?
+            .into_iter()
+            .map(|value| value.unwrap_or(f64::NAN))
+            .collect();
+        let interpolated: Vec<f64> = brackets
+            .iter()
+            .map(|&(lower, weight)| {
+                if weight == 0.0 {
+                    values[lower]
+                } else {
+                    values[lower] + weight * (values[lower + 1] - values[lower])
+                }
+            })
+            .collect();
+        out_data.with_column(Column::new(name, interpolated))?;
+    }
+    out_data.with_column(Column::new((*COL_DEPTH).into(), grid))?;
+
+    Ok(out_data)
+}
+
+/// Zero readings of a channel, in the units of its column.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ZeroReadings {
+    /// Reading of the unloaded cone before the push.
+    pub before: f64,
+    /// Reading of the unloaded cone after extraction.
+    pub after: f64,
+}
+
+/// Zero drift of the channels corrected by `baseline_correct`; channels
+/// left None are not corrected.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Baseline {
+    pub fs: Option<ZeroReadings>,
+    pub u2: Option<ZeroReadings>,
+}
+
+/// Removes sensor zero drift from fs and u2.
+///
+/// The zero offset of each channel is assumed to drift linearly over depth
+/// from its reading before the push, at the first record, to its reading
+/// after extraction, at the last record; the offset at each depth is
+/// subtracted from the channel.
+pub(crate) fn baseline_correct(
+    data: DataFrame,
+    baseline: &Baseline,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("baseline_correct");
+
+    let channels: Vec<(&str, ZeroReadings)> = [
+        (*COL_FS, baseline.fs),
+        (*COL_U2, baseline.u2),
+    ]
+    .into_iter()
+    .filter_map(|(name, readings)| Some((name, readings?)))
+    .collect();
+
+    if let Some((name, _)) = channels.iter().find(|(_, readings)| {
+        !readings.before.is_finite() || !readings.after.is_finite()
+    }) {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot correct baseline: zero readings of '{}' must be finite",
+            name
+        )));
+    }
+    if channels.is_empty() {
+        return Ok(data);
+    }
+
+    let depth = data.column(*COL_DEPTH)?.f64()?;
+    let (Some(top), Some(bottom)) = (depth.first(), depth.last()) else {
+        return Err(CoreError::InvalidData(
+            "Cannot correct baseline: first and last depths are required"
+                .to_string()
+        ));
+    };
+
+    // fraction of the push completed at each record
+    let progress = if bottom > top {
+        (col(*COL_DEPTH) - lit(top)) / lit(bottom - top)
+    } else {
+        lit(0.0)
+    };
+
+    let corrections: Vec<Expr> = channels
+        .into_iter()
+        .map(|(name, readings)| {
+            let offset = lit(readings.before)
+                + progress.clone() * lit(readings.after - readings.before);
+            (col(name) - offset).alias(name)
+        })
+        .collect();
+
+    let out_data = data.lazy().with_columns(corrections).collect()?;
+
+    Ok(out_data)
+}
+
+/// Converts depths along the push into vertical depths using the
+/// inclination column.
+///
+/// The vertical depth is integrated down the push, each depth increment
+/// being multiplied by the cosine of the mean inclination of the records
+/// bounding it; the first depth is multiplied by the cosine of the first
+/// inclination. NaN inclinations take the last valid reading above (0 at the
+/// top of the push). The depth column holds the vertical depth and the
+/// original depths are kept in the push depth column. Data without an
+/// inclination column is returned unchanged.
+pub(crate) fn correct_inclination(data: DataFrame) -> Result<DataFrame, CoreError> {
+    profile_scope!("correct_inclination");
+
+    if data.column(*COL_INCLINATION).is_err() {
+        return Ok(data);
+    }
+    if data.column(*COL_PUSH_DEPTH).is_ok() {
+        return Err(CoreError::InvalidData(
+            "Cannot correct inclination: depth is already corrected".to_string()
+        ));
+    }
+
+    let push_depth = data.column(*COL_DEPTH)?.f64()?;
+    let mut last_inclination = 0.0;
+    let inclination: Vec<f64> = data
+        .column(*COL_INCLINATION)?
+        .f64()?
+        .into_iter()
+        .map(|value| {
+            if let Some(value) = value.filter(|value| value.is_finite()) {
+                last_inclination = value;
+            }
+            last_inclination
+        })
+        .collect();
+
+    if let Some(value) = inclination.iter().find(|value| value.abs() >= 90.0) {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot correct inclination: {} degrees is not a downward push",
+            value
+        )));
+    }
+
+    let mut vertical_depth = Vec::with_capacity(inclination.len());
+    let mut previous: Option<(f64, f64, f64)> = None;
+    for (depth, &inclination) in push_depth.into_iter().zip(&inclination) {
+        let depth = depth.unwrap_or(f64::NAN);
+        let vertical = match previous {
+            None => depth * inclination.to_radians().cos(),
+            Some((above, vertical_above, inclination_above)) => {
+                let mean = (inclination + inclination_above) / 2.0;
+                vertical_above + (depth - above) * mean.to_radians().cos()
+            }
+        };
+        // NaN depths are skipped so they do not break the integration
+        if !depth.is_nan() {
+            previous = Some((depth, vertical, inclination));
+        }
+        vertical_depth.push(vertical);
+    }
+
+    let out_data = data
+        .lazy()
+        .with_columns([
+            col(*COL_DEPTH).alias(*COL_PUSH_DEPTH),
+            lit(Series::new((*COL_DEPTH).into(), vertical_depth)),
+        ])
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Inserts rows with NaN measurements where depth intervals are missing.
+///
+/// A gap is an increment larger than 1.5 times `spacing`; it is filled with
+/// rows on a `spacing` grid starting at the record above the gap. If
+/// `spacing` is None, the median depth increment is used.
+pub(crate) fn fill_depth_gaps(
+    data: DataFrame,
+    spacing: Option<f64>,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("fill_depth_gaps");
+
+    let depth: Vec<f64> = data
+        .column(*COL_DEPTH)?
+        .f64()?
+        .into_iter()
+        .map(|depth| depth.unwrap_or(f64::NAN))
+        .collect();
+
+    let spacing = match spacing {
+        Some(spacing) => spacing,
+        None => {
+            let mut increments: Vec<f64> = depth
+                .windows(2)
+                .map(|pair| pair[1] - pair[0])
+                .filter(|increment| *increment > 0.0)
+                .collect();
+            if increments.is_empty() {
+                return Err(CoreError::InvalidData(
+                    "Cannot fill depth gaps: no positive depth increments to \
+                     infer the spacing from".to_string()
+                ));
+            }
+            increments.sort_by(f64::total_cmp);
+            increments[increments.len() / 2]
+        }
+    };
+
+    if spacing.is_nan() || spacing <= 0.0 {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot fill depth gaps: spacing must be positive, got {}",
+            spacing
+        )));
+    }
+
+    let mut gap_depths = Vec::new();
+    for pair in depth.windows(2) {
+        let (top, bottom) = (pair[0], pair[1]);
+        // NaN depths never open a gap
+        let is_gap = bottom - top > 1.5 * spacing;
+        if !is_gap {
+            continue;
+        }
+
+        let missing = ((bottom - top) / spacing).round() as usize - 1;
+        for step in 1..=missing {
+            let depth = top + step as f64 * spacing;
+            // round to 3 decimal places like binned depths
+            gap_depths.push((depth * 1000.0).round() / 1000.0);
+        }
+    }
+
+    if gap_depths.is_empty() {
+        return Ok(data);
+    }
+
+    let gap_rows = DataFrame::new(gap_depths.len(), vec![
+        Column::new((*COL_DEPTH).into(), gap_depths),
+    ])?;
+
+    // inserted rows get nulls, turned into NaN like other missing values
+    let fill_nan: Vec<Expr> = data
+        .schema()
+        .iter()
+        .filter(|(_, dtype)| **dtype == DataType::Float64)
+        .map(|(name, _)| col(name.clone()).fill_null(lit(f64::NAN)))
+        .collect();
+
+    let out_data = polars::functions::concat_df_diagonal(&[data, gap_rows])?
+        .lazy()
+        .with_columns(fill_nan)
+        .sort(
+            [*COL_DEPTH],
+            SortMultipleOptions::default().with_maintain_order(true),
+        )
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Resolution of records sharing the same depth, e.g. after re-strokes or
+/// overlapping pushes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DuplicateDepths {
+    /// Keep the first record recorded at each depth.
+    KeepFirst,
+    /// Keep the last record recorded at each depth.
+    KeepLast,
+    /// Average the numeric values of the records at each depth; other
+    /// columns keep the first value.
+    Average,
+}
+
+impl DuplicateDepths {
+    /// Builds a resolution strategy from its configuration name.
+    ///
+    /// Accepted names: `keep_first`, `keep_last`, `average`.
+    pub fn from_config(name: &str) -> Result<Self, CoreError> {
+        match name {
+            "keep_first" => Ok(Self::KeepFirst),
+            "keep_last" => Ok(Self::KeepLast),
+            "average" => Ok(Self::Average),
+            _ => Err(CoreError::InvalidConfig(format!(
+                "Invalid duplicate_depths parameter: '{}'. Must be keep_first, \
+                 keep_last or average",
+                name
+            ))),
+        }
+    }
+}
+
+/// Temporary column giving each record with a NaN or null depth a group of
+/// its own in `resolve_duplicate_depths`.
+const COL_DEPTH_KEY: &str = "depth key [duplicates]";
+
+/// Collapses records sharing the same depth into one record per depth.
+///
+/// Records keep the order of the first record at each depth. Records with a
+/// NaN or null depth share no depth with any other record and are kept
+/// unchanged.
+pub(crate) fn resolve_duplicate_depths(
+    data: DataFrame,
+    strategy: Option<DuplicateDepths>,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("resolve_duplicate_depths");
+
+    let strategy = strategy.unwrap_or(*DUPLICATE_DEPTHS);
+
+    let aggregations: Vec<Expr> = data
+        .schema()
+        .iter()
+        .filter(|(name, _)| name.as_str() != *COL_DEPTH)
+        .map(|(name, dtype)| {
+            let column = col(name.clone());
+            match strategy {
+                DuplicateDepths::KeepFirst => column.first(),
+                DuplicateDepths::KeepLast => column.last(),
+                DuplicateDepths::Average if dtype.is_float() => column.mean(),
+                DuplicateDepths::Average => column.first(),
+            }
+        })
+        .collect();
+
+    // the row index keys records without a depth apart; 0 for the rest
+    let missing_depth = col(*COL_DEPTH).is_nan().fill_null(lit(true));
+    let out_data = data
+        .lazy()
+        .with_row_index(COL_DEPTH_KEY, None)
+        .with_column(
+            when(missing_depth)
+                .then(col(COL_DEPTH_KEY))
+                .otherwise(lit(0).cast(IDX_DTYPE))
+                .alias(COL_DEPTH_KEY)
+        )
+        .group_by_stable([col(*COL_DEPTH), col(COL_DEPTH_KEY)])
+        .agg(aggregations)
+        .drop(cols([COL_DEPTH_KEY]))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Returns the records `resolve_duplicate_depths` collapses with
+/// `strategy`, with a reason column, e.g. `resolve_duplicate_depths:
+/// duplicate depth`: the records after the first (`KeepFirst`) or before the
+/// last (`KeepLast`) at each depth, or every record of a repeated depth
+/// (`Average`). Records with a NaN or null depth are never collapsed.
+pub(crate) fn duplicate_depth_rows(
+    data: &DataFrame,
+    strategy: Option<DuplicateDepths>,
+) -> Result<DataFrame, CoreError> {
+    let strategy = strategy.unwrap_or(*DUPLICATE_DEPTHS);
+    let depth: Vec<Option<f64>> = data
+        .column(*COL_DEPTH)?
+        .f64()?
+        .into_iter()
+        .map(|depth| depth.filter(|depth| !depth.is_nan()))
+        .collect();
+
+    // first and last record and record count of each depth; adding 0.0
+    // turns -0.0 into 0.0, which polars groups together
+    let mut groups: HashMap<u64, (usize, usize, usize)> = HashMap::new();
+    for (i, depth) in depth.iter().enumerate() {
+        if let Some(depth) = depth {
+            let group = groups.entry((depth + 0.0).to_bits()).or_insert((i, i, 0));
+            group.1 = i;
+            group.2 += 1;
+        }
+    }
+
+    let mask: BooleanChunked = depth
+        .iter()
+        .enumerate()
+        .map(|(i, depth)| {
+            let Some(depth) = depth else {
+                return false;
+            };
+            let (first, last, count) = groups[&(depth + 0.0).to_bits()];
+            match strategy {
+                DuplicateDepths::KeepFirst => i != first,
+                DuplicateDepths::KeepLast => i != last,
+                DuplicateDepths::Average => count > 1,
+            }
+        })
+        .collect();
+
+    let out_data = data
+        .filter(&mask)?
+        .lazy()
+        .with_column(
+            lit("resolve_duplicate_depths: duplicate depth").alias(*COL_DROP_REASON)
+        )
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Handling of records shallower than a record above them, e.g. reversed
+/// chunks from concatenated files.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NonMonotonicDepth {
+    /// Fail with an error naming the first decreasing depth.
+    Error,
+    /// Drop the records shallower than the deepest record above them.
+    Drop,
+    /// Sort the records by depth, keeping the order of equal depths.
+    Sort,
+}
+
+impl NonMonotonicDepth {
+    /// Builds a policy from its configuration name.
+    ///
+    /// Accepted names: `error`, `drop`, `sort`.
+    pub fn from_config(name: &str) -> Result<Self, CoreError> {
+        match name {
+            "error" => Ok(Self::Error),
+            "drop" => Ok(Self::Drop),
+            "sort" => Ok(Self::Sort),
+            _ => Err(CoreError::InvalidConfig(format!(
+                "Invalid non_monotonic_depth parameter: '{}'. Must be error, \
+                 drop or sort",
+                name
+            ))),
+        }
+    }
+}
+
+/// Returns whether each record is shallower than the deepest record above
+/// it. Records with a NaN depth are never flagged.
+fn decreasing_depth_mask(data: &DataFrame) -> Result<BooleanChunked, CoreError> {
+    let mut deepest = f64::NEG_INFINITY;
+
+    let mask = data
+        .column(*COL_DEPTH)?
+        .f64()?
+        .into_iter()
+        .map(|depth| match depth {
+            Some(depth) if depth < deepest => true,
+            Some(depth) if !depth.is_nan() => {
+                deepest = depth;
+                false
+            }
+            _ => false,
+        })
+        .collect();
+
+    Ok(mask)
+}
+
+/// Returns the records `ensure_monotonic_depth` treats as out of order,
+/// with a reason column, e.g. `ensure_monotonic_depth: depth decreases`.
+pub(crate) fn decreasing_depth_rows(data: &DataFrame) -> Result<DataFrame, CoreError> {
+    let mask = decreasing_depth_mask(data)?;
+
+    let out_data = data
+        .filter(&mask)?
+        .lazy()
+        .with_column(
+            lit("ensure_monotonic_depth: depth decreases").alias(*COL_DROP_REASON)
+        )
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Detects depths decreasing down the records and handles them according
+/// to `policy` (default `non_monotonic_depth` from the configuration).
+pub(crate) fn ensure_monotonic_depth(
+    data: DataFrame,
+    policy: Option<NonMonotonicDepth>,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("ensure_monotonic_depth");
+
+    let mask = decreasing_depth_mask(&data)?;
+    let affected = mask.sum().unwrap_or(0);
+    if affected == 0 {
+        return Ok(data);
+    }
+
+    match policy.unwrap_or(*NON_MONOTONIC_DEPTH) {
+        NonMonotonicDepth::Error => {
+            let row = mask.into_iter().position(|flag| flag == Some(true));
+            let depth = row
+                .and_then(|row| data.column(*COL_DEPTH).ok()?.f64().ok()?.get(row))
+                .unwrap_or(f64::NAN);
+            Err(CoreError::InvalidData(format!(
+                "Depth decreases at {} records, first at record {} ({} m)",
+                affected,
+                row.map_or(0, |row| row + 1),
+                depth
+            )))
+        }
+        NonMonotonicDepth::Drop => Ok(data.filter(&!mask)?),
+        NonMonotonicDepth::Sort => Ok(data.sort(
+            [*COL_DEPTH],
+            SortMultipleOptions::default().with_maintain_order(true),
+        )?),
+    }
+}
+
+/// Resolution of the overlap between pushes when a test is restarted, e.g.
+/// after an obstruction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Repush {
+    /// Keep the records of the later push within the overlap.
+    KeepSecond,
+    /// Keep the depths of the later push within the overlap, averaging its
+    /// float values with those of the earlier push interpolated at them.
+    Average,
+}
+
+impl Repush {
+    /// Builds a resolution strategy from its configuration name.
+    ///
+    /// Accepted names: `keep_second`, `average`.
+    pub fn from_config(name: &str) -> Result<Self, CoreError> {
+        match name {
+            "keep_second" => Ok(Self::KeepSecond),
+            "average" => Ok(Self::Average),
+            _ => Err(CoreError::InvalidConfig(format!(
+                "Invalid repush parameter: '{}'. Must be keep_second or average",
+                name
+            ))),
+        }
+    }
+}
+
+/// Profile produced by `merge_repushes`.
+pub(crate) struct MergedPushes {
+    /// Single profile with increasing depths.
+    pub data: DataFrame,
+    /// Records of earlier pushes replaced within the overlaps, with a reason
+    /// column, e.g. `merge_repushes: overlapped by push 2`.
+    pub replaced: DataFrame,
+    /// Depth range of each overlap: start of the later push and deepest
+    /// record of the earlier ones.
+    pub overlaps: Vec<(f64, f64)>,
+}
+
+/// Merges pushes restarted at a shallower depth into one profile.
+///
+/// A push restarts wherever the depth drops below the deepest record above
+/// it; the earlier records at or below the restart depth form the overlap.
+/// They are replaced by the records of the restarted push and, with
+/// `Repush::Average`, averaged into its float values. Non-float columns keep
+/// the values of the later push. Records with a NaN depth are kept in place.
+pub(crate) fn merge_repushes(
+    data: DataFrame,
+    strategy: Option<Repush>,
+) -> Result<MergedPushes, CoreError> {
+    profile_scope!("merge_repushes");
+
+    let strategy = strategy.unwrap_or(*REPUSH);
+    let depth: Vec<f64> = data
+        .column(*COL_DEPTH)?
+        .f64()?
+        .into_iter()
+        .map(|depth| depth.unwrap_or(f64::NAN))
+        .collect();
+
+    let mut kept: Vec<usize> = Vec::new();
+    // replaced records of each overlap, and the overlap each kept record
+    // falls within
+    let mut overlapped: Vec<Vec<usize>> = Vec::new();
+    // earlier records interpolated within each overlap: the replaced ones
+    // and the record above them
+    let mut earlier: Vec<Vec<usize>> = Vec::new();
+    let mut partner: Vec<Option<usize>> = vec![None; depth.len()];
+    let mut overlaps = Vec::new();
+    let mut deepest = f64::NEG_INFINITY;
+
+    for (i, &z) in depth.iter().enumerate() {
+        if z < deepest {
+            let split = kept
+                .iter()
+                .rposition(|&j| depth[j] < z)
+                .map_or(0, |position| position + 1);
+            let replaced = kept.split_off(split);
+            earlier.push(kept.last().into_iter().chain(&replaced).copied().collect());
+            overlapped.push(replaced);
+            overlaps.push((z, deepest));
+            deepest = z;
+        }
+        if let Some(&(start, end)) = overlaps.last()
+            && z >= start
+            && z <= end
+        {
+            partner[i] = Some(overlaps.len() - 1);
+        }
+        if z > deepest {
+            deepest = z;
+        }
+        kept.push(i);
+    }
+
+    let indices = |rows: &[usize]| {
+        IdxCa::from_vec(
+            "rows".into(),
+            rows.iter().map(|&row| row as IdxSize).collect()
+        )
+    };
+    let mut out_data = data.take(&indices(&kept))?;
+
+    if strategy == Repush::Average && !overlaps.is_empty() {
+        let float_names: Vec<PlSmallStr> = data
+            .schema()
+            .iter()
+            .filter(|(name, dtype)| dtype.is_float() && name.as_str() != *COL_DEPTH)
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in float_names {
+            let values: Vec<f64> = data
+                .column(&name)?
+                .f64()?
+                .into_iter()
+                .map(|value| value.unwrap_or(f64::NAN))
+                .collect();
+            let averaged: Vec<f64> = kept
+                .iter()
+                .map(|&i| {
+                    let Some(overlap) = partner[i] else { return values[i] };
+                    let previous =
+                        interpolate_at(&earlier[overlap], &depth, &values, depth[i]);
+                    match (values[i].is_nan(), previous.is_nan()) {
+                        (false, false) => (values[i] + previous) / 2.0,
+                        (true, _) => previous,
+                        (false, true) => values[i],
+                    }
+                })
+                .collect();
+            out_data.with_column(Column::new(name, averaged))?;
+        }
+    }
+
+    let replaced_rows: Vec<usize> = overlapped.iter().flatten().copied().collect();
+    let reasons: Vec<String> = overlapped
+        .iter()
+        .enumerate()
+        .flat_map(|(overlap, rows)| {
+            let reason = format!("merge_repushes: overlapped by push {}", overlap + 2);
+            std::iter::repeat_n(reason, rows.len())
+        })
+        .collect();
+    let mut replaced = data.take(&indices(&replaced_rows))?;
+    replaced.with_column(Column::new((*COL_DROP_REASON).into(), reasons))?;
+
+    Ok(MergedPushes { data: out_data, replaced, overlaps })
+}
+
+// linear interpolation at `z` of the values of `rows`, ordered by depth;
+// NaN outside their depth range
+fn interpolate_at(rows: &[usize], depth: &[f64], values: &[f64], z: f64) -> f64 {
+    for pair in rows.windows(2) {
+        let (upper, lower) = (pair[0], pair[1]);
+        if depth[upper] <= z && z <= depth[lower] {
+            let span = depth[lower] - depth[upper];
+            if span <= 0.0 {
+                return values[upper];
+            }
+            let weight = (z - depth[upper]) / span;
+            return values[upper] + weight * (values[lower] - values[upper]);
+        }
+    }
+
+    match rows {
+        [row] if depth[*row] == z => values[*row],
+        _ => f64::NAN,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sounding() -> DataFrame {
+        DataFrame::new(5, vec![
+            Column::new((*COL_DEPTH).into(), [0.02, f64::NAN, 0.02, f64::NAN, 0.04]),
+            Column::new((*COL_QC).into(), [1.0, 2.0, 3.0, 4.0, 5.0]),
+        ])
+        .expect("columns should have the same length")
+    }
+
+    fn values(data: &DataFrame, name: &str) -> Vec<f64> {
+        data.column(name)
+            .and_then(|column| column.f64().cloned())
+            .expect("column should be a float column")
+            .into_iter()
+            .map(|value| value.unwrap_or(f64::NAN))
+            .collect()
+    }
+
+    #[test]
+    fn duplicate_depths_keep_nan_depth_records_apart() {
+        let data = resolve_duplicate_depths(sounding(), Some(DuplicateDepths::Average))
+            .expect("duplicate depths should collapse");
+
+        assert_eq!(values(&data, *COL_QC), [2.0, 2.0, 4.0, 5.0]);
+        let depth = values(&data, *COL_DEPTH);
+        assert!(depth[1].is_nan() && depth[2].is_nan());
+    }
+
+    #[test]
+    fn duplicate_depth_rows_are_the_collapsed_records() {
+        let first = duplicate_depth_rows(&sounding(), Some(DuplicateDepths::KeepFirst))
+            .expect("collapsed records should be found");
+        assert_eq!(values(&first, *COL_QC), [3.0]);
+
+        let average = duplicate_depth_rows(&sounding(), Some(DuplicateDepths::Average))
+            .expect("collapsed records should be found");
+        assert_eq!(values(&average, *COL_QC), [1.0, 3.0]);
+    }
+}
@@ -1,3 +1,13 @@
+pub mod ascii;
+pub mod backend;
 pub mod clean;
+pub mod dialect;
 pub mod read;
 pub mod fix;
+pub mod units;
+pub mod write;
+pub mod smooth;
+pub mod dmt;
+#[cfg(feature = "sqlite")]
+pub mod project;
+pub mod qa;
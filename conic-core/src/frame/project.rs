@@ -0,0 +1,373 @@
+use polars::prelude::*;
+use rusqlite::types::Value;
+use rusqlite::{Connection, OptionalExtension, params};
+use crate::kernel::{ConicDataFrame, CoreError};
+use crate::math::sbt::SbtLayer;
+
+/// Table of a project database holding the layer boundaries of each
+/// sounding.
+pub const LAYERS_TABLE: &str = "layers";
+
+/// Layer of a sounding as stored in the `layers` table of a project
+/// database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectLayer {
+    /// Depth of the top of the layer (m).
+    pub top: f64,
+    /// Depth of the bottom of the layer (m).
+    pub bottom: f64,
+    /// Soil description shown for the layer.
+    pub label: String,
+}
+
+impl From<&SbtLayer> for ProjectLayer {
+    fn from(layer: &SbtLayer) -> Self {
+        Self {
+            top: layer.top,
+            bottom: layer.bottom,
+            label: format!("{} {}", layer.zone.number(), layer.zone.description()),
+        }
+    }
+}
+
+/// Returns the sounding IDs stored in `table`, in the order they were first
+/// appended.
+pub fn sounding_ids(db_path: &str, table: &str) -> Result<Vec<String>, CoreError> {
+    let conn = open(db_path)?;
+    check_table(&conn, db_path, table)?;
+
+    let ids = conn
+        .prepare(&format!(
+            "SELECT sounding_id FROM {} GROUP BY sounding_id ORDER BY min(rowid)",
+            quote(table)
+        ))?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+
+    Ok(ids)
+}
+
+/// Reads the records of a sounding appended to `table` by `to_sqlite`.
+///
+/// REAL columns are read as f64 with NULL as NaN, BOOLEAN columns as
+/// booleans, INTEGER columns as i64 and any other column as text. The
+/// `sounding_id` column itself is left out.
+///
+/// # Errors
+///
+/// Returns `CoreError::InvalidData` if the database or table does not exist
+/// or holds no records of `sounding_id`.
+pub fn read_sounding(
+    db_path: &str,
+    table: &str,
+    sounding_id: &str,
+) -> Result<ConicDataFrame, CoreError> {
+    let conn = open(db_path)?;
+    check_table(&conn, db_path, table)?;
+
+    let schema: Vec<(String, String)> = conn
+        .prepare(&format!("PRAGMA table_info({})", quote(table)))?
+        .query_map([], |row| Ok((row.get(1)?, row.get(2)?)))?
+        .collect::<Result<_, _>>()?;
+    let schema: Vec<(String, String)> = schema
+        .into_iter()
+        .filter(|(name, _)| name != "sounding_id")
+        .collect();
+
+    let selected: Vec<String> = schema.iter().map(|(name, _)| quote(name)).collect();
+    let mut statement = conn.prepare(&format!(
+        "SELECT {} FROM {} WHERE sounding_id = ?1 ORDER BY rowid",
+        selected.join(", "),
+        quote(table)
+    ))?;
+    let rows: Vec<Vec<Value>> = statement
+        .query_map([sounding_id], |row| {
+            (0..schema.len()).map(|i| row.get::<_, Value>(i)).collect()
+        })?
+        .collect::<Result<_, _>>()?;
+
+    if rows.is_empty() {
+        return Err(CoreError::InvalidData(format!(
+            "No records of sounding '{}' in table '{}' of '{}'",
+            sounding_id, table, db_path
+        )));
+    }
+
+    let columns: Vec<Column> = schema
+        .iter()
+        .enumerate()
+        .map(|(i, (name, sql_type))| {
+            let values = rows.iter().map(|row| &row[i]);
+            sql_column(name, sql_type, values)
+        })
+        .collect();
+    let data = DataFrame::new(rows.len(), columns)?;
+
+    Ok(ConicDataFrame::new(data))
+}
+
+/// Returns the layers stored for a sounding, top to bottom, or an empty list
+/// if none were stored.
+pub fn read_layers(
+    db_path: &str,
+    sounding_id: &str,
+) -> Result<Vec<ProjectLayer>, CoreError> {
+    let conn = open(db_path)?;
+    if !has_table(&conn, LAYERS_TABLE)? {
+        return Ok(Vec::new());
+    }
+
+    let layers = conn
+        .prepare(&format!(
+            "SELECT top, bottom, label FROM {} WHERE sounding_id = ?1 ORDER BY layer",
+            quote(LAYERS_TABLE)
+        ))?
+        .query_map([sounding_id], |row| {
+            Ok(ProjectLayer {
+                top: row.get(0)?,
+                bottom: row.get(1)?,
+                label: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    Ok(layers)
+}
+
+/// Replaces the layers stored for a sounding in the `layers` table, creating
+/// the table on first use. Layers are numbered from 1, top to bottom.
+/// Returns the number of stored layers.
+///
+/// # Errors
+///
+/// Returns `CoreError::InvalidData` if a layer has a non-finite limit or a
+/// bottom above its top.
+pub fn write_layers(
+    db_path: &str,
+    sounding_id: &str,
+    layers: &[ProjectLayer],
+) -> Result<usize, CoreError> {
+    if let Some(layer) = layers.iter().find(|layer| {
+        !layer.top.is_finite() || !layer.bottom.is_finite() || layer.bottom < layer.top
+    }) {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot store layer '{}': invalid limits {} - {} m",
+            layer.label, layer.top, layer.bottom
+        )));
+    }
+
+    let mut conn = open(db_path)?;
+    let tx = conn.transaction()?;
+
+    tx.execute(
+        &format!(
+            "CREATE TABLE IF NOT EXISTS {} (sounding_id TEXT NOT NULL, \
+             layer INTEGER NOT NULL, top REAL NOT NULL, bottom REAL NOT NULL, \
+             label TEXT)",
+            quote(LAYERS_TABLE)
+        ),
+        [],
+    )?;
+    tx.execute(
+        &format!("DELETE FROM {} WHERE sounding_id = ?1", quote(LAYERS_TABLE)),
+        [sounding_id],
+    )?;
+
+    {
+        let mut statement = tx.prepare(&format!(
+            "INSERT INTO {} (sounding_id, layer, top, bottom, label) \
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            quote(LAYERS_TABLE)
+        ))?;
+        for (i, layer) in layers.iter().enumerate() {
+            statement.execute(params![
+                sounding_id,
+                i as i64 + 1,
+                layer.top,
+                layer.bottom,
+                layer.label
+            ])?;
+        }
+    }
+
+    tx.commit()?;
+
+    Ok(layers.len())
+}
+
+/// Builds a column from the values of a SQLite column of type `sql_type`.
+fn sql_column<'a>(
+    name: &str,
+    sql_type: &str,
+    values: impl Iterator<Item = &'a Value>,
+) -> Column {
+    match sql_type.to_ascii_uppercase().as_str() {
+        "REAL" => {
+            let values: Vec<f64> = values
+                .map(|value| match value {
+                    Value::Real(value) => *value,
+                    Value::Integer(value) => *value as f64,
+                    _ => f64::NAN,
+                })
+                .collect();
+            Column::new(name.into(), values)
+        }
+        "BOOLEAN" => {
+            let values: Vec<Option<bool>> = values
+                .map(|value| match value {
+                    Value::Integer(value) => Some(*value != 0),
+                    _ => None,
+                })
+                .collect();
+            Column::new(name.into(), values)
+        }
+        "INTEGER" => {
+            let values: Vec<Option<i64>> = values
+                .map(|value| match value {
+                    Value::Integer(value) => Some(*value),
+                    _ => None,
+                })
+                .collect();
+            Column::new(name.into(), values)
+        }
+        _ => {
+            let values: Vec<Option<String>> = values
+                .map(|value| match value {
+                    Value::Null => None,
+                    Value::Text(value) => Some(value.clone()),
+                    Value::Integer(value) => Some(value.to_string()),
+                    Value::Real(value) => Some(value.to_string()),
+                    Value::Blob(_) => None,
+                })
+                .collect();
+            Column::new(name.into(), values)
+        }
+    }
+}
+
+// opens an existing database; Connection::open would create an empty one
+fn open(db_path: &str) -> Result<Connection, CoreError> {
+    if !std::path::Path::new(db_path).is_file() {
+        return Err(CoreError::InvalidData(format!(
+            "Project database '{}' does not exist",
+            db_path
+        )));
+    }
+
+    Ok(Connection::open(db_path)?)
+}
+
+fn has_table(conn: &Connection, table: &str) -> Result<bool, CoreError> {
+    let found = conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            [table],
+            |_| Ok(()),
+        )
+        .optional()?;
+
+    Ok(found.is_some())
+}
+
+fn check_table(conn: &Connection, db_path: &str, table: &str) -> Result<(), CoreError> {
+    if has_table(conn, table)? {
+        Ok(())
+    } else {
+        Err(CoreError::InvalidData(format!(
+            "No table '{}' in project database '{}'",
+            table, db_path
+        )))
+    }
+}
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::write::{WriteOptions, to_sqlite};
+
+    // removes the test database when dropped, also on failed assertions
+    struct TempDb(String);
+
+    impl Drop for TempDb {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn temp_db(name: &str) -> TempDb {
+        let path = std::env::temp_dir().join(name);
+        let path = path.to_str().expect("temp path should be UTF-8").to_string();
+        let _ = std::fs::remove_file(&path);
+        TempDb(path)
+    }
+
+    #[test]
+    fn soundings_round_trip_through_the_project_database() {
+        let db = temp_db("conic_project_soundings_test.db");
+        let options = WriteOptions {
+            ascii_headers: Some(false),
+            depth_bin: Some(0.0),
+            ..Default::default()
+        };
+        let data = DataFrame::new(2, vec![
+            Column::new("depth".into(), [0.02, 0.04]),
+            Column::new("qc".into(), [1.5, f64::NAN]),
+            Column::new("reliable".into(), [true, false]),
+            Column::new("flags".into(), [0u32, 5]),
+        ])
+        .expect("columns should have the same length");
+        to_sqlite(&data, &db.0, "soundings", "CPT-02", &options)
+            .expect("sounding should be written");
+        to_sqlite(&data, &db.0, "soundings", "CPT-01", &options)
+            .expect("sounding should be written");
+
+        let ids = sounding_ids(&db.0, "soundings").expect("IDs should be listed");
+        assert_eq!(ids, ["CPT-02", "CPT-01"]);
+
+        let read = read_sounding(&db.0, "soundings", "CPT-01")
+            .expect("sounding should be read back");
+        let read = read.inner();
+        assert_eq!(read.get_column_names(), ["depth", "qc", "reliable", "flags"]);
+        let qc = read.column("qc").and_then(|column| column.f64().cloned());
+        let qc: Vec<f64> = qc.expect("qc should be a float column")
+            .into_no_null_iter()
+            .collect();
+        assert!(qc[0] == 1.5 && qc[1].is_nan());
+        assert_eq!(read.column("reliable").map(|column| column.dtype()).ok(),
+            Some(&DataType::Boolean));
+        assert_eq!(read.column("flags").map(|column| column.dtype()).ok(),
+            Some(&DataType::Int64));
+
+        let missing = read_sounding(&db.0, "soundings", "CPT-03");
+        assert!(matches!(missing, Err(CoreError::InvalidData(_))));
+    }
+
+    #[test]
+    fn layers_replace_the_stored_layers_of_a_sounding() {
+        let db = temp_db("conic_project_layers_test.db");
+        Connection::open(&db.0).expect("database should be created");
+        assert!(read_layers(&db.0, "CPT-01").expect("layers should read").is_empty());
+
+        let layer = |top: f64, bottom: f64, label: &str| ProjectLayer {
+            top,
+            bottom,
+            label: label.to_string(),
+        };
+        write_layers(&db.0, "CPT-01", &[layer(0.0, 2.0, "fill")])
+            .expect("layers should be stored");
+        write_layers(&db.0, "CPT-02", &[layer(0.0, 1.0, "sand")])
+            .expect("layers should be stored");
+        let edited = [layer(0.0, 1.5, "fill"), layer(1.5, 4.0, "clay")];
+        write_layers(&db.0, "CPT-01", &edited).expect("layers should be replaced");
+
+        assert_eq!(read_layers(&db.0, "CPT-01").expect("layers should read"), edited);
+        assert_eq!(read_layers(&db.0, "CPT-02").expect("layers should read").len(), 1);
+
+        let inverted = write_layers(&db.0, "CPT-01", &[layer(2.0, 1.0, "clay")]);
+        assert!(matches!(inverted, Err(CoreError::InvalidData(_))));
+    }
+}
@@ -0,0 +1,519 @@
+use polars::prelude::*;
+use crate::kernel::CoreError;
+use crate::kernel::profile::profile_scope;
+use crate::kernel::config::{
+    COL_DEPTH, COL_QC, COL_FS, COL_U2, COL_U0, COL_FS_OK, COL_U2_OK, COL_U2_SAT,
+    COL_U2_LOSS, COL_RANGE_FLAGS, CLOG_WINDOW, CLOG_FS_RISE, CLOG_QC_STALL,
+    U2_FLAT_RANGE, UNRELIABLE_WEIGHT, DESAT_WINDOW, DESAT_DENSE_QC, DESAT_DAMPING,
+    SAT_LOSS_WINDOW, SAT_LOSS_U2, SAT_LOSS_RF, RANGE_MIN_QC, RANGE_MIN_FS,
+    RANGE_MIN_U2, RANGE_MAX_DEPTH_JUMP
+};
+
+/// Flags intervals of possible sleeve clogging and blocked filters.
+///
+/// A centered window of `window` samples is moved along the sounding:
+/// - fs is flagged where it rises by more than `fs_rise` (relative to its
+///   window mean) while qc changes by less than `qc_stall`, a typical sign of
+///   soil packed around the friction sleeve.
+/// - u2 is flagged where its range stays within `u2_flat` kPa while qc
+///   changes by more than `qc_stall`, suggesting a blocked filter.
+///
+/// All records of a flagged window are marked. Adds one boolean reliability
+/// column per channel (true = reliable).
+pub(crate) fn flag_clogging(
+    data: DataFrame,
+    window: Option<usize>,
+    fs_rise: Option<f64>,
+    qc_stall: Option<f64>,
+    u2_flat: Option<f64>,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("flag_clogging");
+
+    let window = window.unwrap_or(*CLOG_WINDOW);
+    let fs_rise = fs_rise.unwrap_or(*CLOG_FS_RISE);
+    let qc_stall = qc_stall.unwrap_or(*CLOG_QC_STALL);
+    let u2_flat = u2_flat.unwrap_or(*U2_FLAT_RANGE);
+
+    if window < 2 {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot flag clogging: window must be at least 2 samples, got {}",
+            window
+        )));
+    }
+
+    let qc = values(&data, *COL_QC)?;
+    let fs = values(&data, *COL_FS)?;
+    let u2 = values(&data, *COL_U2)?;
+
+    let n_rows = data.height();
+    let mut fs_ok = vec![true; n_rows];
+    let mut u2_ok = vec![true; n_rows];
+
+    for start in 0..n_rows.saturating_sub(window - 1) {
+        let end = start + window - 1;
+
+        let qc_change = relative_change(&qc[start..=end]);
+        let fs_change = relative_change(&fs[start..=end]);
+
+        if qc_change.abs() < qc_stall && fs_change > fs_rise {
+            fs_ok[start..=end].fill(false);
+        }
+
+        let (u2_min, u2_max) = range(&u2[start..=end]);
+        if u2_max - u2_min <= u2_flat && qc_change.abs() > qc_stall {
+            u2_ok[start..=end].fill(false);
+        }
+    }
+
+    let out_data = data
+        .lazy()
+        .with_columns([
+            lit(Series::new((*COL_FS_OK).into(), fs_ok)),
+            lit(Series::new((*COL_U2_OK).into(), u2_ok)),
+        ])
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Flags the depths below which the u2 filter has likely lost saturation.
+///
+/// Dense sand is recognized where qc is at least `dense_qc` MPa and u2 stays
+/// at or below u0 (dilative response) for at least half a window. The pore
+/// pressure response is measured by its gain, the ratio of the standard
+/// deviations of the u2 and qc increments over a centered window of
+/// `window` samples, and is only evaluated in undrained records (u2 above
+/// u0) whose window does not reach into dense sand. If the median gain over
+/// the first `window` such records below a dense sand layer drops under
+/// `damping` times the median gain above the first layer, the filter is
+/// assumed desaturated from the base of that layer to the end of the
+/// sounding, since it cannot resaturate during the push.
+///
+/// Adds a boolean column (true = saturated); u2-based parameters such as Bq
+/// should be distrusted where it is false. Nothing is flagged if no
+/// undrained records precede the first dense sand layer, as no reference
+/// response is available.
+pub(crate) fn flag_desaturation(
+    data: DataFrame,
+    window: Option<usize>,
+    dense_qc: Option<f64>,
+    damping: Option<f64>,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("flag_desaturation");
+
+    let window = window.unwrap_or(*DESAT_WINDOW);
+    let dense_qc = dense_qc.unwrap_or(*DESAT_DENSE_QC);
+    let damping = damping.unwrap_or(*DESAT_DAMPING);
+
+    if window < 2 {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot flag desaturation: window must be at least 2 samples, got {}",
+            window
+        )));
+    }
+
+    let qc = values(&data, *COL_QC)?;
+    let u2 = values(&data, *COL_U2)?;
+    let u0 = values(&data, *COL_U0)?;
+
+    let n_rows = data.height();
+    let half = window / 2;
+
+    let dense: Vec<bool> = (0..n_rows)
+        .map(|i| qc[i] >= dense_qc && u2[i] <= u0[i])
+        .collect();
+    // gains of windows reaching into the sand reflect the qc contrast at
+    // the layer boundary rather than the filter response
+    let usable: Vec<bool> = (0..n_rows)
+        .map(|i| {
+            let start = i.saturating_sub(half);
+            let end = (i + half + 1).min(n_rows);
+            u2[i] > u0[i] && !dense[start..end].contains(&true)
+        })
+        .collect();
+    let gain = response_gain(&qc, &u2, window);
+
+    let mut saturated = vec![true; n_rows];
+
+    let first_sand = dense.iter().position(|&is_dense| is_dense).unwrap_or(n_rows);
+    let above: Vec<f64> = (0..first_sand)
+        .filter(|&i| usable[i])
+        .map(|i| gain[i])
+        .collect();
+
+    if let Some(reference) = median(&above) {
+        let mut i = first_sand;
+
+        while i < n_rows {
+            let run = dense[i..].iter().take_while(|&&is_dense| is_dense).count();
+            if run == 0 {
+                i += 1;
+                continue;
+            }
+
+            let run_end = i + run;
+            // thin dense lenses are left to the clogging heuristics
+            if run >= half {
+                let below: Vec<f64> = (run_end..n_rows)
+                    .filter(|&j| usable[j])
+                    .take(window)
+                    .map(|j| gain[j])
+                    .collect();
+
+                if let Some(below) = median(&below)
+                    && below < damping * reference
+                {
+                    saturated[run_end..].fill(false);
+                    break;
+                }
+            }
+
+            i = run_end;
+        }
+    }
+
+    let out_data = data
+        .lazy()
+        .with_column(lit(Series::new((*COL_U2_SAT).into(), saturated)))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Flags intervals where the u2 sensor has likely lost saturation.
+///
+/// In clayey soil below the water table the cone generates large excess
+/// pore pressures, so u2 should stay well above u0. A record is suspect
+/// where its friction ratio fs/qc is at least `min_rf` percent, |u2| is at
+/// most `max_u2` kPa and u0 exceeds `max_u2`. The friction ratio uses qc
+/// rather than qt, as the u2 correction is itself unreliable there. Windows
+/// of `window` suspect records whose u2 range stays within `u2_flat_range`
+/// kPa are flagged.
+///
+/// Adds a boolean column (true = saturation lost), where Bq and Ic should
+/// be distrusted, and returns the depth interval (top, bottom) of each run
+/// of flagged records.
+pub(crate) fn flag_saturation_loss(
+    data: DataFrame,
+    window: Option<usize>,
+    max_u2: Option<f64>,
+    min_rf: Option<f64>,
+) -> Result<(DataFrame, Vec<(f64, f64)>), CoreError> {
+    profile_scope!("flag_saturation_loss");
+
+    let window = window.unwrap_or(*SAT_LOSS_WINDOW);
+    let max_u2 = max_u2.unwrap_or(*SAT_LOSS_U2);
+    let min_rf = min_rf.unwrap_or(*SAT_LOSS_RF);
+
+    if window < 2 {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot flag saturation loss: window must be at least 2 samples, got {}",
+            window
+        )));
+    }
+
+    let depth = values(&data, *COL_DEPTH)?;
+    let qc = values(&data, *COL_QC)?;
+    let fs = values(&data, *COL_FS)?;
+    let u2 = values(&data, *COL_U2)?;
+    let u0 = values(&data, *COL_U0)?;
+
+    let n_rows = data.height();
+    // fs in kPa and qc in MPa, so fs / qc / 10 is the ratio in percent
+    let suspect: Vec<bool> = (0..n_rows)
+        .map(|i| {
+            qc[i] > 0.0
+                && fs[i] / qc[i] / 10.0 >= min_rf
+                && u2[i].abs() <= max_u2
+                && u0[i] > max_u2
+        })
+        .collect();
+
+    let mut lost = vec![false; n_rows];
+
+    for start in 0..n_rows.saturating_sub(window - 1) {
+        let end = start + window - 1;
+
+        if suspect[start..=end].contains(&false) {
+            continue;
+        }
+        let (u2_min, u2_max) = range(&u2[start..=end]);
+        if u2_max - u2_min <= *U2_FLAT_RANGE {
+            lost[start..=end].fill(true);
+        }
+    }
+
+    let mut intervals = Vec::new();
+    let mut i = 0;
+    while i < n_rows {
+        let run = lost[i..].iter().take_while(|&&is_lost| is_lost).count();
+        if run == 0 {
+            i += 1;
+            continue;
+        }
+        intervals.push((depth[i], depth[i + run - 1]));
+        i += run;
+    }
+
+    let out_data = data
+        .lazy()
+        .with_column(lit(Series::new((*COL_U2_LOSS).into(), lost)))
+        .collect()?;
+
+    Ok((out_data, intervals))
+}
+
+/// Returns the weight of each record in layer statistics.
+///
+/// Records flagged unreliable in any channel by `flag_clogging` get the
+/// configured `unreliable_weight`; all records weigh 1 if no reliability
+/// columns are present.
+pub(crate) fn reliability_weights(data: &DataFrame) -> Result<Vec<f64>, CoreError> {
+    let mut weights = vec![1.0; data.height()];
+
+    for name in [*COL_FS_OK, *COL_U2_OK] {
+        let Ok(column) = data.column(name) else { continue };
+
+        for (weight, reliable) in weights.iter_mut().zip(column.bool()?) {
+            if reliable == Some(false) {
+                *weight = *UNRELIABLE_WEIGHT;
+            }
+        }
+    }
+
+    Ok(weights)
+}
+
+fn values(data: &DataFrame, name: &str) -> Result<Vec<f64>, CoreError> {
+    Ok(data
+        .column(name)?
+        .f64()?
+        .into_iter()
+        .map(|value| value.unwrap_or(f64::NAN))
+        .collect())
+}
+
+// change between the window ends relative to the window mean
+fn relative_change(values: &[f64]) -> f64 {
+    let (Some(first), Some(last)) = (values.first(), values.last()) else {
+        return f64::NAN;
+    };
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+
+    (last - first) / mean.abs()
+}
+
+// ratio of the standard deviations of the u2 (kPa) and qc (kPa) increments
+// over a centered window; increments remove the depth trend of both
+// channels. NaN where qc does not vary
+fn response_gain(qc: &[f64], u2: &[f64], window: usize) -> Vec<f64> {
+    let half = window / 2;
+    let increments = |values: &[f64]| -> Vec<f64> {
+        std::iter::once(0.0)
+            .chain(values.windows(2).map(|pair| pair[1] - pair[0]))
+            .collect()
+    };
+    let qc_step = increments(qc);
+    let u2_step = increments(u2);
+
+    (0..qc.len())
+        .map(|i| {
+            let start = i.saturating_sub(half);
+            let end = (i + half + 1).min(qc.len());
+            // from MPa to kPa
+            let qc_std = std_dev(&qc_step[start..end]) * 1000.0;
+
+            if qc_std > 0.0 {
+                std_dev(&u2_step[start..end]) / qc_std
+            } else {
+                f64::NAN
+            }
+        })
+        .collect()
+}
+
+fn std_dev(values: &[f64]) -> f64 {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>() / values.len() as f64;
+
+    variance.sqrt()
+}
+
+// median of the finite values, if any
+fn median(values: &[f64]) -> Option<f64> {
+    let mut finite: Vec<f64> = values
+        .iter()
+        .copied()
+        .filter(|value| value.is_finite())
+        .collect();
+    if finite.is_empty() {
+        return None;
+    }
+    finite.sort_by(f64::total_cmp);
+
+    let mid = finite.len() / 2;
+    if finite.len().is_multiple_of(2) {
+        Some((finite[mid - 1] + finite[mid]) / 2.0)
+    } else {
+        Some(finite[mid])
+    }
+}
+
+fn range(values: &[f64]) -> (f64, f64) {
+    values
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &value| {
+            (min.min(value), max.max(value))
+        })
+}
+
+/// Physical limits checked by `validate_ranges`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RangeRules {
+    /// Lowest valid qc (MPa).
+    pub min_qc: f64,
+    /// Lowest valid fs (kPa).
+    pub min_fs: f64,
+    /// Lowest valid u2 (kPa); readings far below -100 kPa (cavitation) point
+    /// to a faulty transducer.
+    pub min_u2: f64,
+    /// Largest depth increment (m) between consecutive records.
+    pub max_depth_jump: f64,
+}
+
+impl Default for RangeRules {
+    /// Returns the rules of the `[qa.parameters]` configuration.
+    fn default() -> Self {
+        Self {
+            min_qc: *RANGE_MIN_QC,
+            min_fs: *RANGE_MIN_FS,
+            min_u2: *RANGE_MIN_U2,
+            max_depth_jump: *RANGE_MAX_DEPTH_JUMP,
+        }
+    }
+}
+
+/// Check of `validate_ranges`, stored as one bit of the range flag column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeCheck {
+    /// qc below `min_qc`.
+    LowQc,
+    /// fs below `min_fs`.
+    LowFs,
+    /// u2 below `min_u2`.
+    LowU2,
+    /// Depth increment from the previous record above `max_depth_jump`.
+    DepthJump,
+}
+
+impl RangeCheck {
+    /// Every check, in bit order.
+    pub const ALL: [Self; 4] = [Self::LowQc, Self::LowFs, Self::LowU2, Self::DepthJump];
+
+    /// Returns the bit of the check in the range flag column.
+    pub fn bit(self) -> u32 {
+        1 << self as u32
+    }
+
+    /// Returns a short description of the check.
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::LowQc => "qc below minimum",
+            Self::LowFs => "fs below minimum",
+            Self::LowU2 => "u2 below minimum",
+            Self::DepthJump => "depth jump above maximum",
+        }
+    }
+}
+
+/// Records failing one check of `validate_ranges`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeViolations {
+    pub check: RangeCheck,
+    pub count: usize,
+    /// Depth of the first failing record.
+    pub first_depth: Option<f64>,
+}
+
+/// Flags records outside the physical limits of `rules`.
+///
+/// Adds the range flag column, holding the bits (see `RangeCheck::bit`) of
+/// the checks each record fails; 0 marks a valid record. Values are not
+/// changed, and NaN values pass every check. A depth jump is flagged on the
+/// record below it.
+pub(crate) fn validate_ranges(
+    data: DataFrame,
+    rules: &RangeRules,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("validate_ranges");
+
+    let depth = values(&data, *COL_DEPTH)?;
+    let qc = values(&data, *COL_QC)?;
+    let fs = values(&data, *COL_FS)?;
+    let u2 = values(&data, *COL_U2)?;
+
+    let flags: Vec<u32> = (0..data.height())
+        .map(|i| {
+            let depth_jump = i > 0 && depth[i] - depth[i - 1] > rules.max_depth_jump;
+            [
+                (RangeCheck::LowQc, qc[i] < rules.min_qc),
+                (RangeCheck::LowFs, fs[i] < rules.min_fs),
+                (RangeCheck::LowU2, u2[i] < rules.min_u2),
+                (RangeCheck::DepthJump, depth_jump),
+            ]
+            .into_iter()
+            .filter(|(_, failed)| *failed)
+            .fold(0, |flags, (check, _)| flags | check.bit())
+        })
+        .collect();
+
+    let out_data = data
+        .lazy()
+        .with_column(lit(Series::new((*COL_RANGE_FLAGS).into(), flags)))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Counts the records failing each check from the range flag column.
+///
+/// # Errors
+///
+/// Returns `CoreError::InvalidData` if the range flag column is missing.
+pub(crate) fn range_summary(
+    data: &DataFrame,
+) -> Result<Vec<RangeViolations>, CoreError> {
+    let flags = data.column(*COL_RANGE_FLAGS).map_err(|_| {
+        CoreError::InvalidData(format!(
+            "Cannot summarize ranges: missing column '{}' (see `validate_ranges`)",
+            *COL_RANGE_FLAGS
+        ))
+    })?;
+    let flags = flags.u32()?;
+    let depth = values(data, *COL_DEPTH)?;
+
+    let summary = RangeCheck::ALL
+        .into_iter()
+        .map(|check| {
+            let failing: Vec<usize> = flags
+                .into_iter()
+                .enumerate()
+                .filter(|(_, flags)| {
+                    flags.is_some_and(|flags| flags & check.bit() != 0)
+                })
+                .map(|(i, _)| i)
+                .collect();
+
+            RangeViolations {
+                check,
+                count: failing.len(),
+                first_depth: failing.first().map(|&i| depth[i]),
+            }
+        })
+        .collect();
+
+    Ok(summary)
+}
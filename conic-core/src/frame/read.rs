@@ -1,64 +1,312 @@
+use std::fs::File;
+use std::io::{Cursor, Read, Seek};
+use flate2::read::GzDecoder;
 use polars::prelude::*;
-use crate::kernel::{CoreError, ConicDataFrame};
+use ::zip::ZipArchive;
+use ::zip::result::ZipError;
+use crate::kernel::{CoreError, ConicDataFrame, ConicLazyFrame, Metadata};
+use crate::kernel::remote::{self, is_remote, strip_query};
+use crate::kernel::profile::profile_scope;
 use crate::kernel::config::{
-    COL_DEPTH, COL_QC, COL_FS, COL_U2, COL_U0, GAMMA_W, WATER_LEVEL
+    COL_DEPTH, COL_QC, COL_FS, COL_U2, COL_U0, COL_INCLINATION, GAMMA_W, WATER_LEVEL,
+    ERROR_INDICATORS, ColumnAliases, input_cols, input_units
 };
+use super::clean::column_indicators;
+use super::dialect::{CsvDialect, Encoding, SNIFF_BYTES, normalize, sniff_dialect};
+use super::units::{Unit, UnitConversion, header_unit, split_header};
 
 /// Reads a CSV file into a `ConicDataFrame` with predefined schema.
 ///
 /// Required columns: Depth, qc, fs, u2
-/// Optional columns: u0 (if missing, calculated from water_level)
+/// Optional columns: u0 (if missing, calculated from water_level) and
+/// inclination (see `ConicDataFrame::correct_inclination`)
 ///
-/// All columns are read or cast to `Float64`.
+/// Each column is matched against its configured aliases in order and
+/// renamed to the first alias, so header spellings of different contractors
+/// map to the same logical column. ASCII aliases written by exports with
+/// `ascii_headers` (e.g. `depth`, `qc`) are accepted as well.
+///
+/// All columns are read or cast to `Float64`. Columns whose header declares
+/// a different unit than the configured one (e.g. `qc (kPa)` instead of
+/// `qc (MPa)`), or whose source unit is set in `[input.units]`, are converted
+/// on read and recorded in the dataset metadata. Values equal to the
+/// configured `error_indicators`, or to the indicators of the column in
+/// `[input.indicators]`, are left unscaled so the cleaning steps still find
+/// them.
+///
+/// Compressed files are decompressed transparently: `.gz` files are inflated
+/// and `.zip` archives must contain exactly one CSV member (use
+/// `read_csv_from_zip` to pick a member by name).
+///
+/// `file_path` may also be an object store URL (`s3://`, `az://`,
+/// `http(s)://`) when the crate is built with the `cloud` feature.
+///
+/// The delimiter, decimal mark, header row and encoding are guessed from the
+/// first KB of the file (see `sniff_dialect`) unless set in
+/// `[input.dialect]`, and recorded in the dataset metadata.
 pub fn read_csv(file_path: &str) -> Result<ConicDataFrame, CoreError> {
-    let required_columns = [*COL_DEPTH, *COL_QC, *COL_FS, *COL_U2];
+    profile_scope!("read_csv");
 
-    // read CSV with schema overrides to ensure all numeric columns are Float64
-    let schema_overrides = Schema::from_iter(vec![
-        Field::new((*COL_DEPTH).into(), DataType::Float64),
-        Field::new((*COL_QC).into(), DataType::Float64),
-        Field::new((*COL_FS).into(), DataType::Float64),
-        Field::new((*COL_U2).into(), DataType::Float64),
-        Field::new((*COL_U0).into(), DataType::Float64),
-    ]);
+    let lower_path = strip_query(file_path).to_ascii_lowercase();
 
-    let raw_data = CsvReadOptions::default()
-        .with_has_header(true)
-        .with_schema_overwrite(Some(Arc::new(schema_overrides)))
-        .try_into_reader_with_file_path(Some(file_path.into()))?
+    let raw = if is_remote(file_path) {
+        remote::fetch(file_path)?
+    } else {
+        std::fs::read(file_path)?
+    };
+
+    let bytes = if lower_path.ends_with(".gz") {
+        let mut bytes = Vec::new();
+        GzDecoder::new(raw.as_slice()).read_to_end(&mut bytes)?;
+        bytes
+    } else if lower_path.ends_with(".zip") {
+        read_zip_member(Cursor::new(raw), file_path, None)?
+    } else {
+        raw
+    };
+
+    read_csv_bytes(&bytes, &format!("CSV file '{}'", file_path))
+}
+
+/// Reads a named CSV member out of a `.zip` archive into a `ConicDataFrame`.
+///
+/// The member is processed exactly like a file passed to `read_csv`.
+pub fn read_csv_from_zip(
+    archive_path: &str,
+    member: &str,
+) -> Result<ConicDataFrame, CoreError> {
+    profile_scope!("read_csv");
+
+    let bytes = read_zip_member(File::open(archive_path)?, archive_path, Some(member))?;
+
+    read_csv_bytes(
+        &bytes,
+        &format!("CSV member '{}' of '{}'", member, archive_path)
+    )
+}
+
+/// Parses CSV bytes after guessing their dialect. `source` names the
+/// bytes in parsing errors.
+fn read_csv_bytes(bytes: &[u8], source: &str) -> Result<ConicDataFrame, CoreError> {
+    let dialect = sniff_dialect(bytes);
+    let text = normalize(bytes, &dialect);
+
+    let raw_data = csv_options(&dialect)
+        .into_reader_with_file_handle(Cursor::new(text))
         .finish()
+        .map_err(|err| {
+            CoreError::InvalidData(format!("Failed to read {}: {}", source, err))
+        })?;
+
+    let data = prepare_columns(raw_data)?;
+    let mut meta = data.metadata().clone();
+    meta.record("read_csv", dialect.parameters());
+
+    Ok(ConicDataFrame::with_metadata(data.into_inner(), meta))
+}
+
+/// Returns the CSV reader options shared by all sources.
+fn csv_options(dialect: &CsvDialect) -> CsvReadOptions {
+    CsvReadOptions::default()
+        .with_has_header(true)
+        .with_schema_overwrite(Some(Arc::new(float_schema())))
+        .map_parse_options(|options| {
+            options
+                .with_separator(dialect.delimiter)
+                .with_decimal_comma(dialect.decimal_comma)
+                // skips the processing record written by `write_csv`
+                .with_comment_prefix(Some("#"))
+        })
+}
+
+/// Schema overrides ensuring all numeric input columns are read as Float64.
+fn float_schema() -> Schema {
+    let columns = input_cols();
+
+    [
+        (&columns.depth, "depth"),
+        (&columns.qc, "qc"),
+        (&columns.fs, "fs"),
+        (&columns.u2, "u2"),
+        (&columns.u0, "u0"),
+        (&columns.inclination, "inclination"),
+    ]
+        .into_iter()
+        .flat_map(|(aliases, key)| accepted_names(aliases, key))
+        .map(|name| Field::new(name.into(), DataType::Float64))
+        .collect()
+}
+
+/// Extracts a member of a `.zip` archive into memory.
+///
+/// If `member` is None, the archive must contain exactly one `.csv` file.
+fn read_zip_member<R: Read + Seek>(
+    archive: R,
+    archive_path: &str,
+    member: Option<&str>,
+) -> Result<Vec<u8>, CoreError> {
+    let zip_err = |err: ZipError| {
+        CoreError::InvalidData(format!(
+            "Failed to read zip archive '{}': {}",
+            archive_path, err
+        ))
+    };
+
+    let mut archive = ZipArchive::new(archive).map_err(zip_err)?;
+
+    let member = match member {
+        Some(member) => member.to_string(),
+        None => {
+            let csv_members: Vec<&str> = archive
+                .file_names()
+                .filter(|name| name.to_ascii_lowercase().ends_with(".csv"))
+                .collect();
+
+            match csv_members.as_slice() {
+                [name] => name.to_string(),
+                _ => {
+                    return Err(CoreError::InvalidData(format!(
+                        "Zip archive '{}' must contain exactly one CSV file, \
+                         found {:?}. Use `read_csv_from_zip` to select one",
+                        archive_path, csv_members
+                    )));
+                }
+            }
+        }
+    };
+
+    let mut bytes = Vec::new();
+    archive
+        .by_name(&member)
+        .map_err(zip_err)?
+        .read_to_end(&mut bytes)?;
+
+    Ok(bytes)
+}
+
+/// Validates, converts and casts the raw columns of a CSV source.
+fn prepare_columns(raw_data: DataFrame) -> Result<ConicDataFrame, CoreError> {
+    let column_names: Vec<String> = raw_data
+        .get_column_names()
+        .into_iter()
+        .map(|name| name.to_string())
+        .collect();
+
+    let (raw_data, meta) = select_columns(raw_data.lazy(), &column_names)?;
+
+    let raw_data = raw_data
+        .collect()
+        .map_err(|err| {
+            CoreError::InvalidData(format!(
+                "Failed to process columns: {}",
+                err
+            ))
+        })?;
+
+    Ok(ConicDataFrame::with_metadata(raw_data, meta))
+}
+
+/// Scans a CSV file lazily into a `ConicLazyFrame`.
+///
+/// Only the header is read up front; rows are read when the frame is
+/// collected, which lets cleaning and stress steps run on files that do not
+/// fit in memory. Columns are resolved and converted and the dialect is
+/// guessed as in `read_csv`; compressed files are not supported, and
+/// Latin-1 files are read with invalid UTF-8 replaced.
+pub fn scan_csv(file_path: &str) -> Result<ConicLazyFrame, CoreError> {
+    profile_scope!("scan_csv");
+
+    let mut sample = Vec::with_capacity(SNIFF_BYTES);
+    File::open(file_path)?
+        .take(SNIFF_BYTES as u64)
+        .read_to_end(&mut sample)?;
+    let dialect = sniff_dialect(&sample);
+
+    let encoding = match dialect.encoding {
+        Encoding::Utf8 => CsvEncoding::Utf8,
+        Encoding::Latin1 => CsvEncoding::LossyUtf8,
+    };
+
+    let mut raw_data = LazyCsvReader::new(PlRefPath::new(file_path))
+        .with_has_header(true)
+        .with_skip_lines(dialect.header_row)
+        .with_separator(dialect.delimiter)
+        .with_decimal_comma(dialect.decimal_comma)
+        .with_encoding(encoding)
+        .with_comment_prefix(Some("#".into()))
+        .with_dtype_overwrite(Some(Arc::new(float_schema())))
+        .finish()?;
+
+    let column_names: Vec<String> = raw_data
+        .collect_schema()
         .map_err(|err| {
             CoreError::InvalidData(format!(
                 "Failed to read CSV file '{}': {}",
                 file_path, err
             ))
-        })?;
+        })?
+        .iter_names()
+        .map(|name| name.to_string())
+        .collect();
 
-    // validate required columns and check for u0
-    let column_names = raw_data.get_column_names();
+    let (raw_data, mut meta) = select_columns(raw_data, &column_names)?;
+    meta.record("scan_csv", dialect.parameters());
 
-    // check all required columns are present
-    if let Some(missing) = required_columns.iter()
-        .find(|&&col| !column_names.iter().any(|name| name.as_str() == col)) {
-        return Err(CoreError::InvalidData(format!(
-            "Missing required column '{}'. Required columns: {:?}",
-            missing, required_columns
-        )));
-    }
+    Ok(ConicLazyFrame::with_metadata(raw_data, meta))
+}
 
-    let has_u0 = column_names.iter().any(|name| name.as_str() == *COL_U0);
+/// Selects, converts and casts the configured columns of a CSV source.
+fn select_columns(
+    raw_data: LazyFrame,
+    column_names: &[String],
+) -> Result<(LazyFrame, Metadata), CoreError> {
+    let required_columns = [*COL_DEPTH, *COL_QC, *COL_FS, *COL_U2];
+    let required_keys = ["depth", "qc", "fs", "u2"];
+    let columns = input_cols();
+    let required_aliases = [&columns.depth, &columns.qc, &columns.fs, &columns.u2];
+    let units = input_units();
+    let required_units = [&units.depth, &units.qc, &units.fs, &units.u2];
 
-    // cast required columns to Float64
-    let mut cast_exprs: Vec<Expr> = required_columns
+    let mut cast_exprs: Vec<Expr> = Vec::with_capacity(required_columns.len() + 1);
+    let mut conversions = Vec::new();
+
+    // check all required columns are present and cast them to Float64
+    let required = required_columns
         .iter()
-        .map(|&col_name| col(col_name).cast(DataType::Float64))
-        .collect();
+        .zip(required_keys)
+        .zip(required_aliases)
+        .zip(required_units);
+    for (((&target, key), aliases), explicit) in required {
+        let config_key = format!("input.columns.{}", key);
+        let accepted = accepted_names(aliases, key);
+        let source = find_column(column_names, &accepted).ok_or_else(|| {
+            schema_mismatch(&config_key, &accepted, column_names)
+        })?;
+
+        let (expr, conversion) = convert_column(source, target, explicit.as_deref())?;
+        cast_exprs.push(expr);
+        conversions.extend(conversion);
+    }
 
     // if u0 exists, cast it; otherwise calculate it
-    if has_u0 {
-        cast_exprs.push(col(*COL_U0).cast(DataType::Float64));
-    } else {
-        cast_exprs.push(
+    let u0_accepted = accepted_names(&columns.u0, "u0");
+    let u0_source = find_column(column_names, &u0_accepted);
+    if let Some(source) = u0_source {
+        let (expr, conversion) = convert_column(source, *COL_U0, units.u0.as_deref())?;
+        cast_exprs.push(expr);
+        conversions.extend(conversion);
+    }
+
+    // inclination is kept only if present
+    let inclination_accepted = accepted_names(&columns.inclination, "inclination");
+    if let Some(source) = find_column(column_names, &inclination_accepted) {
+        cast_exprs.push(col(source).cast(DataType::Float64).alias(*COL_INCLINATION));
+    }
+
+    let mut raw_data = raw_data.select(cast_exprs);
+
+    if u0_source.is_none() {
+        raw_data = raw_data.with_column(
             when(col(*COL_DEPTH).gt_eq(lit(*WATER_LEVEL)))
                 .then((col(*COL_DEPTH) - lit(*WATER_LEVEL)) * lit(*GAMMA_W))
                 .otherwise(lit(0.0))
@@ -66,16 +314,192 @@ pub fn read_csv(file_path: &str) -> Result<ConicDataFrame, CoreError> {
         );
     }
 
-    let raw_data = raw_data
-        .lazy()
-        .select(cast_exprs)
+    Ok((raw_data, Metadata { conversions, ..Default::default() }))
+}
+
+/// Returns the configured aliases of a column followed by its ASCII alias
+/// (the configuration key), so files exported with ASCII headers read back.
+fn accepted_names(aliases: &ColumnAliases, key: &str) -> Vec<String> {
+    aliases
+        .aliases()
+        .iter()
+        .cloned()
+        .chain(std::iter::once(key.to_string()))
         .collect()
-        .map_err(|err| {
-            CoreError::InvalidData(format!(
-                "Failed to process columns: {}",
-                err
-            ))
-        })?;
+}
+
+/// Finds the header matching the configured aliases of a column.
+///
+/// Exact matches are preferred, trying aliases in order; otherwise the first
+/// alias whose base name appears with any recognized unit (e.g. `qc (kPa)`
+/// for `qc (MPa)`) is accepted.
+fn find_column<'a>(column_names: &'a [String], aliases: &[String]) -> Option<&'a str> {
+    let exact = aliases.iter().find_map(|alias| {
+        column_names.iter().find(|name| *name == alias)
+    });
+    if let Some(name) = exact {
+        return Some(name);
+    }
+
+    aliases
+        .iter()
+        .find_map(|alias| {
+            let (alias_base, _) = split_header(alias);
+
+            column_names.iter().find(|name| {
+                let (base, unit) = split_header(name);
+                base.eq_ignore_ascii_case(alias_base)
+                    && unit.and_then(Unit::parse).is_some()
+            })
+        })
+        .map(String::as_str)
+}
+
+/// Builds a `SchemaMismatch` error for a configuration entry whose names
+/// were not found, suggesting found headers that closely resemble them.
+pub(crate) fn schema_mismatch(
+    config_key: &str,
+    expected: &[String],
+    found: &[String],
+) -> CoreError {
+    // compare base names only, ignoring case, spacing and punctuation
+    let normalize = |name: &str| -> String {
+        split_header(name)
+            .0
+            .chars()
+            .filter(|ch| ch.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+
+    let mut candidates: Vec<(usize, &String)> = found
+        .iter()
+        .filter_map(|name| {
+            let found_base = normalize(name);
+            expected
+                .iter()
+                .filter_map(|target| {
+                    let target_base = normalize(target);
+                    let max_distance = target_base.chars().count() / 3;
+                    let distance = edit_distance(&found_base, &target_base);
+                    (distance <= max_distance).then_some(distance)
+                })
+                .min()
+                .map(|distance| (distance, name))
+        })
+        .collect();
+    candidates.sort_by_key(|(distance, _)| *distance);
+
+    CoreError::SchemaMismatch {
+        config_key: config_key.to_string(),
+        expected: expected.to_vec(),
+        found: found.to_vec(),
+        suggestions: candidates
+            .into_iter()
+            .take(3)
+            .map(|(_, name)| name.clone())
+            .collect(),
+    }
+}
+
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut prev_row: Vec<usize> = (0..=b_chars.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut curr_row = vec![i + 1; b_chars.len() + 1];
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let substitution = prev_row[j] + usize::from(a_char != *b_char);
+            curr_row[j + 1] = substitution
+                .min(prev_row[j + 1] + 1)
+                .min(curr_row[j] + 1);
+        }
+        prev_row = curr_row;
+    }
 
-    Ok(ConicDataFrame::new(raw_data))
-}
\ No newline at end of file
+    prev_row[b_chars.len()]
+}
+
+/// Builds the cast expression for a column, converting units when the source
+/// unit differs from the unit declared in the configured column name.
+///
+/// Error indicator values (see `column_indicators`) are not converted.
+fn convert_column(
+    source: &str,
+    target: &str,
+    explicit_unit: Option<&str>,
+) -> Result<(Expr, Option<UnitConversion>), CoreError> {
+    let expr = col(source).cast(DataType::Float64);
+
+    let from = explicit_unit
+        .and_then(Unit::parse)
+        .or_else(|| header_unit(source));
+    let to = header_unit(target);
+
+    match (from, to) {
+        (Some(from), Some(to)) if from != to => {
+            let factor = from.factor_to(to).ok_or_else(|| {
+                CoreError::InvalidData(format!(
+                    "Cannot convert column '{}' from {} to {}",
+                    source, from, to
+                ))
+            })?;
+
+            let conversion = UnitConversion {
+                column: target.to_string(),
+                source: source.to_string(),
+                from,
+                to,
+                factor,
+            };
+
+            // indicators keep their raw value so the cleaning steps match them
+            let indicators = Series::from_vec(
+                "indicators".into(),
+                column_indicators(target, &ERROR_INDICATORS),
+            );
+            let converted = when(expr.clone().is_in(lit(indicators).implode(), false))
+                .then(expr.clone())
+                .otherwise(expr * lit(factor));
+
+            Ok((converted.alias(target), Some(conversion)))
+        }
+        _ => Ok((expr.alias(target), None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converted_columns_keep_error_indicators() {
+        let path = std::env::temp_dir().join("conic_read_kpa_indicators.csv");
+        std::fs::write(
+            &path,
+            "Depth (m),qc (kPa),fs (kPa),u2 (kPa)\n\
+             0.02,1500,10,5\n\
+             0.04,-9999,12,6\n\
+             0.06,1800,14,7\n",
+        )
+        .expect("test file should be written");
+
+        let data = read_csv(path.to_str().expect("temp path should be UTF-8"))
+            .expect("kPa file should read")
+            .replace_rows(&ERROR_INDICATORS, &f64::NAN, None)
+            .expect("indicators should be replaced");
+        std::fs::remove_file(&path).ok();
+
+        let qc: Vec<f64> = data
+            .inner()
+            .column(*COL_QC)
+            .and_then(|column| column.f64().cloned())
+            .expect("qc should be a float column")
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(qc[0], 1.5);
+        assert!(qc[1].is_nan(), "the -9999 kPa record should be cleaned");
+        assert_eq!(qc[2], 1.8);
+    }
+}
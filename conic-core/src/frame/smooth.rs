@@ -0,0 +1,380 @@
+use polars::prelude::*;
+use crate::kernel::CoreError;
+use crate::kernel::profile::profile_scope;
+use crate::kernel::config::{
+    COL_QC, COL_FS, COL_U2, COL_SPIKE, DESPIKE_WINDOW, DESPIKE_SIGMAS
+};
+
+/// Scale factor making the MAD a consistent estimator of the standard
+/// deviation for normally distributed data.
+const MAD_SCALE: f64 = 1.4826;
+
+/// Kernel of the rolling smoothing applied to qt and fs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Smoothing {
+    /// Arithmetic mean of the window.
+    Mean,
+    /// Median of the window, robust to the asymmetric spikes common in fs.
+    Median,
+    /// Mean after discarding the given fraction of samples at each end of
+    /// the sorted window.
+    TrimmedMean(f64),
+    /// Mean after clamping the given fraction of samples at each end of the
+    /// sorted window to the nearest retained value.
+    WinsorizedMean(f64),
+    /// Value at the window center of the least-squares polynomial of the
+    /// given order fitted to the window (Savitzky-Golay filter), which
+    /// preserves layer boundaries better than a moving average.
+    SavitzkyGolay(usize),
+}
+
+impl Smoothing {
+    /// Builds a smoothing kernel from its configuration name, trim ratio and
+    /// Savitzky-Golay polynomial order.
+    ///
+    /// Accepted names: `mean`, `median`, `trimmed_mean`, `winsorized_mean`,
+    /// `savitzky_golay`.
+    pub fn from_config(
+        name: &str,
+        trim_ratio: f64,
+        sg_order: usize,
+    ) -> Result<Self, CoreError> {
+        if !(0.0..0.5).contains(&trim_ratio) {
+            return Err(CoreError::InvalidConfig(format!(
+                "Invalid trim_ratio parameter: {}. Must be in [0, 0.5)",
+                trim_ratio
+            )));
+        }
+
+        match name {
+            "mean" => Ok(Self::Mean),
+            "median" => Ok(Self::Median),
+            "trimmed_mean" => Ok(Self::TrimmedMean(trim_ratio)),
+            "winsorized_mean" => Ok(Self::WinsorizedMean(trim_ratio)),
+            "savitzky_golay" => Ok(Self::SavitzkyGolay(sg_order)),
+            _ => Err(CoreError::InvalidConfig(format!(
+                "Invalid smoothing parameter: '{}'. Must be mean, median, \
+                 trimmed_mean, winsorized_mean or savitzky_golay",
+                name
+            ))),
+        }
+    }
+}
+
+/// Applies a centered rolling window of `window` samples to `values`.
+///
+/// Windows that extend past either end of the series or contain missing
+/// values yield NaN, matching the rolling mean used for `Smoothing::Mean`.
+pub(crate) fn rolling(
+    values: &Float64Chunked,
+    window: usize,
+    method: Smoothing,
+) -> Float64Chunked {
+    let values: Vec<f64> = values
+        .into_iter()
+        .map(|value| value.unwrap_or(f64::NAN))
+        .collect();
+    let half = window / 2;
+    let sg_weights = match method {
+        Smoothing::SavitzkyGolay(order) => {
+            Some(savitzky_golay_weights(half, order))
+        }
+        _ => None,
+    };
+
+    let smoothed: Vec<f64> = (0..values.len())
+        .map(|i| {
+            if i < half || i + half >= values.len() {
+                return f64::NAN;
+            }
+
+            let mut samples = values[i - half..=i + half].to_vec();
+            if samples.iter().any(|value| value.is_nan()) {
+                return f64::NAN;
+            }
+
+            // the polynomial fit needs the samples in depth order
+            if let Some(weights) = &sg_weights {
+                return samples
+                    .iter()
+                    .zip(weights)
+                    .map(|(sample, weight)| sample * weight)
+                    .sum();
+            }
+
+            samples.sort_by(f64::total_cmp);
+            window_stat(&samples, method)
+        })
+        .collect();
+
+    Float64Chunked::from_vec(PlSmallStr::EMPTY, smoothed)
+}
+
+/// Returns the Savitzky-Golay weights of a window of `2 * half + 1`
+/// samples, whose dot product with the window is the value at its center of
+/// the least-squares polynomial of order `order`.
+///
+/// The weights are `w_j = sum_k c_k j^k` for offsets `j` in `-half..=half`,
+/// where `c` solves the normal equations `(A^T A) c = e_0` of the
+/// Vandermonde matrix `A_jk = j^k`.
+fn savitzky_golay_weights(half: usize, order: usize) -> Vec<f64> {
+    let half = half as i32;
+    let size = order + 1;
+
+    // augmented normal matrix [A^T A | e_0]
+    let mut matrix = vec![vec![0.0; size + 1]; size];
+    for (row, coefficients) in matrix.iter_mut().enumerate() {
+        for (column, entry) in coefficients.iter_mut().take(size).enumerate() {
+            *entry = (-half..=half)
+                .map(|j| f64::from(j).powi((row + column) as i32))
+                .sum();
+        }
+        coefficients[size] = if row == 0 { 1.0 } else { 0.0 };
+    }
+
+    // Gauss-Jordan elimination with partial pivoting
+    for pivot in 0..size {
+        let best = (pivot..size)
+            .max_by(|&a, &b| {
+                matrix[a][pivot].abs().total_cmp(&matrix[b][pivot].abs())
+            })
+            .unwrap_or(pivot);
+        matrix.swap(pivot, best);
+
+        let pivot_row = matrix[pivot].clone();
+        for (row, coefficients) in matrix.iter_mut().enumerate() {
+            if row == pivot {
+                continue;
+            }
+            let factor = coefficients[pivot] / pivot_row[pivot];
+            for (entry, pivot_entry) in coefficients.iter_mut().zip(&pivot_row) {
+                *entry -= factor * pivot_entry;
+            }
+        }
+    }
+    let solution: Vec<f64> = matrix
+        .iter()
+        .enumerate()
+        .map(|(k, row)| row[size] / row[k])
+        .collect();
+
+    (-half..=half)
+        .map(|j| {
+            solution
+                .iter()
+                .enumerate()
+                .map(|(k, c)| c * f64::from(j).powi(k as i32))
+                .sum()
+        })
+        .collect()
+}
+
+// statistic of a sorted, NaN-free window
+fn window_stat(sorted: &[f64], method: Smoothing) -> f64 {
+    let count = sorted.len();
+
+    match method {
+        Smoothing::Mean => sorted.iter().sum::<f64>() / count as f64,
+        Smoothing::Median => sorted_median(sorted),
+        Smoothing::TrimmedMean(ratio) => {
+            let cut = (ratio * count as f64).floor() as usize;
+            let kept = &sorted[cut..count - cut];
+            kept.iter().sum::<f64>() / kept.len() as f64
+        }
+        Smoothing::WinsorizedMean(ratio) => {
+            let cut = (ratio * count as f64).floor() as usize;
+            let low = sorted[cut];
+            let high = sorted[count - cut - 1];
+            sorted
+                .iter()
+                .map(|value| value.clamp(low, high))
+                .sum::<f64>() / count as f64
+        }
+        // weighted in depth order by `rolling`, never from a sorted window
+        Smoothing::SavitzkyGolay(_) => f64::NAN,
+    }
+}
+
+/// Removes isolated spikes from qc, fs and u2 with a Hampel filter.
+///
+/// Each sample is compared with the median of a centered window of `window`
+/// samples (shortened at the ends of the sounding); samples farther than
+/// `n_sigmas` times the scaled median absolute deviation (MAD) from it are
+/// replaced by the median. Intended for spikes from rod changes and gravel
+/// contacts, before smoothing. Missing values are ignored and kept.
+pub(crate) fn despike(
+    data: DataFrame,
+    window: Option<usize>,
+    n_sigmas: Option<f64>,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("despike");
+
+    let (window, n_sigmas) = hampel_parameters("despike", window, n_sigmas)?;
+
+    let mut despiked = Vec::with_capacity(3);
+    for name in [*COL_QC, *COL_FS, *COL_U2] {
+        let values = data.column(name)?.f64()?;
+        let filtered: Vec<f64> = values
+            .into_iter()
+            .zip(hampel(values, window, n_sigmas))
+            .map(|(value, median)| median.unwrap_or(value.unwrap_or(f64::NAN)))
+            .collect();
+        despiked.push(Series::new(name.into(), filtered));
+    }
+
+    let out_data = data
+        .lazy()
+        .with_columns(despiked.into_iter().map(lit).collect::<Vec<_>>())
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Flags the samples `despike` would replace without changing any value.
+///
+/// Adds a boolean spike column, true where qc, fs or u2 deviates from the
+/// median of its window by more than `n_sigmas` scaled MADs, so spikes can
+/// be reviewed before `despike` is applied or the records removed.
+pub(crate) fn flag_spikes(
+    data: DataFrame,
+    window: Option<usize>,
+    n_sigmas: Option<f64>,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("flag_spikes");
+
+    let (window, n_sigmas) = hampel_parameters("flag spikes", window, n_sigmas)?;
+
+    let mut spikes = vec![false; data.height()];
+    for name in [*COL_QC, *COL_FS, *COL_U2] {
+        let values = data.column(name)?.f64()?;
+        for (spike, median) in spikes.iter_mut().zip(hampel(values, window, n_sigmas)) {
+            *spike |= median.is_some();
+        }
+    }
+
+    let out_data = data
+        .lazy()
+        .with_column(lit(Series::new((*COL_SPIKE).into(), spikes)))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+// resolves and checks the Hampel filter parameters of `step`
+fn hampel_parameters(
+    step: &str,
+    window: Option<usize>,
+    n_sigmas: Option<f64>,
+) -> Result<(usize, f64), CoreError> {
+    let window = window.unwrap_or(*DESPIKE_WINDOW);
+    let n_sigmas = n_sigmas.unwrap_or(*DESPIKE_SIGMAS);
+
+    if window < 3 || window.is_multiple_of(2) {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot {}: window must be odd and at least 3 samples, got {}",
+            step, window
+        )));
+    }
+    if n_sigmas.is_nan() || n_sigmas <= 0.0 {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot {}: n_sigmas must be positive, got {}",
+            step, n_sigmas
+        )));
+    }
+
+    Ok((window, n_sigmas))
+}
+
+// Hampel filter of a series: the window median replacing each spike, None
+// for the samples kept; see `despike`
+fn hampel(values: &Float64Chunked, window: usize, n_sigmas: f64) -> Vec<Option<f64>> {
+    let values: Vec<f64> = values
+        .into_iter()
+        .map(|value| value.unwrap_or(f64::NAN))
+        .collect();
+    let half = window / 2;
+
+    (0..values.len())
+        .map(|i| {
+            let value = values[i];
+            if value.is_nan() {
+                return None;
+            }
+
+            let end = (i + half + 1).min(values.len());
+            let mut samples: Vec<f64> = values[i.saturating_sub(half)..end]
+                .iter()
+                .copied()
+                .filter(|sample| !sample.is_nan())
+                .collect();
+            samples.sort_by(f64::total_cmp);
+            let median = sorted_median(&samples);
+
+            let mut deviations: Vec<f64> = samples
+                .iter()
+                .map(|sample| (sample - median).abs())
+                .collect();
+            deviations.sort_by(f64::total_cmp);
+            let sigma = MAD_SCALE * sorted_median(&deviations);
+
+            ((value - median).abs() > n_sigmas * sigma).then_some(median)
+        })
+        .collect()
+}
+
+// median of a sorted, non-empty slice
+fn sorted_median(sorted: &[f64]) -> f64 {
+    let mid = sorted.len() / 2;
+
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn savitzky_golay_quadratic_weights() {
+        let weights = savitzky_golay_weights(2, 2);
+        let expected = [-3.0, 12.0, 17.0, 12.0, -3.0].map(|weight| weight / 35.0);
+
+        for (weight, expected) in weights.iter().zip(expected) {
+            assert!((weight - expected).abs() < 1e-12, "{:?}", weights);
+        }
+    }
+
+    #[test]
+    fn savitzky_golay_reproduces_polynomials_of_its_order() {
+        let polynomial = |x: f64| 2.0 - x + 0.5 * x.powi(2) - 0.1 * x.powi(3);
+        let center = 1.5;
+
+        let weights = savitzky_golay_weights(4, 3);
+        let smoothed: f64 = (-4..=4)
+            .zip(&weights)
+            .map(|(offset, weight)| weight * polynomial(center + f64::from(offset)))
+            .sum();
+
+        assert!((smoothed - polynomial(center)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn hampel_replaces_a_spike_but_not_a_step() {
+        let spike = Float64Chunked::from_vec(
+            PlSmallStr::EMPTY,
+            vec![1.0, 1.1, 0.9, 1.0, 9.0, 1.1, 0.9, 1.0],
+        );
+        let replaced = hampel(&spike, 5, 3.0);
+        assert_eq!(replaced[4], Some(1.0));
+        assert_eq!(replaced.iter().flatten().count(), 1);
+
+        let step = Float64Chunked::from_vec(
+            PlSmallStr::EMPTY,
+            vec![1.0, 1.1, 0.9, 1.0, 5.0, 5.1, 4.9, 5.0],
+        );
+        assert!(hampel(&step, 5, 3.0).iter().all(Option::is_none));
+    }
+}
@@ -0,0 +1,190 @@
+use std::fmt;
+use polars::prelude::*;
+use crate::kernel::CoreError;
+use crate::kernel::config::{COL_QC, COL_FS};
+
+/// Median qc (MPa) above which qc is suspected to be in kPa.
+const MAX_MEDIAN_QC: f64 = 100.0;
+
+/// Largest fs (kPa) below which fs is suspected to be in MPa.
+const MIN_MAX_FS: f64 = 1.0;
+
+/// Physical units recognized in CPTu column headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Pa,
+    KPa,
+    MPa,
+    Mm,
+    Cm,
+    M,
+}
+
+/// Conversion applied to a column while reading a file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitConversion {
+    pub column: String,
+    pub source: String,
+    pub from: Unit,
+    pub to: Unit,
+    pub factor: f64,
+}
+
+impl Unit {
+    /// Parses a unit label such as `kPa` or `m` (case-insensitive).
+    pub fn parse(label: &str) -> Option<Self> {
+        match label.trim().to_ascii_lowercase().as_str() {
+            "pa" => Some(Self::Pa),
+            "kpa" => Some(Self::KPa),
+            "mpa" => Some(Self::MPa),
+            "mm" => Some(Self::Mm),
+            "cm" => Some(Self::Cm),
+            "m" => Some(Self::M),
+            _ => None,
+        }
+    }
+
+    /// Returns the multiplier that converts a value in `self` into `target`,
+    /// or `None` when both units measure different quantities.
+    pub fn factor_to(self, target: Unit) -> Option<f64> {
+        match (self.to_base(), target.to_base()) {
+            ((from_dim, from_scale), (to_dim, to_scale)) if from_dim == to_dim => {
+                Some(from_scale / to_scale)
+            }
+            _ => None,
+        }
+    }
+
+    // (dimension id, scale to the base unit: kPa for stress, m for length)
+    fn to_base(self) -> (u8, f64) {
+        match self {
+            Self::Pa => (0, 1e-3),
+            Self::KPa => (0, 1.0),
+            Self::MPa => (0, 1e3),
+            Self::Mm => (1, 1e-3),
+            Self::Cm => (1, 1e-2),
+            Self::M => (1, 1.0),
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Self::Pa => "Pa",
+            Self::KPa => "kPa",
+            Self::MPa => "MPa",
+            Self::Mm => "mm",
+            Self::Cm => "cm",
+            Self::M => "m",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// Splits a column header into its base name and unit label.
+///
+/// Both `qc (MPa)` and `qc [MPa]` yield `("qc", Some("MPa"))`; headers without
+/// a trailing unit return the trimmed name and `None`.
+pub fn split_header(header: &str) -> (&str, Option<&str>) {
+    let header = header.trim();
+
+    for (open, close) in [('(', ')'), ('[', ']')] {
+        if let Some(stripped) = header.strip_suffix(close)
+            && let Some(pos) = stripped.rfind(open)
+        {
+            return (stripped[..pos].trim(), Some(stripped[pos + 1..].trim()));
+        }
+    }
+
+    (header, None)
+}
+
+/// Returns the unit declared in a column header, if any.
+pub fn header_unit(header: &str) -> Option<Unit> {
+    split_header(header).1.and_then(Unit::parse)
+}
+
+/// Suspected unit error of an input column, found by `check_units`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitWarning {
+    /// Name of the column.
+    pub column: String,
+    /// Unit the column is read in.
+    pub expected: Unit,
+    /// Unit the values look like they are in.
+    pub likely: Unit,
+    /// Description of the magnitudes that raised the warning.
+    pub evidence: String,
+}
+
+impl fmt::Display for UnitWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "'{}' looks like {} rather than {} ({}); check the source file or \
+             set its unit in [input.units]",
+            self.column, self.likely, self.expected, self.evidence
+        )
+    }
+}
+
+/// Checks the magnitudes of qc and fs for the most common unit errors.
+///
+/// qc is suspected to be in kPa when its median exceeds 100 MPa, and fs in
+/// MPa when every value stays below 1 kPa. Magnitudes are compared after
+/// converting from the unit declared in the configured column names.
+pub(crate) fn check_units(data: &DataFrame) -> Result<Vec<UnitWarning>, CoreError> {
+    let mut warnings = Vec::new();
+
+    let qc_unit = header_unit(*COL_QC).unwrap_or(Unit::MPa);
+    let to_mpa = qc_unit.factor_to(Unit::MPa).unwrap_or(1.0);
+    let qc = finite_values(data, *COL_QC)?;
+    if let Some(median) = median(qc) {
+        let median = median * to_mpa;
+        if median > MAX_MEDIAN_QC {
+            warnings.push(UnitWarning {
+                column: (*COL_QC).to_string(),
+                expected: qc_unit,
+                likely: Unit::KPa,
+                evidence: format!("median {:.0} MPa", median),
+            });
+        }
+    }
+
+    let fs_unit = header_unit(*COL_FS).unwrap_or(Unit::KPa);
+    let to_kpa = fs_unit.factor_to(Unit::KPa).unwrap_or(1.0);
+    let fs = finite_values(data, *COL_FS)?;
+    let max_fs = fs.iter().map(|value| value.abs() * to_kpa).reduce(f64::max);
+    if let Some(max_fs) = max_fs
+        && max_fs < MIN_MAX_FS
+    {
+        warnings.push(UnitWarning {
+            column: (*COL_FS).to_string(),
+            expected: fs_unit,
+            likely: Unit::MPa,
+            evidence: format!("all values below {} kPa", MIN_MAX_FS),
+        });
+    }
+
+    Ok(warnings)
+}
+
+fn finite_values(data: &DataFrame, name: &str) -> Result<Vec<f64>, CoreError> {
+    Ok(data
+        .column(name)?
+        .f64()?
+        .into_iter()
+        .flatten()
+        .filter(|value| value.is_finite())
+        .collect())
+}
+
+fn median(mut values: Vec<f64>) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+
+    Some(values[values.len() / 2])
+}
@@ -0,0 +1,620 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use polars::prelude::*;
+use crate::kernel::CoreError;
+use crate::kernel::meta::Metadata;
+use crate::kernel::provenance::Provenance;
+use crate::kernel::profile::profile_scope;
+use crate::kernel::config::{
+    COL_DEPTH, COL_QT, COL_FS, COL_U2, COL_IC, DEPTH_BIN, ASCII_HEADERS,
+    HEADER_COMMENTS, UNITS_ROW
+};
+use crate::math::sbt::SbtZone;
+use super::ascii::{ascii_alias, ascii_aliases};
+use super::qa::reliability_weights;
+use super::units::{split_header, Unit};
+
+/// Options controlling how processed data is exported.
+#[derive(Debug, Clone, Default)]
+pub struct WriteOptions {
+    /// Depth grid (m) the depth column is snapped to before writing, e.g.
+    /// 0.01 or 0.02. If None, uses `depth_bin` from the configuration; a
+    /// value of zero disables binning.
+    pub depth_bin: Option<f64>,
+    /// Replace column names by their ASCII aliases (e.g. `sigv_tot` for
+    /// `σv_tot (kPa)`) for tools that mangle non-ASCII headers. If None, uses
+    /// `ascii_headers` from the configuration.
+    pub ascii_headers: Option<bool>,
+    /// Precede CSV and LAS output with `#` comment lines recording the
+    /// unit conversions and processing steps applied to the data. If None,
+    /// uses `header_comments` from the configuration.
+    pub header_comments: Option<bool>,
+    /// Write a second CSV header row with the unit of each column. Such
+    /// files cannot be read back by `read_csv`. If None, uses `units_row`
+    /// from the configuration.
+    pub units_row: Option<bool>,
+}
+
+/// File format of per-layer exports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Las,
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+impl ExportFormat {
+    /// Returns the file extension of the format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Las => "las",
+            #[cfg(feature = "parquet")]
+            Self::Parquet => "parquet",
+        }
+    }
+}
+
+/// Writes a DataFrame to a CSV file applying the export options.
+///
+/// `meta` is the processing record written to the leading comment block
+/// when `header_comments` is enabled.
+pub fn write_csv(
+    data: &DataFrame,
+    meta: &Metadata,
+    file_path: &str,
+    options: &WriteOptions,
+) -> Result<(), CoreError> {
+    let mut file = BufWriter::new(File::create(file_path)?);
+    write_csv_to(data, meta, &mut file, options)?;
+    file.flush()?;
+
+    Ok(())
+}
+
+/// Writes a DataFrame as CSV to any writer applying the export options.
+pub(crate) fn write_csv_to<W: Write>(
+    data: &DataFrame,
+    meta: &Metadata,
+    mut file: W,
+    options: &WriteOptions,
+) -> Result<(), CoreError> {
+    profile_scope!("write_csv");
+
+    let mut out_data = prepare_export(data.clone(), options)?;
+    // units are taken from the configured names, before any ASCII renaming
+    let units: Vec<String> = out_data
+        .get_column_names()
+        .into_iter()
+        .map(|name| column_unit(name).to_string())
+        .collect();
+    export_headers(&mut out_data, options)?;
+
+    if options.header_comments.unwrap_or(*HEADER_COMMENTS) {
+        for line in comment_lines(meta, options) {
+            writeln!(file, "# {}", line)?;
+        }
+    }
+
+    let units_row = options.units_row.unwrap_or(*UNITS_ROW);
+    if units_row {
+        let names: Vec<&str> = out_data
+            .get_column_names()
+            .into_iter()
+            .map(|name| name.as_str())
+            .collect();
+        writeln!(file, "{}", csv_row(&names))?;
+        writeln!(file, "{}", csv_row(&units))?;
+    }
+
+    CsvWriter::new(&mut file)
+        .include_header(!units_row)
+        .finish(&mut out_data)?;
+
+    Ok(())
+}
+
+/// Returns the unit of an output column, or an empty string if it has none.
+///
+/// Bracketed suffixes such as `[rolling]` only count as units when they
+/// name a recognized unit.
+fn column_unit(name: &str) -> &str {
+    match split_header(name) {
+        (_, Some(unit)) if name.trim_end().ends_with(')') => unit,
+        (_, Some(unit)) if Unit::parse(unit).is_some() => unit,
+        _ => "",
+    }
+}
+
+/// Joins the fields of a CSV row, quoting those that need it.
+fn csv_row<S: AsRef<str>>(fields: &[S]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            let field = field.as_ref();
+            if field.contains([',', '"', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect();
+
+    fields.join(",")
+}
+
+/// Returns the lines of the processing record written ahead of exports,
+/// starting with the provenance of the export.
+fn comment_lines(meta: &Metadata, options: &WriteOptions) -> Vec<String> {
+    let mut lines = vec![Provenance::now().describe()];
+    lines.extend(meta.describe());
+
+    let depth_bin = options.depth_bin.unwrap_or(*DEPTH_BIN);
+    if depth_bin > 0.0 {
+        lines.push(format!("export: depth_bin={}", depth_bin));
+    }
+
+    lines
+}
+
+/// Null value written for missing samples in LAS files.
+const LAS_NULL: f64 = -999.25;
+
+/// Writes depth-indexed channels to a LAS 2.0 file.
+///
+/// Exports qt, fs, u2 and Ic (when present) against depth so processed
+/// soundings can be opened in standard well-log viewers. `well_name` fills
+/// the WELL entry of the well information section. With `header_comments`
+/// enabled, the processing record in `meta` is written to the other
+/// information section.
+pub fn write_las(
+    data: &DataFrame,
+    meta: &Metadata,
+    file_path: &str,
+    well_name: &str,
+    options: &WriteOptions,
+) -> Result<(), CoreError> {
+    profile_scope!("write_las");
+
+    let out_data = prepare_export(data.clone(), options)?;
+    let ascii_headers = options.ascii_headers.unwrap_or(*ASCII_HEADERS);
+
+    let channels: Vec<(&str, &str)> = [
+        ("QT", *COL_QT),
+        ("FS", *COL_FS),
+        ("U2", *COL_U2),
+        ("IC", *COL_IC),
+    ]
+        .into_iter()
+        .filter(|(_, name)| out_data.column(name).is_ok())
+        .collect();
+
+    let depth = out_data.column(*COL_DEPTH)?.f64()?;
+    let channel_values = channels
+        .iter()
+        .map(|(_, name)| out_data.column(name)?.cast(&DataType::Float64))
+        .collect::<Result<Vec<_>, _>>()?;
+    let channel_values = channel_values
+        .iter()
+        .map(|column| column.f64())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let start = depth.first().unwrap_or(LAS_NULL);
+    let stop = depth.last().unwrap_or(LAS_NULL);
+
+    // LAS requires STEP = 0 when the depth increment is not constant
+    let step = if depth.len() > 1 {
+        let first_step = depth.get(1).unwrap_or(start) - start;
+        let uniform = depth
+            .into_iter()
+            .zip(depth.into_iter().skip(1))
+            .all(|(prev, next)| match (prev, next) {
+                (Some(prev), Some(next)) => ((next - prev) - first_step).abs() < 1e-6,
+                _ => false,
+            });
+        if uniform { first_step } else { 0.0 }
+    } else {
+        0.0
+    };
+
+    let mut las = String::new();
+    las.push_str("~VERSION INFORMATION\n");
+    las.push_str(" VERS.                 2.0 : CWLS LOG ASCII STANDARD 2.0\n");
+    las.push_str(" WRAP.                  NO : ONE LINE PER DEPTH STEP\n");
+    las.push_str("~WELL INFORMATION\n");
+    las.push_str(&format!(" STRT.M {:>18.3} : START DEPTH\n", start));
+    las.push_str(&format!(" STOP.M {:>18.3} : STOP DEPTH\n", stop));
+    las.push_str(&format!(" STEP.M {:>18.3} : STEP\n", step));
+    las.push_str(&format!(" NULL.  {:>18.2} : NULL VALUE\n", LAS_NULL));
+    las.push_str(&format!(" WELL.  {:>18} : WELL\n", well_name));
+    las.push_str("~CURVE INFORMATION\n");
+    let description = |name: &str| {
+        if ascii_headers { ascii_alias(name) } else { name.to_string() }
+    };
+    las.push_str(&format!(
+        " {:<4}.{:<8} : {}\n", "DEPT", "M", description(*COL_DEPTH)
+    ));
+    for (mnemonic, name) in &channels {
+        // dots would end the unit field early in LAS readers
+        let unit = split_header(name).1.unwrap_or("").replace('.', "");
+        las.push_str(&format!(
+            " {:<4}.{:<8} : {}\n", mnemonic, unit, description(name)
+        ));
+    }
+    if options.header_comments.unwrap_or(*HEADER_COMMENTS) {
+        las.push_str("~OTHER INFORMATION\n");
+        for line in comment_lines(meta, options) {
+            las.push_str(&format!(" {}\n", line));
+        }
+    }
+    las.push_str("~ASCII\n");
+
+    for i in 0..out_data.height() {
+        let values = std::iter::once(depth.get(i))
+            .chain(channel_values.iter().map(|values| values.get(i)));
+
+        let line: Vec<String> = values
+            .map(|value| match value {
+                Some(value) if value.is_finite() => format!("{:>12.4}", value),
+                _ => format!("{:>12.4}", LAS_NULL),
+            })
+            .collect();
+        las.push_str(&line.join(" "));
+        las.push('\n');
+    }
+
+    std::fs::write(file_path, las)?;
+
+    Ok(())
+}
+
+/// Writes a DataFrame to a Parquet file applying the export options.
+///
+/// The provenance of the export is stored as file-level key-value metadata.
+#[cfg(feature = "parquet")]
+pub fn write_parquet(
+    data: &DataFrame,
+    file_path: &str,
+    options: &WriteOptions,
+) -> Result<(), CoreError> {
+    profile_scope!("write_parquet");
+
+    let mut out_data = prepare_export(data.clone(), options)?;
+    export_headers(&mut out_data, options)?;
+
+    let provenance = Provenance::now()
+        .pairs()
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect();
+
+    let file = File::create(file_path)?;
+    ParquetWriter::new(file)
+        .with_key_value_metadata(Some(KeyValueMetadata::from_static(provenance)))
+        .finish(&mut out_data)?;
+
+    Ok(())
+}
+
+/// Writes one file per layer plus a `layers.csv` summary into `dir`.
+///
+/// `layers` holds the (top, bottom) depths of each layer, both inclusive; a
+/// record on a shared boundary belongs to the upper layer. Layer files are
+/// named `layer_01.csv`, `layer_02.csv`, etc. The summary lists the depth
+/// limits, record count, mean Ic (down-weighting records flagged by
+/// `flag_clogging`), SBT zone and file of each layer; Ic and zone are left
+/// empty if the data has no Ic column. The directory is created if needed.
+/// Returns the number of layer files written.
+pub fn write_by_layer(
+    data: &DataFrame,
+    meta: &Metadata,
+    dir: &str,
+    format: ExportFormat,
+    layers: &[(f64, f64)],
+    options: &WriteOptions,
+) -> Result<usize, CoreError> {
+    profile_scope!("write_by_layer");
+
+    std::fs::create_dir_all(dir)?;
+
+    let depth: Vec<f64> = data
+        .column(*COL_DEPTH)?
+        .f64()?
+        .into_iter()
+        .map(|value| value.unwrap_or(f64::NAN))
+        .collect();
+    let ic: Option<Vec<f64>> = match data.column(*COL_IC) {
+        Ok(column) => Some(
+            column.f64()?.into_iter().map(|value| value.unwrap_or(f64::NAN)).collect()
+        ),
+        Err(_) => None,
+    };
+    let weights = reliability_weights(data)?;
+
+    // index of the first layer containing each record
+    let assigned: Vec<Option<usize>> = depth
+        .iter()
+        .map(|&z| layers.iter().position(|&(top, bottom)| z >= top && z <= bottom))
+        .collect();
+
+    let mut summary = LayerSummary::default();
+    // round limits to 3 decimal places to avoid floating point noise
+    let round = |value: f64| (value * 1000.0).round() / 1000.0;
+
+    for (index, &(top, bottom)) in layers.iter().enumerate() {
+        let mask: BooleanChunked = assigned
+            .iter()
+            .map(|&layer| layer == Some(index))
+            .collect();
+        let layer_data = data.filter(&mask)?;
+
+        let name = format!("layer_{:02}", index + 1);
+        let file_name = format!("{}.{}", name, format.extension());
+        let file_path = std::path::Path::new(dir).join(&file_name);
+        let file_path = file_path.to_string_lossy();
+
+        let mut layer_meta = meta.clone();
+        layer_meta.record("write_by_layer", [
+            ("layer", (index + 1).to_string()),
+            ("top", round(top).to_string()),
+            ("bottom", round(bottom).to_string()),
+        ]);
+
+        match format {
+            ExportFormat::Csv => {
+                write_csv(&layer_data, &layer_meta, &file_path, options)?
+            }
+            ExportFormat::Las => {
+                write_las(&layer_data, &layer_meta, &file_path, &name, options)?
+            }
+            #[cfg(feature = "parquet")]
+            ExportFormat::Parquet => write_parquet(&layer_data, &file_path, options)?,
+        }
+
+        let mean_ic = ic.as_ref().map(|ic| {
+            let (sum, weight_sum) = (0..depth.len())
+                .filter(|&i| assigned[i] == Some(index) && ic[i].is_finite())
+                .fold((0.0, 0.0), |(sum, weight_sum), i| {
+                    (sum + ic[i] * weights[i], weight_sum + weights[i])
+                });
+            if weight_sum > 0.0 { sum / weight_sum } else { f64::NAN }
+        });
+
+        summary.layer.push((index + 1) as u32);
+        summary.top.push(round(top));
+        summary.bottom.push(round(bottom));
+        summary.records.push(layer_data.height() as u32);
+        summary.mean_ic.push(mean_ic.filter(|ic| ic.is_finite()));
+        summary.zone.push(
+            mean_ic.and_then(SbtZone::from_ic).map(|zone| zone.to_string())
+        );
+        summary.file.push(file_name);
+    }
+
+    let mut summary = DataFrame::new(layers.len(), vec![
+        Column::new("Layer".into(), summary.layer),
+        Column::new("Top (m)".into(), summary.top),
+        Column::new("Bottom (m)".into(), summary.bottom),
+        Column::new("Records".into(), summary.records),
+        Column::new("Mean Ic".into(), summary.mean_ic),
+        Column::new("SBT zone".into(), summary.zone),
+        Column::new("File".into(), summary.file),
+    ])?;
+
+    let summary_path = std::path::Path::new(dir).join("layers.csv");
+    let mut file = File::create(summary_path)?;
+    CsvWriter::new(&mut file)
+        .include_header(true)
+        .finish(&mut summary)?;
+
+    Ok(layers.len())
+}
+
+// columns of the per-layer export summary
+#[derive(Default)]
+struct LayerSummary {
+    layer: Vec<u32>,
+    top: Vec<f64>,
+    bottom: Vec<f64>,
+    records: Vec<u32>,
+    mean_ic: Vec<Option<f64>>,
+    zone: Vec<Option<String>>,
+    file: Vec<String>,
+}
+
+/// Appends a DataFrame to a table of a SQLite database.
+///
+/// The table is created on first use with a leading `sounding_id` column
+/// followed by the DataFrame columns, so processed soundings of a whole
+/// project can be collected in one table and queried by sounding. Columns
+/// missing from an existing table are added, leaving them NULL for the rows
+/// already stored. Boolean and integer columns, such as flags and zone
+/// numbers, are stored as 0/1 BOOLEAN and INTEGER values, and NaN values as
+/// NULL; `project::read_sounding` reads the records back. Returns the number
+/// of inserted rows.
+#[cfg(feature = "sqlite")]
+pub fn to_sqlite(
+    data: &DataFrame,
+    db_path: &str,
+    table: &str,
+    sounding_id: &str,
+    options: &WriteOptions,
+) -> Result<usize, CoreError> {
+    use rusqlite::types::Value;
+
+    profile_scope!("to_sqlite");
+
+    let mut data = prepare_export(data.clone(), options)?;
+    export_headers(&mut data, options)?;
+
+    let quote = |name: &str| format!("\"{}\"", name.replace('"', "\"\""));
+
+    let columns = data.columns();
+    let mut column_defs = vec![format!("{} TEXT NOT NULL", quote("sounding_id"))];
+    for column in columns {
+        let dtype = column.dtype();
+        // BOOLEAN has integer storage but lets `read_sounding` restore flags
+        let sql_type = match dtype {
+            DataType::Boolean => "BOOLEAN",
+            _ if dtype.is_integer() => "INTEGER",
+            _ if dtype.is_float() => "REAL",
+            _ => "TEXT",
+        };
+        column_defs.push(format!("{} {}", quote(column.name()), sql_type));
+    }
+
+    let column_names: Vec<String> = std::iter::once("sounding_id")
+        .chain(columns.iter().map(|column| column.name().as_str()))
+        .map(quote)
+        .collect();
+    let placeholders = vec!["?"; column_names.len()].join(", ");
+
+    let mut conn = rusqlite::Connection::open(db_path)?;
+    let tx = conn.transaction()?;
+
+    tx.execute(
+        &format!(
+            "CREATE TABLE IF NOT EXISTS {} ({})",
+            quote(table),
+            column_defs.join(", ")
+        ),
+        [],
+    )?;
+
+    // soundings appended later may carry columns the first one did not have
+    let existing: Vec<String> = tx
+        .prepare(&format!("PRAGMA table_info({})", quote(table)))?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<_, _>>()?;
+    for (name, column_def) in column_names.iter().zip(&column_defs) {
+        if !existing.iter().any(|column| quote(column) == *name) {
+            tx.execute(
+                &format!("ALTER TABLE {} ADD COLUMN {}", quote(table), column_def),
+                [],
+            )?;
+        }
+    }
+
+    {
+        let mut statement = tx.prepare(&format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            quote(table),
+            column_names.join(", "),
+            placeholders
+        ))?;
+
+        for i in 0..data.height() {
+            let mut row = Vec::with_capacity(column_names.len());
+            row.push(Value::Text(sounding_id.to_string()));
+
+            for column in columns {
+                let value = match column.get(i)? {
+                    AnyValue::Null => Value::Null,
+                    AnyValue::Boolean(value) => Value::Integer(value as i64),
+                    AnyValue::Int8(value) => Value::Integer(value as i64),
+                    AnyValue::Int16(value) => Value::Integer(value as i64),
+                    AnyValue::Int32(value) => Value::Integer(value as i64),
+                    AnyValue::Int64(value) => Value::Integer(value),
+                    AnyValue::UInt8(value) => Value::Integer(value as i64),
+                    AnyValue::UInt16(value) => Value::Integer(value as i64),
+                    AnyValue::UInt32(value) => Value::Integer(value as i64),
+                    // beyond i64, SQLite integers cannot hold the value
+                    AnyValue::UInt64(value) => i64::try_from(value)
+                        .map_or(Value::Real(value as f64), Value::Integer),
+                    AnyValue::Float32(value) if value.is_nan() => Value::Null,
+                    AnyValue::Float32(value) => Value::Real(value as f64),
+                    AnyValue::Float64(value) if value.is_nan() => Value::Null,
+                    AnyValue::Float64(value) => Value::Real(value),
+                    other => Value::Text(other.to_string()),
+                };
+                row.push(value);
+            }
+
+            statement.execute(rusqlite::params_from_iter(row))?;
+        }
+    }
+
+    tx.commit()?;
+
+    Ok(data.height())
+}
+
+/// Renames the columns to their ASCII aliases if requested.
+fn export_headers(
+    data: &mut DataFrame,
+    options: &WriteOptions,
+) -> Result<(), CoreError> {
+    if options.ascii_headers.unwrap_or(*ASCII_HEADERS) {
+        let aliases = ascii_aliases(
+            data.get_column_names().into_iter().map(|name| name.as_str())
+        );
+        data.set_column_names(&aliases)?;
+    }
+
+    Ok(())
+}
+
+/// Applies the export transformations shared by all writers.
+fn prepare_export(
+    data: DataFrame,
+    options: &WriteOptions,
+) -> Result<DataFrame, CoreError> {
+    let depth_bin = options.depth_bin.unwrap_or(*DEPTH_BIN);
+
+    if depth_bin > 0.0 {
+        super::fix::bin_depth(data, depth_bin)
+    } else {
+        Ok(data)
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sqlite_keeps_integer_columns_and_adds_new_ones() {
+        let db_path = std::env::temp_dir().join("conic_to_sqlite_test.db");
+        let db_path = db_path.to_str().expect("temp path should be UTF-8");
+        let _ = std::fs::remove_file(db_path);
+        let options = WriteOptions {
+            ascii_headers: Some(false),
+            depth_bin: Some(0.0),
+            ..Default::default()
+        };
+
+        let first = DataFrame::new(2, vec![
+            Column::new("depth".into(), [0.02, 0.04]),
+            Column::new("flags".into(), [0u32, 5]),
+        ])
+        .expect("columns should have the same length");
+        let second = DataFrame::new(1, vec![
+            Column::new("depth".into(), [0.02]),
+            Column::new("flags".into(), [2u32]),
+            Column::new("zone".into(), [6u32]),
+        ])
+        .expect("columns should have the same length");
+        to_sqlite(&first, db_path, "soundings", "CPT-01", &options)
+            .expect("first sounding should be written");
+        to_sqlite(&second, db_path, "soundings", "CPT-02", &options)
+            .expect("a sounding with an extra column should be appended");
+
+        let conn = rusqlite::Connection::open(db_path).expect("database should open");
+        let rows: Vec<(String, i64, Option<i64>)> = conn
+            .prepare("SELECT typeof(flags), flags & 4, zone FROM soundings")
+            .and_then(|mut statement| {
+                statement
+                    .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+                    .collect()
+            })
+            .expect("rows should be read back");
+        let _ = std::fs::remove_file(db_path);
+
+        assert_eq!(rows, [
+            ("integer".to_string(), 0, None),
+            ("integer".to_string(), 4, None),
+            ("integer".to_string(), 0, Some(6)),
+        ]);
+    }
+}
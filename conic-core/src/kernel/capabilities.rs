@@ -0,0 +1,87 @@
+use super::config::try_load_config;
+
+/// Optional capability of the current build.
+#[derive(Debug, Clone)]
+pub struct Capability {
+    pub name: &'static str,
+    pub available: bool,
+    pub detail: String,
+}
+
+impl Capability {
+    fn feature(name: &'static str, available: bool, detail: &str) -> Self {
+        let detail = if available {
+            detail.to_string()
+        } else {
+            format!("{} (rebuild with `--features {}`)", detail, name)
+        };
+        Self { name, available, detail }
+    }
+}
+
+/// Reports which optional capabilities are available.
+///
+/// Compile-time capabilities follow the cargo features the crate was built
+/// with; runtime capabilities are probed when this function is called.
+pub fn capabilities() -> Vec<Capability> {
+    let config_status = try_load_config();
+    let threads = std::thread::available_parallelism()
+        .map(|count| count.get())
+        .unwrap_or(1);
+
+    vec![
+        Capability {
+            name: "gzip",
+            available: true,
+            detail: "read `.gz` compressed CSV files".to_string(),
+        },
+        Capability {
+            name: "zip",
+            available: true,
+            detail: "read CSV members of `.zip` archives".to_string(),
+        },
+        Capability::feature(
+            "parquet",
+            cfg!(feature = "parquet"),
+            "write Parquet files",
+        ),
+        Capability::feature(
+            "sqlite",
+            cfg!(feature = "sqlite"),
+            "append processed soundings to SQLite databases",
+        ),
+        Capability::feature(
+            "pdf",
+            cfg!(feature = "pdf"),
+            "write PDF reports",
+        ),
+        Capability::feature(
+            "cloud",
+            cfg!(feature = "cloud"),
+            "read and write S3, Azure Blob and HTTP object store URLs",
+        ),
+        Capability::feature(
+            "streaming",
+            cfg!(feature = "streaming"),
+            "process lazily scanned files in batches",
+        ),
+        Capability::feature(
+            "profiling",
+            cfg!(feature = "profiling"),
+            "record pipeline spans for flamegraphs",
+        ),
+        Capability {
+            name: "config",
+            available: config_status.is_ok(),
+            detail: match config_status {
+                Ok(path) => format!("configuration loaded from '{}'", path),
+                Err(err) => err.to_string(),
+            },
+        },
+        Capability {
+            name: "threads",
+            available: true,
+            detail: format!("{} hardware threads available", threads),
+        },
+    ]
+}
@@ -1,12 +1,25 @@
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::sync::{LazyLock, OnceLock};
 use super::CoreError;
+use super::selection::OutputSelection;
+use crate::frame::smooth::Smoothing;
+use crate::frame::fix::{
+    Aggregation, DuplicateDepths, NonMonotonicDepth, Refusal, Repush
+};
+use crate::frame::clean::{NanPolicy, RodChanges, SeatingArtifacts};
+use crate::math::basic::UnitWeight;
+use crate::math::correlations::PhiMethod;
+use crate::math::liquefaction::{MsfMethod, RdMethod};
+use crate::frame::dialect::{Encoding, parse_delimiter};
+use crate::frame::units::Unit;
 
 /// Main configuration structure.
 #[derive(Debug, Deserialize, Clone)]
 pub struct Config {
     pub input: InputConfig,
     pub output: OutputConfig,
+    pub qa: QaConfig,
 }
 
 /// Input configuration.
@@ -14,6 +27,15 @@ pub struct Config {
 pub struct InputConfig {
     pub parameters: InputParameters,
     pub columns: InputColumns,
+    pub dmt_columns: DmtColumns,
+    #[serde(default)]
+    pub units: InputUnits,
+    #[serde(default)]
+    pub dialect: InputDialect,
+    /// Error indicator values of single input columns, by column key, checked
+    /// in addition to the indicators given to the cleaning steps.
+    #[serde(default)]
+    pub indicators: BTreeMap<String, Vec<f64>>,
 }
 
 /// Output configuration.
@@ -23,32 +45,190 @@ pub struct OutputConfig {
     pub columns: OutputColumns,
 }
 
+/// Quality assurance configuration.
+#[derive(Debug, Deserialize, Clone)]
+pub struct QaConfig {
+    pub parameters: QaParameters,
+}
+
 /// Input parameters for CPTu calculations.
 #[derive(Debug, Deserialize, Clone)]
 pub struct InputParameters {
     pub a_ratio: f64,
     pub gamma_w: f64,
     pub gamma_s: f64,
+    pub unit_weight: String,
     pub p_ref: f64,
     pub rolling: usize,
+    pub smoothing: String,
+    pub trim_ratio: f64,
+    pub sg_order: usize,
     pub water_level: f64,
+    pub error_indicators: Vec<f64>,
+    pub despike_window: usize,
+    pub despike_sigmas: f64,
+    pub interp_max_gap: usize,
+    pub duplicate_depths: String,
+    pub non_monotonic_depth: String,
+    pub repush: String,
+    pub seating_length: f64,
+    pub seating_qc: f64,
+    pub seating_artifacts: String,
+    pub rod_length: f64,
+    pub rod_dip_length: f64,
+    pub rod_dip_ratio: f64,
+    pub rod_tolerance: f64,
+    pub rod_changes: String,
+    pub empty_tolerance: f64,
+    pub refusal_qc: f64,
+    pub refusal_ratio: f64,
+    pub refusal: String,
 }
 
-/// Output parameters for iterative calculations.
+/// Output parameters for iterative calculations and export.
 #[derive(Debug, Deserialize, Clone)]
 pub struct OutputParameters {
     pub max_iter: usize,
     pub tolerance: f64,
+    #[serde(default)]
+    pub behavior_top: f64,
+    #[serde(default)]
+    pub behavior_bottom: Option<f64>,
+    pub depth_bin: f64,
+    pub aggregation: String,
+    #[serde(default)]
+    pub ascii_headers: bool,
+    #[serde(default)]
+    pub header_comments: bool,
+    #[serde(default)]
+    pub units_row: bool,
+    pub selection: String,
+    pub nan_policy: String,
+    #[serde(default)]
+    pub operator: Option<String>,
+    pub cone_diameter: f64,
+    pub thin_layer_ic: f64,
+    pub thin_layer_contrast: f64,
+    pub nkt: f64,
+    pub k_ocr: f64,
+    pub su_ic: f64,
+    pub phi_method: String,
+    pub dr_c0: f64,
+    pub dr_c1: f64,
+    pub dr_c2: f64,
+    pub cd_boundary: f64,
+    pub ib_clay: f64,
+    pub ib_sand: f64,
+    pub ccs_qtn: f64,
+    pub ccs_fr: f64,
+    pub fc_cfc: f64,
+    pub liq_ic: f64,
+    pub pga: f64,
+    pub magnitude: f64,
+    pub rd_method: String,
+    pub msf_method: String,
+    pub ldi_depth: f64,
+    pub creep_years: f64,
+}
+
+/// Parameters of the data quality heuristics.
+#[derive(Debug, Deserialize, Clone)]
+pub struct QaParameters {
+    pub clog_window: usize,
+    pub clog_fs_rise: f64,
+    pub clog_qc_stall: f64,
+    pub desat_window: usize,
+    pub desat_dense_qc: f64,
+    pub desat_damping: f64,
+    pub sat_loss_window: usize,
+    pub sat_loss_u2: f64,
+    pub sat_loss_rf: f64,
+    pub u2_flat_range: f64,
+    pub unreliable_weight: f64,
+    pub range_min_qc: f64,
+    pub range_min_fs: f64,
+    pub range_min_u2: f64,
+    pub range_max_depth_jump: f64,
 }
 
 /// Input column names (from CSV).
 #[derive(Debug, Deserialize, Clone)]
 pub struct InputColumns {
+    pub depth: ColumnAliases,
+    pub qc: ColumnAliases,
+    pub fs: ColumnAliases,
+    pub u2: ColumnAliases,
+    pub u0: ColumnAliases,
+    pub inclination: ColumnAliases,
+}
+
+/// Header spellings accepted for one input column.
+///
+/// Given either as a single name or as a list of aliases, e.g.
+/// `depth = ["Depth (m)", "Depth [m]", "z (m)"]`. The first alias is the
+/// canonical name the column is renamed to on read.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(from = "AliasSpec")]
+pub struct ColumnAliases(Vec<String>);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AliasSpec {
+    Single(String),
+    List(Vec<String>),
+}
+
+impl From<AliasSpec> for ColumnAliases {
+    fn from(spec: AliasSpec) -> Self {
+        match spec {
+            AliasSpec::Single(name) => Self(vec![name]),
+            AliasSpec::List(names) => Self(names),
+        }
+    }
+}
+
+impl ColumnAliases {
+    /// Returns the canonical column name.
+    pub fn name(&self) -> &str {
+        self.0.first().map(String::as_str).unwrap_or_default()
+    }
+
+    /// Returns all accepted header spellings in order of preference.
+    pub fn aliases(&self) -> &[String] {
+        &self.0
+    }
+}
+
+/// DMT column names (from CSV).
+#[derive(Debug, Deserialize, Clone)]
+pub struct DmtColumns {
     pub depth: String,
-    pub qc: String,
-    pub fs: String,
-    pub u2: String,
-    pub u0: String
+    pub id: String,
+    pub kd: String,
+    pub ed: String,
+}
+
+/// Explicit source units of the input columns.
+///
+/// Entries left unset are detected from the column headers.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct InputUnits {
+    pub depth: Option<String>,
+    pub qc: Option<String>,
+    pub fs: Option<String>,
+    pub u2: Option<String>,
+    pub u0: Option<String>,
+}
+
+/// Explicit CSV dialect of the input files.
+///
+/// Entries left unset are guessed from the first bytes of each file.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct InputDialect {
+    pub delimiter: Option<String>,
+    pub decimal: Option<String>,
+    pub header_row: Option<usize>,
+    pub encoding: Option<String>,
 }
 
 /// Output column names (derived parameters).
@@ -57,19 +237,96 @@ pub struct OutputColumns {
     pub sigv_tot: String,
     pub sigv_eff: String,
     pub qt: String,
+    pub qn: String,
+    pub qe: String,
+    pub rf: String,
     pub fr: String,
     pub bq: String,
+    pub du: String,
+    pub u2_u0: String,
+    pub du_sigv: String,
     pub n: String,
     pub qtn: String,
     pub ic: String,
     pub convg: String,
+    pub excluded: String,
     pub cd: String,
-    pub ib: String
+    pub ib: String,
+    pub sbt: String,
+    pub sbt_desc: String,
+    pub group: String,
+    pub soil: String,
+    pub schneider: String,
+    pub isbt: String,
+    pub isbt_zone: String,
+    pub kc: String,
+    pub qtn_cs: String,
+    pub fc: String,
+    pub n60: String,
+    pub e_mod: String,
+    pub m_mod: String,
+    pub mv: String,
+    pub cr: String,
+    pub vs: String,
+    pub g0: String,
+    pub su: String,
+    pub st: String,
+    pub sigp: String,
+    pub ocr: String,
+    pub k0: String,
+    pub k: String,
+    pub cv: String,
+    pub phi: String,
+    pub dr: String,
+    pub psi: String,
+    pub gamma: String,
+    pub fs_ok: String,
+    pub u2_ok: String,
+    pub u2_sat: String,
+    pub drop_reason: String,
+    pub interpolated: String,
+    pub range_flags: String,
+    pub kh: String,
+    pub qt_thin: String,
+    pub crr: String,
+    pub liquefiable: String,
+    pub thin_layer: String,
+    pub rd: String,
+    pub csr: String,
+    pub fs_liq: String,
+    pub qc1ncs: String,
+    pub crr_bi: String,
+    pub k_sigma: String,
+    pub fs_liq_bi: String,
+    pub ev: String,
+    pub settlement: String,
+    pub gamma_max: String,
+    pub push_depth: String,
+    pub rod_change: String,
+    pub u2_loss: String,
+    pub spike: String,
+    pub indicator: String,
 }
 
 /// Global configuration instance.
 static CONFIG: OnceLock<Config> = OnceLock::new();
 
+/// Path of the configuration file, relative to the working directory.
+const CONFIG_PATH: &str = "conic-core/config.toml";
+
+/// Configuration file of the crate sources, used when `CONFIG_PATH` does not
+/// exist (e.g. when running the crate tests from `conic-core/`).
+const CRATE_CONFIG_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/config.toml");
+
+/// Returns the path the configuration is read from.
+fn config_path() -> &'static str {
+    if std::path::Path::new(CONFIG_PATH).exists() {
+        CONFIG_PATH
+    } else {
+        CRATE_CONFIG_PATH
+    }
+}
+
 /// Loads and returns the global configuration.
 ///
 /// The configuration is loaded once from `config.toml` and cached.
@@ -79,36 +336,51 @@ static CONFIG: OnceLock<Config> = OnceLock::new();
 /// Panics if the configuration file cannot be read, parsed, or contains
 /// invalid values.
 fn config() -> &'static Config {
-    CONFIG.get_or_init(|| {
-        let config_path = "conic-core/config.toml";
-        let config_content = std::fs::read_to_string(config_path)
-            .unwrap_or_else(|err| {
-                panic!(
-                    "Failed to read configuration file '{}': {}",
-                    config_path, err
-                )
-            });
-
-        let cfg: Config = toml::from_str(&config_content).unwrap_or_else(|err| {
-            panic!(
-                "Failed to parse configuration file '{}': {}",
+    CONFIG.get_or_init(|| load_config().unwrap_or_else(|err| panic!("{}", err)))
+}
+
+/// Reads, parses and validates the configuration file.
+fn load_config() -> Result<Config, CoreError> {
+    let config_path = config_path();
+    let config_content = std::fs::read_to_string(config_path)
+        .map_err(|err| {
+            CoreError::InvalidConfig(format!(
+                "Failed to read configuration file '{}': {}",
                 config_path, err
-            )
-        });
+            ))
+        })?;
+
+    let cfg: Config = toml::from_str(&config_content).map_err(|err| {
+        CoreError::InvalidConfig(format!(
+            "Failed to parse configuration file '{}': {}",
+            config_path, err
+        ))
+    })?;
 
-        // validate configuration
-        validate_config(&cfg).unwrap_or_else(|err| {
-            panic!("{}", err)
-        });
+    // validate configuration
+    validate_config(&cfg)?;
 
-        cfg
-    })
+    Ok(cfg)
 }
 
+/// Checks whether the configuration can be loaded without panicking.
+///
+/// Returns the path of the configuration file on success.
+pub(crate) fn try_load_config() -> Result<&'static str, CoreError> {
+    if CONFIG.get().is_none() {
+        load_config()?;
+    }
+
+    Ok(config_path())
+}
+
+/// Accepted rolling windows (samples), centered on each record.
+pub(crate) const ROLLING_WINDOWS: [usize; 3] = [1, 3, 5];
+
 /// Validates the loaded configuration.
 fn validate_config(cfg: &Config) -> Result<(), CoreError> {
     // validate rolling parameter
-    if ![1, 3, 5].contains(&cfg.input.parameters.rolling) {
+    if !ROLLING_WINDOWS.contains(&cfg.input.parameters.rolling) {
         return Err(CoreError::InvalidConfig(
             format!(
                 "Invalid rolling parameter: {}. Must be 1, 3, or 5",
@@ -117,6 +389,131 @@ fn validate_config(cfg: &Config) -> Result<(), CoreError> {
         ));
     }
 
+    // validate despiking parameters
+    let params = &cfg.input.parameters;
+    if params.despike_window < 3 || params.despike_window.is_multiple_of(2) {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid despike_window parameter: {}. Must be odd and >= 3",
+            params.despike_window
+        )));
+    }
+    if params.despike_sigmas.is_nan() || params.despike_sigmas <= 0.0 {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid despike_sigmas parameter: {}. Must be > 0",
+            params.despike_sigmas
+        )));
+    }
+
+    // validate error indicators
+    let invalid_indicator = params
+        .error_indicators
+        .iter()
+        .find(|value| !value.is_finite());
+    if let Some(value) = invalid_indicator {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid error_indicators value: {}. Must be finite",
+            value
+        )));
+    }
+
+    // validate interpolation gap
+    if params.interp_max_gap == 0 {
+        return Err(CoreError::InvalidConfig(
+            "Invalid interp_max_gap parameter: 0. Must be >= 1".to_string()
+        ));
+    }
+
+    // validate empty record tolerance
+    if params.empty_tolerance.is_nan() || params.empty_tolerance < 0.0 {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid empty_tolerance parameter: {}. Must be >= 0",
+            params.empty_tolerance
+        )));
+    }
+
+    // validate refusal detection
+    Refusal::from_config(&params.refusal)?;
+
+    if params.refusal_qc.is_nan() || params.refusal_qc <= 0.0 {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid refusal_qc parameter: {}. Must be > 0",
+            params.refusal_qc
+        )));
+    }
+    if !(params.refusal_ratio > 0.0 && params.refusal_ratio <= 1.0) {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid refusal_ratio parameter: {}. Must be in (0, 1]",
+            params.refusal_ratio
+        )));
+    }
+
+    // validate rod change detection
+    for (name, value) in [
+        ("rod_length", params.rod_length),
+        ("rod_dip_length", params.rod_dip_length),
+        ("rod_tolerance", params.rod_tolerance),
+    ] {
+        if value.is_nan() || value <= 0.0 {
+            return Err(CoreError::InvalidConfig(format!(
+                "Invalid {} parameter: {}. Must be > 0",
+                name, value
+            )));
+        }
+    }
+    if !(params.rod_dip_ratio > 0.0 && params.rod_dip_ratio < 1.0) {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid rod_dip_ratio parameter: {}. Must be in (0, 1)",
+            params.rod_dip_ratio
+        )));
+    }
+
+    // validate duplicate depth resolution
+    DuplicateDepths::from_config(&params.duplicate_depths)?;
+    NonMonotonicDepth::from_config(&params.non_monotonic_depth)?;
+
+    // validate re-push merging
+    Repush::from_config(&params.repush)?;
+
+    // validate unit weight of the vertical stresses
+    UnitWeight::from_config(&params.unit_weight, params.gamma_s)?;
+
+    // validate seating artifact removal
+    SeatingArtifacts::from_config(&params.seating_artifacts)?;
+    RodChanges::from_config(&params.rod_changes)?;
+    if params.seating_length.is_nan() || params.seating_length < 0.0 {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid seating_length parameter: {}. Must be >= 0",
+            params.seating_length
+        )));
+    }
+
+    // validate smoothing, trim_ratio and sg_order parameters
+    Smoothing::from_config(
+        &cfg.input.parameters.smoothing,
+        cfg.input.parameters.trim_ratio,
+        cfg.input.parameters.sg_order
+    )?;
+    let params = &cfg.input.parameters;
+    if params.smoothing == "savitzky_golay"
+        && params.rolling > 1
+        && (params.rolling.is_multiple_of(2) || params.rolling <= params.sg_order)
+    {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid rolling parameter: {}. Savitzky-Golay smoothing needs an \
+             odd window larger than sg_order ({})",
+            params.rolling, params.sg_order
+        )));
+    }
+
+    // validate output selection
+    OutputSelection::from_config(&cfg.output.parameters.selection)?;
+
+    // validate downsampling aggregation
+    Aggregation::from_config(&cfg.output.parameters.aggregation)?;
+
+    // validate NaN propagation policy
+    NanPolicy::from_config(&cfg.output.parameters.nan_policy)?;
+
     // validate water_level parameter
     if cfg.input.parameters.water_level < 0.0 {
         return Err(CoreError::InvalidConfig(
@@ -127,13 +524,373 @@ fn validate_config(cfg: &Config) -> Result<(), CoreError> {
         ));
     }
 
+    // validate depth_bin parameter
+    if cfg.output.parameters.depth_bin < 0.0 {
+        return Err(CoreError::InvalidConfig(
+            format!(
+                "Invalid depth_bin parameter: {}. Must be >= 0",
+                cfg.output.parameters.depth_bin
+            )
+        ));
+    }
+
+    // validate input column aliases
+    let columns = &cfg.input.columns;
+    for (key, aliases) in [
+        ("depth", &columns.depth),
+        ("qc", &columns.qc),
+        ("fs", &columns.fs),
+        ("u2", &columns.u2),
+        ("u0", &columns.u0),
+        ("inclination", &columns.inclination),
+    ] {
+        if aliases.aliases().is_empty() {
+            return Err(CoreError::InvalidConfig(format!(
+                "Invalid input column '{}': alias list must not be empty",
+                key
+            )));
+        }
+    }
+
+    // validate behavior depth window
+    let params = &cfg.output.parameters;
+    if params.behavior_top < 0.0
+        || params.behavior_bottom.is_some_and(|bottom| bottom <= params.behavior_top)
+    {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid behavior depth window: top {}, bottom {:?}. Top must be \
+             >= 0 and bottom greater than top",
+            params.behavior_top, params.behavior_bottom
+        )));
+    }
+
+    // validate thin-layer correction parameters
+    if params.cone_diameter.is_nan() || params.cone_diameter <= 0.0 {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid cone_diameter parameter: {}. Must be > 0",
+            params.cone_diameter
+        )));
+    }
+    if params.thin_layer_contrast.is_nan() || params.thin_layer_contrast < 1.0 {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid thin_layer_contrast parameter: {}. Must be >= 1",
+            params.thin_layer_contrast
+        )));
+    }
+
+    // validate fine grained correlation parameters
+    if params.nkt.is_nan() || params.nkt <= 0.0 {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid nkt parameter: {}. Must be > 0",
+            params.nkt
+        )));
+    }
+    if params.k_ocr.is_nan() || params.k_ocr <= 0.0 {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid k_ocr parameter: {}. Must be > 0",
+            params.k_ocr
+        )));
+    }
+    if params.su_ic.is_nan() {
+        return Err(CoreError::InvalidConfig(
+            "Invalid su_ic parameter: NaN. Must be a number".to_string()
+        ));
+    }
+
+    PhiMethod::from_config(&params.phi_method)?;
+    if !(params.dr_c0 > 0.0 && params.dr_c2 > 0.0)
+        || params.dr_c1.is_nan()
+        || params.dr_c1.is_infinite()
+    {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid relative density coefficients: dr_c0 {}, dr_c1 {}, dr_c2 {}. \
+             dr_c0 and dr_c2 must be > 0 and dr_c1 finite",
+            params.dr_c0, params.dr_c1, params.dr_c2
+        )));
+    }
+
+    // validate behavior group boundaries
+    if !(params.ib_clay > 0.0 && params.ib_clay < params.ib_sand)
+        || params.ib_sand.is_infinite()
+    {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid behavior group boundaries: ib_clay {}, ib_sand {}. Must \
+             satisfy 0 < ib_clay < ib_sand",
+            params.ib_clay, params.ib_sand
+        )));
+    }
+    if !params.cd_boundary.is_finite() {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid cd_boundary parameter: {}. Must be finite",
+            params.cd_boundary
+        )));
+    }
+    if !(params.ccs_qtn > 0.0 && params.ccs_fr >= 0.0)
+        || params.ccs_qtn.is_infinite()
+        || params.ccs_fr.is_infinite()
+    {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid sensitive boundary: ccs_qtn {}, ccs_fr {}. ccs_qtn must be \
+             > 0 and ccs_fr >= 0",
+            params.ccs_qtn, params.ccs_fr
+        )));
+    }
+    if !params.fc_cfc.is_finite() {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid fc_cfc parameter: {}. Must be finite",
+            params.fc_cfc
+        )));
+    }
+    if params.liq_ic.is_nan() || params.liq_ic <= 0.0 || params.liq_ic.is_infinite() {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid liq_ic parameter: {}. Must be > 0",
+            params.liq_ic
+        )));
+    }
+    if !(params.pga > 0.0 && params.magnitude > 0.0)
+        || params.pga.is_infinite()
+        || params.magnitude.is_infinite()
+    {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid design earthquake: pga {}, magnitude {}. Both must be > 0",
+            params.pga, params.magnitude
+        )));
+    }
+    RdMethod::from_config(&params.rd_method)?;
+    MsfMethod::from_config(&params.msf_method)?;
+    if params.ldi_depth.is_nan() || params.ldi_depth <= 0.0 {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid ldi_depth parameter: {}. Must be > 0",
+            params.ldi_depth
+        )));
+    }
+    if params.creep_years.is_nan() || params.creep_years < 0.1 {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid creep_years parameter: {}. Must be >= 0.1",
+            params.creep_years
+        )));
+    }
+
+    // validate quality assurance parameters
+    let qa = &cfg.qa.parameters;
+    if qa.clog_window < 2 {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid clog_window parameter: {}. Must be >= 2",
+            qa.clog_window
+        )));
+    }
+    if qa.desat_window < 2 {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid desat_window parameter: {}. Must be >= 2",
+            qa.desat_window
+        )));
+    }
+    if !(0.0..=1.0).contains(&qa.desat_damping) {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid desat_damping parameter: {}. Must be in [0, 1]",
+            qa.desat_damping
+        )));
+    }
+    if qa.sat_loss_window < 2 {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid sat_loss_window parameter: {}. Must be >= 2",
+            qa.sat_loss_window
+        )));
+    }
+    if qa.sat_loss_u2.is_nan() || qa.sat_loss_u2 < 0.0 {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid sat_loss_u2 parameter: {}. Must be >= 0",
+            qa.sat_loss_u2
+        )));
+    }
+    if qa.sat_loss_rf.is_nan() || qa.sat_loss_rf < 0.0 {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid sat_loss_rf parameter: {}. Must be >= 0",
+            qa.sat_loss_rf
+        )));
+    }
+    if !(0.0..=1.0).contains(&qa.unreliable_weight) {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid unreliable_weight parameter: {}. Must be in [0, 1]",
+            qa.unreliable_weight
+        )));
+    }
+    if qa.range_max_depth_jump.is_nan() || qa.range_max_depth_jump <= 0.0 {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid range_max_depth_jump parameter: {}. Must be > 0",
+            qa.range_max_depth_jump
+        )));
+    }
+
+    // validate explicit source units
+    let units = &cfg.input.units;
+    for label in [&units.depth, &units.qc, &units.fs, &units.u2, &units.u0]
+        .into_iter()
+        .flatten()
+    {
+        if Unit::parse(label).is_none() {
+            return Err(CoreError::InvalidConfig(format!(
+                "Invalid unit '{}'. Must be one of Pa, kPa, MPa, mm, cm, m",
+                label
+            )));
+        }
+    }
+
+    // validate per-column error indicators
+    let input_keys = ["depth", "qc", "fs", "u2", "u0", "inclination"];
+    if let Some(key) = cfg
+        .input
+        .indicators
+        .keys()
+        .find(|key| !input_keys.contains(&key.as_str()))
+    {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid indicators entry '{}'. Must be one of {}",
+            key,
+            input_keys.join(", ")
+        )));
+    }
+
+    // validate explicit CSV dialect
+    let dialect = &cfg.input.dialect;
+    if let Some(delimiter) = &dialect.delimiter
+        && parse_delimiter(delimiter).is_none()
+    {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid delimiter '{}'. Must be a single ASCII character or \\t",
+            delimiter
+        )));
+    }
+    if let Some(decimal) = &dialect.decimal
+        && decimal != "."
+        && decimal != ","
+    {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid decimal mark '{}'. Must be '.' or ','",
+            decimal
+        )));
+    }
+    if let Some(encoding) = &dialect.encoding
+        && Encoding::parse(encoding).is_none()
+    {
+        return Err(CoreError::InvalidConfig(format!(
+            "Invalid encoding '{}'. Must be utf-8 or latin-1",
+            encoding
+        )));
+    }
+
     Ok(())
 }
 
-fn input_cols() -> &'static InputColumns {
+/// Returns the explicit CSV dialect from the configuration.
+pub(crate) fn input_dialect() -> &'static InputDialect {
+    &config().input.dialect
+}
+
+/// Returns the explicit source units from the configuration.
+pub(crate) fn input_units() -> &'static InputUnits {
+    &config().input.units
+}
+
+/// Returns the configuration key and name of every input and output column.
+///
+/// Keys are plain ASCII identifiers (e.g. `sigv_tot`), used as header aliases
+/// by tools that cannot handle non-ASCII names.
+pub(crate) fn column_keys() -> Vec<(&'static str, &'static str)> {
+    let input = input_cols();
+    let output = output_cols();
+
+    vec![
+        ("depth", input.depth.name()),
+        ("qc", input.qc.name()),
+        ("fs", input.fs.name()),
+        ("u2", input.u2.name()),
+        ("u0", input.u0.name()),
+        ("inclination", input.inclination.name()),
+        ("sigv_tot", &output.sigv_tot),
+        ("sigv_eff", &output.sigv_eff),
+        ("qt", &output.qt),
+        ("qn", &output.qn),
+        ("qe", &output.qe),
+        ("rf", &output.rf),
+        ("fr", &output.fr),
+        ("bq", &output.bq),
+        ("du", &output.du),
+        ("u2_u0", &output.u2_u0),
+        ("du_sigv", &output.du_sigv),
+        ("n", &output.n),
+        ("qtn", &output.qtn),
+        ("ic", &output.ic),
+        ("convg", &output.convg),
+        ("excluded", &output.excluded),
+        ("cd", &output.cd),
+        ("ib", &output.ib),
+        ("sbt", &output.sbt),
+        ("sbt_desc", &output.sbt_desc),
+        ("group", &output.group),
+        ("soil", &output.soil),
+        ("schneider", &output.schneider),
+        ("isbt", &output.isbt),
+        ("isbt_zone", &output.isbt_zone),
+        ("kc", &output.kc),
+        ("qtn_cs", &output.qtn_cs),
+        ("fc", &output.fc),
+        ("n60", &output.n60),
+        ("e_mod", &output.e_mod),
+        ("m_mod", &output.m_mod),
+        ("mv", &output.mv),
+        ("cr", &output.cr),
+        ("vs", &output.vs),
+        ("g0", &output.g0),
+        ("su", &output.su),
+        ("st", &output.st),
+        ("sigp", &output.sigp),
+        ("ocr", &output.ocr),
+        ("k0", &output.k0),
+        ("k", &output.k),
+        ("cv", &output.cv),
+        ("phi", &output.phi),
+        ("dr", &output.dr),
+        ("psi", &output.psi),
+        ("gamma", &output.gamma),
+        ("fs_ok", &output.fs_ok),
+        ("u2_ok", &output.u2_ok),
+        ("u2_sat", &output.u2_sat),
+        ("drop_reason", &output.drop_reason),
+        ("interpolated", &output.interpolated),
+        ("range_flags", &output.range_flags),
+        ("kh", &output.kh),
+        ("qt_thin", &output.qt_thin),
+        ("crr", &output.crr),
+        ("liquefiable", &output.liquefiable),
+        ("thin_layer", &output.thin_layer),
+        ("rd", &output.rd),
+        ("csr", &output.csr),
+        ("fs_liq", &output.fs_liq),
+        ("qc1ncs", &output.qc1ncs),
+        ("crr_bi", &output.crr_bi),
+        ("k_sigma", &output.k_sigma),
+        ("fs_liq_bi", &output.fs_liq_bi),
+        ("ev", &output.ev),
+        ("settlement", &output.settlement),
+        ("gamma_max", &output.gamma_max),
+        ("push_depth", &output.push_depth),
+        ("rod_change", &output.rod_change),
+        ("u2_loss", &output.u2_loss),
+        ("spike", &output.spike),
+        ("indicator", &output.indicator),
+    ]
+}
+
+/// Returns the input column aliases from the configuration.
+pub(crate) fn input_cols() -> &'static InputColumns {
     &config().input.columns
 }
 
+fn dmt_cols() -> &'static DmtColumns {
+    &config().input.dmt_columns
+}
+
 fn output_cols() -> &'static OutputColumns {
     &config().output.columns
 }
@@ -146,39 +903,319 @@ fn output_params() -> &'static OutputParameters {
     &config().output.parameters
 }
 
+fn qa_params() -> &'static QaParameters {
+    &config().qa.parameters
+}
+
 // Lazy-initialized column name constants for cleaner config access
 
 // Input column names
-pub static COL_DEPTH: LazyLock<&str> = LazyLock::new(|| &input_cols().depth);
-pub static COL_QC: LazyLock<&str> = LazyLock::new(|| &input_cols().qc);
-pub static COL_FS: LazyLock<&str> = LazyLock::new(|| &input_cols().fs);
-pub static COL_U2: LazyLock<&str> = LazyLock::new(|| &input_cols().u2);
-pub static COL_U0: LazyLock<&str> = LazyLock::new(|| &input_cols().u0);
+pub static COL_DEPTH: LazyLock<&str> = LazyLock::new(|| input_cols().depth.name());
+pub static COL_QC: LazyLock<&str> = LazyLock::new(|| input_cols().qc.name());
+pub static COL_FS: LazyLock<&str> = LazyLock::new(|| input_cols().fs.name());
+pub static COL_U2: LazyLock<&str> = LazyLock::new(|| input_cols().u2.name());
+pub static COL_U0: LazyLock<&str> = LazyLock::new(|| input_cols().u0.name());
+pub static COL_INCLINATION: LazyLock<&str> =
+    LazyLock::new(|| input_cols().inclination.name());
+
+// Per-column error indicators, by column name
+pub static COLUMN_INDICATORS: LazyLock<Vec<(&str, Vec<f64>)>> = LazyLock::new(|| {
+    column_keys()
+        .into_iter()
+        .filter_map(|(key, name)| {
+            let indicators = config().input.indicators.get(key)?;
+            Some((name, indicators.clone()))
+        })
+        .collect()
+});
+
+// DMT column names
+pub static COL_DMT_DEPTH: LazyLock<&str> = LazyLock::new(|| &dmt_cols().depth);
+pub static COL_DMT_ID: LazyLock<&str> = LazyLock::new(|| &dmt_cols().id);
+pub static COL_DMT_KD: LazyLock<&str> = LazyLock::new(|| &dmt_cols().kd);
+pub static COL_DMT_ED: LazyLock<&str> = LazyLock::new(|| &dmt_cols().ed);
 
 // Output column names
 pub static COL_SIGV_TOT: LazyLock<&str> = LazyLock::new(|| &output_cols().sigv_tot);
 pub static COL_SIGV_EFF: LazyLock<&str> = LazyLock::new(|| &output_cols().sigv_eff);
 pub static COL_QT: LazyLock<&str> = LazyLock::new(|| &output_cols().qt);
+pub static COL_QN: LazyLock<&str> = LazyLock::new(|| &output_cols().qn);
+pub static COL_QE: LazyLock<&str> = LazyLock::new(|| &output_cols().qe);
+pub static COL_RF: LazyLock<&str> = LazyLock::new(|| &output_cols().rf);
 pub static COL_FR: LazyLock<&str> = LazyLock::new(|| &output_cols().fr);
 pub static COL_BQ: LazyLock<&str> = LazyLock::new(|| &output_cols().bq);
+pub static COL_DU: LazyLock<&str> = LazyLock::new(|| &output_cols().du);
+pub static COL_U2_U0: LazyLock<&str> = LazyLock::new(|| &output_cols().u2_u0);
+pub static COL_DU_SIGV: LazyLock<&str> = LazyLock::new(|| &output_cols().du_sigv);
 pub static COL_N: LazyLock<&str> = LazyLock::new(|| &output_cols().n);
 pub static COL_QTN: LazyLock<&str> = LazyLock::new(|| &output_cols().qtn);
 pub static COL_IC: LazyLock<&str> = LazyLock::new(|| &output_cols().ic);
 pub static COL_CONVG: LazyLock<&str> = LazyLock::new(|| &output_cols().convg);
+pub static COL_EXCLUDED: LazyLock<&str> =
+    LazyLock::new(|| &output_cols().excluded);
 pub static COL_CD: LazyLock<&str> = LazyLock::new(|| &output_cols().cd);
 pub static COL_IB: LazyLock<&str> = LazyLock::new(|| &output_cols().ib);
+pub static COL_SBT: LazyLock<&str> = LazyLock::new(|| &output_cols().sbt);
+pub static COL_SBT_DESC: LazyLock<&str> = LazyLock::new(|| &output_cols().sbt_desc);
+pub static COL_GROUP: LazyLock<&str> = LazyLock::new(|| &output_cols().group);
+pub static COL_SOIL: LazyLock<&str> = LazyLock::new(|| &output_cols().soil);
+pub static COL_SCHNEIDER: LazyLock<&str> = LazyLock::new(|| &output_cols().schneider);
+pub static COL_ISBT: LazyLock<&str> = LazyLock::new(|| &output_cols().isbt);
+pub static COL_ISBT_ZONE: LazyLock<&str> =
+    LazyLock::new(|| &output_cols().isbt_zone);
+pub static COL_KC: LazyLock<&str> = LazyLock::new(|| &output_cols().kc);
+pub static COL_QTN_CS: LazyLock<&str> = LazyLock::new(|| &output_cols().qtn_cs);
+pub static COL_FC: LazyLock<&str> = LazyLock::new(|| &output_cols().fc);
+pub static COL_N60: LazyLock<&str> = LazyLock::new(|| &output_cols().n60);
+pub static COL_E_MOD: LazyLock<&str> = LazyLock::new(|| &output_cols().e_mod);
+pub static COL_M_MOD: LazyLock<&str> = LazyLock::new(|| &output_cols().m_mod);
+pub static COL_MV: LazyLock<&str> = LazyLock::new(|| &output_cols().mv);
+pub static COL_CR: LazyLock<&str> = LazyLock::new(|| &output_cols().cr);
+pub static COL_VS: LazyLock<&str> = LazyLock::new(|| &output_cols().vs);
+pub static COL_G0: LazyLock<&str> = LazyLock::new(|| &output_cols().g0);
+pub static COL_SU: LazyLock<&str> = LazyLock::new(|| &output_cols().su);
+pub static COL_ST: LazyLock<&str> = LazyLock::new(|| &output_cols().st);
+pub static COL_SIGP: LazyLock<&str> = LazyLock::new(|| &output_cols().sigp);
+pub static COL_OCR: LazyLock<&str> = LazyLock::new(|| &output_cols().ocr);
+pub static COL_K0: LazyLock<&str> = LazyLock::new(|| &output_cols().k0);
+pub static COL_K: LazyLock<&str> = LazyLock::new(|| &output_cols().k);
+pub static COL_CV: LazyLock<&str> = LazyLock::new(|| &output_cols().cv);
+pub static COL_PHI: LazyLock<&str> = LazyLock::new(|| &output_cols().phi);
+pub static COL_DR: LazyLock<&str> = LazyLock::new(|| &output_cols().dr);
+pub static COL_PSI: LazyLock<&str> = LazyLock::new(|| &output_cols().psi);
+pub static COL_GAMMA: LazyLock<&str> = LazyLock::new(|| &output_cols().gamma);
+pub static COL_FS_OK: LazyLock<&str> = LazyLock::new(|| &output_cols().fs_ok);
+pub static COL_U2_OK: LazyLock<&str> = LazyLock::new(|| &output_cols().u2_ok);
+pub static COL_U2_SAT: LazyLock<&str> = LazyLock::new(|| &output_cols().u2_sat);
+pub static COL_DROP_REASON: LazyLock<&str> =
+    LazyLock::new(|| &output_cols().drop_reason);
+pub static COL_INTERPOLATED: LazyLock<&str> =
+    LazyLock::new(|| &output_cols().interpolated);
+pub static COL_RANGE_FLAGS: LazyLock<&str> =
+    LazyLock::new(|| &output_cols().range_flags);
+pub static COL_KH: LazyLock<&str> = LazyLock::new(|| &output_cols().kh);
+pub static COL_QT_THIN: LazyLock<&str> = LazyLock::new(|| &output_cols().qt_thin);
+pub static COL_CRR: LazyLock<&str> = LazyLock::new(|| &output_cols().crr);
+pub static COL_LIQUEFIABLE: LazyLock<&str> =
+    LazyLock::new(|| &output_cols().liquefiable);
+pub static COL_THIN_LAYER: LazyLock<&str> =
+    LazyLock::new(|| &output_cols().thin_layer);
+pub static COL_RD: LazyLock<&str> = LazyLock::new(|| &output_cols().rd);
+pub static COL_CSR: LazyLock<&str> = LazyLock::new(|| &output_cols().csr);
+pub static COL_FS_LIQ: LazyLock<&str> = LazyLock::new(|| &output_cols().fs_liq);
+pub static COL_QC1NCS: LazyLock<&str> = LazyLock::new(|| &output_cols().qc1ncs);
+pub static COL_CRR_BI: LazyLock<&str> = LazyLock::new(|| &output_cols().crr_bi);
+pub static COL_K_SIGMA: LazyLock<&str> = LazyLock::new(|| &output_cols().k_sigma);
+pub static COL_FS_LIQ_BI: LazyLock<&str> =
+    LazyLock::new(|| &output_cols().fs_liq_bi);
+pub static COL_EV: LazyLock<&str> = LazyLock::new(|| &output_cols().ev);
+pub static COL_SETTLEMENT: LazyLock<&str> = LazyLock::new(|| &output_cols().settlement);
+pub static COL_GAMMA_MAX: LazyLock<&str> = LazyLock::new(|| &output_cols().gamma_max);
+pub static COL_PUSH_DEPTH: LazyLock<&str> =
+    LazyLock::new(|| &output_cols().push_depth);
+pub static COL_ROD_CHANGE: LazyLock<&str> =
+    LazyLock::new(|| &output_cols().rod_change);
+pub static COL_U2_LOSS: LazyLock<&str> = LazyLock::new(|| &output_cols().u2_loss);
+pub static COL_SPIKE: LazyLock<&str> = LazyLock::new(|| &output_cols().spike);
+pub static COL_INDICATOR: LazyLock<&str> =
+    LazyLock::new(|| &output_cols().indicator);
 
 // Input parameters
 pub static A_RATIO: LazyLock<f64> = LazyLock::new(|| input_params().a_ratio);
 pub static GAMMA_W: LazyLock<f64> = LazyLock::new(|| input_params().gamma_w);
 pub static GAMMA_S: LazyLock<f64> = LazyLock::new(|| input_params().gamma_s);
+pub static UNIT_WEIGHT: LazyLock<UnitWeight> = LazyLock::new(|| {
+    // already validated when the configuration was loaded
+    UnitWeight::from_config(&input_params().unit_weight, *GAMMA_S)
+        .unwrap_or(UnitWeight::Constant(*GAMMA_S))
+});
 pub static P_REF: LazyLock<f64> = LazyLock::new(|| input_params().p_ref);
 pub static ROLLING: LazyLock<usize> = LazyLock::new(|| input_params().rolling);
+pub static TRIM_RATIO: LazyLock<f64> =
+    LazyLock::new(|| input_params().trim_ratio);
+pub static SG_ORDER: LazyLock<usize> = LazyLock::new(|| input_params().sg_order);
+pub static SMOOTHING: LazyLock<Smoothing> = LazyLock::new(|| {
+    let params = input_params();
+    // already validated when the configuration was loaded
+    Smoothing::from_config(&params.smoothing, params.trim_ratio, params.sg_order)
+        .unwrap_or(Smoothing::Mean)
+});
 pub static WATER_LEVEL: LazyLock<f64> =
     LazyLock::new(|| input_params().water_level);
+pub static ERROR_INDICATORS: LazyLock<Vec<f64>> =
+    LazyLock::new(|| input_params().error_indicators.clone());
+pub static DESPIKE_WINDOW: LazyLock<usize> =
+    LazyLock::new(|| input_params().despike_window);
+pub static DESPIKE_SIGMAS: LazyLock<f64> =
+    LazyLock::new(|| input_params().despike_sigmas);
+pub static INTERP_MAX_GAP: LazyLock<usize> =
+    LazyLock::new(|| input_params().interp_max_gap);
+pub static DUPLICATE_DEPTHS: LazyLock<DuplicateDepths> = LazyLock::new(|| {
+    // already validated when the configuration was loaded
+    DuplicateDepths::from_config(&input_params().duplicate_depths)
+        .unwrap_or(DuplicateDepths::KeepFirst)
+});
+pub static NON_MONOTONIC_DEPTH: LazyLock<NonMonotonicDepth> = LazyLock::new(|| {
+    // already validated when the configuration was loaded
+    NonMonotonicDepth::from_config(&input_params().non_monotonic_depth)
+        .unwrap_or(NonMonotonicDepth::Error)
+});
+pub static REPUSH: LazyLock<Repush> = LazyLock::new(|| {
+    // already validated when the configuration was loaded
+    Repush::from_config(&input_params().repush).unwrap_or(Repush::KeepSecond)
+});
+pub static SEATING_LENGTH: LazyLock<f64> =
+    LazyLock::new(|| input_params().seating_length);
+pub static SEATING_QC: LazyLock<f64> = LazyLock::new(|| input_params().seating_qc);
+pub static SEATING_ARTIFACTS: LazyLock<SeatingArtifacts> = LazyLock::new(|| {
+    // already validated when the configuration was loaded
+    SeatingArtifacts::from_config(&input_params().seating_artifacts)
+        .unwrap_or(SeatingArtifacts::Remove)
+});
+pub static EMPTY_TOLERANCE: LazyLock<f64> =
+    LazyLock::new(|| input_params().empty_tolerance);
+pub static REFUSAL_QC: LazyLock<f64> = LazyLock::new(|| input_params().refusal_qc);
+pub static REFUSAL_RATIO: LazyLock<f64> =
+    LazyLock::new(|| input_params().refusal_ratio);
+pub static REFUSAL: LazyLock<Refusal> = LazyLock::new(|| {
+    // already validated when the configuration was loaded
+    Refusal::from_config(&input_params().refusal).unwrap_or(Refusal::Detect)
+});
+pub static ROD_LENGTH: LazyLock<f64> = LazyLock::new(|| input_params().rod_length);
+pub static ROD_DIP_LENGTH: LazyLock<f64> =
+    LazyLock::new(|| input_params().rod_dip_length);
+pub static ROD_DIP_RATIO: LazyLock<f64> =
+    LazyLock::new(|| input_params().rod_dip_ratio);
+pub static ROD_TOLERANCE: LazyLock<f64> =
+    LazyLock::new(|| input_params().rod_tolerance);
+pub static ROD_CHANGES: LazyLock<RodChanges> = LazyLock::new(|| {
+    // already validated when the configuration was loaded
+    RodChanges::from_config(&input_params().rod_changes)
+        .unwrap_or(RodChanges::Flag)
+});
 
 // Output parameters
 pub static MAX_ITER: LazyLock<usize> =
     LazyLock::new(|| output_params().max_iter);
 pub static TOLERANCE: LazyLock<f64> =
     LazyLock::new(|| output_params().tolerance);
+pub static BEHAVIOR_TOP: LazyLock<f64> =
+    LazyLock::new(|| output_params().behavior_top);
+pub static BEHAVIOR_BOTTOM: LazyLock<Option<f64>> =
+    LazyLock::new(|| output_params().behavior_bottom);
+pub static DEPTH_BIN: LazyLock<f64> =
+    LazyLock::new(|| output_params().depth_bin);
+pub static AGGREGATION: LazyLock<Aggregation> = LazyLock::new(|| {
+    // already validated when the configuration was loaded
+    Aggregation::from_config(&output_params().aggregation)
+        .unwrap_or(Aggregation::Mean)
+});
+pub static ASCII_HEADERS: LazyLock<bool> =
+    LazyLock::new(|| output_params().ascii_headers);
+pub static HEADER_COMMENTS: LazyLock<bool> =
+    LazyLock::new(|| output_params().header_comments);
+pub static UNITS_ROW: LazyLock<bool> =
+    LazyLock::new(|| output_params().units_row);
+pub static OPERATOR: LazyLock<Option<String>> = LazyLock::new(|| {
+    std::env::var("CONIC_OPERATOR")
+        .ok()
+        .or_else(|| output_params().operator.clone())
+        .filter(|operator| !operator.trim().is_empty())
+});
+pub static SELECTION: LazyLock<OutputSelection> = LazyLock::new(|| {
+    // already validated when the configuration was loaded
+    OutputSelection::from_config(&output_params().selection)
+        .unwrap_or(OutputSelection::Full)
+});
+pub static NAN_POLICY: LazyLock<NanPolicy> = LazyLock::new(|| {
+    // already validated when the configuration was loaded
+    NanPolicy::from_config(&output_params().nan_policy)
+        .unwrap_or(NanPolicy::Propagate)
+});
+pub static CONE_DIAMETER: LazyLock<f64> =
+    LazyLock::new(|| output_params().cone_diameter);
+pub static THIN_LAYER_IC: LazyLock<f64> =
+    LazyLock::new(|| output_params().thin_layer_ic);
+pub static THIN_LAYER_CONTRAST: LazyLock<f64> =
+    LazyLock::new(|| output_params().thin_layer_contrast);
+pub static NKT: LazyLock<f64> =
+    LazyLock::new(|| output_params().nkt);
+pub static K_OCR: LazyLock<f64> =
+    LazyLock::new(|| output_params().k_ocr);
+pub static SU_IC: LazyLock<f64> =
+    LazyLock::new(|| output_params().su_ic);
+pub static PHI_METHOD: LazyLock<PhiMethod> = LazyLock::new(|| {
+    // already validated when the configuration was loaded
+    PhiMethod::from_config(&output_params().phi_method)
+        .unwrap_or(PhiMethod::KulhawyMayne)
+});
+pub static DR_C0: LazyLock<f64> =
+    LazyLock::new(|| output_params().dr_c0);
+pub static DR_C1: LazyLock<f64> =
+    LazyLock::new(|| output_params().dr_c1);
+pub static DR_C2: LazyLock<f64> =
+    LazyLock::new(|| output_params().dr_c2);
+pub static CD_BOUNDARY: LazyLock<f64> =
+    LazyLock::new(|| output_params().cd_boundary);
+pub static IB_CLAY: LazyLock<f64> =
+    LazyLock::new(|| output_params().ib_clay);
+pub static IB_SAND: LazyLock<f64> =
+    LazyLock::new(|| output_params().ib_sand);
+pub static CCS_QTN: LazyLock<f64> =
+    LazyLock::new(|| output_params().ccs_qtn);
+pub static CCS_FR: LazyLock<f64> =
+    LazyLock::new(|| output_params().ccs_fr);
+pub static FC_CFC: LazyLock<f64> =
+    LazyLock::new(|| output_params().fc_cfc);
+pub static LIQ_IC: LazyLock<f64> =
+    LazyLock::new(|| output_params().liq_ic);
+pub static PGA: LazyLock<f64> =
+    LazyLock::new(|| output_params().pga);
+pub static MAGNITUDE: LazyLock<f64> =
+    LazyLock::new(|| output_params().magnitude);
+pub static RD_METHOD: LazyLock<RdMethod> = LazyLock::new(|| {
+    // already validated when the configuration was loaded
+    RdMethod::from_config(&output_params().rd_method)
+        .unwrap_or(RdMethod::LiaoWhitman)
+});
+pub static MSF_METHOD: LazyLock<MsfMethod> = LazyLock::new(|| {
+    // already validated when the configuration was loaded
+    MsfMethod::from_config(&output_params().msf_method)
+        .unwrap_or(MsfMethod::Youd)
+});
+pub static LDI_DEPTH: LazyLock<f64> =
+    LazyLock::new(|| output_params().ldi_depth);
+pub static CREEP_YEARS: LazyLock<f64> =
+    LazyLock::new(|| output_params().creep_years);
+
+// Quality assurance parameters
+pub static CLOG_WINDOW: LazyLock<usize> =
+    LazyLock::new(|| qa_params().clog_window);
+pub static CLOG_FS_RISE: LazyLock<f64> =
+    LazyLock::new(|| qa_params().clog_fs_rise);
+pub static CLOG_QC_STALL: LazyLock<f64> =
+    LazyLock::new(|| qa_params().clog_qc_stall);
+pub static U2_FLAT_RANGE: LazyLock<f64> =
+    LazyLock::new(|| qa_params().u2_flat_range);
+pub static DESAT_WINDOW: LazyLock<usize> =
+    LazyLock::new(|| qa_params().desat_window);
+pub static DESAT_DENSE_QC: LazyLock<f64> =
+    LazyLock::new(|| qa_params().desat_dense_qc);
+pub static DESAT_DAMPING: LazyLock<f64> =
+    LazyLock::new(|| qa_params().desat_damping);
+pub static SAT_LOSS_WINDOW: LazyLock<usize> =
+    LazyLock::new(|| qa_params().sat_loss_window);
+pub static SAT_LOSS_U2: LazyLock<f64> =
+    LazyLock::new(|| qa_params().sat_loss_u2);
+pub static SAT_LOSS_RF: LazyLock<f64> =
+    LazyLock::new(|| qa_params().sat_loss_rf);
+pub static UNRELIABLE_WEIGHT: LazyLock<f64> =
+    LazyLock::new(|| qa_params().unreliable_weight);
+pub static RANGE_MIN_QC: LazyLock<f64> =
+    LazyLock::new(|| qa_params().range_min_qc);
+pub static RANGE_MIN_FS: LazyLock<f64> =
+    LazyLock::new(|| qa_params().range_min_fs);
+pub static RANGE_MIN_U2: LazyLock<f64> =
+    LazyLock::new(|| qa_params().range_min_u2);
+pub static RANGE_MAX_DEPTH_JUMP: LazyLock<f64> =
+    LazyLock::new(|| qa_params().range_max_depth_jump);
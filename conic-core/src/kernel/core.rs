@@ -1,82 +1,1170 @@
+use std::ops::{Bound, RangeBounds};
 use polars::prelude::*;
 use super::error::CoreError;
+use super::meta::{Location, Metadata};
+use super::config::{
+    A_RATIO, GAMMA_S, GAMMA_W, UNIT_WEIGHT, ROLLING, SMOOTHING, MAX_ITER, TOLERANCE,
+    BEHAVIOR_TOP, BEHAVIOR_BOTTOM, CLOG_WINDOW, CLOG_FS_RISE, CLOG_QC_STALL,
+    U2_FLAT_RANGE, DESAT_WINDOW, DESAT_DENSE_QC, DESAT_DAMPING, DESPIKE_WINDOW,
+    DESPIKE_SIGMAS, INTERP_MAX_GAP, SELECTION, DUPLICATE_DEPTHS, NON_MONOTONIC_DEPTH,
+    COLUMN_INDICATORS, COL_INCLINATION, CONE_DIAMETER, THIN_LAYER_IC,
+    THIN_LAYER_CONTRAST, SEATING_LENGTH, SEATING_QC, SEATING_ARTIFACTS, ROD_LENGTH,
+    ROD_DIP_LENGTH, ROD_DIP_RATIO, ROD_TOLERANCE, ROD_CHANGES, EMPTY_TOLERANCE,
+    SAT_LOSS_WINDOW, SAT_LOSS_U2, SAT_LOSS_RF, REFUSAL_QC, REFUSAL_RATIO, REFUSAL,
+    NAN_POLICY, AGGREGATION, REPUSH, ERROR_INDICATORS, COL_DEPTH, COL_QC, COL_FS,
+    COL_U2, COL_U0, COL_QT, COL_SIGV_EFF, CD_BOUNDARY, IB_CLAY, IB_SAND, NKT, K_OCR,
+    SU_IC, PHI_METHOD, DR_C0, DR_C1, DR_C2, COL_GAMMA, FC_CFC, LIQ_IC, PGA, MAGNITUDE,
+    RD_METHOD, MSF_METHOD
+};
+use super::selection::{OutputSelection, Step};
+use crate::frame::fix::{
+    Aggregation, Baseline, DuplicateDepths, NonMonotonicDepth, Refusal, Repush,
+    ZeroReadings
+};
+use crate::frame::clean::{
+    NanPolicy, Replacement, RodChangeOptions, SeatingArtifacts
+};
+use crate::frame::qa::{RangeRules, RangeViolations};
+use crate::frame::smooth::Smoothing;
+use crate::frame::units::UnitWarning;
+use crate::frame::write::{ExportFormat, WriteOptions};
+use crate::math::basic::UnitWeight;
+use crate::math::classify::ClassificationMethod;
+use crate::math::correlations::PhiMethod;
+use crate::math::liquefaction::{
+    CsrOptions, GroundGeometry, LateralDisplacement, TriggeringMethod
+};
+use crate::math::thin_layer::ThinLayerOptions;
+
+/// Parameter list of steps without parameters.
+const NO_PARAMETERS: [(&str, String); 0] = [];
 
 /// DataFrame specialized for CPTu data processing.
 ///
 /// This wrapper provides domain-specific methods for CPTu (Cone Penetration
 /// Test with pore pressure measurement) data analysis while maintaining full
 /// access to underlying Polars DataFrame functionality through Deref.
-pub struct ConicDataFrame(DataFrame);
+#[derive(Clone)]
+pub struct ConicDataFrame {
+    data: DataFrame,
+    meta: Metadata,
+}
 
 impl ConicDataFrame {
     /// Creates a new ConicDataFrame from a Polars DataFrame.
     pub fn new(data: DataFrame) -> Self {
-        Self(data)
+        Self { data, meta: Metadata::default() }
+    }
+
+    /// Creates a new ConicDataFrame with an existing processing record.
+    pub fn with_metadata(data: DataFrame, meta: Metadata) -> Self {
+        Self { data, meta }
     }
 
     /// Computes basic stress-related and normalized CPT parameters.
     ///
     /// This function derives fundamental quantities from raw CPTu data,
-    /// including total and effective vertical stresses.
+    /// including total and effective vertical stresses, the excess pore
+    /// pressure `Δu = u2 - u0` and `U = Δu / σ'v`, the net and effective cone
+    /// resistances `qn = qt - σv_tot` and `qe = qt - u2` (MPa) and the
+    /// friction ratio `Rf = fs / qt` (%). qt and fs are
+    /// smoothed over `rolling` samples with the `smoothing` kernel before
+    /// normalization; robust kernels (median, trimmed or winsorized means)
+    /// limit the influence of short spikes such as gravel contacts.
+    ///
+    /// σv_tot is integrated from `unit_weight` (default `unit_weight` from
+    /// the configuration): a constant γ, or the depth-varying γ of
+    /// `add_gamma_col` in a second pass over the stresses.
+    ///
+    /// Records with a NaN depth, qc, fs, u2 or u0 are handled by
+    /// `nan_policy` (default `nan_policy` from the configuration); skipped
+    /// records are kept by `retain_dropped`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if `rolling` is not 1, 3 or 5, at the
+    /// first NaN input with `NanPolicy::Error`, or with `UnitWeight::Profile`
+    /// if `add_gamma_col` was not applied.
     pub fn add_stress_cols(
         self,
         a_ratio: Option<f64>,
-        gamma: Option<f64>,
-        rolling: Option<usize>
+        unit_weight: Option<UnitWeight>,
+        rolling: Option<usize>,
+        smoothing: Option<Smoothing>,
+        nan_policy: Option<NanPolicy>
     ) -> Result<Self, CoreError> {
+        let nan_policy = nan_policy.unwrap_or(*NAN_POLICY);
+        let inputs = [*COL_DEPTH, *COL_QC, *COL_FS, *COL_U2, *COL_U0];
+        let (data, skipped) =
+            self.apply_nan_policy("add_stress_cols", &inputs, nan_policy)?;
+
         let out_data = crate::math::basic::add_stress_cols(
-            self.0,
+            data.data,
             a_ratio,
-            gamma,
-            rolling
+            unit_weight,
+            rolling,
+            smoothing
         )?;
-        Ok(Self(out_data))
+        let mut meta = data.meta;
+        meta.record("add_stress_cols", [
+            ("a_ratio", a_ratio.unwrap_or(*A_RATIO).to_string()),
+            ("gamma", unit_weight.unwrap_or(*UNIT_WEIGHT).to_string()),
+            ("rolling", rolling.unwrap_or(*ROLLING).to_string()),
+            ("smoothing", format!("{:?}", smoothing.unwrap_or(*SMOOTHING))),
+            ("nan_policy", format!("{:?}", nan_policy)),
+            ("skipped", skipped.to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Computes the penetration pore pressure ratio u2/u0.
+    ///
+    /// Δu and Δu/σ'v are standard columns of `add_stress_cols`.
+    ///
+    /// Requires `add_stress_cols` to be applied first.
+    pub fn add_pore_pressure_cols(self) -> Result<Self, CoreError> {
+        let out_data = crate::math::basic::add_pore_pressure_cols(self.data)?;
+        let mut meta = self.meta;
+        meta.record("add_pore_pressure_cols", NO_PARAMETERS);
+        Ok(Self { data: out_data, meta })
     }
 
     /// Computes the stress exponent `n`, normalized tip resistance `Qtn`,
     /// and soil behavior type index `Ic` for each CPTu record.
+    ///
+    /// Records shallower than `top` or deeper than `bottom` (e.g. the
+    /// disturbed upper meter) are not evaluated: their behavior values are
+    /// NaN and they are flagged in the excluded column. Stress columns are
+    /// unaffected.
+    ///
+    /// Records with a NaN depth, qt, fs or σ'v are handled by `nan_policy`
+    /// as in `add_stress_cols`; NaN values left by the rolling window at the
+    /// ends of the sounding are not inputs and always propagate.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if the depth window is invalid, or
+    /// at the first NaN input with `NanPolicy::Error`.
     pub fn add_behavior_cols(
         self,
         max_iter: Option<usize>,
-        tolerance: Option<f64>
+        tolerance: Option<f64>,
+        top: Option<f64>,
+        bottom: Option<f64>,
+        nan_policy: Option<NanPolicy>
     ) -> Result<Self, CoreError> {
+        let nan_policy = nan_policy.unwrap_or(*NAN_POLICY);
+        let inputs = [*COL_DEPTH, *COL_QT, *COL_FS, *COL_SIGV_EFF];
+        let (data, skipped) =
+            self.apply_nan_policy("add_behavior_cols", &inputs, nan_policy)?;
+
         let out_data = crate::math::basic::add_behavior_cols(
-            self.0,
+            data.data,
             max_iter,
-            tolerance
+            tolerance,
+            top,
+            bottom
         )?;
-        Ok(Self(out_data))
+        let mut meta = data.meta;
+        meta.record("add_behavior_cols", [
+            ("max_iter", max_iter.unwrap_or(*MAX_ITER).to_string()),
+            ("tolerance", tolerance.unwrap_or(*TOLERANCE).to_string()),
+            ("top", top.unwrap_or(*BEHAVIOR_TOP).to_string()),
+            (
+                "bottom",
+                bottom
+                    .or(*BEHAVIOR_BOTTOM)
+                    .map_or_else(|| "none".to_string(), |bottom| bottom.to_string())
+            ),
+            ("nan_policy", format!("{:?}", nan_policy)),
+            ("skipped", skipped.to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    // applies `policy` to the records with NaN `inputs` of `step`, returning
+    // the number of records skipped
+    fn apply_nan_policy(
+        self,
+        step: &str,
+        inputs: &[&str],
+        policy: NanPolicy
+    ) -> Result<(Self, usize), CoreError> {
+        match policy {
+            NanPolicy::Propagate => Ok((self, 0)),
+            NanPolicy::Error => {
+                crate::frame::clean::check_nan(&self.data, inputs, step)?;
+                Ok((self, 0))
+            }
+            NanPolicy::SkipRow => {
+                let mut meta = self.meta;
+                let skipped =
+                    crate::frame::clean::nan_rows(&self.data, Some(inputs), step)?;
+                let out_data = crate::frame::clean::drop_nan(self.data, Some(inputs))?;
+                let n_skipped = skipped.height();
+                if let Some(retained) = meta.dropped.take() {
+                    meta.dropped =
+                        Some(crate::frame::clean::append_rows(retained, skipped)?);
+                }
+                Ok((Self { data: out_data, meta }, n_skipped))
+            }
+        }
+    }
+
+    /// Assigns the 9-zone normalized soil behavior type of Robertson (1990)
+    /// from the Qtn-Fr chart, with a description column.
+    ///
+    /// Zones 2 to 7 follow the Ic boundaries; sensitive fine grained (1) and
+    /// very stiff soils (8, 9) are identified from their chart regions.
+    /// Requires `add_behavior_cols` to be applied first.
+    pub fn add_sbt_cols(self) -> Result<Self, CoreError> {
+        let out_data = crate::math::classify::add_sbt_cols(self.data)?;
+        let mut meta = self.meta;
+        meta.record("add_sbt_cols", NO_PARAMETERS);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Assigns the zone of the Q-U2 chart of Schneider et al. (2008): 1a
+    /// (silts and low Ir clays), 1b (clays), 1c (sensitive clays), 2
+    /// (essentially drained sands) or 3 (transitional).
+    ///
+    /// Uses the excess pore pressure instead of fs, so it suits soft clays
+    /// and silts where the sleeve friction is unreliable. Requires
+    /// `add_stress_cols` to be applied first.
+    pub fn add_schneider_col(self) -> Result<Self, CoreError> {
+        let out_data = crate::math::classify::add_schneider_col(self.data)?;
+        let mut meta = self.meta;
+        meta.record("add_schneider_col", NO_PARAMETERS);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Assigns the soil behavior zones of the chart of `method`, with
+    /// `add_sbt_cols` or `add_schneider_col`.
+    ///
+    /// Requires `add_behavior_cols` to be applied first for the chart of
+    /// Robertson, and `add_stress_cols` for that of Schneider et al.
+    pub fn add_classification_cols(
+        self,
+        method: ClassificationMethod,
+    ) -> Result<Self, CoreError> {
+        match method {
+            ClassificationMethod::Robertson => self.add_sbt_cols(),
+            ClassificationMethod::Schneider => self.add_schneider_col(),
+        }
+    }
+
+    /// Adds a plain-language soil description of each record ("clean sand to
+    /// silty sand", "clay to silty clay", ...) from its Ic zone.
+    ///
+    /// Makes exported tables readable by non-specialists. Requires
+    /// `add_behavior_cols` to be applied first.
+    pub fn add_description_col(self) -> Result<Self, CoreError> {
+        let out_data = crate::math::classify::add_description_col(self.data)?;
+        let mut meta = self.meta;
+        meta.record("add_description_col", NO_PARAMETERS);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Assigns the behavior group of Robertson (2016) (CCS, CC, CD, TC, TD,
+    /// SC or SD) from the CD and IB columns.
+    ///
+    /// The chart boundaries are read from `[output.parameters]`. Requires
+    /// `add_behavior_cols` to be applied first.
+    pub fn add_group_col(self) -> Result<Self, CoreError> {
+        let out_data = crate::math::classify::add_group_col(self.data)?;
+        let mut meta = self.meta;
+        meta.record("add_group_col", [
+            ("cd_boundary", format!("{}", *CD_BOUNDARY)),
+            ("ib_clay", format!("{}", *IB_CLAY)),
+            ("ib_sand", format!("{}", *IB_SAND)),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Adds the fines correction factor Kc and the clean sand equivalent
+    /// normalized cone resistance Qtn,cs = Kc Qtn (Robertson and Wride,
+    /// 1998).
+    ///
+    /// Qtn,cs refines the classification of silty sands and is the input of
+    /// the state parameter and liquefaction triggering calculations.
+    /// Requires `add_behavior_cols` to be applied first.
+    pub fn add_clean_sand_cols(self) -> Result<Self, CoreError> {
+        let out_data = crate::math::classify::add_clean_sand_cols(self.data)?;
+        let mut meta = self.meta;
+        meta.record("add_clean_sand_cols", NO_PARAMETERS);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Estimates the apparent fines content FC (%) from Ic with the
+    /// relationship of Boulanger and Idriss (2014).
+    ///
+    /// Used by the liquefaction fines corrections and to plan laboratory
+    /// tests. `c_fc` is the site-specific fitting parameter, defaulting to
+    /// the configured value; `fines_calibration` derives it from a sampled
+    /// record. Requires `add_behavior_cols` to be applied first.
+    pub fn add_fines_col(self, c_fc: Option<f64>) -> Result<Self, CoreError> {
+        let out_data = crate::math::classify::add_fines_col(self.data, c_fc)?;
+        let mut meta = self.meta;
+        meta.record("add_fines_col", [
+            ("c_fc", c_fc.unwrap_or(*FC_CFC).to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Computes the non-normalized soil behavior type index ISBT from qc/pa
+    /// and Rf, with its zone.
+    ///
+    /// Needs neither stresses nor unit weights, so it suits a quick look at
+    /// shallow soundings or at data whose unit weight is uncertain. Zones 1, 8
+    /// and 9 are not assigned.
+    pub fn add_isbt_cols(self) -> Result<Self, CoreError> {
+        let out_data = crate::math::classify::add_isbt_cols(self.data)?;
+        let mut meta = self.meta;
+        meta.record("add_isbt_cols", NO_PARAMETERS);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Estimates the soil unit weight γ of each record from qt and Rf
+    /// (Robertson and Cabal, 2010).
+    ///
+    /// Apply `add_stress_cols` again with `UnitWeight::Profile` to integrate
+    /// the vertical stresses from this profile instead of a single γ.
+    /// Requires `add_stress_cols` to be applied first.
+    pub fn add_gamma_col(self) -> Result<Self, CoreError> {
+        let out_data = crate::math::correlations::add_gamma_col(self.data)?;
+        let mut meta = self.meta;
+        meta.record("add_gamma_col", [("gamma_w", GAMMA_W.to_string())]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Estimates the equivalent SPT blow count N60 from qt and Ic.
+    ///
+    /// Uses the Robertson (2012) relationship between the normalized cone
+    /// resistance, Ic and N60. Requires `add_behavior_cols` to be applied
+    /// first.
+    pub fn add_spt_cols(self) -> Result<Self, CoreError> {
+        let out_data = crate::math::correlations::add_spt_cols(self.data)?;
+        let mut meta = self.meta;
+        meta.record("add_spt_cols", NO_PARAMETERS);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Estimates the drained Young's modulus E' from qt and Ic.
+    ///
+    /// The modulus factor αE depends on Ic, so clays and sands with the
+    /// same net resistance receive different stiffnesses. E' suits the
+    /// foundation stiffness of sand-like soils; use it alongside the
+    /// constrained modulus of `add_constrained_modulus_col` for fine grained
+    /// soils. Requires `add_behavior_cols` to be applied first.
+    pub fn add_modulus_cols(self) -> Result<Self, CoreError> {
+        let out_data = crate::math::correlations::add_modulus_cols(self.data)?;
+        let mut meta = self.meta;
+        meta.record("add_modulus_cols", NO_PARAMETERS);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Estimates the undrained shear strength su = (qt - σv_tot) / Nkt of
+    /// fine grained records (Ic above the configured `su_ic`).
+    ///
+    /// Default `nkt` is taken from the configuration; typical values range
+    /// from 10 to 20 and should be calibrated against laboratory or vane
+    /// tests. Requires `add_behavior_cols` to be applied first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if `nkt` is not positive.
+    pub fn add_su_col(self, nkt: Option<f64>) -> Result<Self, CoreError> {
+        let out_data = crate::math::correlations::add_su_col(self.data, nkt)?;
+        let mut meta = self.meta;
+        meta.record("add_su_col", [
+            ("nkt", nkt.unwrap_or(*NKT).to_string()),
+            ("ic_cutoff", SU_IC.to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Estimates the sensitivity St = su / fs of fine grained records, with
+    /// fs taken as the remolded strength.
+    ///
+    /// Requires `add_su_col` to be applied first.
+    pub fn add_sensitivity_col(self) -> Result<Self, CoreError> {
+        let out_data = crate::math::correlations::add_sensitivity_col(self.data)?;
+        let mut meta = self.meta;
+        meta.record("add_sensitivity_col", NO_PARAMETERS);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Estimates the effective friction angle φ' of coarse grained records
+    /// (Ic up to the configured `su_ic`).
+    ///
+    /// Default `method` is taken from the configuration. Requires
+    /// `add_behavior_cols` to be applied first.
+    pub fn add_phi_col(self, method: Option<PhiMethod>) -> Result<Self, CoreError> {
+        let out_data = crate::math::correlations::add_phi_col(self.data, method)?;
+        let mut meta = self.meta;
+        meta.record("add_phi_col", [
+            ("method", format!("{:?}", method.unwrap_or(*PHI_METHOD))),
+            ("ic_cutoff", SU_IC.to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Estimates the relative density Dr of coarse grained records (Ic up to
+    /// the configured `su_ic`) with the correlation of Baldi et al. (1986).
+    ///
+    /// The correlation coefficients are read from the configuration.
+    /// Requires `add_behavior_cols` to be applied first.
+    pub fn add_dr_col(self) -> Result<Self, CoreError> {
+        let out_data = crate::math::correlations::add_dr_col(self.data)?;
+        let mut meta = self.meta;
+        meta.record("add_dr_col", [
+            ("c0", DR_C0.to_string()),
+            ("c1", DR_C1.to_string()),
+            ("c2", DR_C2.to_string()),
+            ("ic_cutoff", SU_IC.to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Estimates the state parameter ψ of coarse grained records (Ic up to
+    /// the configured `su_ic`) from the clean sand equivalent Qtn,cs
+    /// (Robertson, 2010).
+    ///
+    /// Screens contractive (ψ above about -0.05) and dilative zones.
+    /// Requires `add_clean_sand_cols` to be applied first.
+    pub fn add_psi_col(self) -> Result<Self, CoreError> {
+        let out_data = crate::math::correlations::add_psi_col(self.data)?;
+        let mut meta = self.meta;
+        meta.record("add_psi_col", [("ic_cutoff", SU_IC.to_string())]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Estimates the preconsolidation stress σ'p = k (qt - σv_tot) and
+    /// OCR = σ'p / σv_eff of fine grained records (Ic above the configured
+    /// `su_ic`).
+    ///
+    /// Default `k_ocr` is taken from the configuration; k typically ranges
+    /// from 0.2 to 0.5. Requires `add_behavior_cols` to be applied first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if `k_ocr` is not positive.
+    pub fn add_ocr_cols(self, k_ocr: Option<f64>) -> Result<Self, CoreError> {
+        let out_data = crate::math::correlations::add_ocr_cols(self.data, k_ocr)?;
+        let mut meta = self.meta;
+        meta.record("add_ocr_cols", [
+            ("k_ocr", k_ocr.unwrap_or(*K_OCR).to_string()),
+            ("ic_cutoff", SU_IC.to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Estimates the at-rest earth pressure coefficient
+    /// K0 = 0.1 (qt - σv_tot) / σv_eff of fine grained records (Ic above the
+    /// configured `su_ic`).
+    ///
+    /// Gives a first-pass K0 profile for retaining structures. Requires
+    /// `add_behavior_cols` to be applied first.
+    pub fn add_k0_col(self) -> Result<Self, CoreError> {
+        let out_data = crate::math::correlations::add_k0_col(self.data)?;
+        let mut meta = self.meta;
+        meta.record("add_k0_col", [("ic_cutoff", SU_IC.to_string())]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Estimates the constrained modulus M = αM (qt - σv_tot), with αM
+    /// chosen from Ic and Qtn (Robertson, 2009).
+    ///
+    /// Feeds 1D settlement computations directly. Requires
+    /// `add_behavior_cols` to be applied first.
+    pub fn add_constrained_modulus_col(self) -> Result<Self, CoreError> {
+        let out_data =
+            crate::math::correlations::add_constrained_modulus_col(self.data)?;
+        let mut meta = self.meta;
+        meta.record("add_constrained_modulus_col", NO_PARAMETERS);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Estimates the coefficient of volume compressibility mv = 1 / M and,
+    /// on fine grained records (Ic above the configured `su_ic`), the
+    /// compression ratio Cc / (1 + e0) = ln(10) σ'v / M.
+    ///
+    /// Provides the per-depth inputs of 1D consolidation settlement.
+    /// Requires `add_constrained_modulus_col` to be applied first.
+    pub fn add_compressibility_cols(self) -> Result<Self, CoreError> {
+        let out_data =
+            crate::math::correlations::add_compressibility_cols(self.data)?;
+        let mut meta = self.meta;
+        meta.record("add_compressibility_cols", [
+            ("ic_cutoff", SU_IC.to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Estimates the shear wave velocity Vs from qt and Ic (Robertson, 2009)
+    /// and the small-strain shear modulus G0 = ρ Vs².
+    ///
+    /// The density comes from the γ column of `add_gamma_col` when present,
+    /// or from the configured `gamma_s`. Provides a first Vs profile for
+    /// seismic site classification when no seismic CPT data exist. Requires
+    /// `add_behavior_cols` to be applied first.
+    pub fn add_vs_cols(self) -> Result<Self, CoreError> {
+        let density = if self.data.column(*COL_GAMMA).is_ok() {
+            "profile".to_string()
+        } else {
+            GAMMA_S.to_string()
+        };
+        let out_data = crate::math::correlations::add_vs_cols(self.data)?;
+        let mut meta = self.meta;
+        meta.record("add_vs_cols", [("gamma", density)]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Estimates the hydraulic conductivity k from Ic (Robertson, 2010).
+    ///
+    /// Gives a screening profile for dewatering and contaminant transport
+    /// studies; k spans orders of magnitude within each zone, so it should
+    /// be confirmed with dissipation or field tests. Requires
+    /// `add_behavior_cols` to be applied first.
+    pub fn add_permeability_col(self) -> Result<Self, CoreError> {
+        let out_data = crate::math::correlations::add_permeability_col(self.data)?;
+        let mut meta = self.meta;
+        meta.record("add_permeability_col", NO_PARAMETERS);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Estimates the coefficient of consolidation cv = k M / γw of fine
+    /// grained records (Ic above the configured `su_ic`).
+    ///
+    /// Allows consolidation-time screening directly from the profile.
+    /// Requires `add_constrained_modulus_col` and `add_permeability_col` to
+    /// be applied first.
+    pub fn add_cv_col(self) -> Result<Self, CoreError> {
+        let out_data = crate::math::correlations::add_cv_col(self.data)?;
+        let mut meta = self.meta;
+        meta.record("add_cv_col", [
+            ("gamma_w", GAMMA_W.to_string()),
+            ("ic_cutoff", SU_IC.to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Corrects qt of thin stiff layers, e.g. sand seams embedded in clay,
+    /// which the cone under-measures because it senses the softer soil
+    /// around them.
+    ///
+    /// Adds the thin-layer factor KH of Youd et al. (2001) and the corrected
+    /// qt; records outside thin layers keep KH = 1. Unset `options` default
+    /// to the `[output.parameters]` configuration. Requires
+    /// `add_behavior_cols` to be applied first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if the cone diameter is not positive.
+    pub fn thin_layer_correction(
+        self,
+        options: &ThinLayerOptions
+    ) -> Result<Self, CoreError> {
+        let out_data =
+            crate::math::thin_layer::thin_layer_correction(self.data, options)?;
+        let mut meta = self.meta;
+        meta.record("thin_layer_correction", [
+            (
+                "cone_diameter",
+                options.cone_diameter.unwrap_or(*CONE_DIAMETER).to_string()
+            ),
+            ("max_ic", options.max_ic.unwrap_or(*THIN_LAYER_IC).to_string()),
+            (
+                "min_contrast",
+                options.min_contrast.unwrap_or(*THIN_LAYER_CONTRAST).to_string()
+            ),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Computes the cyclic resistance ratio CRR7.5 with the NCEER procedure
+    /// of Robertson and Wride (1998), with liquefiable and thin layer flags.
+    ///
+    /// Records with Ic above `max_ic` (default `liq_ic` from the
+    /// configuration) are excluded as clay-like. Apply
+    /// `thin_layer_correction` first to correct and flag thin layers.
+    /// Requires `add_clean_sand_cols` to be applied first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if `max_ic` is not positive.
+    pub fn add_crr_cols(self, max_ic: Option<f64>) -> Result<Self, CoreError> {
+        let out_data = crate::math::liquefaction::add_crr_cols(self.data, max_ic)?;
+        let mut meta = self.meta;
+        meta.record("add_crr_cols", [
+            ("max_ic", max_ic.unwrap_or(*LIQ_IC).to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Computes the cyclic stress ratio CSR of the design earthquake, with
+    /// its depth reduction factor rd, and the factor of safety against
+    /// liquefaction FS = CRR7.5 MSF / CSR.
+    ///
+    /// Unset `options` fields use `pga`, `magnitude`, `rd_method` and
+    /// `msf_method` from the configuration. FS is only added if
+    /// `add_crr_cols` was applied first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if the PGA or the magnitude is not
+    /// positive.
+    pub fn add_csr_cols(self, options: &CsrOptions) -> Result<Self, CoreError> {
+        let out_data = crate::math::liquefaction::add_csr_cols(self.data, options)?;
+        let magnitude = options.magnitude.unwrap_or(*MAGNITUDE);
+        let msf_method = options.msf_method.unwrap_or(*MSF_METHOD);
+        let mut meta = self.meta;
+        meta.record("add_csr_cols", [
+            ("pga", options.pga.unwrap_or(*PGA).to_string()),
+            ("magnitude", magnitude.to_string()),
+            ("rd_method", format!("{:?}", options.rd_method.unwrap_or(*RD_METHOD))),
+            ("msf_method", format!("{:?}", msf_method)),
+            ("msf", msf_method.msf(magnitude).to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Computes the liquefaction triggering columns of Boulanger and Idriss
+    /// (2014): qc1Ncs with the Δqc1N fines adjustment, CRR7.5, Kσ and FS.
+    ///
+    /// The columns sit alongside those of `add_crr_cols`, so both
+    /// procedures can be compared in the same frame. `options` must be the
+    /// ones `add_csr_cols` was applied with. Requires `add_fines_col` and
+    /// `add_csr_cols` to be applied first.
+    pub fn add_boulanger_idriss_cols(
+        self,
+        options: &CsrOptions
+    ) -> Result<Self, CoreError> {
+        let out_data =
+            crate::math::liquefaction::add_boulanger_idriss_cols(self.data, options)?;
+        let mut meta = self.meta;
+        meta.record("add_boulanger_idriss_cols", [
+            ("magnitude", options.magnitude.unwrap_or(*MAGNITUDE).to_string()),
+            ("max_ic", LIQ_IC.to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Runs the liquefaction triggering procedure `method` for the design
+    /// earthquake of `options`: `add_crr_cols` or
+    /// `add_boulanger_idriss_cols`, after `add_csr_cols`.
+    ///
+    /// Call it once per method to compare the procedures side by side.
+    /// Requires `add_fines_col` and `add_clean_sand_cols` to be applied
+    /// first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if the PGA or the magnitude is not
+    /// positive.
+    pub fn add_triggering_cols(
+        self,
+        method: TriggeringMethod,
+        options: &CsrOptions
+    ) -> Result<Self, CoreError> {
+        match method {
+            TriggeringMethod::RobertsonWride => {
+                self.add_crr_cols(None)?.add_csr_cols(options)
+            }
+            TriggeringMethod::BoulangerIdriss => {
+                self.add_csr_cols(options)?.add_boulanger_idriss_cols(options)
+            }
+        }
+    }
+
+    /// Estimates the post-liquefaction volumetric strain εv of Zhang et al.
+    /// (2002) from the factor of safety and Qtn,cs of Robertson and Wride.
+    ///
+    /// Feeds `liquefaction_severity` and `add_settlement_col`. Requires
+    /// `add_crr_cols` and `add_csr_cols` to be applied first.
+    pub fn add_volumetric_strain_col(self) -> Result<Self, CoreError> {
+        let out_data =
+            crate::math::liquefaction::add_volumetric_strain_col(self.data)?;
+        let mut meta = self.meta;
+        meta.record("add_volumetric_strain_col", NO_PARAMETERS);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Adds the cumulative post-liquefaction reconsolidation settlement
+    /// (mm), integrating εv from the bottom of the sounding up to each
+    /// record.
+    ///
+    /// The settlement of the first record is that of the whole sounding
+    /// (see `liquefaction_settlement`). Requires `add_volumetric_strain_col`
+    /// to be applied first.
+    pub fn add_settlement_col(self) -> Result<Self, CoreError> {
+        let out_data = crate::math::liquefaction::add_settlement_col(self.data)?;
+        let mut meta = self.meta;
+        meta.record("add_settlement_col", NO_PARAMETERS);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Estimates the maximum cyclic shear strain γmax of Yoshimine et al.
+    /// (2006) from the factor of safety of Robertson and Wride and Dr.
+    ///
+    /// Feeds `lateral_displacement`. Requires `add_dr_col`, `add_crr_cols`
+    /// and `add_csr_cols` to be applied first.
+    pub fn add_shear_strain_col(self) -> Result<Self, CoreError> {
+        let out_data =
+            crate::math::liquefaction::add_shear_strain_col(self.data)?;
+        let mut meta = self.meta;
+        meta.record("add_shear_strain_col", NO_PARAMETERS);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Applies the cleaning sequence of `conic process` with the configured
+    /// parameters.
+    ///
+    /// Checks the depth order, collapses duplicate depths, adjusts depth by
+    /// 0.125 m, replaces the configured `error_indicators` with NaN and drops
+    /// the incomplete records, then detects refusal, corrects inclination,
+    /// despikes and flags clogging, desaturation and saturation loss.
+    pub fn clean_standard(self) -> Result<Self, CoreError> {
+        self.ensure_monotonic_depth(None)?
+            .resolve_duplicate_depths(None)?
+            .adjust_depth(Some(0.125), None)?
+            .replace_rows(&ERROR_INDICATORS, &f64::NAN, None)?
+            .drop_nan(None)?
+            .detect_refusal(None, None, None)?
+            .correct_inclination()?
+            .despike(None, None)?
+            .flag_clogging(None, None, None, None)?
+            .flag_desaturation(None, None, None)?
+            .flag_saturation_loss(None, None, None)
+    }
+
+    /// Computes the derived columns of `selection` (default `selection` from
+    /// the configuration) with the configured parameters.
+    ///
+    /// Runs only the steps the selected columns depend on, from
+    /// `add_stress_cols` to `add_shear_strain_col`, and drops the
+    /// derived columns a `OutputSelection::Columns` list leaves out.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if a listed column is not a derived
+    /// column.
+    pub fn add_derived_cols(
+        self,
+        selection: Option<&OutputSelection>
+    ) -> Result<Self, CoreError> {
+        let selection = selection.unwrap_or(&SELECTION);
+        let excluded = selection.excluded_names()?;
+
+        let mut data = self;
+        for step in selection.steps()? {
+            data = match step {
+                Step::Stress => {
+                    // the γ profile needs the stresses of a first constant pass
+                    let data = if *UNIT_WEIGHT == UnitWeight::Profile {
+                        let constant = Some(UnitWeight::Constant(*GAMMA_S));
+                        data.add_stress_cols(None, constant, None, None, None)?
+                            .add_gamma_col()?
+                    } else {
+                        data
+                    };
+                    data.add_stress_cols(None, None, None, None, None)?
+                }
+                Step::PorePressure => data.add_pore_pressure_cols()?,
+                Step::Behavior => {
+                    data.add_behavior_cols(None, None, None, None, None)?
+                }
+                Step::Sbt => data
+                    .add_sbt_cols()?
+                    .add_schneider_col()?
+                    .add_group_col()?
+                    .add_description_col()?
+                    .add_clean_sand_cols()?
+                    .add_fines_col(None)?,
+                Step::Spt => data.add_spt_cols()?,
+                Step::Modulus => data
+                    .add_modulus_cols()?
+                    .add_constrained_modulus_col()?
+                    .add_compressibility_cols()?
+                    .add_vs_cols()?,
+                Step::Strength => {
+                    data.add_su_col(None)?.add_sensitivity_col()?.add_phi_col(None)?
+                }
+                Step::Density => data.add_dr_col()?.add_psi_col()?,
+                Step::Consolidation => data.add_ocr_cols(None)?.add_k0_col()?,
+                Step::Permeability => {
+                    data.add_permeability_col()?.add_cv_col()?
+                }
+                Step::Liquefaction => {
+                    let options = CsrOptions::default();
+                    data.thin_layer_correction(&ThinLayerOptions::default())?
+                        .add_triggering_cols(
+                            TriggeringMethod::RobertsonWride,
+                            &options
+                        )?
+                        .add_triggering_cols(
+                            TriggeringMethod::BoulangerIdriss,
+                            &options
+                        )?
+                        .add_volumetric_strain_col()?
+                        .add_settlement_col()?
+                        .add_shear_strain_col()?
+                }
+            };
+        }
+
+        let out_data = data.data.drop_many(excluded);
+        let mut meta = data.meta;
+        meta.record("add_derived_cols", [("selection", format!("{:?}", selection))]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Returns the mean E' over depth intervals of `interval` meters, with
+    /// the mean M alongside when `add_constrained_modulus_col` was applied.
+    ///
+    /// Produces a compact stiffness profile for spreadsheet settlement
+    /// checks. Requires `add_modulus_cols` to be applied first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if `interval` is not positive.
+    pub fn modulus_profile(&self, interval: f64) -> Result<DataFrame, CoreError> {
+        crate::math::correlations::modulus_profile(&self.data, interval)
     }
 
     /// Removes rows containing any of the specified indicator values.
     ///
-    /// A row is eliminated if ANY column contains ANY value from the
-    /// indicators list.
-    pub fn remove_rows(self, indicators: &[f64]) -> Result<Self, CoreError> {
+    /// A row is eliminated if ANY float column contains ANY value from the
+    /// indicators list, or a value configured for that column in
+    /// `[input.indicators]`. Boolean flag columns, such as those added by
+    /// `flag_indicators` or `flag_spikes`, are not inspected. If
+    /// `retain_dropped` was called, the eliminated rows are kept in the
+    /// metadata with the reason of their removal.
+    ///
+    /// `columns` restricts the columns inspected, e.g. to the measurement
+    /// columns, so derived columns that legitimately hold an indicator value
+    /// do not remove their row; None inspects every float column.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if a listed column does not exist or
+    /// is not numeric.
+    pub fn remove_rows(
+        self,
+        indicators: &[f64],
+        columns: Option<&[&str]>
+    ) -> Result<Self, CoreError> {
+        let mut meta = self.meta;
+        if let Some(retained) = meta.dropped.take() {
+            let dropped =
+                crate::frame::clean::dropped_rows(&self.data, indicators, columns)?;
+            meta.dropped = Some(crate::frame::clean::append_rows(retained, dropped)?);
+        }
         let out_data = crate::frame::clean::remove_rows(
-            self.0,
-            indicators
+            self.data,
+            indicators,
+            columns
+        )?;
+        meta.record("remove_rows", indicator_parameters(indicators, columns));
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Flags the rows holding an indicator value without changing any value.
+    ///
+    /// Adds a boolean indicator column, true where `remove_rows` with the
+    /// same arguments would remove the row, so the flags can be reviewed
+    /// before committing to removal, e.g. with
+    /// `remove_rows_where(col("indicator (?)"))`. The flag column is not
+    /// inspected by later `remove_rows` or `replace_rows` calls.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if a listed column does not exist.
+    pub fn flag_indicators(
+        self,
+        indicators: &[f64],
+        columns: Option<&[&str]>
+    ) -> Result<Self, CoreError> {
+        let out_data = crate::frame::clean::flag_indicators(
+            self.data,
+            indicators,
+            columns
+        )?;
+        let mut meta = self.meta;
+        meta.record("flag_indicators", indicator_parameters(indicators, columns));
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Removes the rows with a NaN or null value in any column of `subset`.
+    ///
+    /// Unlike `remove_rows(&[f64::NAN], None)`, which inspects every float
+    /// column, NaN values in optional or derived columns do not remove
+    /// otherwise valid rows. None inspects the required input columns
+    /// (depth, qc, fs and u2). If `retain_dropped` was called, the removed
+    /// rows are kept in the metadata with the first missing column as reason.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if a listed column does not exist.
+    pub fn drop_nan(self, subset: Option<&[&str]>) -> Result<Self, CoreError> {
+        let mut meta = self.meta;
+        let dropped = crate::frame::clean::nan_rows(&self.data, subset, "drop_nan")?;
+        let out_data = crate::frame::clean::drop_nan(self.data, subset)?;
+        let subset_label =
+            subset.map_or("required".to_string(), |subset| format!("{:?}", subset));
+        meta.record("drop_nan", [
+            ("subset", subset_label),
+            ("removed", dropped.height().to_string()),
+        ]);
+        if let Some(retained) = meta.dropped.take() {
+            meta.dropped = Some(crate::frame::clean::append_rows(retained, dropped)?);
+        }
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Removes or masks the seating records of each push, where qc and fs
+    /// ramp up from zero after a rod change or a pre-drilled interval.
+    ///
+    /// Pushes start at the first record and after every run of records with
+    /// qc at or below `zero_qc`; those records and the first `length` meters
+    /// of each push are dropped (kept by `retain_dropped`) or, with
+    /// `SeatingArtifacts::Mask`, keep their depth with NaN qc, fs and u2.
+    /// Parameters default to `seating_length`, `seating_qc` and
+    /// `seating_artifacts` from the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if `length` is negative or a
+    /// parameter is NaN.
+    pub fn remove_seating_artifacts(
+        self,
+        length: Option<f64>,
+        zero_qc: Option<f64>,
+        action: Option<SeatingArtifacts>
+    ) -> Result<Self, CoreError> {
+        let action = action.unwrap_or(*SEATING_ARTIFACTS);
+        let affected = crate::frame::clean::seating_rows(&self.data, length, zero_qc)?;
+        let mut meta = self.meta;
+        if action == SeatingArtifacts::Remove
+            && let Some(retained) = meta.dropped.take()
+        {
+            meta.dropped = Some(crate::frame::clean::append_rows(
+                retained,
+                affected.clone()
+            )?);
+        }
+        let out_data = crate::frame::clean::remove_seating_artifacts(
+            self.data,
+            length,
+            zero_qc,
+            Some(action)
+        )?;
+        meta.record("remove_seating_artifacts", [
+            ("length", length.unwrap_or(*SEATING_LENGTH).to_string()),
+            ("zero_qc", zero_qc.unwrap_or(*SEATING_QC).to_string()),
+            ("action", format!("{:?}", action)),
+            ("affected", affected.height().to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Detects the short qc and u2 troughs left by rod changes, which repeat
+    /// at roughly the rod length, and flags them in the rod change column.
+    ///
+    /// With `RodChanges::Interpolate`, qc, fs and u2 are also interpolated
+    /// linearly across the dips. Unset `options` default to the `rod_*`
+    /// parameters of the configuration. The number of dips is kept in the
+    /// processing record.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if a length is not positive or the
+    /// ratio is outside (0, 1).
+    pub fn clean_rod_changes(
+        self,
+        options: &RodChangeOptions
+    ) -> Result<Self, CoreError> {
+        let (out_data, dips) =
+            crate::frame::clean::clean_rod_changes(self.data, options)?;
+        let mut meta = self.meta;
+        meta.record("clean_rod_changes", [
+            ("rod_length", options.rod_length.unwrap_or(*ROD_LENGTH).to_string()),
+            (
+                "max_length",
+                options.max_length.unwrap_or(*ROD_DIP_LENGTH).to_string()
+            ),
+            ("ratio", options.ratio.unwrap_or(*ROD_DIP_RATIO).to_string()),
+            ("tolerance", options.tolerance.unwrap_or(*ROD_TOLERANCE).to_string()),
+            ("action", format!("{:?}", options.action.unwrap_or(*ROD_CHANGES))),
+            ("dips", dips.to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Removes the rows where `predicate` is true, e.g.
+    /// `col("u2 (kPa)").lt(lit(-50.0))`.
+    ///
+    /// Rows where the predicate is null are kept. If `retain_dropped` was
+    /// called, the removed rows are kept in the metadata; the predicate is
+    /// kept in the processing record.
+    pub fn remove_rows_where(self, predicate: Expr) -> Result<Self, CoreError> {
+        let mask = crate::frame::clean::predicate_mask(&self.data, predicate.clone())?;
+        self.remove_masked_rows(&mask, &predicate.to_string())
+    }
+
+    /// Removes the rows selected by the boolean mask `predicate` computes
+    /// from the data, for conditions that are easier to write in Rust than
+    /// as a polars expression.
+    ///
+    /// The mask must have one value per row; null values keep their row.
+    /// Removed rows are retained as in `remove_rows_where`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `predicate`, or `CoreError::InvalidData` if the
+    /// mask length differs from the number of rows.
+    pub fn remove_rows_where_fn<F>(self, predicate: F) -> Result<Self, CoreError>
+    where
+        F: FnOnce(&DataFrame) -> Result<BooleanChunked, CoreError>,
+    {
+        let mask = predicate(&self.data)?;
+        self.remove_masked_rows(&mask, "closure")
+    }
+
+    fn remove_masked_rows(
+        self,
+        mask: &BooleanChunked,
+        predicate: &str
+    ) -> Result<Self, CoreError> {
+        let mut meta = self.meta;
+        if let Some(retained) = meta.dropped.take() {
+            let reason = format!("remove_rows_where: {}", predicate);
+            let dropped = crate::frame::clean::masked_rows(&self.data, mask, &reason)?;
+            meta.dropped = Some(crate::frame::clean::append_rows(retained, dropped)?);
+        }
+        let rows = self.data.height();
+        let out_data = crate::frame::clean::remove_masked_rows(self.data, mask)?;
+        meta.record("remove_rows_where", [
+            ("predicate", predicate.to_string()),
+            ("removed", (rows - out_data.height()).to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Clamps the values of `column` to `[min, max]`, e.g. negative fs to
+    /// zero before computing Fr; a None limit leaves that end open.
+    ///
+    /// NaN values are kept. The number of clipped values is kept in the
+    /// processing record.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if a limit is NaN or `min` is greater
+    /// than `max`, and a polars error if `column` is missing or not a float
+    /// column.
+    pub fn clip(
+        self,
+        column: &str,
+        min: Option<f64>,
+        max: Option<f64>
+    ) -> Result<Self, CoreError> {
+        let (out_data, clipped) =
+            crate::frame::clean::clip(self.data, column, min, max)?;
+        let mut meta = self.meta;
+        let limit = |value: Option<f64>| {
+            value.map_or_else(|| "none".to_string(), |value| value.to_string())
+        };
+        meta.record("clip", [
+            ("column", column.to_string()),
+            ("min", limit(min)),
+            ("max", limit(max)),
+            ("clipped", clipped.to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Linearly interpolates qc, fs and u2 across short runs of indicator
+    /// values, keeping depth intact.
+    ///
+    /// Runs of at most `max_gap` records (default `interp_max_gap` from the
+    /// configuration) with valid records above and below are interpolated
+    /// against depth; other indicator values are left for `replace_rows` or
+    /// `remove_rows`. Interpolated rows are flagged in the interpolated
+    /// column.
+    pub fn interpolate_rows(
+        self,
+        indicators: &[f64],
+        max_gap: Option<usize>,
+    ) -> Result<Self, CoreError> {
+        let out_data = crate::frame::clean::interpolate_rows(
+            self.data,
+            indicators,
+            max_gap
         )?;
-        Ok(Self(out_data))
+        let mut meta = self.meta;
+        let mut parameters = indicator_parameters(indicators, None);
+        parameters.push(("max_gap", max_gap.unwrap_or(*INTERP_MAX_GAP).to_string()));
+        meta.record("interpolate_rows", parameters);
+        Ok(Self { data: out_data, meta })
     }
 
     /// Replaces values in rows containing indicator values.
     ///
-    /// When a row has ANY column containing ANY value from the indicators
-    /// list, ALL values in that row (except depth) are replaced with
-    /// `replace_value`. This preserves depth information while marking
-    /// invalid measurements.
+    /// When a row has ANY float column containing ANY value from the
+    /// indicators list, or a value configured for that column in
+    /// `[input.indicators]`, ALL float values in that row (except depth) are
+    /// replaced with `replace_value`. This preserves depth information while
+    /// marking invalid measurements; flag and zone columns keep their values.
+    ///
+    /// `columns` restricts the columns inspected for indicators as in
+    /// `remove_rows`; None inspects every float column.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if a listed column does not exist or
+    /// is not numeric.
     pub fn replace_rows(
         self,
         indicators: &[f64],
         replace_value: &f64,
+        columns: Option<&[&str]>,
+    ) -> Result<Self, CoreError> {
+        self.replace_rows_with(indicators, replace_value, &[], columns)
+    }
+
+    /// Replaces values in rows containing indicator values, with a
+    /// replacement per column.
+    ///
+    /// Like `replace_rows`, but the columns listed in `replacements` get their
+    /// own replacement instead of `replace_value`, so partially valid rows
+    /// keep their valid channels, e.g. `[("u2 (kPa)", Replacement::Hydrostatic),
+    /// ("fs (kPa)", Replacement::Keep)]`. Depth is never replaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if a listed column does not exist, or
+    /// if a replacement other than `Keep` targets a column that is not a
+    /// float column.
+    pub fn replace_rows_with(
+        self,
+        indicators: &[f64],
+        replace_value: &f64,
+        replacements: &[(&str, Replacement)],
+        columns: Option<&[&str]>,
     ) -> Result<Self, CoreError> {
         let out_data = crate::frame::clean::replace_rows(
-            self.0,
+            self.data,
             indicators,
-            replace_value
+            replace_value,
+            replacements,
+            columns
         )?;
-        Ok(Self(out_data))
+        let mut meta = self.meta;
+        meta.record(
+            "replace_rows",
+            replace_parameters(indicators, replace_value, replacements, columns)
+        );
+        Ok(Self { data: out_data, meta })
     }
 
     /// Adjusts depth values to uniform spacing.
@@ -84,7 +1172,8 @@ impl ConicDataFrame {
     /// Replaces depth values with uniformly spaced values starting from
     /// the specified or first depth value. If spacing is not provided, it is
     /// calculated as the mean of differences between consecutive depth values,
-    /// excluding NaN values.
+    /// excluding NaN values; repeated depths bias this estimate, so apply
+    /// `resolve_duplicate_depths` first.
     ///
     /// # Arguments
     ///
@@ -105,26 +1194,813 @@ impl ConicDataFrame {
         spacing: Option<f64>
     ) -> Result<Self, CoreError> {
         let out_data = crate::frame::fix::adjust_depth(
-            self.0,
+            self.data,
             start_depth,
             spacing
         )?;
-        Ok(Self(out_data))
+        let mut meta = self.meta;
+        meta.record("adjust_depth", [
+            ("start_depth", describe(start_depth)),
+            ("spacing", describe(spacing)),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Pairs DMT readings with this CPTu sounding for side-by-side reporting.
+    ///
+    /// Returns the DMT table (see `frame::dmt::read_dmt_csv`) extended with
+    /// the mean qt, fs, u2, Qtn and Ic of the CPT records within `window / 2`
+    /// of each DMT depth, plus the ED/qt ratio.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if `window` is not positive.
+    pub fn align_dmt(
+        &self,
+        dmt: &DataFrame,
+        window: f64
+    ) -> Result<DataFrame, CoreError> {
+        crate::frame::dmt::align_dmt(&self.data, dmt, window)
+    }
+
+    /// Removes isolated spikes from qc, fs and u2 with a Hampel filter.
+    ///
+    /// Samples deviating from the median of a centered window of `window`
+    /// samples by more than `n_sigmas` scaled MADs are replaced by that
+    /// median. Parameters default to `despike_window` and `despike_sigmas`
+    /// from the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if `window` is not an odd number of
+    /// at least 3 samples or `n_sigmas` is not positive.
+    pub fn despike(
+        self,
+        window: Option<usize>,
+        n_sigmas: Option<f64>
+    ) -> Result<Self, CoreError> {
+        let out_data = crate::frame::smooth::despike(self.data, window, n_sigmas)?;
+        let mut meta = self.meta;
+        meta.record("despike", [
+            ("window", window.unwrap_or(*DESPIKE_WINDOW).to_string()),
+            ("n_sigmas", n_sigmas.unwrap_or(*DESPIKE_SIGMAS).to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Flags the samples `despike` would replace without changing any value.
+    ///
+    /// Adds a boolean spike column, true where qc, fs or u2 is a spike, so
+    /// the flags can be reviewed before despiking. Parameters default to
+    /// `despike_window` and `despike_sigmas` from the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if `window` is not an odd number of
+    /// at least 3 samples or `n_sigmas` is not positive.
+    pub fn flag_spikes(
+        self,
+        window: Option<usize>,
+        n_sigmas: Option<f64>
+    ) -> Result<Self, CoreError> {
+        let out_data = crate::frame::smooth::flag_spikes(self.data, window, n_sigmas)?;
+        let mut meta = self.meta;
+        meta.record("flag_spikes", [
+            ("window", window.unwrap_or(*DESPIKE_WINDOW).to_string()),
+            ("n_sigmas", n_sigmas.unwrap_or(*DESPIKE_SIGMAS).to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Flags intervals of possible sleeve clogging and blocked filters.
+    ///
+    /// fs is flagged where it rises while qc stalls, and u2 where it
+    /// flatlines while qc changes. Adds one reliability column per channel;
+    /// flagged records are down-weighted in layer statistics. Parameters
+    /// default to the `[qa.parameters]` configuration.
+    pub fn flag_clogging(
+        self,
+        window: Option<usize>,
+        fs_rise: Option<f64>,
+        qc_stall: Option<f64>,
+        u2_flat: Option<f64>
+    ) -> Result<Self, CoreError> {
+        let out_data = crate::frame::qa::flag_clogging(
+            self.data,
+            window,
+            fs_rise,
+            qc_stall,
+            u2_flat
+        )?;
+        let mut meta = self.meta;
+        meta.record("flag_clogging", [
+            ("window", window.unwrap_or(*CLOG_WINDOW).to_string()),
+            ("fs_rise", fs_rise.unwrap_or(*CLOG_FS_RISE).to_string()),
+            ("qc_stall", qc_stall.unwrap_or(*CLOG_QC_STALL).to_string()),
+            ("u2_flat", u2_flat.unwrap_or(*U2_FLAT_RANGE).to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Flags the depths below which the u2 filter has likely desaturated.
+    ///
+    /// Looks for a damped pore pressure response below dense, dilative sand
+    /// and adds a saturation column; u2-based parameters such as Bq should
+    /// be distrusted where it is false. Parameters default to the
+    /// `[qa.parameters]` configuration.
+    pub fn flag_desaturation(
+        self,
+        window: Option<usize>,
+        dense_qc: Option<f64>,
+        damping: Option<f64>
+    ) -> Result<Self, CoreError> {
+        let out_data = crate::frame::qa::flag_desaturation(
+            self.data,
+            window,
+            dense_qc,
+            damping
+        )?;
+        let mut meta = self.meta;
+        meta.record("flag_desaturation", [
+            ("window", window.unwrap_or(*DESAT_WINDOW).to_string()),
+            ("dense_qc", dense_qc.unwrap_or(*DESAT_DENSE_QC).to_string()),
+            ("damping", damping.unwrap_or(*DESAT_DAMPING).to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Flags intervals where the u2 sensor has likely lost saturation.
+    ///
+    /// Looks for u2 collapsed near zero and flat in clayey soil below the
+    /// water table, where large excess pore pressures are expected, and
+    /// adds a saturation loss column; Bq and Ic should be distrusted where
+    /// it is true. The flagged depth intervals are kept in the metadata.
+    /// Parameters default to the `[qa.parameters]` configuration.
+    pub fn flag_saturation_loss(
+        self,
+        window: Option<usize>,
+        max_u2: Option<f64>,
+        min_rf: Option<f64>
+    ) -> Result<Self, CoreError> {
+        let (out_data, intervals) = crate::frame::qa::flag_saturation_loss(
+            self.data,
+            window,
+            max_u2,
+            min_rf
+        )?;
+        let intervals = if intervals.is_empty() {
+            "none".to_string()
+        } else {
+            intervals
+                .iter()
+                .map(|(top, bottom)| format!("{}-{}", top, bottom))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut meta = self.meta;
+        meta.record("flag_saturation_loss", [
+            ("window", window.unwrap_or(*SAT_LOSS_WINDOW).to_string()),
+            ("max_u2", max_u2.unwrap_or(*SAT_LOSS_U2).to_string()),
+            ("min_rf", min_rf.unwrap_or(*SAT_LOSS_RF).to_string()),
+            ("intervals", intervals),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Flags records outside physical limits without changing any value.
+    ///
+    /// Checks qc, fs and u2 against their lowest valid values and the depth
+    /// increment between records against a maximum, and adds a range flag
+    /// column with one bit per failed check (see `RangeCheck`). `rules`
+    /// defaults to the `[qa.parameters]` configuration.
+    pub fn validate_ranges(self, rules: Option<RangeRules>) -> Result<Self, CoreError> {
+        let rules = rules.unwrap_or_default();
+        let out_data = crate::frame::qa::validate_ranges(self.data, &rules)?;
+        let mut meta = self.meta;
+        meta.record("validate_ranges", [
+            ("min_qc", rules.min_qc.to_string()),
+            ("min_fs", rules.min_fs.to_string()),
+            ("min_u2", rules.min_u2.to_string()),
+            ("max_depth_jump", rules.max_depth_jump.to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Returns the number of records failing each range check, with the
+    /// depth of the first one. Requires `validate_ranges` to be applied
+    /// first.
+    pub fn range_summary(&self) -> Result<Vec<RangeViolations>, CoreError> {
+        crate::frame::qa::range_summary(&self.data)
+    }
+
+    /// Checks qc and fs magnitudes for likely unit errors, e.g. qc in kPa
+    /// (values in the thousands) or fs in MPa (values below 1 throughout),
+    /// which are the most common cause of nonsense Ic profiles.
+    ///
+    /// Returns one warning per suspect column, empty when the magnitudes are
+    /// plausible; apply right after reading.
+    pub fn check_units(&self) -> Result<Vec<UnitWarning>, CoreError> {
+        crate::frame::units::check_units(&self.data)
+    }
+
+    /// Returns the Liquefaction Severity Number LSN of the sounding, the
+    /// integral of εv / z over depth.
+    ///
+    /// Summarizes the expected damage of a sounding in one value, e.g. to
+    /// map it over a site; around 20 or more indicates moderate to severe
+    /// damage. Requires `add_volumetric_strain_col` to be applied first.
+    pub fn liquefaction_severity(&self) -> Result<f64, CoreError> {
+        crate::math::liquefaction::liquefaction_severity(&self.data)
+    }
+
+    /// Returns the Liquefaction Potential Index LPI of Iwasaki et al. (1978),
+    /// the integral of (1 - FS) (10 - 0.5 z) over the top 20 m where FS is
+    /// below 1.
+    ///
+    /// Above 15 the liquefaction risk is usually rated very high and from 5
+    /// to 15 high. Requires `add_csr_cols` to be applied first.
+    pub fn liquefaction_potential(&self) -> Result<f64, CoreError> {
+        crate::math::liquefaction::liquefaction_potential(&self.data)
+    }
+
+    /// Returns the total post-liquefaction reconsolidation settlement (mm)
+    /// of the sounding, the integral of εv over depth.
+    ///
+    /// Requires `add_volumetric_strain_col` to be applied first.
+    pub fn liquefaction_settlement(&self) -> Result<f64, CoreError> {
+        crate::math::liquefaction::liquefaction_settlement(&self.data)
+    }
+
+    /// Returns the lateral displacement index LDI and the lateral
+    /// displacement of Zhang et al. (2004) for the ground `geometry` around
+    /// the sounding.
+    ///
+    /// Screens the lateral spreading of gently sloping ground or of level
+    /// ground near a free face, such as a river bank. Requires
+    /// `add_shear_strain_col` to be applied first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if the slope is negative or the free
+    /// face height or distance is not positive.
+    pub fn lateral_displacement(
+        &self,
+        geometry: GroundGeometry,
+    ) -> Result<LateralDisplacement, CoreError> {
+        crate::math::liquefaction::lateral_displacement(&self.data, geometry)
+    }
+
+    /// Returns the SBT zone transitions as an event table.
+    ///
+    /// Each row holds a boundary depth and the zones above and below it,
+    /// e.g. to seed layer boundaries or compare with driller logs. Requires
+    /// `add_behavior_cols` to be applied first.
+    pub fn zone_changes(&self) -> Result<DataFrame, CoreError> {
+        crate::math::sbt::zone_change_table(&self.data)
+    }
+
+    /// Inserts rows with NaN measurements where depth intervals are missing,
+    /// e.g. after pauses or data loss, so rolling windows and plots do not
+    /// bridge the gap.
+    ///
+    /// Increments larger than 1.5 times `spacing` are filled on a `spacing`
+    /// grid; if `spacing` is None, the median depth increment is used. Apply
+    /// before `adjust_depth`, which renumbers depths uniformly and would
+    /// hide the gaps.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if `spacing` is not positive or
+    /// cannot be inferred.
+    pub fn fill_depth_gaps(self, spacing: Option<f64>) -> Result<Self, CoreError> {
+        let rows = self.data.height();
+        let out_data = crate::frame::fix::fill_depth_gaps(self.data, spacing)?;
+        let mut meta = self.meta;
+        meta.record("fill_depth_gaps", [
+            ("spacing", describe(spacing)),
+            ("inserted", (out_data.height() - rows).to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Detects records shallower than a record above them, e.g. reversed
+    /// chunks from concatenated files, which produce meaningless Ic values.
+    ///
+    /// Depending on `policy` (default `non_monotonic_depth` from the
+    /// configuration), fails, drops those records (kept by `retain_dropped`)
+    /// or sorts the records by depth. The number of affected records is kept
+    /// in the processing record.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` with `NonMonotonicDepth::Error` if any
+    /// depth decreases.
+    pub fn ensure_monotonic_depth(
+        self,
+        policy: Option<NonMonotonicDepth>
+    ) -> Result<Self, CoreError> {
+        let policy = policy.unwrap_or(*NON_MONOTONIC_DEPTH);
+        let affected = crate::frame::fix::decreasing_depth_rows(&self.data)?;
+        let mut meta = self.meta;
+        if policy == NonMonotonicDepth::Drop
+            && let Some(retained) = meta.dropped.take()
+        {
+            meta.dropped = Some(crate::frame::clean::append_rows(
+                retained,
+                affected.clone()
+            )?);
+        }
+        let out_data = crate::frame::fix::ensure_monotonic_depth(
+            self.data,
+            Some(policy)
+        )?;
+        meta.record("ensure_monotonic_depth", [
+            ("policy", format!("{:?}", policy)),
+            ("affected", affected.height().to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Collapses records sharing the same depth, e.g. from re-strokes or
+    /// overlapping pushes, into one record per depth.
+    ///
+    /// `strategy` defaults to `duplicate_depths` from the configuration.
+    /// Records with a NaN depth are kept unchanged. The number of collapsed
+    /// records is kept in the processing record and the records replaced by
+    /// the collapsed one (all of them when averaging) by `retain_dropped`.
+    pub fn resolve_duplicate_depths(
+        self,
+        strategy: Option<DuplicateDepths>
+    ) -> Result<Self, CoreError> {
+        let rows = self.data.height();
+        let mut meta = self.meta;
+        if let Some(retained) = meta.dropped.take() {
+            let collapsed =
+                crate::frame::fix::duplicate_depth_rows(&self.data, strategy)?;
+            meta.dropped = Some(crate::frame::clean::append_rows(retained, collapsed)?);
+        }
+        let out_data = crate::frame::fix::resolve_duplicate_depths(
+            self.data,
+            strategy
+        )?;
+        meta.record("resolve_duplicate_depths", [
+            ("strategy", format!("{:?}", strategy.unwrap_or(*DUPLICATE_DEPTHS))),
+            ("collapsed", (rows - out_data.height()).to_string()),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Merges pushes restarted at a shallower depth, e.g. after an
+    /// obstruction, into a single profile with increasing depths.
+    ///
+    /// A push restarts wherever the depth drops below the deepest record
+    /// above it. Within the overlap, the records of the later push are kept
+    /// or, with `Repush::Average`, averaged with the earlier push; `strategy`
+    /// defaults to `repush` from the configuration. The overlaps are kept in
+    /// the processing record and the replaced records by `retain_dropped`.
+    /// Apply before `ensure_monotonic_depth`.
+    pub fn merge_repushes(self, strategy: Option<Repush>) -> Result<Self, CoreError> {
+        let merged = crate::frame::fix::merge_repushes(self.data, strategy)?;
+        let overlaps = if merged.overlaps.is_empty() {
+            "none".to_string()
+        } else {
+            merged
+                .overlaps
+                .iter()
+                .map(|(start, end)| format!("{}-{}", start, end))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut meta = self.meta;
+        meta.record("merge_repushes", [
+            ("strategy", format!("{:?}", strategy.unwrap_or(*REPUSH))),
+            ("overlaps", overlaps),
+            ("replaced", merged.replaced.height().to_string()),
+        ]);
+        if let Some(retained) = meta.dropped.take() {
+            meta.dropped =
+                Some(crate::frame::clean::append_rows(retained, merged.replaced)?);
+        }
+        Ok(Self { data: merged.data, meta })
+    }
+
+    /// Keeps the records between `min_depth` and `max_depth` (inclusive),
+    /// e.g. to cut pre-drilled sections and noise below refusal before the
+    /// stress calculations. A None limit leaves that end open.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if a limit is NaN or `min_depth` is
+    /// greater than `max_depth`.
+    pub fn trim_depth(
+        self,
+        min_depth: Option<f64>,
+        max_depth: Option<f64>
+    ) -> Result<Self, CoreError> {
+        let out_data = crate::frame::fix::trim_depth(self.data, min_depth, max_depth)?;
+        let mut meta = self.meta;
+        let limit = |depth: Option<f64>| {
+            depth.map_or_else(|| "none".to_string(), |depth| depth.to_string())
+        };
+        meta.record("trim_depth", [
+            ("min_depth", limit(min_depth)),
+            ("max_depth", limit(max_depth)),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Keeps the records with depths within `range`, e.g. `0.0..20.0` for
+    /// the liquefiable upper 20 m or `5.0..` below a pre-drilled section.
+    ///
+    /// Records with a NaN depth are dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if a limit is NaN or the start is
+    /// greater than the end.
+    pub fn slice_by_depth<R>(self, range: R) -> Result<Self, CoreError>
+    where
+        R: RangeBounds<f64>,
+    {
+        let start = range.start_bound().cloned();
+        let end = range.end_bound().cloned();
+        let out_data = crate::frame::fix::slice_by_depth(self.data, start, end)?;
+        let mut meta = self.meta;
+        meta.record("slice_by_depth", [("range", depth_range(start, end))]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Splits the sounding into the records above `depth` and those at or
+    /// below it, e.g. to process strata with different parameters.
+    ///
+    /// Both parts keep the processing record, with the split noted.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if `depth` is NaN.
+    pub fn split_at_depth(self, depth: f64) -> Result<(Self, Self), CoreError> {
+        if depth.is_nan() {
+            return Err(CoreError::InvalidData(
+                "Cannot split at depth: depth must not be NaN".to_string()
+            ));
+        }
+
+        let upper = crate::frame::fix::slice_by_depth(
+            self.data.clone(),
+            Bound::Unbounded,
+            Bound::Excluded(depth)
+        )?;
+        let lower = crate::frame::fix::slice_by_depth(
+            self.data,
+            Bound::Included(depth),
+            Bound::Unbounded
+        )?;
+
+        let mut upper_meta = self.meta.clone();
+        upper_meta.record("split_at_depth", [
+            ("depth", depth.to_string()),
+            ("part", "upper".to_string()),
+        ]);
+        let mut lower_meta = self.meta;
+        lower_meta.record("split_at_depth", [
+            ("depth", depth.to_string()),
+            ("part", "lower".to_string()),
+        ]);
+
+        Ok((
+            Self { data: upper, meta: upper_meta },
+            Self { data: lower, meta: lower_meta },
+        ))
+    }
+
+    /// Converts push depths into vertical depths when the sounding has an
+    /// inclination column, e.g. for deep pushes deviating from the vertical.
+    ///
+    /// The depth column is replaced by the vertical depth, integrated from
+    /// the cosine of the inclination over the push, and the uncorrected
+    /// depths are kept in the push depth column. Apply after `adjust_depth`,
+    /// which regularizes depths along the push, and after replacing error
+    /// indicators. Data without an inclination column is returned unchanged.
+    /// Like `adjust_depth`, it leaves u0 as read.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if the depth is already corrected or
+    /// an inclination reaches 90 degrees.
+    pub fn correct_inclination(self) -> Result<Self, CoreError> {
+        let corrected = self.data.column(*COL_INCLINATION).is_ok();
+        let out_data = crate::frame::fix::correct_inclination(self.data)?;
+        let mut meta = self.meta;
+        meta.record("correct_inclination", [("corrected", corrected.to_string())]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Removes the zero-filled or empty records logged before ground contact
+    /// and after extraction.
+    ///
+    /// Only the runs at the top and bottom of the sounding are removed:
+    /// records whose qc and fs are both NaN or within `tolerance` (default
+    /// `empty_tolerance` from the configuration) of 0. Removed records are
+    /// kept by `retain_dropped`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if `tolerance` is negative or NaN.
+    pub fn trim_empty_rows(self, tolerance: Option<f64>) -> Result<Self, CoreError> {
+        let mut meta = self.meta;
+        let removed = crate::frame::fix::empty_edge_rows(&self.data, tolerance)?;
+        let out_data = crate::frame::fix::trim_empty_rows(self.data, tolerance)?;
+        meta.record("trim_empty_rows", [
+            ("tolerance", tolerance.unwrap_or(*EMPTY_TOLERANCE).to_string()),
+            ("removed", removed.height().to_string()),
+        ]);
+        if let Some(retained) = meta.dropped.take() {
+            meta.dropped = Some(crate::frame::clean::append_rows(retained, removed)?);
+        }
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Detects the refusal at the end of the sounding, where qc climbs to
+    /// the capacity of the cone and the push is stopped.
+    ///
+    /// The refusal is the trailing run of records with qc at or above
+    /// `ratio` times `capacity` (MPa), provided qc reaches `capacity`. Its
+    /// first depth is kept as the termination depth in the processing
+    /// record ("none" without refusal); with `Refusal::Truncate` its records
+    /// are also dropped (kept by `retain_dropped`). Parameters default to
+    /// `refusal_qc`, `refusal_ratio` and `refusal` from the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if `capacity` is not positive or
+    /// `ratio` is outside (0, 1].
+    pub fn detect_refusal(
+        self,
+        capacity: Option<f64>,
+        ratio: Option<f64>,
+        action: Option<Refusal>
+    ) -> Result<Self, CoreError> {
+        let action = action.unwrap_or(*REFUSAL);
+        let refusal = crate::frame::fix::refusal_rows(&self.data, capacity, ratio)?;
+        let termination_depth = refusal
+            .column(*COL_DEPTH)?
+            .f64()?
+            .into_iter()
+            .next()
+            .flatten()
+            .map_or("none".to_string(), |depth| depth.to_string());
+        let out_data = crate::frame::fix::detect_refusal(
+            self.data,
+            capacity,
+            ratio,
+            Some(action)
+        )?;
+        let mut meta = self.meta;
+        meta.record("detect_refusal", [
+            ("capacity", capacity.unwrap_or(*REFUSAL_QC).to_string()),
+            ("ratio", ratio.unwrap_or(*REFUSAL_RATIO).to_string()),
+            ("action", format!("{:?}", action)),
+            ("termination_depth", termination_depth),
+            ("records", refusal.height().to_string()),
+        ]);
+        if action == Refusal::Truncate
+            && let Some(retained) = meta.dropped.take()
+        {
+            meta.dropped = Some(crate::frame::clean::append_rows(retained, refusal)?);
+        }
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Interpolates every channel onto a regular depth grid of `spacing`
+    /// meters (e.g. 0.02), e.g. to merge soundings recorded at different
+    /// sampling rates or before layer detection.
+    ///
+    /// Float columns are interpolated linearly against depth; flag columns
+    /// take the value of the nearest record.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if `spacing` is not positive or the
+    /// depth decreases (see `ensure_monotonic_depth`).
+    pub fn resample(self, spacing: f64) -> Result<Self, CoreError> {
+        let out_data = crate::frame::fix::resample(self.data, spacing)?;
+        let mut meta = self.meta;
+        meta.record("resample", [("spacing", spacing.to_string())]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Removes sensor zero drift from fs and u2 using the zero readings
+    /// taken before the push and after extraction.
+    ///
+    /// The offset of each channel is interpolated linearly over depth
+    /// between its two readings and subtracted. Apply before the stress
+    /// calculations.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if a zero reading is not finite.
+    pub fn baseline_correct(self, baseline: &Baseline) -> Result<Self, CoreError> {
+        let out_data = crate::frame::fix::baseline_correct(self.data, baseline)?;
+        let mut meta = self.meta;
+        let readings = |readings: Option<ZeroReadings>| {
+            readings.map_or_else(
+                || "none".to_string(),
+                |readings| format!("{}..{}", readings.before, readings.after)
+            )
+        };
+        meta.record("baseline_correct", [
+            ("fs", readings(baseline.fs)),
+            ("u2", readings(baseline.u2)),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Aggregates the records into depth intervals of `interval` meters
+    /// (e.g. 0.25), e.g. before feeding results into settlement and pile
+    /// design spreadsheets.
+    ///
+    /// Each interval becomes one record at its midpoint depth, with float
+    /// columns aggregated by `aggregation` (default `aggregation` from the
+    /// configuration) ignoring NaN values. Apply after the derived columns,
+    /// which are computed from the full-resolution records.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if `interval` is not positive.
+    pub fn downsample(
+        self,
+        interval: f64,
+        aggregation: Option<Aggregation>
+    ) -> Result<Self, CoreError> {
+        let out_data = crate::frame::fix::downsample(self.data, interval, aggregation)?;
+        let mut meta = self.meta;
+        meta.record("downsample", [
+            ("interval", interval.to_string()),
+            ("aggregation", format!("{:?}", aggregation.unwrap_or(*AGGREGATION))),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Snaps depth values to the nearest multiple of `step`.
+    ///
+    /// Binned depths are rounded to 3 decimal places, so datasets recorded
+    /// by different rigs share identical depth keys when joined.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if `step` is not positive.
+    pub fn bin_depth(self, step: f64) -> Result<Self, CoreError> {
+        let out_data = crate::frame::fix::bin_depth(self.data, step)?;
+        let mut meta = self.meta;
+        meta.record("bin_depth", [("step", step.to_string())]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Keeps the rows removed by later cleaning steps, each with the reason
+    /// of its removal, in a side table available from `dropped_rows`.
+    pub fn retain_dropped(mut self) -> Self {
+        self.meta.dropped.get_or_insert_with(DataFrame::empty);
+        self
+    }
+
+    /// Returns the rows removed since `retain_dropped` was called, or None
+    /// if dropped rows are not retained.
+    pub fn dropped_rows(&self) -> Option<&DataFrame> {
+        self.meta.dropped.as_ref()
+    }
+
+    /// Writes the retained dropped rows and their reasons to a CSV file.
+    ///
+    /// Rows are written as they were removed: depth binning is not applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if dropped rows are not retained.
+    pub fn write_dropped(
+        &self,
+        file_path: &str,
+        options: &WriteOptions
+    ) -> Result<(), CoreError> {
+        let dropped = self.dropped_rows().ok_or_else(|| {
+            CoreError::InvalidData(
+                "Dropped rows are not retained (see `retain_dropped`)".to_string()
+            )
+        })?;
+        let options = WriteOptions { depth_bin: Some(0.0), ..options.clone() };
+        crate::frame::write::write_csv(dropped, &self.meta, file_path, &options)
+    }
+
+    /// Writes the dataset to a CSV file.
+    pub fn write_csv(
+        &self,
+        file_path: &str,
+        options: &WriteOptions
+    ) -> Result<(), CoreError> {
+        crate::frame::write::write_csv(&self.data, &self.meta, file_path, options)
+    }
+
+    /// Writes qt, fs, u2 and Ic against depth to a LAS 2.0 file.
+    pub fn write_las(
+        &self,
+        file_path: &str,
+        well_name: &str,
+        options: &WriteOptions
+    ) -> Result<(), CoreError> {
+        crate::frame::write::write_las(
+            &self.data,
+            &self.meta,
+            file_path,
+            well_name,
+            options
+        )
+    }
+
+    /// Writes one file per layer plus a `layers.csv` summary into `dir`.
+    ///
+    /// `layers` holds manually defined (top, bottom) depth limits; if None,
+    /// the SBT layers detected from Ic are used, which requires
+    /// `add_behavior_cols` to be applied first. Returns the number of layer
+    /// files written.
+    pub fn write_by_layer(
+        &self,
+        dir: &str,
+        format: ExportFormat,
+        layers: Option<&[(f64, f64)]>,
+        options: &WriteOptions
+    ) -> Result<usize, CoreError> {
+        let layers = match layers {
+            Some(layers) => layers.to_vec(),
+            None => crate::math::sbt::sbt_layers(&self.data)?
+                .into_iter()
+                .map(|layer| (layer.top, layer.bottom))
+                .collect(),
+        };
+
+        crate::frame::write::write_by_layer(
+            &self.data,
+            &self.meta,
+            dir,
+            format,
+            &layers,
+            options
+        )
+    }
+
+    /// Writes the dataset to a Parquet file.
+    #[cfg(feature = "parquet")]
+    pub fn write_parquet(
+        &self,
+        file_path: &str,
+        options: &WriteOptions
+    ) -> Result<(), CoreError> {
+        crate::frame::write::write_parquet(&self.data, file_path, options)
+    }
+
+    /// Appends the dataset to a SQLite table tagged with `sounding_id`.
+    ///
+    /// Columns the table does not have yet are added to it. Returns the
+    /// number of inserted rows.
+    #[cfg(feature = "sqlite")]
+    pub fn to_sqlite(
+        &self,
+        db_path: &str,
+        table: &str,
+        sounding_id: &str,
+        options: &WriteOptions
+    ) -> Result<usize, CoreError> {
+        crate::frame::write::to_sqlite(
+            &self.data,
+            db_path,
+            table,
+            sounding_id,
+            options
+        )
     }
 
     /// Consumes the wrapper and returns the inner DataFrame.
     pub fn into_inner(self) -> DataFrame {
-        self.0
+        self.data
     }
 
     /// Returns a reference to the inner DataFrame.
     pub fn inner(&self) -> &DataFrame {
-        &self.0
+        &self.data
     }
 
     /// Returns a mutable reference to the inner DataFrame.
     pub fn inner_mut(&mut self) -> &mut DataFrame {
-        &mut self.0
+        &mut self.data
+    }
+
+    /// Sets the geographic location of the sounding, used by map exports.
+    pub fn with_location(mut self, location: Location) -> Self {
+        self.meta.location = Some(location);
+        self
+    }
+
+    /// Returns the processing record of this dataset.
+    pub fn metadata(&self) -> &Metadata {
+        &self.meta
     }
 }
 
@@ -132,24 +2008,82 @@ impl std::ops::Deref for ConicDataFrame {
     type Target = DataFrame;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.data
     }
 }
 
 impl std::ops::DerefMut for ConicDataFrame {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.data
     }
 }
 
 impl From<DataFrame> for ConicDataFrame {
     fn from(df: DataFrame) -> Self {
-        Self(df)
+        Self::new(df)
     }
 }
 
 impl From<ConicDataFrame> for DataFrame {
     fn from(conic: ConicDataFrame) -> Self {
-        conic.0
+        conic.data
     }
 }
+
+// describes a depth range in interval notation, e.g. `[0, 20)`
+fn depth_range(start: Bound<f64>, end: Bound<f64>) -> String {
+    let start = match start {
+        Bound::Included(depth) => format!("[{}", depth),
+        Bound::Excluded(depth) => format!("({}", depth),
+        Bound::Unbounded => "(-inf".to_string(),
+    };
+    let end = match end {
+        Bound::Included(depth) => format!("{}]", depth),
+        Bound::Excluded(depth) => format!("{})", depth),
+        Bound::Unbounded => "inf)".to_string(),
+    };
+
+    format!("{}, {}", start, end)
+}
+
+// describes the indicators of a cleaning step, including those configured
+// for single columns
+pub(super) fn indicator_parameters(
+    indicators: &[f64],
+    columns: Option<&[&str]>,
+) -> Vec<(&'static str, String)> {
+    let mut parameters = vec![("indicators", format!("{:?}", indicators))];
+    if !COLUMN_INDICATORS.is_empty() {
+        parameters.push(("column_indicators", format!("{:?}", *COLUMN_INDICATORS)));
+    }
+    if let Some(columns) = columns {
+        parameters.push(("columns", format!("{:?}", columns)));
+    }
+
+    parameters
+}
+
+// describes the indicators and replacements of `replace_rows`
+pub(super) fn replace_parameters(
+    indicators: &[f64],
+    replace_value: &f64,
+    replacements: &[(&str, Replacement)],
+    columns: Option<&[&str]>,
+) -> Vec<(&'static str, String)> {
+    let mut parameters = indicator_parameters(indicators, columns);
+    parameters.push(("replace_value", replace_value.to_string()));
+    if !replacements.is_empty() {
+        let replacements: Vec<String> = replacements
+            .iter()
+            .map(|(name, replacement)| format!("{}={}", name, replacement))
+            .collect();
+        parameters.push(("replacements", replacements.join("; ")));
+    }
+
+    parameters
+}
+
+// describes an optional parameter resolved from the data when None
+fn describe(value: Option<f64>) -> String {
+    value.map_or_else(|| "auto".to_string(), |value| value.to_string())
+}
@@ -10,9 +10,41 @@ pub enum CoreError {
     #[error("Polars error: {0}")]
     Polars(#[from] PolarsError),
 
+    #[cfg(feature = "sqlite")]
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    #[cfg(feature = "cloud")]
+    #[error("Object store error: {0}")]
+    ObjectStore(#[from] object_store::Error),
+
     #[error("Invalid data: {0}")]
     InvalidData(String),
 
     #[error("Invalid configuration: {0}")]
     InvalidConfig(String),
+
+    #[error(
+        "Missing required column for `{config_key}`: expected one of \
+         {expected:?}{}. Columns found: {found:?}",
+        did_you_mean(.suggestions)
+    )]
+    SchemaMismatch {
+        /// Configuration entry being matched, e.g. `input.columns.depth`.
+        config_key: String,
+        /// Accepted header names for the entry.
+        expected: Vec<String>,
+        /// Headers present in the file.
+        found: Vec<String>,
+        /// Found headers closely resembling the expected names.
+        suggestions: Vec<String>,
+    },
+}
+
+fn did_you_mean(suggestions: &[String]) -> String {
+    match suggestions {
+        [] => String::new(),
+        [name] => format!(" (did you mean '{}'?)", name),
+        names => format!(" (did you mean one of {:?}?)", names),
+    }
 }
@@ -0,0 +1,175 @@
+use polars::prelude::*;
+use super::core::{ConicDataFrame, indicator_parameters, replace_parameters};
+use super::error::CoreError;
+use super::meta::Metadata;
+use super::config::{
+    A_RATIO, UNIT_WEIGHT, ROLLING, SMOOTHING, NAN_POLICY, COL_DEPTH, COL_QC, COL_FS,
+    COL_U2, COL_U0
+};
+use crate::frame::clean::{NanPolicy, Replacement};
+use crate::frame::smooth::Smoothing;
+use crate::math::basic::UnitWeight;
+
+/// Lazy counterpart of `ConicDataFrame` for very large CPTu files.
+///
+/// Cleaning and stress steps only extend the query plan; data is read and
+/// processed when `collect` is called. Steps that need the whole sounding at
+/// once (depth adjustment, robust smoothing, the behavior solver) are
+/// applied to the collected `ConicDataFrame`.
+pub struct ConicLazyFrame {
+    data: LazyFrame,
+    meta: Metadata,
+}
+
+impl ConicLazyFrame {
+    /// Creates a new ConicLazyFrame from a Polars LazyFrame.
+    pub fn new(data: LazyFrame) -> Self {
+        Self { data, meta: Metadata::default() }
+    }
+
+    /// Creates a new ConicLazyFrame with an existing processing record.
+    pub fn with_metadata(data: LazyFrame, meta: Metadata) -> Self {
+        Self { data, meta }
+    }
+
+    /// Lazy version of `ConicDataFrame::remove_rows`.
+    pub fn remove_rows(
+        self,
+        indicators: &[f64],
+        columns: Option<&[&str]>,
+    ) -> Result<Self, CoreError> {
+        let out_data = crate::frame::clean::remove_rows_lazy(
+            self.data,
+            indicators,
+            columns
+        )?;
+        let mut meta = self.meta;
+        meta.record("remove_rows", indicator_parameters(indicators, columns));
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Lazy version of `ConicDataFrame::drop_nan`.
+    pub fn drop_nan(self, subset: Option<&[&str]>) -> Result<Self, CoreError> {
+        let out_data = crate::frame::clean::drop_nan_lazy(self.data, subset)?;
+        let mut meta = self.meta;
+        meta.record("drop_nan", [(
+            "subset",
+            subset.map_or("required".to_string(), |subset| format!("{:?}", subset))
+        )]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Lazy version of `ConicDataFrame::replace_rows`.
+    pub fn replace_rows(
+        self,
+        indicators: &[f64],
+        replace_value: &f64,
+        columns: Option<&[&str]>,
+    ) -> Result<Self, CoreError> {
+        self.replace_rows_with(indicators, replace_value, &[], columns)
+    }
+
+    /// Lazy version of `ConicDataFrame::replace_rows_with`.
+    pub fn replace_rows_with(
+        self,
+        indicators: &[f64],
+        replace_value: &f64,
+        replacements: &[(&str, Replacement)],
+        columns: Option<&[&str]>,
+    ) -> Result<Self, CoreError> {
+        let out_data = crate::frame::clean::replace_rows_lazy(
+            self.data,
+            indicators,
+            replace_value,
+            replacements,
+            columns
+        )?;
+        let mut meta = self.meta;
+        meta.record(
+            "replace_rows",
+            replace_parameters(indicators, replace_value, replacements, columns)
+        );
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Lazy version of `ConicDataFrame::add_stress_cols`.
+    ///
+    /// Skipped records are not counted, as the frame is not collected.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if `rolling` is not 1, 3 or 5, if a
+    /// robust smoothing kernel is selected with a rolling window larger than
+    /// 1, or with
+    /// `UnitWeight::Profile` or `NanPolicy::Error`, which need the collected
+    /// frame.
+    pub fn add_stress_cols(
+        self,
+        a_ratio: Option<f64>,
+        unit_weight: Option<UnitWeight>,
+        rolling: Option<usize>,
+        smoothing: Option<Smoothing>,
+        nan_policy: Option<NanPolicy>
+    ) -> Result<Self, CoreError> {
+        let nan_policy = nan_policy.unwrap_or(*NAN_POLICY);
+        let data = match nan_policy {
+            NanPolicy::Propagate => self.data,
+            NanPolicy::SkipRow => crate::frame::clean::drop_nan_lazy(
+                self.data,
+                Some(&[*COL_DEPTH, *COL_QC, *COL_FS, *COL_U2, *COL_U0])
+            )?,
+            NanPolicy::Error => {
+                return Err(CoreError::InvalidData(
+                    "NaN policy Error cannot be applied lazily. Collect the frame \
+                     before calling add_stress_cols".to_string()
+                ));
+            }
+        };
+        let out_data = crate::math::basic::add_stress_cols_lazy(
+            data,
+            a_ratio,
+            unit_weight,
+            rolling,
+            smoothing
+        )?;
+        let mut meta = self.meta;
+        meta.record("add_stress_cols", [
+            ("a_ratio", a_ratio.unwrap_or(*A_RATIO).to_string()),
+            ("gamma", unit_weight.unwrap_or(*UNIT_WEIGHT).to_string()),
+            ("rolling", rolling.unwrap_or(*ROLLING).to_string()),
+            ("smoothing", format!("{:?}", smoothing.unwrap_or(*SMOOTHING))),
+            ("nan_policy", format!("{:?}", nan_policy)),
+        ]);
+        Ok(Self { data: out_data, meta })
+    }
+
+    /// Executes the query plan and returns the resulting `ConicDataFrame`.
+    ///
+    /// Uses the streaming engine when built with the `streaming` feature, so
+    /// the input is processed in batches instead of being loaded at once.
+    pub fn collect(self) -> Result<ConicDataFrame, CoreError> {
+        #[cfg(feature = "streaming")]
+        let out_data = self.data.collect_with_engine(Engine::Streaming)?;
+
+        #[cfg(not(feature = "streaming"))]
+        let out_data = self.data.collect()?;
+
+        Ok(ConicDataFrame::with_metadata(out_data, self.meta))
+    }
+
+    /// Consumes the wrapper and returns the inner LazyFrame.
+    pub fn into_inner(self) -> LazyFrame {
+        self.data
+    }
+
+    /// Returns the processing record of this dataset.
+    pub fn metadata(&self) -> &Metadata {
+        &self.meta
+    }
+}
+
+impl From<LazyFrame> for ConicLazyFrame {
+    fn from(lf: LazyFrame) -> Self {
+        Self::new(lf)
+    }
+}
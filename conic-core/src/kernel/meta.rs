@@ -0,0 +1,85 @@
+use polars::prelude::DataFrame;
+use crate::frame::units::UnitConversion;
+
+/// Processing record carried alongside a `ConicDataFrame`.
+#[derive(Debug, Clone, Default)]
+pub struct Metadata {
+    /// Unit conversions applied while reading the source file.
+    pub conversions: Vec<UnitConversion>,
+    /// Processing steps applied since reading, in order.
+    pub steps: Vec<ProcessingStep>,
+    /// Location of the sounding, if known.
+    pub location: Option<Location>,
+    /// Rows removed by cleaning steps, with the reason of each removal.
+    /// None unless retention was enabled with
+    /// `ConicDataFrame::retain_dropped`.
+    pub dropped: Option<DataFrame>,
+}
+
+/// Geographic location of a sounding (WGS 84 degrees).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Location {
+    pub longitude: f64,
+    pub latitude: f64,
+}
+
+/// Processing step with the parameter values it was run with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessingStep {
+    pub name: String,
+    /// Parameter names and values, after applying configuration defaults.
+    pub parameters: Vec<(String, String)>,
+}
+
+impl Metadata {
+    /// Appends a processing step to the record.
+    pub fn record<I, K, V>(&mut self, name: &str, parameters: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.steps.push(ProcessingStep {
+            name: name.to_string(),
+            parameters: parameters
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        });
+    }
+
+    /// Returns the record as human-readable lines, one per conversion and
+    /// step, e.g. `add_stress_cols: a_ratio=0.8, gamma=18.7`.
+    pub fn describe(&self) -> Vec<String> {
+        let conversions = self.conversions.iter().map(|conversion| {
+            format!(
+                "unit conversion: {} from '{}' ({} to {}, x{})",
+                conversion.column, conversion.source, conversion.from,
+                conversion.to, conversion.factor
+            )
+        });
+
+        let steps = self.steps.iter().map(|step| {
+            let parameters: Vec<String> = step
+                .parameters
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect();
+
+            if parameters.is_empty() {
+                step.name.clone()
+            } else {
+                format!("{}: {}", step.name, parameters.join(", "))
+            }
+        });
+
+        let location = self.location.iter().map(|location| {
+            format!(
+                "location: longitude={}, latitude={}",
+                location.longitude, location.latitude
+            )
+        });
+
+        location.chain(conversions).chain(steps).collect()
+    }
+}
@@ -1,6 +1,21 @@
+pub mod capabilities;
 pub mod error;
 pub mod config;
+mod lazy;
+pub mod meta;
+pub(crate) mod profile;
+pub mod provenance;
+pub mod remote;
+pub mod selection;
+pub mod sink;
+pub mod store;
 mod core;
 
 pub use error::CoreError;
+pub use meta::{Location, Metadata};
+pub use provenance::Provenance;
 pub use core::ConicDataFrame;
+pub use lazy::ConicLazyFrame;
+pub use selection::OutputSelection;
+pub use sink::{FileSink, OutputSink};
+pub use store::DatasetStore;
@@ -0,0 +1,12 @@
+/// Opens a profiling span that lasts until the end of the enclosing scope.
+///
+/// Expands to nothing unless the `profiling` feature is enabled, so the
+/// instrumentation has no cost in regular builds.
+macro_rules! profile_scope {
+    ($name:literal) => {
+        #[cfg(feature = "profiling")]
+        let _profile_span = tracing::info_span!($name).entered();
+    };
+}
+
+pub(crate) use profile_scope;
@@ -0,0 +1,57 @@
+use chrono::{SecondsFormat, Utc};
+use super::config::OPERATOR;
+
+/// Build and run information embedded in exports and reports, so each file
+/// can be traced back to the software and person that produced it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Provenance {
+    /// Version of the `conic-core` crate.
+    pub version: &'static str,
+    /// Short hash of the commit the crate was built from, or `unknown`.
+    pub git_hash: &'static str,
+    /// Processing time, RFC 3339 in UTC (e.g. `2025-03-14T09:26:53Z`).
+    pub timestamp: String,
+    /// Operator name from the `CONIC_OPERATOR` environment variable or the
+    /// `operator` configuration parameter, in that order.
+    pub operator: Option<String>,
+}
+
+impl Provenance {
+    /// Returns the provenance of a run happening now.
+    pub fn now() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            git_hash: env!("CONIC_GIT_HASH"),
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            operator: OPERATOR.clone(),
+        }
+    }
+
+    /// Returns the provenance as key-value pairs, e.g. for file metadata.
+    pub fn pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = vec![
+            ("conic_version", self.version.to_string()),
+            ("conic_git_hash", self.git_hash.to_string()),
+            ("processed_at", self.timestamp.clone()),
+        ];
+        if let Some(operator) = &self.operator {
+            pairs.push(("operator", operator.clone()));
+        }
+
+        pairs
+    }
+
+    /// Returns the provenance as one sentence, e.g. `conic 0.1.0 (git
+    /// 1a2b3c4), processed 2025-03-14T09:26:53Z by J. Doe`.
+    pub fn describe(&self) -> String {
+        let mut sentence = format!(
+            "conic {} (git {}), processed {}",
+            self.version, self.git_hash, self.timestamp
+        );
+        if let Some(operator) = &self.operator {
+            sentence.push_str(&format!(" by {}", operator));
+        }
+
+        sentence
+    }
+}
@@ -0,0 +1,110 @@
+use super::CoreError;
+
+/// URL schemes of object store locations.
+const SCHEMES: [&str; 4] = ["s3://", "az://", "http://", "https://"];
+
+/// Returns true if `location` is an object store URL rather than a local
+/// path.
+pub fn is_remote(location: &str) -> bool {
+    SCHEMES.iter().any(|scheme| location.starts_with(scheme))
+}
+
+/// Returns a location without the query and fragment of a URL, e.g. the
+/// signature of a presigned URL, so its file extension can be matched.
+/// Local paths are returned unchanged.
+pub fn strip_query(location: &str) -> &str {
+    if !is_remote(location) {
+        return location;
+    }
+
+    let end = location.find(['?', '#']).unwrap_or(location.len());
+    &location[..end]
+}
+
+/// Opens the object store of a URL and returns it with the path of the URL
+/// within the store.
+///
+/// Supported URLs are `s3://bucket/path`, `az://container/path` and
+/// `http(s)://host/path`. S3 and Azure credentials are read from the
+/// standard `AWS_*` and `AZURE_*` environment variables.
+#[cfg(feature = "cloud")]
+pub(crate) fn open_store(
+    url: &str,
+) -> Result<(Box<dyn object_store::ObjectStore>, String), CoreError> {
+    use object_store::ClientOptions;
+    use object_store::aws::AmazonS3Builder;
+    use object_store::azure::MicrosoftAzureBuilder;
+    use object_store::http::HttpBuilder;
+
+    let split = |rest: &str| {
+        let (root, path) = rest.split_once('/').unwrap_or((rest, ""));
+        (root.to_string(), path.trim_matches('/').to_string())
+    };
+
+    if let Some(rest) = url.strip_prefix("s3://") {
+        let (bucket, path) = split(rest);
+        let store = AmazonS3Builder::from_env().with_bucket_name(bucket).build()?;
+        Ok((Box::new(store), path))
+    } else if let Some(rest) = url.strip_prefix("az://") {
+        let (container, path) = split(rest);
+        let store = MicrosoftAzureBuilder::from_env()
+            .with_container_name(container)
+            .build()?;
+        Ok((Box::new(store), path))
+    } else if let Some((scheme, rest)) = url.split_once("://")
+        && (scheme == "http" || scheme == "https")
+    {
+        // the query, e.g. a presigned signature, stays on the store URL so
+        // every request carries it
+        let rest = rest.split('#').next().unwrap_or_default();
+        let (rest, query) = match rest.split_once('?') {
+            Some((rest, query)) => (rest, format!("?{}", query)),
+            None => (rest, String::new()),
+        };
+        let (host, path) = split(rest);
+        let options = ClientOptions::new().with_allow_http(scheme == "http");
+        let store = HttpBuilder::new()
+            .with_url(format!("{}://{}{}", scheme, host, query))
+            .with_client_options(options)
+            .build()?;
+        Ok((Box::new(store), path))
+    } else {
+        Err(CoreError::InvalidData(format!(
+            "Unsupported object store URL '{}': expected s3://, az:// or \
+             http(s)://",
+            url
+        )))
+    }
+}
+
+/// Builds the runtime object store requests are run on.
+#[cfg(feature = "cloud")]
+pub(crate) fn runtime() -> Result<tokio::runtime::Runtime, CoreError> {
+    Ok(tokio::runtime::Builder::new_current_thread().enable_all().build()?)
+}
+
+/// Downloads the object at `url`.
+///
+/// # Errors
+///
+/// Returns `CoreError::InvalidData` when the crate is built without the
+/// `cloud` feature.
+pub(crate) fn fetch(url: &str) -> Result<Vec<u8>, CoreError> {
+    #[cfg(feature = "cloud")]
+    {
+        use object_store::ObjectStoreExt;
+
+        let (store, path) = open_store(url)?;
+        let path = object_store::path::Path::from(path);
+        let bytes = runtime()?.block_on(async {
+            store.get(&path).await?.bytes().await
+        })?;
+        Ok(bytes.to_vec())
+    }
+
+    #[cfg(not(feature = "cloud"))]
+    Err(CoreError::InvalidData(format!(
+        "Cannot read '{}': object store input requires the `cloud` feature",
+        url
+    )))
+}
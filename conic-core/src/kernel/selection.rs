@@ -0,0 +1,174 @@
+use super::CoreError;
+use super::config::column_keys;
+
+/// Configuration keys of the derived columns, grouped by the step computing
+/// them, in pipeline order.
+const DERIVED_KEYS: [(Step, &[&str]); 11] = [
+    (Step::Stress, &[
+        "sigv_tot", "sigv_eff", "du", "du_sigv", "qt", "qn", "qe", "rf", "fr", "bq",
+    ]),
+    (Step::PorePressure, &["u2_u0"]),
+    (Step::Behavior, &["n", "qtn", "ic", "convg", "excluded", "cd", "ib"]),
+    (Step::Sbt, &[
+        "sbt", "sbt_desc", "group", "soil", "schneider", "kc", "qtn_cs", "fc",
+    ]),
+    (Step::Spt, &["n60"]),
+    (Step::Modulus, &["e_mod", "m_mod", "mv", "cr", "vs", "g0"]),
+    (Step::Strength, &["su", "st", "phi"]),
+    (Step::Density, &["dr", "psi"]),
+    (Step::Consolidation, &["sigp", "ocr", "k0"]),
+    (Step::Permeability, &["k", "cv"]),
+    (Step::Liquefaction, &[
+        "kh", "qt_thin", "crr", "liquefiable", "thin_layer", "rd", "csr", "fs_liq",
+        "qc1ncs", "crr_bi", "k_sigma", "fs_liq_bi", "ev", "settlement", "gamma_max",
+    ]),
+];
+
+/// Derivation step of the processing pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Step {
+    Stress,
+    PorePressure,
+    Behavior,
+    Sbt,
+    Spt,
+    Modulus,
+    Strength,
+    Density,
+    Consolidation,
+    Permeability,
+    Liquefaction,
+}
+
+/// Derived columns computed by `ConicDataFrame::add_derived_cols`.
+///
+/// Smaller selections skip the more expensive steps, such as the iterative
+/// behavior columns, and produce narrower outputs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutputSelection {
+    /// Stresses, the excess pore pressure Δu and Δu/σ'v, qt, the net and
+    /// effective cone resistances qn and qe, Rf, Fr, Bq and u2/u0.
+    Minimal,
+    /// Minimal plus the behavior columns (n, Qtn, Ic, CD, IB), the SBTn and
+    /// Schneider et al. zones, the behavior groups, the soil descriptions,
+    /// the clean sand equivalent Qtn,cs with its Kc and the apparent fines
+    /// content.
+    Standard,
+    /// Standard plus the N60, E', M, mv, CR, Vs, G0, su, St, φ', Dr, ψ, σ'p,
+    /// OCR, K0, k and cv correlations, the thin-layer correction, the
+    /// liquefaction triggering columns, the reconsolidation settlement and the
+    /// maximum shear strain.
+    Full,
+    /// Only the listed derived columns, given by configuration key (e.g.
+    /// `ic`) or column name. Steps they depend on are computed, but their
+    /// other columns are dropped; input columns are always kept.
+    Columns(Vec<String>),
+}
+
+impl OutputSelection {
+    /// Builds a selection from its configuration name.
+    ///
+    /// Accepted names: `minimal`, `standard`, `full`.
+    pub fn from_config(name: &str) -> Result<Self, CoreError> {
+        match name {
+            "minimal" => Ok(Self::Minimal),
+            "standard" => Ok(Self::Standard),
+            "full" => Ok(Self::Full),
+            _ => Err(CoreError::InvalidConfig(format!(
+                "Invalid selection parameter: '{}'. Must be minimal, standard \
+                 or full",
+                name
+            ))),
+        }
+    }
+
+    /// Returns the configuration keys of the selected derived columns.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if a listed column is not a derived
+    /// column.
+    pub(crate) fn keys(&self) -> Result<Vec<&'static str>, CoreError> {
+        let last_step = match self {
+            Self::Minimal => Step::PorePressure,
+            Self::Standard => Step::Sbt,
+            Self::Full => Step::Liquefaction,
+            Self::Columns(columns) => {
+                return columns.iter().map(|column| derived_key(column)).collect();
+            }
+        };
+
+        Ok(DERIVED_KEYS
+            .iter()
+            .filter(|(step, _)| *step <= last_step)
+            .flat_map(|(_, keys)| keys.iter().copied())
+            .collect())
+    }
+
+    /// Returns the derivation steps needed to compute the selected columns.
+    pub(crate) fn steps(&self) -> Result<Vec<Step>, CoreError> {
+        let keys = self.keys()?;
+        let mut steps: Vec<Step> = DERIVED_KEYS
+            .iter()
+            .filter(|(_, step_keys)| step_keys.iter().any(|key| keys.contains(key)))
+            .map(|(step, _)| *step)
+            .collect();
+
+        // every step needs the stresses, the zones and correlations need Ic,
+        // ψ and CRR need Qtn,cs and FC, γmax needs Dr and cv needs M
+        steps.push(Step::Stress);
+        if steps.contains(&Step::Liquefaction) {
+            steps.push(Step::Density);
+        }
+        if steps.contains(&Step::Density) {
+            steps.push(Step::Sbt);
+        }
+        if steps.contains(&Step::Permeability) {
+            steps.push(Step::Modulus);
+        }
+        if steps
+            .iter()
+            .any(|step| !matches!(step, Step::Stress | Step::PorePressure))
+        {
+            steps.push(Step::Behavior);
+        }
+        steps.sort();
+        steps.dedup();
+
+        Ok(steps)
+    }
+
+    /// Returns the names of the derived columns left out of the selection.
+    pub(crate) fn excluded_names(&self) -> Result<Vec<&'static str>, CoreError> {
+        let keys = self.keys()?;
+
+        Ok(column_keys()
+            .into_iter()
+            .filter(|(key, _)| is_derived(key) && !keys.contains(key))
+            .map(|(_, name)| name)
+            .collect())
+    }
+}
+
+fn is_derived(key: &str) -> bool {
+    DERIVED_KEYS.iter().any(|(_, keys)| keys.contains(&key))
+}
+
+// resolves a derived column given by key or name to its key
+fn derived_key(column: &str) -> Result<&'static str, CoreError> {
+    DERIVED_KEYS
+        .iter()
+        .flat_map(|(_, keys)| keys.iter().copied())
+        .find(|key| {
+            *key == column
+                || column_keys()
+                    .iter()
+                    .any(|(other, name)| other == key && *name == column)
+        })
+        .ok_or_else(|| {
+            CoreError::InvalidData(format!(
+                "Unknown derived column '{}' in the output selection",
+                column
+            ))
+        })
+}
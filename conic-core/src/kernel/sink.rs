@@ -0,0 +1,131 @@
+use std::path::PathBuf;
+use super::{ConicDataFrame, CoreError};
+use crate::frame::write::{write_csv_to, WriteOptions};
+
+/// Destination of the files produced by a processing run.
+///
+/// Implementations only provide `put`; frames, reports and plots are
+/// serialized by the provided methods. Names are relative paths with `/`
+/// separators, e.g. `CPT-01/report.html`.
+pub trait OutputSink {
+    /// Stores `bytes` under `name`, replacing any previous content.
+    fn put(&self, name: &str, bytes: Vec<u8>) -> Result<(), CoreError>;
+
+    /// Writes a dataset as CSV, including the processing record comments
+    /// when enabled in `options`.
+    fn write_frame(
+        &self,
+        name: &str,
+        data: &ConicDataFrame,
+        options: &WriteOptions,
+    ) -> Result<(), CoreError> {
+        let mut bytes = Vec::new();
+        write_csv_to(data, data.metadata(), &mut bytes, options)?;
+        self.put(name, bytes)
+    }
+
+    /// Writes a rendered text report (HTML, Markdown, GeoJSON).
+    fn write_report(&self, name: &str, report: &str) -> Result<(), CoreError> {
+        self.put(name, report.as_bytes().to_vec())
+    }
+
+    /// Writes an encoded plot (SVG, PNG).
+    fn write_plot(&self, name: &str, plot: &[u8]) -> Result<(), CoreError> {
+        self.put(name, plot.to_vec())
+    }
+}
+
+/// Sink writing to a local directory, created on demand.
+#[derive(Debug, Clone)]
+pub struct FileSink {
+    root: PathBuf,
+}
+
+impl FileSink {
+    /// Creates a sink writing below the directory `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl OutputSink for FileSink {
+    fn put(&self, name: &str, bytes: Vec<u8>) -> Result<(), CoreError> {
+        let path = self.root.join(name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, bytes)?;
+
+        Ok(())
+    }
+}
+
+/// Sink writing to an object store: an S3 bucket, an Azure Blob container
+/// or an HTTP (WebDAV) server.
+///
+/// Uploads run to completion before `put` returns, on a runtime owned by
+/// the sink.
+#[cfg(feature = "cloud")]
+pub struct ObjectStoreSink {
+    store: Box<dyn object_store::ObjectStore>,
+    prefix: String,
+    runtime: tokio::runtime::Runtime,
+}
+
+#[cfg(feature = "cloud")]
+impl ObjectStoreSink {
+    /// Creates a sink from a URL such as `s3://bucket/results`,
+    /// `az://container/results` or `https://host/dav/results`.
+    ///
+    /// Credentials are read from the standard `AWS_*` and `AZURE_*`
+    /// environment variables.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::InvalidData` if the URL scheme is not supported.
+    pub fn from_url(url: &str) -> Result<Self, CoreError> {
+        let (store, prefix) = super::remote::open_store(url)?;
+
+        Ok(Self { store, prefix, runtime: super::remote::runtime()? })
+    }
+}
+
+#[cfg(feature = "cloud")]
+impl OutputSink for ObjectStoreSink {
+    fn put(&self, name: &str, bytes: Vec<u8>) -> Result<(), CoreError> {
+        use object_store::ObjectStoreExt;
+
+        let location = if self.prefix.is_empty() {
+            object_store::path::Path::from(name)
+        } else {
+            object_store::path::Path::from(format!("{}/{}", self.prefix, name))
+        };
+        self.runtime
+            .block_on(self.store.put(&location, bytes.into()))?;
+
+        Ok(())
+    }
+}
+
+/// Returns the sink for an output location: an object store for URLs (see
+/// `remote::is_remote`), otherwise a local directory.
+///
+/// # Errors
+///
+/// Returns `CoreError::InvalidData` for URLs when the crate is built
+/// without the `cloud` feature.
+pub fn sink_for(location: &str) -> Result<Box<dyn OutputSink>, CoreError> {
+    if !super::remote::is_remote(location) {
+        return Ok(Box::new(FileSink::new(location)));
+    }
+
+    #[cfg(feature = "cloud")]
+    return Ok(Box::new(ObjectStoreSink::from_url(location)?));
+
+    #[cfg(not(feature = "cloud"))]
+    Err(CoreError::InvalidData(format!(
+        "Cannot write to '{}': object store output requires the `cloud` \
+         feature",
+        location
+    )))
+}
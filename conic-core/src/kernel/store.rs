@@ -0,0 +1,143 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use super::{ConicDataFrame, CoreError};
+
+/// Thread-safe cache of loaded or processed soundings keyed by sounding ID.
+///
+/// Holds at most `capacity` datasets and evicts the least recently used one
+/// when full. Datasets are shared as `Arc<ConicDataFrame>`, so an evicted
+/// sounding stays valid for callers still holding it.
+pub struct DatasetStore {
+    capacity: usize,
+    inner: Mutex<StoreInner>,
+}
+
+struct StoreInner {
+    entries: HashMap<String, Arc<ConicDataFrame>>,
+    // least recently used first
+    order: VecDeque<String>,
+}
+
+impl DatasetStore {
+    /// Creates an empty store holding up to `capacity` datasets (at least 1).
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+
+        Self {
+            capacity,
+            inner: Mutex::new(StoreInner {
+                entries: HashMap::with_capacity(capacity),
+                order: VecDeque::with_capacity(capacity),
+            }),
+        }
+    }
+
+    /// Returns the dataset stored under `id`, marking it as recently used.
+    pub fn get(&self, id: &str) -> Option<Arc<ConicDataFrame>> {
+        let mut inner = self.lock();
+        let data = inner.entries.get(id).cloned()?;
+        inner.touch(id);
+        Some(data)
+    }
+
+    /// Stores a dataset under `id`, replacing any previous entry and evicting
+    /// the least recently used dataset if the store is full.
+    pub fn insert(
+        &self,
+        id: impl Into<String>,
+        data: ConicDataFrame
+    ) -> Arc<ConicDataFrame> {
+        let data = Arc::new(data);
+        self.lock().insert(id.into(), data.clone(), self.capacity);
+        data
+    }
+
+    /// Returns the dataset stored under `id`, loading and storing it first if
+    /// it is not cached.
+    ///
+    /// The store is not locked while `load` runs, so slow reads do not block
+    /// other callers. If another caller stores the same ID meanwhile, its
+    /// dataset is kept and returned.
+    pub fn get_or_load<F>(
+        &self,
+        id: &str,
+        load: F
+    ) -> Result<Arc<ConicDataFrame>, CoreError>
+    where
+        F: FnOnce() -> Result<ConicDataFrame, CoreError>,
+    {
+        if let Some(data) = self.get(id) {
+            return Ok(data);
+        }
+
+        let data = Arc::new(load()?);
+
+        let mut inner = self.lock();
+        if let Some(existing) = inner.entries.get(id).cloned() {
+            inner.touch(id);
+            return Ok(existing);
+        }
+        inner.insert(id.to_string(), data.clone(), self.capacity);
+
+        Ok(data)
+    }
+
+    /// Removes the dataset stored under `id`.
+    pub fn remove(&self, id: &str) -> Option<Arc<ConicDataFrame>> {
+        let mut inner = self.lock();
+        inner.order.retain(|key| key != id);
+        inner.entries.remove(id)
+    }
+
+    /// Removes all datasets.
+    pub fn clear(&self) {
+        let mut inner = self.lock();
+        inner.entries.clear();
+        inner.order.clear();
+    }
+
+    /// Returns the number of stored datasets.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Returns true if the store holds no datasets.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the maximum number of stored datasets.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn lock(&self) -> MutexGuard<'_, StoreInner> {
+        // a panic while holding the lock cannot leave the maps inconsistent
+        // in a way that matters for a cache, so recover the guard
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl StoreInner {
+    fn touch(&mut self, id: &str) {
+        if let Some(pos) = self.order.iter().position(|key| key == id) {
+            let key = self.order.remove(pos).unwrap_or_else(|| id.to_string());
+            self.order.push_back(key);
+        }
+    }
+
+    fn insert(&mut self, id: String, data: Arc<ConicDataFrame>, capacity: usize) {
+        if self.entries.insert(id.clone(), data).is_some() {
+            self.touch(&id);
+            return;
+        }
+
+        self.order.push_back(id);
+
+        while self.order.len() > capacity {
+            if let Some(evicted) = self.order.pop_front() {
+                self.entries.remove(&evicted);
+            }
+        }
+    }
+}
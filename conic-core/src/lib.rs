@@ -1,8 +1,10 @@
 pub mod kernel;
 pub mod math;
 pub mod frame;
+pub mod report;
+pub mod design;
 
-pub use kernel::{CoreError, ConicDataFrame};
+pub use kernel::{CoreError, ConicDataFrame, ConicLazyFrame, Metadata};
 
 /// Prelude module for convenient imports.
 ///
@@ -11,6 +13,23 @@ pub use kernel::{CoreError, ConicDataFrame};
 /// use conic_core::prelude::*;
 /// ```
 pub mod prelude {
-    pub use crate::kernel::{ConicDataFrame, CoreError};
-    pub use crate::frame::read::read_csv;
+    pub use crate::kernel::{
+        ConicDataFrame, ConicLazyFrame, CoreError, Location, Metadata, OutputSelection,
+        OutputSink
+    };
+    pub use crate::frame::backend::{ReaderBackend, read_auto, register_reader};
+    pub use crate::frame::read::{read_csv, read_csv_from_zip, scan_csv};
+    pub use crate::frame::clean::{NanPolicy, Replacement, RodChanges, SeatingArtifacts};
+    pub use crate::frame::fix::{
+        Aggregation, DuplicateDepths, NonMonotonicDepth, Refusal, Repush
+    };
+    pub use crate::frame::smooth::Smoothing;
+    pub use crate::math::basic::UnitWeight;
+    pub use crate::math::classify::ClassificationMethod;
+    pub use crate::math::correlations::PhiMethod;
+    pub use crate::math::liquefaction::{
+        GroundGeometry, MsfMethod, RdMethod, TriggeringMethod
+    };
+    pub use crate::frame::write::{ExportFormat, WriteOptions};
+    pub use crate::design::settlement::LoadDistribution;
 }
\ No newline at end of file
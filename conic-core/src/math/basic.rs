@@ -1,56 +1,186 @@
 use polars::prelude::*;
 use crate::kernel::CoreError;
+use crate::kernel::profile::profile_scope;
 use crate::kernel::config::{
     COL_DEPTH, COL_QC, COL_FS, COL_U2, COL_U0,
-    COL_SIGV_TOT, COL_SIGV_EFF, COL_QT, COL_FR, COL_BQ,
-    COL_N, COL_QTN, COL_IC, COL_CONVG, COL_CD, COL_IB,
-    A_RATIO, GAMMA_S, P_REF, ROLLING, MAX_ITER, TOLERANCE
+    COL_SIGV_TOT, COL_SIGV_EFF, COL_QT, COL_QN, COL_QE, COL_RF, COL_FR, COL_BQ,
+    COL_N, COL_QTN, COL_IC, COL_CONVG, COL_EXCLUDED, COL_CD, COL_IB,
+    COL_DU, COL_U2_U0, COL_DU_SIGV, COL_GAMMA,
+    A_RATIO, GAMMA_S, P_REF, ROLLING, SMOOTHING, MAX_ITER, TOLERANCE,
+    BEHAVIOR_TOP, BEHAVIOR_BOTTOM, UNIT_WEIGHT, ROLLING_WINDOWS
 };
+use crate::frame::smooth::{self, Smoothing};
 
-const COL_FS_ROL: &str = "fs [rolling]";
-const COL_QT_ROL: &str = "qt [rolling]";
+pub(crate) const COL_FS_ROL: &str = "fs [rolling]";
+pub(crate) const COL_QT_ROL: &str = "qt [rolling]";
+
+/// Unit weight the total vertical stress is integrated from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnitWeight {
+    /// Single unit weight (kN/m³) for the whole sounding.
+    Constant(f64),
+    /// Depth-varying unit weight of the column added by `add_gamma_col`;
+    /// records where it is NaN fall back to the configured `gamma_s`.
+    Profile,
+}
+
+impl UnitWeight {
+    /// Builds a unit weight from its configuration name and the constant
+    /// soil unit weight.
+    ///
+    /// Accepted names: `constant`, `profile`.
+    pub fn from_config(name: &str, gamma_s: f64) -> Result<Self, CoreError> {
+        match name {
+            "constant" => Ok(Self::Constant(gamma_s)),
+            "profile" => Ok(Self::Profile),
+            _ => Err(CoreError::InvalidConfig(format!(
+                "Invalid unit_weight parameter: '{}'. Must be constant or profile",
+                name
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for UnitWeight {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Constant(gamma) => write!(f, "{}", gamma),
+            Self::Profile => write!(f, "profile"),
+        }
+    }
+}
 
 /// Computes basic stress-related and normalized CPT parameters.
 ///
 /// This function derives fundamental quantities from raw CPTu data,
-/// including total and effective vertical stresses.
+/// including total and effective vertical stresses, the excess pore pressure
+/// Δu and its normalized ratio `U = Δu / σ'v` (NaN where σ'v is not
+/// positive; the U2 axis of the chart of Schneider et al., 2008), the net and
+/// effective cone resistances and the friction ratio. σv_tot is integrated
+/// from `unit_weight` (default `unit_weight` from the configuration).
+///
+/// # Errors
+///
+/// Returns `CoreError::InvalidData` if `rolling` is not 1, 3 or 5, or with
+/// `UnitWeight::Profile` if the unit weight column is missing.
 pub(crate) fn add_stress_cols(
     data: DataFrame,
     a_ratio: Option<f64>,
-    gamma: Option<f64>,
-    rolling: Option<usize>
+    unit_weight: Option<UnitWeight>,
+    rolling: Option<usize>,
+    smoothing: Option<Smoothing>
 ) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_stress_cols");
+
     let a_ratio = a_ratio.unwrap_or(*A_RATIO);
-    let gamma = gamma.unwrap_or(*GAMMA_S);
+    let sigv_tot = match unit_weight.unwrap_or(*UNIT_WEIGHT) {
+        UnitWeight::Constant(gamma) => lit(gamma) * col(*COL_DEPTH),
+        UnitWeight::Profile => lit(integrate_sigv_tot(&data)?),
+    };
     let rolling = rolling.unwrap_or(*ROLLING);
+    let smoothing = smoothing.unwrap_or(*SMOOTHING);
+    validate_rolling(rolling)?;
 
-    let out_data = data
+    if let Smoothing::SavitzkyGolay(order) = smoothing
+        && rolling > 1
+        && (rolling.is_multiple_of(2) || rolling <= order)
+    {
+        return Err(CoreError::InvalidData(format!(
+            "Savitzky-Golay smoothing of order {} needs an odd rolling window \
+             larger than the order, got {}",
+            order, rolling
+        )));
+    }
+
+    if rolling == 1 || smoothing == Smoothing::Mean {
+        let out_data = stress_cols_lazy(
+            data.lazy(), a_ratio, sigv_tot, rolling, smoothing
+        )?.collect()?;
+
+        return Ok(out_data);
+    }
+
+    let out_data = with_stress_cols(data.lazy(), a_ratio, sigv_tot).collect()?;
+
+    // robust kernels sort or fit each window, which polars expressions
+    // cannot do
+    let fs_rolling = smooth::rolling(
+        out_data.column(*COL_FS)?.f64()?, rolling, smoothing
+    );
+    let qt_rolling = smooth::rolling(
+        out_data.column(*COL_QT)?.f64()?, rolling, smoothing
+    );
+
+    let out_data = out_data
         .lazy()
-        // total vertical stress = γ * z
-        .with_column((
-                lit(gamma) * col(*COL_DEPTH)
-            ).alias(*COL_SIGV_TOT)
-        )
-        // effective vertical stress = σv_tot - u0
-        .with_column((
-                col(*COL_SIGV_TOT) - col(*COL_U0)
-            ).alias(*COL_SIGV_EFF)
-        )
-        // corrected cone resistance = qc + (1 - a) * u2
-        .with_column((
-                col(*COL_QC) + col(*COL_U2) * lit(1.0 - a_ratio)
-                / lit(1000)
-            ).alias(*COL_QT)
-        )
-        .collect()?;
+        .with_column(lit(fs_rolling.into_series()).alias(COL_FS_ROL))
+        .with_column(lit(qt_rolling.into_series()).alias(COL_QT_ROL));
+
+    let out_data = with_ratio_cols(out_data).collect()?;
+
+    Ok(out_data)
+}
+
+/// Lazy counterpart of `add_stress_cols`.
+///
+/// Only the mean kernel and a constant unit weight can be expressed lazily;
+/// robust smoothing kernels and unit weight profiles require collecting the
+/// frame first.
+pub(crate) fn add_stress_cols_lazy(
+    data: LazyFrame,
+    a_ratio: Option<f64>,
+    unit_weight: Option<UnitWeight>,
+    rolling: Option<usize>,
+    smoothing: Option<Smoothing>
+) -> Result<LazyFrame, CoreError> {
+    let UnitWeight::Constant(gamma) = unit_weight.unwrap_or(*UNIT_WEIGHT) else {
+        return Err(CoreError::InvalidData(
+            "Unit weight profile cannot be integrated lazily. Collect the frame \
+             before calling add_stress_cols".to_string()
+        ));
+    };
+
+    let rolling = rolling.unwrap_or(*ROLLING);
+    validate_rolling(rolling)?;
+
+    stress_cols_lazy(
+        data,
+        a_ratio.unwrap_or(*A_RATIO),
+        lit(gamma) * col(*COL_DEPTH),
+        rolling,
+        smoothing.unwrap_or(*SMOOTHING)
+    )
+}
+
+// checks the rolling window against the windows accepted in the
+// configuration, so every kernel centers the same number of samples
+fn validate_rolling(rolling: usize) -> Result<(), CoreError> {
+    if !ROLLING_WINDOWS.contains(&rolling) {
+        return Err(CoreError::InvalidData(format!(
+            "Invalid rolling parameter: {}. Must be 1, 3, or 5",
+            rolling
+        )));
+    }
+
+    Ok(())
+}
+
+// adds the stress columns with σv_tot given by `sigv_tot`, then the smoothed
+// qt and fs and the ratios computed from them
+fn stress_cols_lazy(
+    data: LazyFrame,
+    a_ratio: f64,
+    sigv_tot: Expr,
+    rolling: usize,
+    smoothing: Smoothing
+) -> Result<LazyFrame, CoreError> {
+    let out_data = with_stress_cols(data, a_ratio, sigv_tot);
 
     let out_data = if rolling == 1 {
         out_data
-            .lazy()
             .with_column(col(*COL_QT).alias(COL_QT_ROL))
             .with_column(col(*COL_FS).alias(COL_FS_ROL))
-            .collect()?
-    } else {
+    } else if smoothing == Smoothing::Mean {
         let rolling_opts = RollingOptionsFixedWindow {
             window_size: rolling,
             min_periods: rolling,
@@ -59,7 +189,6 @@ pub(crate) fn add_stress_cols(
         };
 
         out_data
-            .lazy()
             .with_column(
                 col(*COL_FS)
                     .rolling_mean(rolling_opts.clone())
@@ -72,11 +201,96 @@ pub(crate) fn add_stress_cols(
                     .fill_null(lit(f64::NAN))
                     .alias(COL_QT_ROL)
             )
-            .collect()?
+    } else {
+        return Err(CoreError::InvalidData(format!(
+            "Smoothing {:?} cannot be computed lazily. Collect the frame \
+             before calling add_stress_cols",
+            smoothing
+        )));
     };
 
-    let out_data = out_data
-        .lazy()
+    Ok(with_ratio_cols(out_data))
+}
+
+// integrates σv_tot = Σ γ Δz from the ground surface over the unit weight
+// column, falling back to gamma_s where it is NaN
+fn integrate_sigv_tot(data: &DataFrame) -> Result<Series, CoreError> {
+    let Ok(gamma) = data.column(*COL_GAMMA) else {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot integrate σv_tot: column '{}' not found. Apply add_gamma_col \
+             first",
+            *COL_GAMMA
+        )));
+    };
+    let gamma = gamma.f64()?;
+    let depth = data.column(*COL_DEPTH)?.f64()?;
+
+    let mut sigv_tot = Vec::with_capacity(depth.len());
+    let (mut last_depth, mut last_sigv) = (0.0, 0.0);
+    for (depth, gamma) in depth.into_iter().zip(gamma) {
+        let Some(depth) = depth.filter(|depth| depth.is_finite()) else {
+            sigv_tot.push(f64::NAN);
+            continue;
+        };
+        let gamma = gamma
+            .filter(|gamma| gamma.is_finite() && *gamma > 0.0)
+            .unwrap_or(*GAMMA_S);
+
+        last_sigv += gamma * (depth - last_depth);
+        last_depth = depth;
+        sigv_tot.push(last_sigv);
+    }
+
+    Ok(Series::new((*COL_SIGV_TOT).into(), sigv_tot))
+}
+
+// adds total and effective vertical stresses, the excess pore pressure and
+// its normalized ratio, and corrected cone resistance
+fn with_stress_cols(data: LazyFrame, a_ratio: f64, sigv_tot: Expr) -> LazyFrame {
+    data
+        // total vertical stress = γ * z, or integrated over a γ profile
+        .with_column(sigv_tot.alias(*COL_SIGV_TOT))
+        // effective vertical stress = σv_tot - u0
+        .with_column((
+                col(*COL_SIGV_TOT) - col(*COL_U0)
+            ).alias(*COL_SIGV_EFF)
+        )
+        // excess pore pressure = u2 - u0
+        .with_column((col(*COL_U2) - col(*COL_U0)).alias(*COL_DU))
+        // normalized excess pore pressure = (u2 - u0) / σv_eff
+        .with_column(
+            when(col(*COL_SIGV_EFF).gt(lit(0.0)))
+                .then(col(*COL_DU) / col(*COL_SIGV_EFF))
+                .otherwise(lit(f64::NAN))
+                .alias(*COL_DU_SIGV)
+        )
+        // corrected cone resistance = qc + (1 - a) * u2
+        .with_column((
+                col(*COL_QC) + col(*COL_U2) * lit(1.0 - a_ratio)
+                / lit(1000)
+            ).alias(*COL_QT)
+        )
+}
+
+// adds net and effective cone resistances, the friction ratio and
+// normalized ratios computed from the smoothed qt and fs
+fn with_ratio_cols(data: LazyFrame) -> LazyFrame {
+    data
+        // net cone resistance = qt_rolling - σv_tot, in MPa
+        .with_column((
+                col(COL_QT_ROL) - col(*COL_SIGV_TOT) / lit(1000)
+            ).alias(*COL_QN)
+        )
+        // effective cone resistance = qt_rolling - u2, in MPa
+        .with_column((
+                col(COL_QT_ROL) - col(*COL_U2) / lit(1000)
+            ).alias(*COL_QE)
+        )
+        // friction ratio = fs_rolling / qt_rolling * 100
+        .with_column((
+                col(COL_FS_ROL) / (col(COL_QT_ROL) * lit(1000)) * lit(100.0)
+            ).alias(*COL_RF)
+        )
         // normalized friction ratio = fs_rolling / (qt_rolling - σv_tot) * 100
         .with_column((
                 col(COL_FS_ROL)
@@ -90,22 +304,59 @@ pub(crate) fn add_stress_cols(
                 / (col(COL_QT_ROL) * lit(1000) - col(*COL_SIGV_TOT))
             ).alias(*COL_BQ)
         )
+}
+
+/// Computes the penetration pore pressure ratio u2/u0.
+///
+/// u2/u0 well above 1 points to contractive, normally to lightly
+/// overconsolidated fine-grained soils, while values near or below 1 point
+/// to dilative or drained response, as does Δu/σ'v of `add_stress_cols`
+/// near or below 0. The ratio is NaN where u0 is not positive. Requires the
+/// columns added by `add_stress_cols`.
+pub(crate) fn add_pore_pressure_cols(data: DataFrame) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_pore_pressure_cols");
+
+    let out_data = data
+        .lazy()
+        // penetration pore pressure ratio = u2 / u0
+        .with_column(
+            when(col(*COL_U0).gt(lit(0.0)))
+                .then(col(*COL_U2) / col(*COL_U0))
+                .otherwise(lit(f64::NAN))
+                .alias(*COL_U2_U0)
+        )
         .collect()?;
 
     Ok(out_data)
 }
 
-
 /// Computes the stress exponent `n`, normalized tip resistance `Qtn`,
 /// and soil behavior type index `Ic` for each CPTu record.
+///
+/// Only records with depth within `top` and `bottom` are evaluated; the rest
+/// get NaN behavior values and are flagged in the excluded column.
 pub(crate) fn add_behavior_cols(
     data: DataFrame,
     max_iter: Option<usize>,
-    tolerance: Option<f64>
+    tolerance: Option<f64>,
+    top: Option<f64>,
+    bottom: Option<f64>
 ) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_behavior_cols");
+
     let max_iter = max_iter.unwrap_or(*MAX_ITER);
     let tolerance = tolerance.unwrap_or(*TOLERANCE);
+    let top = top.unwrap_or(*BEHAVIOR_TOP);
+    let bottom = bottom.or(*BEHAVIOR_BOTTOM).unwrap_or(f64::INFINITY);
+
+    if top.is_nan() || bottom.is_nan() || top > bottom {
+        return Err(CoreError::InvalidData(format!(
+            "Invalid behavior depth window: top {} must not exceed bottom {}",
+            top, bottom
+        )));
+    }
 
+    let depth = data.column(*COL_DEPTH)?.f64()?;
     let sigv_tot = data.column(*COL_SIGV_TOT)?.f64()?;
     let sigv_eff = data.column(*COL_SIGV_EFF)?.f64()?;
     let qt = data.column(COL_QT_ROL)?.f64()?;
@@ -115,8 +366,22 @@ pub(crate) fn add_behavior_cols(
     let mut qtn_vec   = Vec::with_capacity(data.height());
     let mut ic_vec    = Vec::with_capacity(data.height());
     let mut convg_vec = Vec::with_capacity(data.height());
+    let mut excluded_vec = Vec::with_capacity(data.height());
 
+    profile_scope!("behavior_solver");
     for i in 0..data.height() {
+        let depth_i = depth.get(i).unwrap_or(f64::NAN);
+        let excluded = !(top..=bottom).contains(&depth_i);
+        excluded_vec.push(excluded);
+
+        if excluded {
+            n_vec.push(f64::NAN);
+            ic_vec.push(f64::NAN);
+            qtn_vec.push(f64::NAN);
+            convg_vec.push(None);
+            continue;
+        }
+
         let sigv_tot_i = sigv_tot.get(i).unwrap_or(f64::NAN);
         let sigv_eff_i = sigv_eff.get(i).unwrap_or(f64::NAN);
         let qt_i = qt.get(i).unwrap_or(f64::NAN) * 1000.0;  // from MPa to kPa
@@ -165,6 +430,7 @@ pub(crate) fn add_behavior_cols(
             lit(Series::new((*COL_QTN).into(), qtn_vec)),
             lit(Series::new((*COL_IC).into(), ic_vec)),
             lit(Series::new((*COL_CONVG).into(), convg_vec)),
+            lit(Series::new((*COL_EXCLUDED).into(), excluded_vec)),
         ])
         // contractive-dilative boundary parameter
         .with_column((
@@ -207,3 +473,18 @@ pub(crate) fn calc_ic(qtn: f64, fr: f64) -> f64 {
 
     (fr_term.powi(2) + qtn_term.powi(2)).sqrt()
 }
+
+/// Returns the thickness (m) each record represents, between the midpoints
+/// to its neighbors; NaN where it cannot be computed or is not positive.
+pub(crate) fn record_thicknesses(depth: &[f64]) -> Vec<f64> {
+    let n_rows = depth.len();
+    (0..n_rows)
+        .map(|i| {
+            let z = depth[i];
+            let top = if i > 0 { (depth[i - 1] + z) / 2.0 } else { z };
+            let bottom = if i + 1 < n_rows { (z + depth[i + 1]) / 2.0 } else { z };
+            let thickness = bottom - top;
+            if thickness.is_finite() && thickness > 0.0 { thickness } else { f64::NAN }
+        })
+        .collect()
+}
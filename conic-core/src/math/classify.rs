@@ -0,0 +1,561 @@
+use polars::prelude::*;
+use crate::kernel::CoreError;
+use crate::kernel::profile::profile_scope;
+use crate::kernel::config::{
+    COL_QC, COL_FS, COL_QTN, COL_FR, COL_IC, COL_CD, COL_IB, COL_SBT, COL_SBT_DESC,
+    COL_GROUP, COL_SOIL, COL_ISBT, COL_ISBT_ZONE, COL_KC, COL_QTN_CS, CD_BOUNDARY,
+    COL_FC, COL_SCHNEIDER, COL_SIGV_TOT, COL_SIGV_EFF, COL_DU_SIGV, IB_CLAY, IB_SAND,
+    CCS_QTN, CCS_FR, FC_CFC, P_REF,
+};
+use super::basic::{COL_QT_ROL, calc_ic};
+use super::sbt::SbtZone;
+
+/// Returns the normalized SBT zone of a record on the Qtn-Fr chart of
+/// Robertson (1990), or None if an input is not finite.
+///
+/// Zones 1, 8 and 9 are bounded on the chart by the approximations of
+/// Robertson (2010):
+/// - zone 1 where `Qtn < 12 exp(-1.4 Fr)`;
+/// - zones 8 (Fr up to 4.5 %) and 9 (Fr above 4.5 %) where Fr exceeds
+///   1.5 % and `Qtn > 1 / (0.005 (Fr - 1) - 0.0003 (Fr - 1)² - 0.002)`.
+///
+/// Zones 2 to 7 follow from Ic (see `SbtZone::from_ic`).
+pub fn chart_zone(qtn: f64, fr: f64, ic: f64) -> Option<SbtZone> {
+    if !qtn.is_finite() || !fr.is_finite() {
+        return None;
+    }
+
+    if qtn < 12.0 * (-1.4 * fr).exp() {
+        return Some(SbtZone::SensitiveFines);
+    }
+    if fr > 1.5 {
+        let ratio = fr - 1.0;
+        let limit = 0.005 * ratio - 0.0003 * ratio.powi(2) - 0.002;
+        if limit > 0.0 && qtn > 1.0 / limit {
+            let zone = if fr <= 4.5 {
+                SbtZone::VeryStiffSands
+            } else {
+                SbtZone::VeryStiffFines
+            };
+            return Some(zone);
+        }
+    }
+
+    SbtZone::from_ic(ic)
+}
+
+/// Assigns the 9-zone normalized soil behavior type of each record.
+///
+/// Adds the zone number (null where Qtn, Fr or Ic is NaN, e.g. outside the
+/// behavior depth window) and its description (see `chart_zone`). Requires
+/// the columns added by `add_behavior_cols`.
+pub(crate) fn add_sbt_cols(data: DataFrame) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_sbt_cols");
+
+    let qtn = data.column(*COL_QTN)?.f64()?;
+    let fr = data.column(*COL_FR)?.f64()?;
+    let ic = data.column(*COL_IC)?.f64()?;
+
+    let zones: Vec<Option<SbtZone>> = qtn
+        .into_iter()
+        .zip(fr)
+        .zip(ic)
+        .map(|((qtn, fr), ic)| {
+            chart_zone(
+                qtn.unwrap_or(f64::NAN),
+                fr.unwrap_or(f64::NAN),
+                ic.unwrap_or(f64::NAN)
+            )
+        })
+        .collect();
+
+    let numbers: Vec<Option<u32>> = zones
+        .iter()
+        .map(|zone| zone.map(|zone| u32::from(zone.number())))
+        .collect();
+    let descriptions: Vec<Option<&str>> = zones
+        .iter()
+        .map(|zone| zone.map(SbtZone::description))
+        .collect();
+
+    let out_data = data
+        .lazy()
+        .with_column(lit(Series::new((*COL_SBT).into(), numbers)))
+        .with_column(lit(Series::new((*COL_SBT_DESC).into(), descriptions)))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Describes the soil of each record in plain words from its Ic zone.
+///
+/// Adds the description of `SbtZone::soil_name` (null where Ic is NaN).
+/// Requires the columns added by `add_behavior_cols`.
+pub(crate) fn add_description_col(data: DataFrame) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_description_col");
+
+    let names: Vec<Option<&str>> = data
+        .column(*COL_IC)?
+        .f64()?
+        .into_iter()
+        .map(|ic| SbtZone::from_ic(ic.unwrap_or(f64::NAN)).map(SbtZone::soil_name))
+        .collect();
+
+    let out_data = data
+        .lazy()
+        .with_column(lit(Series::new((*COL_SOIL).into(), names)))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Behavior groups of the classification of Robertson (2016).
+///
+/// IB splits clay-like, transitional and sand-like soils, and CD splits
+/// contractive from dilative soils; sensitive clay-like contractive soils
+/// are set apart below a Qtn-Fr boundary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BehaviorGroup {
+    /// CCS: clay-like, contractive, sensitive.
+    ClayContractiveSensitive,
+    /// CC: clay-like, contractive.
+    ClayContractive,
+    /// CD: clay-like, dilative.
+    ClayDilative,
+    /// TC: transitional, contractive.
+    TransitionalContractive,
+    /// TD: transitional, dilative.
+    TransitionalDilative,
+    /// SC: sand-like, contractive.
+    SandContractive,
+    /// SD: sand-like, dilative.
+    SandDilative,
+}
+
+impl BehaviorGroup {
+    /// Returns the group abbreviation used by Robertson (2016).
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::ClayContractiveSensitive => "CCS",
+            Self::ClayContractive => "CC",
+            Self::ClayDilative => "CD",
+            Self::TransitionalContractive => "TC",
+            Self::TransitionalDilative => "TD",
+            Self::SandContractive => "SC",
+            Self::SandDilative => "SD",
+        }
+    }
+
+    /// Returns a short description of the group.
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::ClayContractiveSensitive => "Clay-like, contractive, sensitive",
+            Self::ClayContractive => "Clay-like, contractive",
+            Self::ClayDilative => "Clay-like, dilative",
+            Self::TransitionalContractive => "Transitional, contractive",
+            Self::TransitionalDilative => "Transitional, dilative",
+            Self::SandContractive => "Sand-like, contractive",
+            Self::SandDilative => "Sand-like, dilative",
+        }
+    }
+}
+
+impl std::fmt::Display for BehaviorGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.description(), self.code())
+    }
+}
+
+/// Returns the behavior group of a record, or None if an input is not finite.
+///
+/// Uses the chart boundaries of `[output.parameters]`: IB below `ib_clay` is
+/// clay-like and above `ib_sand` sand-like, CD of at least `cd_boundary` is
+/// dilative, and clay-like contractive records with
+/// `Qtn < ccs_qtn exp(-ccs_fr Fr)` are sensitive.
+pub fn behavior_group(qtn: f64, fr: f64, cd: f64, ib: f64) -> Option<BehaviorGroup> {
+    if ![qtn, fr, cd, ib].iter().all(|value| value.is_finite()) {
+        return None;
+    }
+
+    let dilative = cd >= *CD_BOUNDARY;
+    let group = if ib > *IB_SAND {
+        if dilative {
+            BehaviorGroup::SandDilative
+        } else {
+            BehaviorGroup::SandContractive
+        }
+    } else if ib >= *IB_CLAY {
+        if dilative {
+            BehaviorGroup::TransitionalDilative
+        } else {
+            BehaviorGroup::TransitionalContractive
+        }
+    } else if dilative {
+        BehaviorGroup::ClayDilative
+    } else if qtn < *CCS_QTN * (-*CCS_FR * fr).exp() {
+        BehaviorGroup::ClayContractiveSensitive
+    } else {
+        BehaviorGroup::ClayContractive
+    };
+
+    Some(group)
+}
+
+/// Assigns the behavior group of Robertson (2016) to each record.
+///
+/// Adds the group code (null where Qtn, Fr, CD or IB is NaN). Requires the
+/// columns added by `add_behavior_cols`.
+pub(crate) fn add_group_col(data: DataFrame) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_group_col");
+
+    let qtn = data.column(*COL_QTN)?.f64()?;
+    let fr = data.column(*COL_FR)?.f64()?;
+    let cd = data.column(*COL_CD)?.f64()?;
+    let ib = data.column(*COL_IB)?.f64()?;
+
+    let codes: Vec<Option<&str>> = qtn
+        .into_iter()
+        .zip(fr)
+        .zip(cd)
+        .zip(ib)
+        .map(|(((qtn, fr), cd), ib)| {
+            behavior_group(
+                qtn.unwrap_or(f64::NAN),
+                fr.unwrap_or(f64::NAN),
+                cd.unwrap_or(f64::NAN),
+                ib.unwrap_or(f64::NAN)
+            )
+            .map(BehaviorGroup::code)
+        })
+        .collect();
+
+    let out_data = data
+        .lazy()
+        .with_column(lit(Series::new((*COL_GROUP).into(), codes)))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Classification chart of the soil behavior.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClassificationMethod {
+    /// Qtn-Fr chart of Robertson (1990); adds the SBTn zone and its
+    /// description.
+    Robertson,
+    /// Q-U2 chart of Schneider et al. (2008), which does not use fs and
+    /// suits soft clays and silts with an unreliable sleeve friction; adds
+    /// the Schneider zone.
+    Schneider,
+}
+
+impl ClassificationMethod {
+    /// Builds a classification chart from its name.
+    ///
+    /// Accepted names: `robertson`, `schneider`.
+    pub fn from_config(name: &str) -> Result<Self, CoreError> {
+        match name {
+            "robertson" => Ok(Self::Robertson),
+            "schneider" => Ok(Self::Schneider),
+            _ => Err(CoreError::InvalidConfig(format!(
+                "Invalid classification method: '{}'. Must be robertson or \
+                 schneider",
+                name
+            ))),
+        }
+    }
+}
+
+/// Soil behavior zones of the Q-U2 chart of Schneider et al. (2008).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SchneiderZone {
+    /// 1a: silts and low rigidity index clays.
+    SiltsLowIrClays,
+    /// 1b: clays.
+    Clays,
+    /// 1c: sensitive clays.
+    SensitiveClays,
+    /// 2: essentially drained sands.
+    DrainedSands,
+    /// 3: transitional soils, partially drained.
+    Transitional,
+}
+
+impl SchneiderZone {
+    /// Returns the zone code used by Schneider et al. (2008).
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::SiltsLowIrClays => "1a",
+            Self::Clays => "1b",
+            Self::SensitiveClays => "1c",
+            Self::DrainedSands => "2",
+            Self::Transitional => "3",
+        }
+    }
+
+    /// Returns a short description of the zone.
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::SiltsLowIrClays => "Silts and low Ir clays",
+            Self::Clays => "Clays",
+            Self::SensitiveClays => "Sensitive clays",
+            Self::DrainedSands => "Essentially drained sands",
+            Self::Transitional => "Transitional",
+        }
+    }
+}
+
+impl std::fmt::Display for SchneiderZone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.description(), self.code())
+    }
+}
+
+/// Bq of the zone boundaries approximating the chart of Schneider et al.
+/// (2008): 1c above the first, 1b above the second, 1a above the third and
+/// 2 within the last of 0.
+const SCHNEIDER_BQ: [f64; 4] = [1.0, 0.4, 0.1, 0.02];
+
+/// Returns the zone of a record on the chart of Schneider et al. (2008)
+/// from `Q = (qt - σv_tot) / σv_eff` and `U2 = (u2 - u0) / σv_eff`, or None
+/// if an input is not finite or Q is not positive.
+///
+/// The boundaries are approximated by the lines `U2 = Bq Q` of constant
+/// `Bq = U2 / Q`: 1c (sensitive clays) from Bq = 1, 1b (clays) from 0.4, 1a
+/// (silts and low Ir clays) from 0.1, 2 (essentially drained sands) for |Bq|
+/// up to 0.02 and 3 (transitional) otherwise.
+pub fn schneider_zone(q: f64, u2: f64) -> Option<SchneiderZone> {
+    if !q.is_finite() || !u2.is_finite() || q <= 0.0 {
+        return None;
+    }
+
+    let bq = u2 / q;
+    let [sensitive, clay, silt, drained] = SCHNEIDER_BQ;
+    let zone = if bq >= sensitive {
+        SchneiderZone::SensitiveClays
+    } else if bq >= clay {
+        SchneiderZone::Clays
+    } else if bq >= silt {
+        SchneiderZone::SiltsLowIrClays
+    } else if bq.abs() <= drained {
+        SchneiderZone::DrainedSands
+    } else {
+        SchneiderZone::Transitional
+    };
+
+    Some(zone)
+}
+
+/// Assigns the zone of the chart of Schneider et al. (2008) to each record.
+///
+/// Adds the zone code (null where Q or U2 cannot be computed, see
+/// `schneider_zone`). U2 is the Δu/σ'v column, and only qt and the pore
+/// pressures are read, so fs is not needed. Requires the columns added by
+/// `add_stress_cols`.
+pub(crate) fn add_schneider_col(data: DataFrame) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_schneider_col");
+
+    let qt = data.column(COL_QT_ROL)?.f64()?;
+    let sigv_tot = data.column(*COL_SIGV_TOT)?.f64()?;
+    let sigv_eff = data.column(*COL_SIGV_EFF)?.f64()?;
+    let du_sigv = data.column(*COL_DU_SIGV)?.f64()?;
+
+    let codes: Vec<Option<&str>> = qt
+        .into_iter()
+        .zip(sigv_tot)
+        .zip(sigv_eff)
+        .zip(du_sigv)
+        .map(|(((qt, sigv_tot), sigv_eff), du_sigv)| {
+            let (Some(qt), Some(sigv_tot), Some(sigv_eff), Some(du_sigv)) =
+                (qt, sigv_tot, sigv_eff, du_sigv)
+            else {
+                return None;
+            };
+            // qt from MPa to kPa
+            let q = (1000.0 * qt - sigv_tot) / sigv_eff;
+            schneider_zone(q, du_sigv).map(SchneiderZone::code)
+        })
+        .collect();
+
+    let out_data = data
+        .lazy()
+        .with_column(lit(Series::new((*COL_SCHNEIDER).into(), codes)))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Returns the non-normalized soil behavior type index ISBT of Robertson
+/// (2010) from qc (MPa) and fs (kPa), or NaN where qc is not positive.
+///
+/// ISBT follows the Ic formula with qc/pa in place of Qtn and the friction
+/// ratio `Rf = fs / qc` in place of Fr.
+pub fn calc_isbt(qc: f64, fs: f64) -> f64 {
+    if qc.is_nan() || qc <= 0.0 {
+        return f64::NAN;
+    }
+
+    let qc_kpa = 1000.0 * qc;
+    calc_ic(qc_kpa / *P_REF, 100.0 * fs / qc_kpa)
+}
+
+/// Assigns the non-normalized soil behavior type of each record.
+///
+/// Adds ISBT and its zone (see `SbtZone::from_ic`), null where ISBT is NaN.
+/// Only qc and fs are read, so no stresses or unit weights are needed.
+pub(crate) fn add_isbt_cols(data: DataFrame) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_isbt_cols");
+
+    let qc = data.column(*COL_QC)?.f64()?;
+    let fs = data.column(*COL_FS)?.f64()?;
+
+    let isbt: Vec<f64> = qc
+        .into_iter()
+        .zip(fs)
+        .map(|(qc, fs)| calc_isbt(qc.unwrap_or(f64::NAN), fs.unwrap_or(f64::NAN)))
+        .collect();
+    let zones: Vec<Option<u32>> = isbt
+        .iter()
+        .map(|isbt| SbtZone::from_ic(*isbt).map(|zone| u32::from(zone.number())))
+        .collect();
+
+    let out_data = data
+        .lazy()
+        .with_columns([
+            lit(Series::new((*COL_ISBT).into(), isbt)),
+            lit(Series::new((*COL_ISBT_ZONE).into(), zones)),
+        ])
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Returns the fines correction factor Kc of Robertson and Wride (1998).
+///
+/// Kc is 1 for Ic up to 1.64 and
+/// `-0.403 Ic⁴ + 5.581 Ic³ - 21.63 Ic² + 33.75 Ic - 17.88` above; NaN if Ic
+/// is NaN.
+pub fn fines_correction(ic: f64) -> f64 {
+    if ic.is_nan() {
+        return f64::NAN;
+    }
+    if ic <= 1.64 {
+        return 1.0;
+    }
+    -0.403 * ic.powi(4) + 5.581 * ic.powi(3) - 21.63 * ic.powi(2) + 33.75 * ic
+        - 17.88
+}
+
+/// Adds the fines correction factor Kc and the clean sand equivalent
+/// normalized cone resistance `Qtn,cs = Kc Qtn` (Robertson and Wride, 1998).
+///
+/// Kc grows quickly with Ic, so Qtn,cs of records with Ic above about 2.6
+/// is outside the calibration and only indicative. Requires the columns
+/// added by `add_behavior_cols`.
+pub(crate) fn add_clean_sand_cols(data: DataFrame) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_clean_sand_cols");
+
+    let qtn = data.column(*COL_QTN)?.f64()?;
+    let ic = data.column(*COL_IC)?.f64()?;
+
+    let kc: Vec<f64> = ic
+        .into_iter()
+        .map(|ic| fines_correction(ic.unwrap_or(f64::NAN)))
+        .collect();
+    let qtn_cs: Vec<f64> = qtn
+        .into_iter()
+        .zip(&kc)
+        .map(|(qtn, kc)| qtn.unwrap_or(f64::NAN) * kc)
+        .collect();
+
+    let out_data = data
+        .lazy()
+        .with_columns([
+            lit(Series::new((*COL_KC).into(), kc)),
+            lit(Series::new((*COL_QTN_CS).into(), qtn_cs)),
+        ])
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Returns the apparent fines content FC (%) of a record from Ic.
+///
+/// Uses `FC = 80 (Ic + C_FC) - 137` of Boulanger and Idriss (2014), clamped
+/// to 0-100 %, where C_FC is a site-specific fitting parameter (0 for the
+/// generic relationship); NaN if Ic is NaN.
+pub fn calc_fines_content(ic: f64, c_fc: f64) -> f64 {
+    if ic.is_nan() {
+        return f64::NAN;
+    }
+    (80.0 * (ic + c_fc) - 137.0).clamp(0.0, 100.0)
+}
+
+/// Returns the C_FC fitting the Ic-FC relationship of Boulanger and Idriss
+/// (2014) to a site-specific calibration pair: the Ic of a record and the
+/// fines content (%) measured on a sample taken at its depth.
+pub fn fines_calibration(ic: f64, fc: f64) -> f64 {
+    (fc + 137.0) / 80.0 - ic
+}
+
+/// Adds the apparent fines content FC (%) estimated from Ic.
+///
+/// `c_fc` defaults to the configured value; see `calc_fines_content` and
+/// `fines_calibration`. Requires the columns added by `add_behavior_cols`.
+pub(crate) fn add_fines_col(
+    data: DataFrame,
+    c_fc: Option<f64>,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_fines_col");
+
+    let c_fc = c_fc.unwrap_or(*FC_CFC);
+    if c_fc.is_nan() || c_fc.is_infinite() {
+        return Err(CoreError::InvalidData(format!(
+            "Invalid fines content calibration: {}. Must be finite",
+            c_fc
+        )));
+    }
+
+    let ic = data.column(*COL_IC)?.f64()?;
+    let fc: Vec<f64> = ic
+        .into_iter()
+        .map(|ic| calc_fines_content(ic.unwrap_or(f64::NAN), c_fc))
+        .collect();
+
+    let out_data = data
+        .lazy()
+        .with_column(lit(Series::new((*COL_FC).into(), fc)))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // zones on either side of the boundary line U2 = Bq Q, farther from and
+    // closer to U2 = 0
+    fn assert_boundary(q: f64, bq: f64, outer: SchneiderZone, inner: SchneiderZone) {
+        assert_eq!(schneider_zone(q, 1.05 * bq * q), Some(outer), "outside Bq {}", bq);
+        assert_eq!(schneider_zone(q, 0.95 * bq * q), Some(inner), "inside Bq {}", bq);
+    }
+
+    #[test]
+    fn schneider_zone_boundaries() {
+        use SchneiderZone::*;
+
+        assert_boundary(4.0, 1.0, SensitiveClays, Clays);
+        assert_boundary(6.0, 0.4, Clays, SiltsLowIrClays);
+        assert_boundary(20.0, 0.1, SiltsLowIrClays, Transitional);
+        assert_boundary(150.0, 0.02, Transitional, DrainedSands);
+        assert_boundary(150.0, -0.02, Transitional, DrainedSands);
+    }
+
+    #[test]
+    fn schneider_zone_needs_positive_q() {
+        assert_eq!(schneider_zone(0.0, 1.0), None);
+        assert_eq!(schneider_zone(f64::NAN, 1.0), None);
+        assert_eq!(schneider_zone(10.0, f64::INFINITY), None);
+    }
+}
@@ -0,0 +1,657 @@
+use std::collections::BTreeMap;
+use polars::prelude::*;
+use crate::kernel::CoreError;
+use crate::kernel::profile::profile_scope;
+use crate::kernel::config::{
+    COL_DEPTH, COL_FS, COL_SIGV_TOT, COL_SIGV_EFF, COL_IC, COL_QTN, COL_QTN_CS, COL_N60,
+    COL_E_MOD, COL_M_MOD, COL_MV, COL_CR, COL_VS, COL_G0, COL_SU, COL_ST, COL_PHI,
+    COL_DR, COL_PSI, COL_SIGP, COL_OCR, COL_K0, COL_K, COL_CV, COL_GAMMA, P_REF,
+    GAMMA_W, GAMMA_S, NKT, K_OCR, SU_IC, PHI_METHOD, DR_C0, DR_C1, DR_C2
+};
+use super::basic::{COL_FS_ROL, COL_QT_ROL};
+
+/// Standard gravity (m/s²), converting unit weights to densities.
+const GRAVITY: f64 = 9.81;
+
+/// Seconds in a year of 365.25 days.
+const SECONDS_PER_YEAR: f64 = 31_557_600.0;
+
+/// Estimates the soil unit weight γ (kN/m³) of each record.
+///
+/// Uses the Robertson and Cabal (2010) relationship
+/// `γ / γw = 0.27 log10(Rf) + 0.36 log10(qt / pa) + 1.236`, with
+/// `Rf = fs / qt` in percent and qt and fs taken from the smoothed series.
+/// γ is NaN where qt or fs is not positive. Requires the columns added by
+/// `add_stress_cols`.
+pub(crate) fn add_gamma_col(data: DataFrame) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_gamma_col");
+
+    let qt = data.column(COL_QT_ROL)?.f64()?;
+    let fs = data.column(COL_FS_ROL)?.f64()?;
+
+    let gamma: Vec<f64> = qt
+        .into_iter()
+        .zip(fs)
+        .map(|(qt, fs)| {
+            let (Some(qt), Some(fs)) = (qt, fs) else { return f64::NAN };
+            if qt.is_nan() || fs.is_nan() || qt <= 0.0 || fs <= 0.0 {
+                return f64::NAN;
+            }
+            // qt from MPa to kPa
+            let qt = 1000.0 * qt;
+            let rf = 100.0 * fs / qt;
+            *GAMMA_W * (0.27 * rf.log10() + 0.36 * (qt / *P_REF).log10() + 1.236)
+        })
+        .collect();
+
+    let out_data = data
+        .lazy()
+        .with_column(lit(Series::new((*COL_GAMMA).into(), gamma)))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Estimates the equivalent SPT blow count N60 from qt and Ic.
+///
+/// Uses the Robertson (2012) relationship
+/// `(qt/pa) / N60 = 10^(1.1268 - 0.2817 Ic)`, with qt taken from the same
+/// smoothed series used for Ic. Requires the columns added by
+/// `add_stress_cols` and `add_behavior_cols`.
+pub(crate) fn add_spt_cols(data: DataFrame) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_spt_cols");
+
+    let out_data = data
+        .lazy()
+        // N60 = (qt / pa) / 10^(1.1268 - 0.2817 * Ic), qt from MPa to kPa
+        .with_column((
+                col(COL_QT_ROL) * lit(1000) / lit(*P_REF)
+                / lit(10.0).pow(lit(1.1268) - lit(0.2817) * col(*COL_IC))
+            ).alias(*COL_N60)
+        )
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Estimates the drained Young's modulus E' from qt and Ic.
+///
+/// Uses `E' = αE (qt - σv_tot)` with the soil behavior dependent factor
+/// `αE = 0.015 * 10^(0.55 Ic + 1.68)` (Robertson, 2009), so stiffness per
+/// unit of net resistance grows from clean sands to clayey soils. The
+/// relationship is calibrated on sand-like soils; for fine grained soils the
+/// constrained modulus M is usually preferred. Requires the columns added by
+/// `add_stress_cols` and `add_behavior_cols`.
+pub(crate) fn add_modulus_cols(data: DataFrame) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_modulus_cols");
+
+    let out_data = data
+        .lazy()
+        // E' = αE * (qt - σv_tot), qt in MPa and σv_tot from kPa to MPa
+        .with_column((
+                lit(0.015)
+                * lit(10.0).pow(lit(0.55) * col(*COL_IC) + lit(1.68))
+                * (col(COL_QT_ROL) - col(*COL_SIGV_TOT) / lit(1000))
+            ).alias(*COL_E_MOD)
+        )
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Estimates the constrained modulus M (MPa) for 1D settlement analyses.
+///
+/// Uses `M = αM (qt - σv_tot)` (Robertson, 2009), with
+/// `αM = 0.0188 * 10^(0.55 Ic + 1.68)` where Ic <= 2.2 and `αM = Qtn`
+/// capped at 14 where Ic > 2.2. Requires the columns added by
+/// `add_stress_cols` and `add_behavior_cols`.
+pub(crate) fn add_constrained_modulus_col(
+    data: DataFrame
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_constrained_modulus_col");
+
+    let alpha_m = when(col(*COL_IC).gt(lit(2.2)))
+        .then(
+            when(col(*COL_QTN).lt(lit(14.0)))
+                .then(col(*COL_QTN))
+                .otherwise(lit(14.0))
+        )
+        .otherwise(lit(0.0188) * lit(10.0).pow(lit(0.55) * col(*COL_IC) + lit(1.68)));
+
+    let out_data = data
+        .lazy()
+        // M = αM * (qt - σv_tot), qt in MPa and σv_tot from kPa to MPa
+        .with_column((
+                alpha_m * (col(COL_QT_ROL) - col(*COL_SIGV_TOT) / lit(1000))
+            ).alias(*COL_M_MOD)
+        )
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Estimates the coefficient of volume compressibility mv (1/MPa) and the
+/// compression ratio `CR = Cc / (1 + e0)` for 1D consolidation settlement.
+///
+/// Uses `mv = 1 / M` on every record with a positive M, and
+/// `CR = ln(10) σv_eff / M`, the normally consolidated slope of the
+/// e-log σ' curve at the in situ stress, on records with Ic above the
+/// configured `su_ic` cutoff; CR is NaN elsewhere, where primary
+/// consolidation is not a concern. CR of overconsolidated clays is that of
+/// the recompression range. Requires the column added by
+/// `add_constrained_modulus_col`.
+pub(crate) fn add_compressibility_cols(
+    data: DataFrame
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_compressibility_cols");
+
+    let out_data = data
+        .lazy()
+        // mv = 1 / M
+        .with_column(
+            when(col(*COL_M_MOD).gt(lit(0.0)))
+                .then(lit(1.0) / col(*COL_M_MOD))
+                .otherwise(lit(f64::NAN))
+                .alias(*COL_MV)
+        )
+        // CR = ln(10) * σv_eff / M, σv_eff from kPa to MPa
+        .with_column(
+            when(col(*COL_IC).gt(lit(*SU_IC)).and(col(*COL_M_MOD).gt(lit(0.0))))
+                .then(
+                    lit(std::f64::consts::LN_10) * col(*COL_SIGV_EFF) / lit(1000)
+                    / col(*COL_M_MOD)
+                )
+                .otherwise(lit(f64::NAN))
+                .alias(*COL_CR)
+        )
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Estimates the shear wave velocity Vs (m/s) and the small-strain shear
+/// modulus G0 (MPa).
+///
+/// Uses `Vs = (αvs (qt - σv_tot) / pa)^0.5` with
+/// `αvs = 10^(0.55 Ic + 1.68)` (Robertson, 2009), and `G0 = ρ Vs²` with the
+/// density taken from the unit weight column of `add_gamma_col` when
+/// present, or from the configured `gamma_s` otherwise. Requires the columns
+/// added by `add_stress_cols` and `add_behavior_cols`.
+pub(crate) fn add_vs_cols(data: DataFrame) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_vs_cols");
+
+    let gamma = if data.column(*COL_GAMMA).is_ok() {
+        col(*COL_GAMMA)
+    } else {
+        lit(*GAMMA_S)
+    };
+
+    let out_data = data
+        .lazy()
+        // Vs = (αvs * (qt - σv_tot) / pa)^0.5, qt from MPa to kPa
+        .with_column((
+                lit(10.0).pow(lit(0.55) * col(*COL_IC) + lit(1.68))
+                * (col(COL_QT_ROL) * lit(1000) - col(*COL_SIGV_TOT))
+                / lit(*P_REF)
+            ).sqrt().alias(*COL_VS)
+        )
+        // G0 = ρ * Vs², ρ = γ / g in t/m³ gives kPa, converted to MPa
+        .with_column((
+                gamma / lit(GRAVITY) * col(*COL_VS).pow(lit(2)) / lit(1000)
+            ).alias(*COL_G0)
+        )
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Estimates the undrained shear strength su of fine grained soils.
+///
+/// Uses `su = (qt - σv_tot) / Nkt` (Lunne et al., 1997) on records with Ic
+/// above the configured `su_ic` cutoff; su is NaN elsewhere, where the soil
+/// does not behave undrained. Requires the columns added by
+/// `add_stress_cols` and `add_behavior_cols`.
+///
+/// # Errors
+///
+/// Returns `CoreError::InvalidData` if `nkt` is not positive.
+pub(crate) fn add_su_col(
+    data: DataFrame,
+    nkt: Option<f64>,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_su_col");
+
+    let nkt = nkt.unwrap_or(*NKT);
+    if nkt.is_nan() || nkt <= 0.0 {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot compute su: Nkt must be positive, got {}",
+            nkt
+        )));
+    }
+
+    let out_data = data
+        .lazy()
+        // su = (qt - σv_tot) / Nkt, qt from MPa to kPa
+        .with_column(
+            when(col(*COL_IC).gt(lit(*SU_IC)))
+                .then(
+                    (col(COL_QT_ROL) * lit(1000) - col(*COL_SIGV_TOT)) / lit(nkt)
+                )
+                .otherwise(lit(f64::NAN))
+                .alias(*COL_SU)
+        )
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Estimates the sensitivity St of fine grained soils.
+///
+/// Takes fs as the remolded strength su,rem (Robertson, 2009), so
+/// `St = su / fs`. St is NaN wherever su is, i.e. outside the fine grained
+/// records, and where fs is not positive. Requires the column added by
+/// `add_su_col`.
+pub(crate) fn add_sensitivity_col(data: DataFrame) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_sensitivity_col");
+
+    let out_data = data
+        .lazy()
+        .with_column(
+            when(col(*COL_FS).gt(lit(0)))
+                .then(col(*COL_SU) / col(*COL_FS))
+                .otherwise(lit(f64::NAN))
+                .alias(*COL_ST)
+        )
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Estimates the preconsolidation stress σ'p and the overconsolidation ratio
+/// OCR of fine grained soils.
+///
+/// Uses `σ'p = k (qt - σv_tot)` (Kulhawy and Mayne, 1990) and
+/// `OCR = σ'p / σv_eff` on records with Ic above the configured `su_ic`
+/// cutoff; both are NaN elsewhere. Requires the columns added by
+/// `add_stress_cols` and `add_behavior_cols`.
+///
+/// # Errors
+///
+/// Returns `CoreError::InvalidData` if `k_ocr` is not positive.
+pub(crate) fn add_ocr_cols(
+    data: DataFrame,
+    k_ocr: Option<f64>,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_ocr_cols");
+
+    let k_ocr = k_ocr.unwrap_or(*K_OCR);
+    if k_ocr.is_nan() || k_ocr <= 0.0 {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot compute OCR: k must be positive, got {}",
+            k_ocr
+        )));
+    }
+
+    let out_data = data
+        .lazy()
+        // σ'p = k * (qt - σv_tot), qt from MPa to kPa
+        .with_column(
+            when(col(*COL_IC).gt(lit(*SU_IC)))
+                .then(
+                    lit(k_ocr) * (col(COL_QT_ROL) * lit(1000) - col(*COL_SIGV_TOT))
+                )
+                .otherwise(lit(f64::NAN))
+                .alias(*COL_SIGP)
+        )
+        .with_column((col(*COL_SIGP) / col(*COL_SIGV_EFF)).alias(*COL_OCR))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Estimates the at-rest earth pressure coefficient K0 of fine grained soils.
+///
+/// Uses the direct correlation `K0 = 0.1 (qt - σv_tot) / σv_eff` of Kulhawy
+/// and Mayne (1990) on records with Ic above the configured `su_ic` cutoff;
+/// K0 is NaN elsewhere. Requires the columns added by `add_stress_cols` and
+/// `add_behavior_cols`.
+pub(crate) fn add_k0_col(data: DataFrame) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_k0_col");
+
+    let out_data = data
+        .lazy()
+        // K0 = 0.1 * (qt - σv_tot) / σv_eff, qt from MPa to kPa
+        .with_column(
+            when(col(*COL_IC).gt(lit(*SU_IC)))
+                .then(
+                    lit(0.1) * (col(COL_QT_ROL) * lit(1000) - col(*COL_SIGV_TOT))
+                    / col(*COL_SIGV_EFF)
+                )
+                .otherwise(lit(f64::NAN))
+                .alias(*COL_K0)
+        )
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Correlation between cone resistance and the effective friction angle φ'.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PhiMethod {
+    /// Robertson and Campanella (1983):
+    /// `tan φ' = (log10(qt / σv_eff) + 0.29) / 2.68`.
+    RobertsonCampanella,
+    /// Kulhawy and Mayne (1990): `φ' = 17.6 + 11 log10(Qtn)`.
+    KulhawyMayne,
+}
+
+impl PhiMethod {
+    /// Builds a friction angle correlation from its configuration name.
+    ///
+    /// Accepted names: `robertson_campanella`, `kulhawy_mayne`.
+    pub fn from_config(name: &str) -> Result<Self, CoreError> {
+        match name {
+            "robertson_campanella" => Ok(Self::RobertsonCampanella),
+            "kulhawy_mayne" => Ok(Self::KulhawyMayne),
+            _ => Err(CoreError::InvalidConfig(format!(
+                "Invalid phi_method parameter: '{}'. Must be robertson_campanella \
+                 or kulhawy_mayne",
+                name
+            ))),
+        }
+    }
+
+    // φ' in degrees from qt (kPa), σv_eff (kPa) and Qtn
+    fn phi(self, qt: f64, sigv_eff: f64, qtn: f64) -> f64 {
+        match self {
+            Self::RobertsonCampanella => {
+                ((qt / sigv_eff).log10() + 0.29).atan2(2.68).to_degrees()
+            }
+            Self::KulhawyMayne => 17.6 + 11.0 * qtn.log10(),
+        }
+    }
+}
+
+/// Estimates the effective friction angle φ' (degrees) of coarse grained
+/// soils.
+///
+/// Uses `method` (default `phi_method` from the configuration) on records
+/// with Ic up to the configured `su_ic` cutoff, where the cone penetrates
+/// under drained conditions; φ' is NaN elsewhere. Requires the columns added
+/// by `add_stress_cols` and `add_behavior_cols`.
+pub(crate) fn add_phi_col(
+    data: DataFrame,
+    method: Option<PhiMethod>,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_phi_col");
+
+    let method = method.unwrap_or(*PHI_METHOD);
+
+    let qt = data.column(COL_QT_ROL)?.f64()?;
+    let sigv_eff = data.column(*COL_SIGV_EFF)?.f64()?;
+    let qtn = data.column(*COL_QTN)?.f64()?;
+    let ic = data.column(*COL_IC)?.f64()?;
+
+    let phi: Vec<f64> = qt
+        .into_iter()
+        .zip(sigv_eff)
+        .zip(qtn)
+        .zip(ic)
+        .map(|(((qt, sigv_eff), qtn), ic)| {
+            let (Some(qt), Some(sigv_eff), Some(qtn), Some(ic)) =
+                (qt, sigv_eff, qtn, ic)
+            else {
+                return f64::NAN;
+            };
+            if ic.is_nan() || ic > *SU_IC {
+                return f64::NAN;
+            }
+            // qt from MPa to kPa
+            method.phi(1000.0 * qt, sigv_eff, qtn)
+        })
+        .collect();
+
+    let out_data = data
+        .lazy()
+        .with_column(lit(Series::new((*COL_PHI).into(), phi)))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Estimates the relative density Dr (%) of coarse grained soils.
+///
+/// Uses the calibration chamber correlation of Baldi et al. (1986),
+/// `qt = C0 σv_eff^C1 exp(C2 Dr)` with stresses in kPa, solved for Dr on
+/// records with Ic up to the configured `su_ic` cutoff; Dr is NaN elsewhere.
+/// C0, C1 and C2 are read from `[output.parameters]` and default to the
+/// values for normally consolidated sands. Dr is not clamped, so values
+/// outside 0-100 % flag soils beyond the calibration. Requires the columns
+/// added by `add_stress_cols` and `add_behavior_cols`.
+pub(crate) fn add_dr_col(data: DataFrame) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_dr_col");
+
+    let qt = data.column(COL_QT_ROL)?.f64()?;
+    let sigv_eff = data.column(*COL_SIGV_EFF)?.f64()?;
+    let ic = data.column(*COL_IC)?.f64()?;
+
+    let dr: Vec<f64> = qt
+        .into_iter()
+        .zip(sigv_eff)
+        .zip(ic)
+        .map(|((qt, sigv_eff), ic)| {
+            let (Some(qt), Some(sigv_eff), Some(ic)) = (qt, sigv_eff, ic) else {
+                return f64::NAN;
+            };
+            if ic.is_nan() || ic > *SU_IC {
+                return f64::NAN;
+            }
+            // qt from MPa to kPa, Dr from a fraction to percent
+            let ratio = 1000.0 * qt / (*DR_C0 * sigv_eff.powf(*DR_C1));
+            100.0 * ratio.ln() / *DR_C2
+        })
+        .collect();
+
+    let out_data = data
+        .lazy()
+        .with_column(lit(Series::new((*COL_DR).into(), dr)))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Estimates the state parameter ψ of coarse grained soils.
+///
+/// Uses the screening relationship of Robertson (2010),
+/// `ψ = 0.56 - 0.33 log10(Qtn,cs)`, on records with Ic up to the configured
+/// `su_ic` cutoff; ψ is NaN elsewhere. Negative values indicate dilative
+/// soils and values above about -0.05 contractive ones. Requires the columns
+/// added by `add_clean_sand_cols`.
+pub(crate) fn add_psi_col(data: DataFrame) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_psi_col");
+
+    let qtn_cs = data.column(*COL_QTN_CS)?.f64()?;
+    let ic = data.column(*COL_IC)?.f64()?;
+
+    let psi: Vec<f64> = qtn_cs
+        .into_iter()
+        .zip(ic)
+        .map(|(qtn_cs, ic)| {
+            let (Some(qtn_cs), Some(ic)) = (qtn_cs, ic) else {
+                return f64::NAN;
+            };
+            if ic.is_nan() || ic > *SU_IC {
+                return f64::NAN;
+            }
+            0.56 - 0.33 * qtn_cs.log10()
+        })
+        .collect();
+
+    let out_data = data
+        .lazy()
+        .with_column(lit(Series::new((*COL_PSI).into(), psi)))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Estimates the hydraulic conductivity k (m/s) from Ic.
+///
+/// Uses the relationship of Robertson (2010):
+/// `k = 10^(0.952 - 3.04 Ic)` for 1.0 < Ic <= 3.27 and
+/// `k = 10^(-4.52 - 1.37 Ic)` for 3.27 < Ic < 4.0; k is NaN outside these
+/// ranges. Requires the columns added by `add_behavior_cols`.
+pub(crate) fn add_permeability_col(data: DataFrame) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_permeability_col");
+
+    let ic = col(*COL_IC);
+
+    let out_data = data
+        .lazy()
+        .with_column(
+            when(ic.clone().gt(lit(1.0)).and(ic.clone().lt_eq(lit(3.27))))
+                .then(lit(10.0).pow(lit(0.952) - lit(3.04) * ic.clone()))
+                .when(ic.clone().gt(lit(3.27)).and(ic.clone().lt(lit(4.0))))
+                .then(lit(10.0).pow(lit(-4.52) - lit(1.37) * ic))
+                .otherwise(lit(f64::NAN))
+                .alias(*COL_K)
+        )
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Estimates the coefficient of consolidation cv (m²/yr) of fine grained
+/// soils.
+///
+/// Uses `cv = k M / γw` with k from `add_permeability_col` and M from
+/// `add_constrained_modulus_col`, on records with Ic above the configured
+/// `su_ic` cutoff; cv is NaN elsewhere, where consolidation is not a
+/// concern. The estimate applies to vertical and horizontal flow alike, as
+/// k is not distinguished by direction. Requires both columns.
+pub(crate) fn add_cv_col(data: DataFrame) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_cv_col");
+
+    let out_data = data
+        .lazy()
+        // cv = k * M / γw, M from MPa to kPa and seconds to years
+        .with_column(
+            when(col(*COL_IC).gt(lit(*SU_IC)))
+                .then(
+                    col(*COL_K) * col(*COL_M_MOD) * lit(1000) / lit(*GAMMA_W)
+                    * lit(SECONDS_PER_YEAR)
+                )
+                .otherwise(lit(f64::NAN))
+                .alias(*COL_CV)
+        )
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Discretizes the E' profile, and the M profile when present, into depth
+/// intervals of `interval` meters.
+///
+/// Intervals start at the nearest multiple of `interval` above the first
+/// record. Each row holds the interval limits and the mean of the finite
+/// values of each modulus within it; intervals without data are skipped.
+pub(crate) fn modulus_profile(
+    data: &DataFrame,
+    interval: f64,
+) -> Result<DataFrame, CoreError> {
+    if interval.is_nan() || interval <= 0.0 {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot discretize E' profile: interval must be positive, got {}",
+            interval
+        )));
+    }
+
+    let depth = data.column(*COL_DEPTH)?.f64()?;
+    let mut names = vec![*COL_E_MOD];
+    if data.column(*COL_M_MOD).is_ok() {
+        names.push(*COL_M_MOD);
+    }
+    let moduli = names
+        .iter()
+        .map(|name| data.column(name)?.f64())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // interval index -> (sum, count) of each modulus, ordered by depth
+    let mut bins: BTreeMap<i64, Vec<(f64, usize)>> = BTreeMap::new();
+    for (row, depth) in depth.into_iter().enumerate() {
+        let Some(depth) = depth.filter(|depth| depth.is_finite()) else { continue };
+
+        let index = (depth / interval).floor() as i64;
+        for (modulus_index, modulus) in moduli.iter().enumerate() {
+            let Some(value) = modulus.get(row).filter(|value| value.is_finite())
+            else {
+                continue;
+            };
+
+            let sums = bins
+                .entry(index)
+                .or_insert_with(|| vec![(0.0, 0); moduli.len()]);
+            let (sum, count) = &mut sums[modulus_index];
+            *sum += value;
+            *count += 1;
+        }
+    }
+
+    // round limits to 3 decimal places to avoid floating point noise
+    let round = |value: f64| (value * 1000.0).round() / 1000.0;
+    let top: Vec<f64> = bins
+        .keys()
+        .map(|index| round(*index as f64 * interval))
+        .collect();
+    let bottom: Vec<f64> = bins
+        .keys()
+        .map(|index| round((*index + 1) as f64 * interval))
+        .collect();
+
+    let mut columns = vec![
+        Column::new("Top (m)".into(), top),
+        Column::new("Bottom (m)".into(), bottom),
+    ];
+    for (modulus_index, name) in names.iter().enumerate() {
+        let mean: Vec<f64> = bins
+            .values()
+            .map(|sums| {
+                let (sum, count) = sums[modulus_index];
+                if count == 0 { f64::NAN } else { sum / count as f64 }
+            })
+            .collect();
+        columns.push(Column::new((*name).into(), mean));
+    }
+
+    let out_data = DataFrame::new(bins.len(), columns)?;
+
+    Ok(out_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modulus_profile_bins_records_in_depth_order() {
+        let data = DataFrame::new(5, vec![
+            Column::new((*COL_DEPTH).into(), [1.2, 0.1, 0.4, 1.3, f64::NAN]),
+            Column::new((*COL_E_MOD).into(), [30.0, 10.0, 20.0, f64::NAN, 99.0]),
+        ])
+        .expect("columns should have the same length");
+
+        let profile = modulus_profile(&data, 0.5).expect("profile should be built");
+
+        let values = |name: &str| -> Vec<f64> {
+            profile
+                .column(name)
+                .and_then(|column| column.f64().cloned())
+                .expect("column should be a float column")
+                .into_no_null_iter()
+                .collect()
+        };
+        assert_eq!(values("Top (m)"), [0.0, 1.0]);
+        assert_eq!(values("Bottom (m)"), [0.5, 1.5]);
+        assert_eq!(values(*COL_E_MOD), [15.0, 30.0]);
+    }
+}
@@ -0,0 +1,797 @@
+use polars::prelude::*;
+use crate::kernel::CoreError;
+use crate::kernel::profile::profile_scope;
+use crate::kernel::config::{
+    COL_DEPTH, COL_U0, COL_SIGV_TOT, COL_SIGV_EFF, COL_IC, COL_QTN_CS, COL_KH, COL_CRR,
+    COL_LIQUEFIABLE, COL_THIN_LAYER, COL_RD, COL_CSR, COL_FS_LIQ, COL_FC, COL_QC1NCS,
+    COL_CRR_BI, COL_K_SIGMA, COL_FS_LIQ_BI, COL_EV, COL_SETTLEMENT, COL_DR,
+    COL_GAMMA_MAX, LDI_DEPTH, LIQ_IC, PGA, MAGNITUDE, RD_METHOD, MSF_METHOD, P_REF
+};
+use super::basic::{COL_QT_ROL, record_thicknesses};
+
+/// Qtn,cs from which soils are too dense to liquefy at magnitude 7.5
+/// (Robertson and Wride, 1998).
+const DENSE_QTN_CS: f64 = 160.0;
+
+/// Returns the cyclic resistance ratio CRR7.5 of a clean sand equivalent
+/// normalized cone resistance (Robertson and Wride, 1998):
+/// - `CRR7.5 = 0.833 (Qtn,cs / 1000) + 0.05` for Qtn,cs below 50;
+/// - `CRR7.5 = 93 (Qtn,cs / 1000)³ + 0.08` for Qtn,cs from 50 to 160.
+///
+/// Returns NaN if Qtn,cs is NaN, not positive, or 160 or above, where the
+/// soil is too dense to liquefy.
+pub fn calc_crr(qtn_cs: f64) -> f64 {
+    if qtn_cs.is_nan() || qtn_cs <= 0.0 || qtn_cs >= DENSE_QTN_CS {
+        return f64::NAN;
+    }
+
+    let ratio = qtn_cs / 1000.0;
+    if qtn_cs < 50.0 {
+        0.833 * ratio + 0.05
+    } else {
+        93.0 * ratio.powi(3) + 0.08
+    }
+}
+
+/// Adds the cyclic resistance ratio CRR7.5 with the NCEER procedure of
+/// Robertson and Wride (1998), as summarized by Youd et al. (2001).
+///
+/// Records with Ic above `max_ic` (default `liq_ic` from the configuration)
+/// are clay-like and excluded, records above the water table (u0 of 0) are
+/// not saturated, and records with Qtn,cs of 160 or above are too dense to
+/// liquefy; all are flagged not liquefiable and have a NaN CRR7.5. If the KH
+/// column of `thin_layer_correction` is present, Qtn,cs is multiplied by KH
+/// and the corrected records are flagged as thin layers; otherwise the thin
+/// layer flag is null. Requires the columns added by
+/// `add_clean_sand_cols`.
+///
+/// # Errors
+///
+/// Returns `CoreError::InvalidData` if `max_ic` is not positive.
+pub(crate) fn add_crr_cols(
+    data: DataFrame,
+    max_ic: Option<f64>,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_crr_cols");
+
+    let max_ic = max_ic.unwrap_or(*LIQ_IC);
+    if max_ic.is_nan() || max_ic <= 0.0 {
+        return Err(CoreError::InvalidData(format!(
+            "Invalid liquefaction Ic cutoff: {}. Must be > 0",
+            max_ic
+        )));
+    }
+
+    let qtn_cs = values(&data, *COL_QTN_CS)?;
+    let ic = values(&data, *COL_IC)?;
+    let u0 = values(&data, *COL_U0)?;
+    let kh = match data.column(*COL_KH) {
+        Ok(_) => Some(values(&data, *COL_KH)?),
+        Err(_) => None,
+    };
+
+    let mut crr = Vec::with_capacity(data.height());
+    let mut liquefiable = Vec::with_capacity(data.height());
+    for (i, (&qtn_cs, &ic)) in qtn_cs.iter().zip(&ic).enumerate() {
+        let qtn_cs = qtn_cs * kh.as_ref().map_or(1.0, |kh| kh[i]);
+        if qtn_cs.is_nan() || ic.is_nan() || u0[i].is_nan() {
+            crr.push(f64::NAN);
+            liquefiable.push(None);
+        } else if ic > max_ic || u0[i] <= 0.0 || qtn_cs >= DENSE_QTN_CS {
+            crr.push(f64::NAN);
+            liquefiable.push(Some(false));
+        } else {
+            crr.push(calc_crr(qtn_cs));
+            liquefiable.push(Some(true));
+        }
+    }
+    let thin_layer: Vec<Option<bool>> = match &kh {
+        Some(kh) => kh.iter().map(|&kh| Some(kh > 1.0)).collect(),
+        None => vec![None; data.height()],
+    };
+
+    let out_data = data
+        .lazy()
+        .with_columns([
+            lit(Series::new((*COL_CRR).into(), crr)),
+            lit(Series::new((*COL_LIQUEFIABLE).into(), liquefiable)),
+            lit(Series::new((*COL_THIN_LAYER).into(), thin_layer)),
+        ])
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Depth reduction factor rd of the cyclic stress ratio.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RdMethod {
+    /// Liao and Whitman (1986), as given by Youd et al. (2001):
+    /// `rd = 1 - 0.00765 z` down to 9.15 m, `1.174 - 0.0267 z` down to
+    /// 23 m, `0.744 - 0.008 z` down to 30 m and 0.5 below.
+    LiaoWhitman,
+    /// Idriss (1999): `rd = exp(α(z) + β(z) Mw)` with
+    /// `α = -1.012 - 1.126 sin(z / 11.73 + 5.133)` and
+    /// `β = 0.106 + 0.118 sin(z / 11.28 + 5.142)` down to 34 m, and
+    /// `rd = 0.12 exp(0.22 Mw)` below.
+    Idriss,
+}
+
+impl RdMethod {
+    /// Builds a depth reduction factor from its configuration name.
+    ///
+    /// Accepted names: `liao_whitman`, `idriss`.
+    pub fn from_config(name: &str) -> Result<Self, CoreError> {
+        match name {
+            "liao_whitman" => Ok(Self::LiaoWhitman),
+            "idriss" => Ok(Self::Idriss),
+            _ => Err(CoreError::InvalidConfig(format!(
+                "Invalid rd_method parameter: '{}'. Must be liao_whitman or idriss",
+                name
+            ))),
+        }
+    }
+
+    /// Returns rd at `depth` (m) for an earthquake of moment magnitude `mw`.
+    pub fn rd(self, depth: f64, mw: f64) -> f64 {
+        match self {
+            Self::LiaoWhitman => match depth {
+                z if z <= 9.15 => 1.0 - 0.00765 * z,
+                z if z <= 23.0 => 1.174 - 0.0267 * z,
+                z if z <= 30.0 => 0.744 - 0.008 * z,
+                _ => 0.5,
+            },
+            Self::Idriss if depth <= 34.0 => {
+                let alpha = -1.012 - 1.126 * (depth / 11.73 + 5.133).sin();
+                let beta = 0.106 + 0.118 * (depth / 11.28 + 5.142).sin();
+                (alpha + beta * mw).exp()
+            }
+            Self::Idriss => 0.12 * (0.22 * mw).exp(),
+        }
+    }
+}
+
+/// Magnitude scaling factor MSF, converting CRR7.5 to the design magnitude.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MsfMethod {
+    /// `MSF = 10^2.24 / Mw^2.56`, the lower bound recommended by Youd et
+    /// al. (2001).
+    Youd,
+    /// Idriss (1999): `MSF = 6.9 exp(-Mw / 4) - 0.058`, at most 1.8.
+    Idriss,
+}
+
+impl MsfMethod {
+    /// Builds a magnitude scaling factor from its configuration name.
+    ///
+    /// Accepted names: `youd`, `idriss`.
+    pub fn from_config(name: &str) -> Result<Self, CoreError> {
+        match name {
+            "youd" => Ok(Self::Youd),
+            "idriss" => Ok(Self::Idriss),
+            _ => Err(CoreError::InvalidConfig(format!(
+                "Invalid msf_method parameter: '{}'. Must be youd or idriss",
+                name
+            ))),
+        }
+    }
+
+    /// Returns the MSF of an earthquake of moment magnitude `mw`.
+    pub fn msf(self, mw: f64) -> f64 {
+        match self {
+            Self::Youd => 10f64.powf(2.24) / mw.powf(2.56),
+            Self::Idriss => (6.9 * (-mw / 4.0).exp() - 0.058).min(1.8),
+        }
+    }
+}
+
+/// Options of the cyclic stress ratio.
+#[derive(Debug, Clone, Default)]
+pub struct CsrOptions {
+    /// Peak ground acceleration amax (g). If None, uses `pga` from the
+    /// configuration.
+    pub pga: Option<f64>,
+    /// Moment magnitude Mw of the design earthquake. If None, uses
+    /// `magnitude` from the configuration.
+    pub magnitude: Option<f64>,
+    /// If None, uses `rd_method` from the configuration.
+    pub rd_method: Option<RdMethod>,
+    /// If None, uses `msf_method` from the configuration.
+    pub msf_method: Option<MsfMethod>,
+}
+
+/// Adds the cyclic stress ratio of the design earthquake and the factor of
+/// safety against liquefaction.
+///
+/// Uses `CSR = 0.65 (amax / g) (σv_tot / σv_eff) rd` (Seed and Idriss,
+/// 1971) and `FS = CRR7.5 MSF / CSR`. Adds the rd and CSR columns, and the
+/// FS column if the CRR7.5 column of `add_crr_cols` is present; FS is NaN
+/// where CRR7.5 is, i.e. on records that are not liquefiable. Requires the
+/// columns added by `add_stress_cols`.
+///
+/// # Errors
+///
+/// Returns `CoreError::InvalidData` if the PGA or the magnitude is not
+/// positive.
+pub(crate) fn add_csr_cols(
+    data: DataFrame,
+    options: &CsrOptions,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_csr_cols");
+
+    let pga = options.pga.unwrap_or(*PGA);
+    let magnitude = options.magnitude.unwrap_or(*MAGNITUDE);
+    let rd_method = options.rd_method.unwrap_or(*RD_METHOD);
+    let msf_method = options.msf_method.unwrap_or(*MSF_METHOD);
+
+    if !(pga > 0.0 && magnitude > 0.0) || pga.is_infinite() || magnitude.is_infinite()
+    {
+        return Err(CoreError::InvalidData(format!(
+            "Invalid design earthquake: pga {}, magnitude {}. Both must be > 0",
+            pga, magnitude
+        )));
+    }
+
+    let rd: Vec<f64> = values(&data, *COL_DEPTH)?
+        .into_iter()
+        .map(|depth| rd_method.rd(depth, magnitude))
+        .collect();
+
+    let has_crr = data.column(*COL_CRR).is_ok();
+    let mut out_data = data
+        .lazy()
+        .with_column(lit(Series::new((*COL_RD).into(), rd)))
+        .with_column((
+                lit(0.65 * pga) * col(*COL_SIGV_TOT) / col(*COL_SIGV_EFF)
+                * col(*COL_RD)
+            ).alias(*COL_CSR)
+        );
+    if has_crr {
+        out_data = out_data.with_column((
+                col(*COL_CRR) * lit(msf_method.msf(magnitude)) / col(*COL_CSR)
+            ).alias(*COL_FS_LIQ)
+        );
+    }
+    let out_data = out_data.collect()?;
+
+    Ok(out_data)
+}
+
+/// Liquefaction triggering procedure.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriggeringMethod {
+    /// Robertson and Wride (1998), the NCEER procedure of Youd et al.
+    /// (2001), from Qtn,cs; adds the CRR7.5 and FS liq columns.
+    RobertsonWride,
+    /// Boulanger and Idriss (2014), from qc1Ncs with the Δqc1N fines
+    /// adjustment and Kσ; adds the qc1Ncs, CRR7.5 BI, Kσ and FS liq BI
+    /// columns.
+    BoulangerIdriss,
+}
+
+impl TriggeringMethod {
+    /// Builds a triggering procedure from its name.
+    ///
+    /// Accepted names: `robertson_wride`, `boulanger_idriss`.
+    pub fn from_config(name: &str) -> Result<Self, CoreError> {
+        match name {
+            "robertson_wride" => Ok(Self::RobertsonWride),
+            "boulanger_idriss" => Ok(Self::BoulangerIdriss),
+            _ => Err(CoreError::InvalidConfig(format!(
+                "Invalid triggering method: '{}'. Must be robertson_wride or \
+                 boulanger_idriss",
+                name
+            ))),
+        }
+    }
+}
+
+/// Returns the clean sand equivalent qc1Ncs of Boulanger and Idriss (2014)
+/// from qt (kPa), σv_eff (kPa) and the fines content FC (%).
+///
+/// Solves `qc1N = CN qt / pa`, `CN = (pa / σv_eff)^m` (at most 1.7),
+/// `m = 1.338 - 0.249 qc1Ncs^0.264` and `qc1Ncs = qc1N + Δqc1N`, with
+/// `Δqc1N = (11.9 + qc1N / 14.6) exp(1.63 - 9.7 / F - (15.7 / F)²)` and
+/// `F = FC + 2`, by fixed-point iteration, with qc1Ncs limited to 21-254 in
+/// m. Returns NaN if an input is NaN or the iteration does not converge.
+pub fn calc_qc1ncs(qt: f64, sigv_eff: f64, fc: f64) -> f64 {
+    if qt.is_nan() || sigv_eff.is_nan() || fc.is_nan() || sigv_eff <= 0.0 {
+        return f64::NAN;
+    }
+
+    let pa = *P_REF;
+    let fines = 1.63 - 9.7 / (fc + 2.0) - (15.7 / (fc + 2.0)).powi(2);
+    let mut qc1ncs: f64 = 100.0;
+    for _ in 0..100 {
+        let m = 1.338 - 0.249 * qc1ncs.clamp(21.0, 254.0).powf(0.264);
+        let qc1n = (pa / sigv_eff).powf(m).min(1.7) * qt / pa;
+        let next = qc1n + (11.9 + qc1n / 14.6) * fines.exp();
+        if (next - qc1ncs).abs() < 1e-6 {
+            return next;
+        }
+        qc1ncs = next;
+    }
+
+    f64::NAN
+}
+
+/// Adds the liquefaction triggering columns of Boulanger and Idriss (2014),
+/// alongside those of `add_crr_cols` so both procedures can be compared.
+///
+/// `CRR7.5 = exp(q / 113 + (q / 1000)² - (q / 140)³ + (q / 137)⁴ - 2.80)`,
+/// with q = qc1Ncs, at σv_eff = 1 atm, corrected to the design
+/// earthquake with `MSF = 1 + (MSFmax - 1) (8.64 exp(-Mw / 4) - 1.325)`,
+/// `MSFmax = 1.09 + (qc1Ncs / 180)³` (at most 2.2), and to the overburden
+/// with `Kσ = 1 - Cσ ln(σv_eff / pa)` (at most 1.1),
+/// `Cσ = 1 / (37.3 - 8.27 qc1Ncs^0.264)` (at most 0.3). The factor of safety
+/// is `FS = CRR7.5 MSF Kσ / CSR`. Records with Ic above `liq_ic` are
+/// excluded as clay-like and records above the water table as unsaturated,
+/// and qc1Ncs is multiplied by KH where the column of
+/// `thin_layer_correction` is present. Requires the columns added by
+/// `add_fines_col` and `add_csr_cols`; `options` must be those CSR was
+/// computed with, as the magnitude sets MSF.
+pub(crate) fn add_boulanger_idriss_cols(
+    data: DataFrame,
+    options: &CsrOptions,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_boulanger_idriss_cols");
+
+    let magnitude = options.magnitude.unwrap_or(*MAGNITUDE);
+    let qt = values(&data, COL_QT_ROL)?;
+    let sigv_eff = values(&data, *COL_SIGV_EFF)?;
+    let fc = values(&data, *COL_FC)?;
+    let ic = values(&data, *COL_IC)?;
+    let u0 = values(&data, *COL_U0)?;
+    let csr = values(&data, *COL_CSR)?;
+    let kh = match data.column(*COL_KH) {
+        Ok(_) => Some(values(&data, *COL_KH)?),
+        Err(_) => None,
+    };
+
+    let n_rows = data.height();
+    let mut qc1ncs = Vec::with_capacity(n_rows);
+    let mut crr = Vec::with_capacity(n_rows);
+    let mut k_sigma = Vec::with_capacity(n_rows);
+    let mut fs = Vec::with_capacity(n_rows);
+    for i in 0..n_rows {
+        // qt from MPa to kPa
+        let kh = kh.as_ref().map_or(1.0, |kh| kh[i]);
+        let q = kh * calc_qc1ncs(1000.0 * qt[i], sigv_eff[i], fc[i]);
+        qc1ncs.push(q);
+        let saturated = u0[i] > 0.0;
+        if q.is_nan() || ic[i].is_nan() || ic[i] > *LIQ_IC || !saturated {
+            crr.push(f64::NAN);
+            k_sigma.push(f64::NAN);
+            fs.push(f64::NAN);
+            continue;
+        }
+
+        let crr_75 = (q / 113.0 + (q / 1000.0).powi(2) - (q / 140.0).powi(3)
+            + (q / 137.0).powi(4)
+            - 2.80)
+            .exp();
+        let c_sigma = (1.0 / (37.3 - 8.27 * q.powf(0.264))).min(0.3);
+        let k = (1.0 - c_sigma * (sigv_eff[i] / *P_REF).ln()).min(1.1);
+        let msf_max = (1.09 + (q / 180.0).powi(3)).min(2.2);
+        let msf = 1.0 + (msf_max - 1.0) * (8.64 * (-magnitude / 4.0).exp() - 1.325);
+        crr.push(crr_75);
+        k_sigma.push(k);
+        fs.push(crr_75 * msf * k / csr[i]);
+    }
+
+    let out_data = data
+        .lazy()
+        .with_columns([
+            lit(Series::new((*COL_QC1NCS).into(), qc1ncs)),
+            lit(Series::new((*COL_CRR_BI).into(), crr)),
+            lit(Series::new((*COL_K_SIGMA).into(), k_sigma)),
+            lit(Series::new((*COL_FS_LIQ_BI).into(), fs)),
+        ])
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Curves of the volumetric strain of Zhang et al. (2002): factor of safety
+/// and the coefficients (a, b) of `εv = a qc1Ncs^-b` (%), bounded above by
+/// the strain at FS = 0.5.
+const STRAIN_CURVES: [(f64, f64, f64); 9] = [
+    (0.5, 102.0, 0.82),
+    (0.6, 2411.0, 1.45),
+    (0.7, 1701.0, 1.42),
+    (0.8, 1690.0, 1.46),
+    (0.9, 1430.0, 1.48),
+    (1.0, 64.0, 0.93),
+    (1.1, 11.0, 0.65),
+    (1.2, 9.7, 0.69),
+    (1.3, 7.6, 0.71),
+];
+
+/// Factor of safety from which no volumetric strain develops.
+const NO_STRAIN_FS: f64 = 2.0;
+
+/// Returns the post-liquefaction volumetric strain εv (%) of Zhang et al.
+/// (2002) from the factor of safety against liquefaction and Qtn,cs.
+///
+/// Interpolates linearly on FS between the curves of the method, with
+/// Qtn,cs limited to 33-200; εv is the largest strain for FS up to 0.5 and 0
+/// from FS = 2. Returns NaN if an input is NaN.
+pub fn calc_volumetric_strain(fs: f64, qtn_cs: f64) -> f64 {
+    if fs.is_nan() || qtn_cs.is_nan() {
+        return f64::NAN;
+    }
+    if fs >= NO_STRAIN_FS {
+        return 0.0;
+    }
+
+    let q = qtn_cs.clamp(33.0, 200.0);
+    let (_, a_max, b_max) = STRAIN_CURVES[0];
+    let strain = |(_, a, b): (f64, f64, f64)| {
+        (a * q.powf(-b)).min(a_max * q.powf(-b_max))
+    };
+
+    let upper = STRAIN_CURVES.iter().position(|curve| curve.0 >= fs);
+    match upper {
+        Some(0) => strain(STRAIN_CURVES[0]),
+        Some(i) => {
+            let (low, high) = (STRAIN_CURVES[i - 1], STRAIN_CURVES[i]);
+            let t = (fs - low.0) / (high.0 - low.0);
+            strain(low) + t * (strain(high) - strain(low))
+        }
+        // between the last curve and no strain
+        None => {
+            let last = STRAIN_CURVES[STRAIN_CURVES.len() - 1];
+            strain(last) * (NO_STRAIN_FS - fs) / (NO_STRAIN_FS - last.0)
+        }
+    }
+}
+
+/// Adds the post-liquefaction volumetric strain εv (%) of Zhang et al.
+/// (2002), computed from the FS and Qtn,cs of Robertson and Wride.
+///
+/// εv is 0 on records flagged not liquefiable and NaN where the flag is
+/// null. Requires the columns added by `add_crr_cols` and `add_csr_cols`.
+pub(crate) fn add_volumetric_strain_col(
+    data: DataFrame,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_volumetric_strain_col");
+
+    let fs = values(&data, *COL_FS_LIQ)?;
+    let qtn_cs = values(&data, *COL_QTN_CS)?;
+    let kh = match data.column(*COL_KH) {
+        Ok(_) => Some(values(&data, *COL_KH)?),
+        Err(_) => None,
+    };
+    let liquefiable = data.column(*COL_LIQUEFIABLE)?.bool()?;
+
+    let ev: Vec<f64> = liquefiable
+        .into_iter()
+        .enumerate()
+        .map(|(i, liquefiable)| match liquefiable {
+            Some(true) => {
+                let kh = kh.as_ref().map_or(1.0, |kh| kh[i]);
+                calc_volumetric_strain(fs[i], kh * qtn_cs[i])
+            }
+            Some(false) => 0.0,
+            None => f64::NAN,
+        })
+        .collect();
+
+    let out_data = data
+        .lazy()
+        .with_column(lit(Series::new((*COL_EV).into(), ev)))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Adds the cumulative post-liquefaction reconsolidation settlement (mm),
+/// the volumetric strain εv integrated from the bottom of the sounding up to
+/// each record.
+///
+/// Each record contributes εv over the thickness it represents; records
+/// with a NaN εv do not contribute. The value of the first record is the
+/// total settlement of `liquefaction_settlement`. Requires the column added
+/// by `add_volumetric_strain_col`.
+pub(crate) fn add_settlement_col(data: DataFrame) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_settlement_col");
+
+    let depth = values(&data, *COL_DEPTH)?;
+    let ev = values(&data, *COL_EV)?;
+    let thickness = record_thicknesses(&depth);
+
+    let mut settlement = vec![0.0; depth.len()];
+    let mut total = 0.0;
+    for i in (0..depth.len()).rev() {
+        if ev[i].is_finite() && thickness[i].is_finite() {
+            // εv from percent to a fraction and thickness from m to mm
+            total += ev[i] / 100.0 * thickness[i] * 1000.0;
+        }
+        settlement[i] = total;
+    }
+
+    let out_data = data
+        .lazy()
+        .with_column(lit(Series::new((*COL_SETTLEMENT).into(), settlement)))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Computes the total post-liquefaction reconsolidation settlement (mm) of a
+/// sounding, integrating εv over the thickness each record represents.
+///
+/// Records with a NaN εv do not contribute. Requires the column added by
+/// `add_volumetric_strain_col`.
+pub fn liquefaction_settlement(data: &DataFrame) -> Result<f64, CoreError> {
+    let depth = values(data, *COL_DEPTH)?;
+    let ev = values(data, *COL_EV)?;
+
+    let settlement = record_thicknesses(&depth)
+        .iter()
+        .zip(&ev)
+        .filter(|(thickness, ev)| thickness.is_finite() && ev.is_finite())
+        // εv from percent to a fraction and thickness from m to mm
+        .map(|(thickness, ev)| ev / 100.0 * thickness * 1000.0)
+        .sum();
+
+    Ok(settlement)
+}
+
+/// Computes the Liquefaction Severity Number of a sounding (van Ballegooy
+/// et al., 2014).
+///
+/// `LSN = 1000 ∫ εv / z dz`, with εv as a fraction, integrated over the
+/// sounding with the thickness each record represents; records with a NaN
+/// εv do not contribute. Requires the column added by
+/// `add_volumetric_strain_col`.
+pub fn liquefaction_severity(data: &DataFrame) -> Result<f64, CoreError> {
+    let depth = values(data, *COL_DEPTH)?;
+    let ev = values(data, *COL_EV)?;
+    let thickness = record_thicknesses(&depth);
+
+    let mut lsn = 0.0;
+    for i in 0..depth.len() {
+        let z = depth[i];
+        if z.is_nan() || z <= 0.0 || ev[i].is_nan() || thickness[i].is_nan() {
+            continue;
+        }
+        // εv from percent to a fraction
+        lsn += 1000.0 * ev[i] / 100.0 / z * thickness[i];
+    }
+
+    Ok(lsn)
+}
+
+/// Depth (m) down to which the Liquefaction Potential Index is integrated
+/// (Iwasaki et al., 1978).
+const LPI_DEPTH: f64 = 20.0;
+
+/// Computes the Liquefaction Potential Index of a sounding (Iwasaki et al.,
+/// 1978).
+///
+/// `LPI = ∫ F (10 - 0.5 z) dz` over the top 20 m, with `F = 1 - FS` for FS
+/// below 1 and 0 otherwise, integrated with the thickness each record
+/// represents. FS is the factor of safety of Robertson and Wride; records
+/// with a NaN FS, such as those not liquefiable, do not contribute. Requires
+/// the column added by `add_csr_cols`.
+pub fn liquefaction_potential(data: &DataFrame) -> Result<f64, CoreError> {
+    let depth = values(data, *COL_DEPTH)?;
+    let fs = values(data, *COL_FS_LIQ)?;
+    let thickness = record_thicknesses(&depth);
+
+    let mut lpi = 0.0;
+    for i in 0..depth.len() {
+        let z = depth[i];
+        if !(0.0..LPI_DEPTH).contains(&z) || thickness[i].is_nan() {
+            continue;
+        }
+        if fs[i] < 1.0 {
+            lpi += (1.0 - fs[i]) * (10.0 - 0.5 * z) * thickness[i];
+        }
+    }
+
+    Ok(lpi)
+}
+
+/// Relative density (fraction) from which the limiting shear strain of the
+/// loosest sands is no longer scaled by Fα.
+const MIN_FALPHA_DR: f64 = 0.4;
+
+/// Returns the maximum cyclic shear strain γmax (%) of Yoshimine et al.
+/// (2006) from the factor of safety against liquefaction and Dr (%).
+///
+/// `γmax = min(γlim, 0.035 (2 - FS) (1 - Fα) / (FS - Fα))` for FS between
+/// Fα and 2, with `γlim = 1.859 (1.1 - Dr)³` and
+/// `Fα = 0.032 + 4.7 Dr - 6.0 Dr²` (Dr as a fraction, limited to 0-1 and at
+/// least 0.4 in Fα); γmax is γlim for FS up to Fα and 0 from FS = 2. Returns
+/// NaN if an input is NaN.
+pub fn calc_max_shear_strain(fs: f64, dr: f64) -> f64 {
+    if fs.is_nan() || dr.is_nan() {
+        return f64::NAN;
+    }
+    if fs >= NO_STRAIN_FS {
+        return 0.0;
+    }
+
+    // Dr from percent to a fraction
+    let dr = (dr / 100.0).clamp(0.0, 1.0);
+    let gamma_lim = 1.859 * (1.1 - dr).powi(3);
+    let dr_alpha = dr.max(MIN_FALPHA_DR);
+    let f_alpha = 0.032 + 4.7 * dr_alpha - 6.0 * dr_alpha.powi(2);
+
+    let gamma_max = if fs <= f_alpha {
+        gamma_lim
+    } else {
+        let gamma = 0.035 * (2.0 - fs) * (1.0 - f_alpha) / (fs - f_alpha);
+        gamma.min(gamma_lim)
+    };
+
+    // from a fraction to percent
+    100.0 * gamma_max
+}
+
+/// Adds the maximum cyclic shear strain γmax (%) of Yoshimine et al. (2006),
+/// computed from the FS of Robertson and Wride and Dr.
+///
+/// γmax is 0 on records flagged not liquefiable and NaN where the flag is
+/// null or Dr is NaN. Feeds `lateral_displacement`. Requires the columns
+/// added by `add_dr_col`, `add_crr_cols` and `add_csr_cols`.
+pub(crate) fn add_shear_strain_col(data: DataFrame) -> Result<DataFrame, CoreError> {
+    profile_scope!("add_shear_strain_col");
+
+    let fs = values(&data, *COL_FS_LIQ)?;
+    let dr = values(&data, *COL_DR)?;
+    let liquefiable = data.column(*COL_LIQUEFIABLE)?.bool()?;
+
+    let gamma_max: Vec<f64> = liquefiable
+        .into_iter()
+        .enumerate()
+        .map(|(i, liquefiable)| match liquefiable {
+            Some(true) => calc_max_shear_strain(fs[i], dr[i]),
+            Some(false) => 0.0,
+            None => f64::NAN,
+        })
+        .collect();
+
+    let out_data = data
+        .lazy()
+        .with_column(lit(Series::new((*COL_GAMMA_MAX).into(), gamma_max)))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+/// Ground geometry of the lateral spreading screening of Zhang et al. (2004).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GroundGeometry {
+    /// Gently sloping ground without a free face, of slope S (%); the
+    /// method was calibrated for S of 0.2-3.5 %.
+    Slope(f64),
+    /// Level ground near a free face of height H (m), at a distance L (m)
+    /// from its toe; the method was calibrated for L/H of 4-40.
+    FreeFace { height: f64, distance: f64 },
+}
+
+impl GroundGeometry {
+    // depth (m) down to which γmax is integrated
+    fn max_depth(self) -> f64 {
+        match self {
+            Self::Slope(_) => *LDI_DEPTH,
+            // layers below twice the free face height do not contribute
+            Self::FreeFace { height, .. } => LDI_DEPTH.min(2.0 * height),
+        }
+    }
+
+    /// Returns the lateral displacement (m) of a lateral displacement index
+    /// `ldi` (m).
+    ///
+    /// `LD = (S + 0.2) LDI` for sloping ground and
+    /// `LD = 6 (L / H)^-0.8 LDI` near a free face.
+    pub fn displacement(self, ldi: f64) -> f64 {
+        match self {
+            Self::Slope(slope) => (slope + 0.2) * ldi,
+            Self::FreeFace { height, distance } => {
+                6.0 * (distance / height).powf(-0.8) * ldi
+            }
+        }
+    }
+}
+
+/// Lateral spreading estimate of a sounding.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LateralDisplacement {
+    /// Lateral displacement index LDI (m).
+    pub ldi: f64,
+    /// Lateral displacement LD (m).
+    pub displacement: f64,
+}
+
+/// Estimates the lateral displacement of a sounding with the method of Zhang
+/// et al. (2004).
+///
+/// `LDI = ∫ γmax dz`, with γmax as a fraction, integrated with the thickness
+/// each record represents down to the configured `ldi_depth`, or to twice
+/// the free face height if shallower; records with a NaN γmax do not
+/// contribute. Requires the column added by `add_shear_strain_col`.
+///
+/// # Errors
+///
+/// Returns `CoreError::InvalidData` if the slope is negative or the free
+/// face height or distance is not positive.
+pub fn lateral_displacement(
+    data: &DataFrame,
+    geometry: GroundGeometry,
+) -> Result<LateralDisplacement, CoreError> {
+    let valid = match geometry {
+        GroundGeometry::Slope(slope) => slope.is_finite() && slope >= 0.0,
+        GroundGeometry::FreeFace { height, distance } => {
+            height.is_finite() && height > 0.0 && distance.is_finite() && distance > 0.0
+        }
+    };
+    if !valid {
+        return Err(CoreError::InvalidData(format!(
+            "Invalid ground geometry: {:?}. The slope must be >= 0 and the free \
+             face height and distance > 0",
+            geometry
+        )));
+    }
+
+    let depth = values(data, *COL_DEPTH)?;
+    let gamma_max = values(data, *COL_GAMMA_MAX)?;
+    let max_depth = geometry.max_depth();
+
+    let ldi = record_thicknesses(&depth)
+        .iter()
+        .zip(&depth)
+        .zip(&gamma_max)
+        .filter(|((thickness, z), gamma)| {
+            thickness.is_finite() && **z <= max_depth && gamma.is_finite()
+        })
+        // γmax from percent to a fraction
+        .map(|((thickness, _), gamma)| gamma / 100.0 * thickness)
+        .sum();
+
+    Ok(LateralDisplacement { ldi, displacement: geometry.displacement(ldi) })
+}
+
+fn values(data: &DataFrame, name: &str) -> Result<Vec<f64>, CoreError> {
+    Ok(data
+        .column(name)?
+        .f64()?
+        .into_iter()
+        .map(|value| value.unwrap_or(f64::NAN))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // records 1 m apart, each representing 1 m (the first and last 0.5 m)
+    fn sounding(depth: &[f64], fs: &[f64]) -> DataFrame {
+        DataFrame::new(depth.len(), vec![
+            Column::new((*COL_DEPTH).into(), depth),
+            Column::new((*COL_FS_LIQ).into(), fs),
+        ])
+        .expect("columns should have the same length")
+    }
+
+    #[test]
+    fn liquefaction_potential_index() {
+        // F = 0.5 at 4 m weighs 8 and F = 0.2 at 6 m weighs 7 over 1 m each
+        let data = sounding(
+            &[3.0, 4.0, 5.0, 6.0, 7.0],
+            &[1.5, 0.5, f64::NAN, 0.8, 1.0],
+        );
+        let lpi = liquefaction_potential(&data).expect("LPI should compute");
+        assert!((lpi - (0.5 * 8.0 + 0.2 * 7.0)).abs() < 1e-12, "LPI {}", lpi);
+    }
+
+    #[test]
+    fn liquefaction_potential_ignores_records_below_20_m() {
+        let data = sounding(&[19.0, 20.0, 21.0], &[0.0, 0.0, 0.0]);
+        let lpi = liquefaction_potential(&data).expect("LPI should compute");
+        // only the 19 m record, over half a meter, with a weight of 0.5
+        assert!((lpi - 0.25).abs() < 1e-12, "LPI {}", lpi);
+    }
+}
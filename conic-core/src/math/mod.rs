@@ -1 +1,7 @@
 pub mod basic;
+pub mod classify;
+pub mod correlations;
+pub mod liquefaction;
+pub mod sbt;
+pub mod thin_layer;
+pub mod verify;
@@ -0,0 +1,216 @@
+use std::fmt;
+use polars::prelude::*;
+use crate::kernel::CoreError;
+use crate::kernel::config::{COL_DEPTH, COL_IC};
+use crate::frame::qa::reliability_weights;
+
+/// Normalized soil behavior type zones delimited by Ic (Robertson, 2009).
+///
+/// Zones 1 (sensitive fine grained), 8 and 9 (very stiff soils) cannot be
+/// identified from Ic alone; they are only assigned from the Qtn-Fr chart
+/// by `classify::chart_zone`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SbtZone {
+    /// Zone 1: sensitive, fine grained soils.
+    SensitiveFines,
+    /// Zone 2: organic soils, Ic > 3.60.
+    OrganicSoils,
+    /// Zone 3: clays, 2.95 < Ic <= 3.60.
+    Clays,
+    /// Zone 4: silt mixtures, 2.60 < Ic <= 2.95.
+    SiltMixtures,
+    /// Zone 5: sand mixtures, 2.05 < Ic <= 2.60.
+    SandMixtures,
+    /// Zone 6: sands, 1.31 < Ic <= 2.05.
+    Sands,
+    /// Zone 7: gravelly sand to dense sand, Ic <= 1.31.
+    GravellySands,
+    /// Zone 8: very stiff sand to clayey sand.
+    VeryStiffSands,
+    /// Zone 9: very stiff, fine grained soils.
+    VeryStiffFines,
+}
+
+impl SbtZone {
+    /// Returns the zone of an Ic value, or None if Ic is not finite.
+    pub fn from_ic(ic: f64) -> Option<Self> {
+        if !ic.is_finite() {
+            return None;
+        }
+
+        let zone = match ic {
+            ic if ic <= 1.31 => Self::GravellySands,
+            ic if ic <= 2.05 => Self::Sands,
+            ic if ic <= 2.60 => Self::SandMixtures,
+            ic if ic <= 2.95 => Self::SiltMixtures,
+            ic if ic <= 3.60 => Self::Clays,
+            _ => Self::OrganicSoils,
+        };
+
+        Some(zone)
+    }
+
+    /// Returns the zone number of the Robertson SBTn chart.
+    pub fn number(self) -> u8 {
+        match self {
+            Self::SensitiveFines => 1,
+            Self::OrganicSoils => 2,
+            Self::Clays => 3,
+            Self::SiltMixtures => 4,
+            Self::SandMixtures => 5,
+            Self::Sands => 6,
+            Self::GravellySands => 7,
+            Self::VeryStiffSands => 8,
+            Self::VeryStiffFines => 9,
+        }
+    }
+
+    /// Returns the soil behavior description of the zone.
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::SensitiveFines => "Sensitive, fine grained",
+            Self::OrganicSoils => "Organic soils - clay",
+            Self::Clays => "Clays - silty clay to clay",
+            Self::SiltMixtures => "Silt mixtures - clayey silt to silty clay",
+            Self::SandMixtures => "Sand mixtures - silty sand to sandy silt",
+            Self::Sands => "Sands - clean sand to silty sand",
+            Self::GravellySands => "Gravelly sand to dense sand",
+            Self::VeryStiffSands => "Very stiff sand to clayey sand",
+            Self::VeryStiffFines => "Very stiff, fine grained",
+        }
+    }
+
+    /// Returns a plain soil description of the zone, without the chart group
+    /// names, for readers unfamiliar with the SBT chart.
+    pub fn soil_name(self) -> &'static str {
+        match self {
+            Self::SensitiveFines => "sensitive fine grained soil",
+            Self::OrganicSoils => "organic clay or peat",
+            Self::Clays => "clay to silty clay",
+            Self::SiltMixtures => "clayey silt to silty clay",
+            Self::SandMixtures => "silty sand to sandy silt",
+            Self::Sands => "clean sand to silty sand",
+            Self::GravellySands => "gravelly sand to dense sand",
+            Self::VeryStiffSands => "very stiff sand to clayey sand",
+            Self::VeryStiffFines => "very stiff fine grained soil",
+        }
+    }
+}
+
+impl fmt::Display for SbtZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.number(), self.description())
+    }
+}
+
+/// Interval of consecutive records sharing the same SBT zone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SbtLayer {
+    /// Depth of the first record of the layer (m).
+    pub top: f64,
+    /// Depth of the last record of the layer (m).
+    pub bottom: f64,
+    pub zone: SbtZone,
+    /// Mean Ic of the layer.
+    pub mean_ic: f64,
+}
+
+/// Groups consecutive records with the same Ic-based zone into layers.
+///
+/// Records without a valid Ic are skipped and do not split layers. Records
+/// flagged unreliable by `flag_clogging` are down-weighted in the mean Ic.
+/// Requires the Ic column added by `add_behavior_cols`.
+pub fn sbt_layers(data: &DataFrame) -> Result<Vec<SbtLayer>, CoreError> {
+    let depth = data.column(*COL_DEPTH)?.f64()?;
+    let ic = data.column(*COL_IC)?.f64()?;
+    let weights = reliability_weights(data)?;
+
+    let mut layers: Vec<SbtLayer> = Vec::new();
+    let mut weight_sum = 0.0;
+
+    for ((depth, ic), weight) in depth.into_iter().zip(ic).zip(weights) {
+        let (Some(depth), Some(ic)) = (depth, ic) else { continue };
+        let Some(zone) = SbtZone::from_ic(ic) else { continue };
+
+        match layers.last_mut() {
+            Some(layer) if layer.zone == zone => {
+                // running weighted mean of Ic within the layer
+                weight_sum += weight;
+                if weight_sum > 0.0 {
+                    layer.mean_ic += (ic - layer.mean_ic) * weight / weight_sum;
+                }
+                layer.bottom = depth;
+            }
+            _ => {
+                weight_sum = weight;
+                layers.push(SbtLayer { top: depth, bottom: depth, zone, mean_ic: ic });
+            }
+        }
+    }
+
+    Ok(layers)
+}
+
+/// Transition between two SBT zones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZoneChange {
+    /// Boundary depth, midway between the last record of the upper zone and
+    /// the first record of the lower zone (m).
+    pub depth: f64,
+    pub from: SbtZone,
+    pub to: SbtZone,
+}
+
+/// Extracts the SBT zone transitions of a sounding, from top to bottom.
+///
+/// Requires the Ic column added by `add_behavior_cols`.
+pub fn zone_changes(data: &DataFrame) -> Result<Vec<ZoneChange>, CoreError> {
+    let layers = sbt_layers(data)?;
+
+    let changes = layers
+        .windows(2)
+        .map(|pair| ZoneChange {
+            depth: (pair[0].bottom + pair[1].top) / 2.0,
+            from: pair[0].zone,
+            to: pair[1].zone,
+        })
+        .collect();
+
+    Ok(changes)
+}
+
+/// Returns the SBT zone transitions as an event table.
+///
+/// Columns: boundary depth, zone numbers and descriptions before and after
+/// the transition.
+pub(crate) fn zone_change_table(data: &DataFrame) -> Result<DataFrame, CoreError> {
+    let changes = zone_changes(data)?;
+
+    let depth: Vec<f64> = changes.iter().map(|change| change.depth).collect();
+    let from: Vec<u32> = changes
+        .iter()
+        .map(|change| change.from.number() as u32)
+        .collect();
+    let to: Vec<u32> = changes
+        .iter()
+        .map(|change| change.to.number() as u32)
+        .collect();
+    let from_desc: Vec<&str> = changes
+        .iter()
+        .map(|change| change.from.description())
+        .collect();
+    let to_desc: Vec<&str> = changes
+        .iter()
+        .map(|change| change.to.description())
+        .collect();
+
+    let out_data = DataFrame::new(changes.len(), vec![
+        Column::new((*COL_DEPTH).into(), depth),
+        Column::new("From zone".into(), from),
+        Column::new("To zone".into(), to),
+        Column::new("From".into(), from_desc),
+        Column::new("To".into(), to_desc),
+    ])?;
+
+    Ok(out_data)
+}
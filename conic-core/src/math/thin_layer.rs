@@ -0,0 +1,132 @@
+use polars::prelude::*;
+use crate::kernel::CoreError;
+use crate::kernel::profile::profile_scope;
+use crate::kernel::config::{
+    COL_DEPTH, COL_QT, COL_IC, COL_KH, COL_QT_THIN, CONE_DIAMETER, THIN_LAYER_IC,
+    THIN_LAYER_CONTRAST
+};
+
+/// Largest normalized thickness H/dc with a correction factor above 1, where
+/// the KH curve reaches its minimum.
+const MAX_THIN_RATIO: f64 = 17.6 * 1.77;
+
+/// Options of the thin-layer correction.
+#[derive(Debug, Clone, Default)]
+pub struct ThinLayerOptions {
+    /// Cone diameter (mm), 35.7 for a 10 cm² cone. If None, uses
+    /// `cone_diameter` from the configuration.
+    pub cone_diameter: Option<f64>,
+    /// Largest Ic of the stiff, sand-like records forming a layer. If None,
+    /// uses `thin_layer_ic` from the configuration.
+    pub max_ic: Option<f64>,
+    /// Smallest ratio between the mean qt of a layer and that of the soft
+    /// soil around it for the layer to be corrected. If None, uses
+    /// `thin_layer_contrast` from the configuration.
+    pub min_contrast: Option<f64>,
+}
+
+/// Corrects qt of thin stiff layers embedded in soft soil.
+///
+/// A thin layer is a run of records with Ic below `max_ic` bounded above and
+/// below by softer records, whose mean qt is at least `min_contrast` times
+/// the mean qt of the soft records within one layer thickness above and
+/// below. The cone senses the soft soil before reaching the layer and after
+/// leaving it, so qt of such layers is multiplied by the factor of Youd et
+/// al. (2001), after Robertson and Fear (1995):
+///
+/// `KH = 0.25 ((H/dc) / 17.6 - 1.77)² + 1`
+///
+/// where H is the layer thickness and dc the cone diameter; layers thicker
+/// than 31 cone diameters are not corrected. Adds the KH factor (1 outside
+/// corrected layers) and the corrected qt columns. Requires the columns
+/// added by `add_behavior_cols`.
+pub(crate) fn thin_layer_correction(
+    data: DataFrame,
+    options: &ThinLayerOptions,
+) -> Result<DataFrame, CoreError> {
+    profile_scope!("thin_layer_correction");
+
+    let cone_diameter = options.cone_diameter.unwrap_or(*CONE_DIAMETER);
+    let max_ic = options.max_ic.unwrap_or(*THIN_LAYER_IC);
+    let min_contrast = options.min_contrast.unwrap_or(*THIN_LAYER_CONTRAST);
+
+    if cone_diameter.is_nan() || cone_diameter <= 0.0 {
+        return Err(CoreError::InvalidData(format!(
+            "Cannot correct thin layers: cone diameter must be positive, got {}",
+            cone_diameter
+        )));
+    }
+
+    let depth = values(&data, *COL_DEPTH)?;
+    let qt = values(&data, *COL_QT)?;
+    let ic = values(&data, *COL_IC)?;
+
+    let n_rows = data.height();
+    let stiff: Vec<bool> = ic.iter().map(|&ic| ic < max_ic).collect();
+    let soft: Vec<bool> = ic.iter().map(|&ic| ic >= max_ic).collect();
+    let mut kh = vec![1.0; n_rows];
+
+    let mut start = 0;
+    while start < n_rows {
+        if !stiff[start] {
+            start += 1;
+            continue;
+        }
+        let end = (start..n_rows).find(|&i| !stiff[i]).unwrap_or(n_rows);
+
+        // layers must be embedded in soft soil on both sides
+        if start > 0 && end < n_rows && soft[start - 1] && soft[end] {
+            // limits halfway between the layer records and the soft ones
+            let top = (depth[start - 1] + depth[start]) / 2.0;
+            let bottom = (depth[end - 1] + depth[end]) / 2.0;
+            let thickness = bottom - top;
+            let ratio = thickness * 1000.0 / cone_diameter;
+
+            let layer_qt = mean((start..end).map(|i| qt[i]));
+            // soft records within one layer thickness above and below
+            let around = |i: &usize| {
+                let z = depth[*i];
+                soft[*i]
+                    && ((z < top && z >= top - thickness)
+                        || (z > bottom && z <= bottom + thickness))
+            };
+            let soft_qt = mean((0..n_rows).filter(around).map(|i| qt[i]));
+
+            if ratio > 0.0
+                && ratio < MAX_THIN_RATIO
+                && layer_qt >= min_contrast * soft_qt
+            {
+                let factor = 0.25 * (ratio / 17.6 - 1.77).powi(2) + 1.0;
+                kh[start..end].fill(factor);
+            }
+        }
+
+        start = end;
+    }
+
+    let out_data = data
+        .lazy()
+        .with_column(lit(Series::new((*COL_KH).into(), kh)))
+        .with_column((col(*COL_QT) * col(*COL_KH)).alias(*COL_QT_THIN))
+        .collect()?;
+
+    Ok(out_data)
+}
+
+fn values(data: &DataFrame, name: &str) -> Result<Vec<f64>, CoreError> {
+    Ok(data
+        .column(name)?
+        .f64()?
+        .into_iter()
+        .map(|value| value.unwrap_or(f64::NAN))
+        .collect())
+}
+
+// mean of the finite values, NaN if there are none
+fn mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values
+        .filter(|value| value.is_finite())
+        .fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+
+    if count > 0 { sum / count as f64 } else { f64::NAN }
+}
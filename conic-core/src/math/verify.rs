@@ -0,0 +1,487 @@
+use polars::prelude::*;
+use crate::kernel::CoreError;
+use crate::kernel::config::{
+    COL_DEPTH, COL_QC, COL_FS, COL_U2, COL_U0, COL_SIGV_TOT, COL_SIGV_EFF, COL_QT,
+    COL_QN, COL_QE, COL_RF, COL_FR, COL_BQ, COL_DU, COL_U2_U0, COL_DU_SIGV, COL_N,
+    COL_QTN, COL_IC, COL_CD, COL_IB, COL_N60, COL_E_MOD, COL_SU, COL_ST, COL_SIGP,
+    COL_OCR, COL_K0, COL_PHI, COL_DR, COL_PSI, COL_GAMMA, COL_VS, COL_G0, COL_M_MOD,
+    COL_MV, COL_CR, COL_K, COL_CV, COL_KC, COL_QTN_CS, COL_FC, COL_CRR, COL_RD, COL_CSR,
+    COL_FS_LIQ, COL_QC1NCS, COL_CRR_BI, COL_K_SIGMA, COL_FS_LIQ_BI, COL_EV,
+    COL_GAMMA_MAX
+};
+use crate::frame::smooth::Smoothing;
+use super::{basic, classify, correlations, liquefaction};
+use super::basic::UnitWeight;
+use super::correlations::PhiMethod;
+use super::liquefaction::{CsrOptions, MsfMethod, RdMethod};
+use super::sbt::SbtZone;
+
+/// Largest relative difference between computed and expected values
+/// accepted as a pass; expected values are given to 4-5 significant digits.
+pub const RELATIVE_TOLERANCE: f64 = 1e-3;
+
+/// Name of the SBT zone check, which has no output column.
+pub const SBT_ZONE: &str = "SBT zone";
+
+// parameters the expected values were calculated with
+const A_RATIO: f64 = 0.80;
+const GAMMA: f64 = 18.7;
+const NKT: f64 = 14.0;
+const K_OCR: f64 = 0.33;
+const C_FC: f64 = 0.0;
+const LIQ_IC: f64 = 2.6;
+const PGA: f64 = 0.3;
+const MAGNITUDE: f64 = 7.5;
+
+/// Check of one correlation against a hand-calculated value.
+#[derive(Debug, Clone)]
+pub struct CorrelationCheck {
+    /// Quantity checked: an output column name or `SBT_ZONE`.
+    pub quantity: String,
+    /// Input record of the check.
+    pub case: &'static str,
+    /// Publication the equation is taken from, not the source of the
+    /// expected value.
+    pub reference: &'static str,
+    pub expected: f64,
+    pub computed: f64,
+}
+
+impl CorrelationCheck {
+    /// Returns whether the computed value matches the expected one within
+    /// `RELATIVE_TOLERANCE`.
+    pub fn passed(&self) -> bool {
+        let scale = self.expected.abs().max(f64::MIN_POSITIVE);
+        (self.computed - self.expected).abs() / scale <= RELATIVE_TOLERANCE
+    }
+}
+
+/// Input record of the checks with the expected values of each quantity,
+/// hand-calculated from the equations of the cited publications.
+///
+/// These values check the implementation against the equations as
+/// transcribed, not the transcription itself; that needs the numerical
+/// examples printed in the publications.
+struct Case {
+    name: &'static str,
+    depth: f64,
+    qc: f64,
+    fs: f64,
+    u2: f64,
+    u0: f64,
+    expected: Vec<(&'static str, &'static str, f64)>,
+}
+
+/// Runs every correlation of the processing pipeline on the check records
+/// and compares the results with the hand-calculated expected values.
+///
+/// The records are single CPTu records processed with a = 0.80,
+/// γ = 18.7 kN/m³, Nkt = 14, k = 0.33, C_FC = 0, a liquefaction Ic cutoff of
+/// 2.6, the φ' of Kulhawy and Mayne and an Mw 7.5 earthquake with a PGA of
+/// 0.3 g, the rd of Liao and Whitman and the MSF of Youd et al.; the expected
+/// values assume the default `p_ref` of 101.33 kPa, `gamma_w` of 9.81 kN/m³,
+/// `su_ic` and `liq_ic` of 2.6 and Dr coefficients, so different configured
+/// values make the affected checks fail.
+pub fn check_correlations() -> Result<Vec<CorrelationCheck>, CoreError> {
+    let mut checks = Vec::new();
+
+    for case in cases() {
+        let data = DataFrame::new(1, vec![
+            Column::new((*COL_DEPTH).into(), [case.depth]),
+            Column::new((*COL_QC).into(), [case.qc]),
+            Column::new((*COL_FS).into(), [case.fs]),
+            Column::new((*COL_U2).into(), [case.u2]),
+            Column::new((*COL_U0).into(), [case.u0]),
+        ])?;
+
+        let data = basic::add_stress_cols(
+            data,
+            Some(A_RATIO),
+            Some(UnitWeight::Constant(GAMMA)),
+            Some(1),
+            Some(Smoothing::Mean)
+        )?;
+        let data = correlations::add_gamma_col(data)?;
+        let data = basic::add_pore_pressure_cols(data)?;
+        // tight tolerance so the solver reaches the fixed point of n
+        let data = basic::add_behavior_cols(
+            data, Some(999), Some(1e-9), Some(0.0), Some(f64::INFINITY)
+        )?;
+        let data = classify::add_clean_sand_cols(data)?;
+        let data = classify::add_fines_col(data, Some(C_FC))?;
+        let data = correlations::add_spt_cols(data)?;
+        let data = correlations::add_modulus_cols(data)?;
+        let data = correlations::add_constrained_modulus_col(data)?;
+        let data = correlations::add_compressibility_cols(data)?;
+        let data = correlations::add_vs_cols(data)?;
+        let data = correlations::add_su_col(data, Some(NKT))?;
+        let data = correlations::add_sensitivity_col(data)?;
+        let data = correlations::add_phi_col(data, Some(PhiMethod::KulhawyMayne))?;
+        let data = correlations::add_dr_col(data)?;
+        let data = correlations::add_psi_col(data)?;
+        let data = correlations::add_ocr_cols(data, Some(K_OCR))?;
+        let data = correlations::add_k0_col(data)?;
+        let data = correlations::add_permeability_col(data)?;
+        let data = correlations::add_cv_col(data)?;
+        let data = liquefaction::add_crr_cols(data, Some(LIQ_IC))?;
+        let options = CsrOptions {
+            pga: Some(PGA),
+            magnitude: Some(MAGNITUDE),
+            rd_method: Some(RdMethod::LiaoWhitman),
+            msf_method: Some(MsfMethod::Youd),
+        };
+        let data = liquefaction::add_csr_cols(data, &options)?;
+        let data = liquefaction::add_boulanger_idriss_cols(data, &options)?;
+        let data = liquefaction::add_volumetric_strain_col(data)?;
+        let data = liquefaction::add_shear_strain_col(data)?;
+
+        for (quantity, reference, expected) in case.expected {
+            let computed = if quantity == SBT_ZONE {
+                let ic = data.column(*COL_IC)?.f64()?.get(0).unwrap_or(f64::NAN);
+                SbtZone::from_ic(ic).map_or(f64::NAN, |zone| f64::from(zone.number()))
+            } else {
+                data.column(quantity)?.f64()?.get(0).unwrap_or(f64::NAN)
+            };
+
+            checks.push(CorrelationCheck {
+                quantity: quantity.to_string(),
+                case: case.name,
+                reference,
+                expected,
+                computed,
+            });
+        }
+    }
+
+    Ok(checks)
+}
+
+fn cases() -> Vec<Case> {
+    const BALDI_1986: &str = "Baldi et al. (1986)";
+    const BOULANGER_IDRISS_2014: &str = "Boulanger and Idriss (2014)";
+    const KULHAWY_MAYNE_1990: &str = "Kulhawy and Mayne (1990)";
+    const LUNNE_1997: &str = "Lunne et al. (1997)";
+    const ROBERTSON_2009: &str = "Robertson (2009)";
+    const ROBERTSON_2010: &str = "Robertson (2010)";
+    const ROBERTSON_2012: &str = "Robertson (2012)";
+    const ROBERTSON_2016: &str = "Robertson (2016)";
+    const ROBERTSON_CABAL_2010: &str = "Robertson and Cabal (2010)";
+    const ROBERTSON_WRIDE_1998: &str = "Robertson and Wride (1998)";
+    const YOSHIMINE_2006: &str = "Yoshimine et al. (2006)";
+    const YOUD_2001: &str = "Youd et al. (2001)";
+    const ZHANG_2002: &str = "Zhang et al. (2002)";
+    const DEFINITION: &str = "definition";
+
+    vec![
+        // normally consolidated clay, stress exponent capped at 1
+        Case {
+            name: "clay at 10 m",
+            depth: 10.0,
+            qc: 1.0,
+            fs: 30.0,
+            u2: 400.0,
+            u0: 98.1,
+            expected: vec![
+                (*COL_SIGV_TOT, DEFINITION, 187.0),
+                (*COL_SIGV_EFF, DEFINITION, 88.9),
+                (*COL_QT, LUNNE_1997, 1.08),
+                (*COL_QN, LUNNE_1997, 0.893),
+                (*COL_QE, LUNNE_1997, 0.68),
+                (*COL_GAMMA, ROBERTSON_CABAL_2010, 16.930),
+                (*COL_RF, LUNNE_1997, 2.7778),
+                (*COL_FR, LUNNE_1997, 3.3595),
+                (*COL_BQ, LUNNE_1997, 0.33807),
+                (*COL_DU, DEFINITION, 301.9),
+                (*COL_U2_U0, DEFINITION, 4.0775),
+                (*COL_DU_SIGV, DEFINITION, 3.3960),
+                (*COL_N, ROBERTSON_2009, 1.0),
+                (*COL_QTN, ROBERTSON_2009, 10.045),
+                (*COL_IC, ROBERTSON_2009, 3.0234),
+                (SBT_ZONE, ROBERTSON_2009, 3.0),
+                (*COL_KC, ROBERTSON_WRIDE_1998, 7.0079),
+                (*COL_QTN_CS, ROBERTSON_WRIDE_1998, 70.394),
+                (*COL_FC, BOULANGER_IDRISS_2014, 100.0),
+                (*COL_N60, ROBERTSON_2012, 5.6568),
+                (*COL_E_MOD, ROBERTSON_2009, 29.498),
+                (*COL_M_MOD, ROBERTSON_2009, 8.9702),
+                (*COL_MV, DEFINITION, 0.11148),
+                (*COL_CR, DEFINITION, 0.022820),
+                (*COL_VS, ROBERTSON_2009, 139.31),
+                (*COL_G0, ROBERTSON_2009, 33.494),
+                (*COL_SU, LUNNE_1997, 63.786),
+                (*COL_ST, ROBERTSON_2009, 2.1262),
+                (*COL_SIGP, KULHAWY_MAYNE_1990, 294.69),
+                (*COL_OCR, KULHAWY_MAYNE_1990, 3.3149),
+                (*COL_K0, KULHAWY_MAYNE_1990, 1.0045),
+                (*COL_K, ROBERTSON_2010, 5.7673e-9),
+                (*COL_CV, ROBERTSON_2010, 166.42),
+                (*COL_CD, ROBERTSON_2016, -21.663),
+                (*COL_IB, ROBERTSON_2016, 19.321),
+            ],
+        },
+        // dense dilative sand, stress exponent iterated below 1
+        Case {
+            name: "sand at 5 m",
+            depth: 5.0,
+            qc: 12.0,
+            fs: 80.0,
+            u2: 40.0,
+            u0: 49.05,
+            expected: vec![
+                (*COL_SIGV_TOT, DEFINITION, 93.5),
+                (*COL_SIGV_EFF, DEFINITION, 44.45),
+                (*COL_QT, LUNNE_1997, 12.008),
+                (*COL_QN, LUNNE_1997, 11.9145),
+                (*COL_QE, LUNNE_1997, 11.968),
+                (*COL_GAMMA, ROBERTSON_CABAL_2010, 18.982),
+                (*COL_RF, LUNNE_1997, 0.66622),
+                (*COL_FR, LUNNE_1997, 0.67145),
+                (*COL_BQ, LUNNE_1997, -7.5958e-4),
+                (*COL_DU, DEFINITION, -9.05),
+                (*COL_U2_U0, DEFINITION, 0.81549),
+                (*COL_DU_SIGV, DEFINITION, -0.20360),
+                (*COL_N, ROBERTSON_2009, 0.48611),
+                (*COL_QTN, ROBERTSON_2009, 175.51),
+                (*COL_IC, ROBERTSON_2009, 1.6120),
+                (SBT_ZONE, ROBERTSON_2009, 6.0),
+                (*COL_KC, ROBERTSON_WRIDE_1998, 1.0),
+                (*COL_QTN_CS, ROBERTSON_WRIDE_1998, 175.51),
+                (*COL_FC, BOULANGER_IDRISS_2014, 0.0),
+                (*COL_N60, ROBERTSON_2012, 25.179),
+                (*COL_E_MOD, ROBERTSON_2009, 65.883),
+                (*COL_M_MOD, ROBERTSON_2009, 82.572),
+                (*COL_MV, DEFINITION, 0.012111),
+                (*COL_VS, ROBERTSON_2009, 208.19),
+                (*COL_G0, ROBERTSON_2009, 83.869),
+                (*COL_CD, ROBERTSON_2016, 321.96),
+                (*COL_IB, ROBERTSON_2016, 98.756),
+                (*COL_PHI, KULHAWY_MAYNE_1990, 42.287),
+                (*COL_DR, BALDI_1986, 93.369),
+                (*COL_PSI, ROBERTSON_2010, -0.18062),
+                (*COL_K, ROBERTSON_2010, 1.1259e-4),
+            ],
+        },
+        // loose silty sand, fines corrected and susceptible to liquefaction
+        Case {
+            name: "silty sand at 4 m",
+            depth: 4.0,
+            qc: 4.0,
+            fs: 30.0,
+            u2: 20.0,
+            u0: 29.43,
+            expected: vec![
+                (*COL_SIGV_TOT, DEFINITION, 74.8),
+                (*COL_SIGV_EFF, DEFINITION, 45.37),
+                (*COL_N, ROBERTSON_2009, 0.63198),
+                (*COL_QTN, ROBERTSON_2009, 64.433),
+                (*COL_IC, ROBERTSON_2009, 1.9937),
+                (*COL_KC, ROBERTSON_WRIDE_1998, 1.2918),
+                (*COL_QTN_CS, ROBERTSON_WRIDE_1998, 83.237),
+                (*COL_FC, BOULANGER_IDRISS_2014, 22.495),
+                (*COL_DR, BALDI_1986, 47.329),
+                (*COL_PSI, ROBERTSON_2010, -0.073704),
+                (*COL_CRR, ROBERTSON_WRIDE_1998, 0.13363),
+                (*COL_RD, YOUD_2001, 0.96940),
+                (*COL_CSR, YOUD_2001, 0.31165),
+                (*COL_FS_LIQ, YOUD_2001, 0.42863),
+                (*COL_QC1NCS, BOULANGER_IDRISS_2014, 95.790),
+                (*COL_CRR_BI, BOULANGER_IDRISS_2014, 0.13207),
+                (*COL_K_SIGMA, BOULANGER_IDRISS_2014, 1.0827),
+                (*COL_FS_LIQ_BI, BOULANGER_IDRISS_2014, 0.45879),
+                (*COL_EV, ZHANG_2002, 2.7161),
+                (*COL_GAMMA_MAX, YOSHIMINE_2006, 45.759),
+            ],
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // asserts that every check of a quantity passes
+    fn assert_checks_pass(quantity: &str) {
+        let checks: Vec<CorrelationCheck> = check_correlations()
+            .expect("correlation checks should run")
+            .into_iter()
+            .filter(|check| check.quantity == quantity)
+            .collect();
+
+        assert!(!checks.is_empty(), "no check for {}", quantity);
+        for check in checks {
+            assert!(
+                check.passed(),
+                "{} ({}): expected {}, computed {}",
+                check.quantity, check.case, check.expected, check.computed
+            );
+        }
+    }
+
+    #[test]
+    fn vertical_stresses() {
+        assert_checks_pass(*COL_SIGV_TOT);
+        assert_checks_pass(*COL_SIGV_EFF);
+    }
+
+    #[test]
+    fn corrected_cone_resistance() {
+        assert_checks_pass(*COL_QT);
+    }
+
+    #[test]
+    fn net_and_effective_cone_resistance() {
+        assert_checks_pass(*COL_QN);
+        assert_checks_pass(*COL_QE);
+    }
+
+    #[test]
+    fn unit_weight() {
+        assert_checks_pass(*COL_GAMMA);
+    }
+
+    #[test]
+    fn friction_and_pore_pressure_ratios() {
+        assert_checks_pass(*COL_RF);
+        assert_checks_pass(*COL_FR);
+        assert_checks_pass(*COL_BQ);
+    }
+
+    #[test]
+    fn pore_pressure_ratios() {
+        assert_checks_pass(*COL_DU);
+        assert_checks_pass(*COL_U2_U0);
+        assert_checks_pass(*COL_DU_SIGV);
+    }
+
+    #[test]
+    fn stress_exponent_and_normalized_resistance() {
+        assert_checks_pass(*COL_N);
+        assert_checks_pass(*COL_QTN);
+    }
+
+    #[test]
+    fn behavior_type_index() {
+        assert_checks_pass(*COL_IC);
+        assert_checks_pass(SBT_ZONE);
+    }
+
+    #[test]
+    fn clean_sand_resistance() {
+        assert_checks_pass(*COL_KC);
+        assert_checks_pass(*COL_QTN_CS);
+    }
+
+    #[test]
+    fn fines_content() {
+        assert_checks_pass(*COL_FC);
+    }
+
+    #[test]
+    fn cyclic_resistance_ratio() {
+        assert_checks_pass(*COL_CRR);
+    }
+
+    #[test]
+    fn cyclic_stress_ratio_and_factor_of_safety() {
+        assert_checks_pass(*COL_RD);
+        assert_checks_pass(*COL_CSR);
+        assert_checks_pass(*COL_FS_LIQ);
+    }
+
+    #[test]
+    fn boulanger_idriss_triggering() {
+        assert_checks_pass(*COL_QC1NCS);
+        assert_checks_pass(*COL_CRR_BI);
+        assert_checks_pass(*COL_K_SIGMA);
+        assert_checks_pass(*COL_FS_LIQ_BI);
+    }
+
+    #[test]
+    fn volumetric_strain() {
+        assert_checks_pass(*COL_EV);
+    }
+
+    #[test]
+    fn maximum_shear_strain() {
+        assert_checks_pass(*COL_GAMMA_MAX);
+    }
+
+    #[test]
+    fn spt_blow_count() {
+        assert_checks_pass(*COL_N60);
+    }
+
+    #[test]
+    fn youngs_modulus() {
+        assert_checks_pass(*COL_E_MOD);
+    }
+
+    #[test]
+    fn constrained_modulus() {
+        assert_checks_pass(*COL_M_MOD);
+    }
+
+    #[test]
+    fn compressibility() {
+        assert_checks_pass(*COL_MV);
+        assert_checks_pass(*COL_CR);
+    }
+
+    #[test]
+    fn shear_wave_velocity() {
+        assert_checks_pass(*COL_VS);
+        assert_checks_pass(*COL_G0);
+    }
+
+    #[test]
+    fn undrained_shear_strength() {
+        assert_checks_pass(*COL_SU);
+    }
+
+    #[test]
+    fn sensitivity() {
+        assert_checks_pass(*COL_ST);
+    }
+
+    #[test]
+    fn friction_angle() {
+        assert_checks_pass(*COL_PHI);
+    }
+
+    #[test]
+    fn relative_density() {
+        assert_checks_pass(*COL_DR);
+    }
+
+    #[test]
+    fn state_parameter() {
+        assert_checks_pass(*COL_PSI);
+    }
+
+    #[test]
+    fn overconsolidation() {
+        assert_checks_pass(*COL_SIGP);
+        assert_checks_pass(*COL_OCR);
+    }
+
+    #[test]
+    fn earth_pressure_at_rest() {
+        assert_checks_pass(*COL_K0);
+    }
+
+    #[test]
+    fn hydraulic_conductivity() {
+        assert_checks_pass(*COL_K);
+    }
+
+    #[test]
+    fn coefficient_of_consolidation() {
+        assert_checks_pass(*COL_CV);
+    }
+
+    #[test]
+    fn contractive_dilative_boundary() {
+        assert_checks_pass(*COL_CD);
+        assert_checks_pass(*COL_IB);
+    }
+}
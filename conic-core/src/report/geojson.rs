@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use polars::prelude::*;
+use crate::kernel::{ConicDataFrame, CoreError, Location};
+use crate::kernel::config::{COL_EV, COL_FS_LIQ, COL_IC, WATER_LEVEL};
+use crate::frame::qa::reliability_weights;
+use super::{column_values, depth_values};
+
+/// Renders a GeoJSON FeatureCollection with one point per sounding.
+///
+/// Each feature is placed at the location stored in the sounding metadata
+/// (see `ConicDataFrame::with_location`) and carries the sounding ID,
+/// record count, termination depth, mean Ic (down-weighting records flagged
+/// by `flag_clogging`), Liquefaction Potential Index (see
+/// `ConicDataFrame::liquefaction_potential`), Liquefaction Severity Number
+/// (see `ConicDataFrame::liquefaction_severity`), reconsolidation settlement
+/// and the configured groundwater level. Soundings without a location get a null
+/// geometry; values that cannot be computed are null.
+pub fn render_geojson(
+    soundings: &[(&str, &ConicDataFrame)],
+) -> Result<String, CoreError> {
+    let mut features = Vec::with_capacity(soundings.len());
+
+    for (id, data) in soundings {
+        let depth = depth_values(data)?;
+        let termination = depth
+            .iter()
+            .copied()
+            .filter(|z| z.is_finite())
+            .fold(f64::NAN, f64::max);
+
+        let mean_ic = match column_values(data, *COL_IC)? {
+            Some(ic) => {
+                let weights = reliability_weights(data)?;
+                let (sum, weight_sum) = ic
+                    .iter()
+                    .zip(&weights)
+                    .filter(|(ic, _)| ic.is_finite())
+                    .fold((0.0, 0.0), |(sum, weight_sum), (ic, weight)| {
+                        (sum + ic * weight, weight_sum + weight)
+                    });
+                if weight_sum > 0.0 { sum / weight_sum } else { f64::NAN }
+            }
+            None => f64::NAN,
+        };
+
+        let lpi = if data.column(*COL_FS_LIQ).is_ok() {
+            data.liquefaction_potential()?
+        } else {
+            f64::NAN
+        };
+        let (lsn, settlement) = if data.column(*COL_EV).is_ok() {
+            (data.liquefaction_severity()?, data.liquefaction_settlement()?)
+        } else {
+            (f64::NAN, f64::NAN)
+        };
+
+        let geometry = match data.metadata().location {
+            Some(Location { longitude, latitude }) => format!(
+                "{{\"type\": \"Point\", \"coordinates\": [{}, {}]}}",
+                number(longitude),
+                number(latitude)
+            ),
+            None => "null".to_string(),
+        };
+
+        let mut feature = String::new();
+        let _ = write!(
+            feature,
+            "    {{\"type\": \"Feature\", \"geometry\": {}, \"properties\": {{\
+             \"id\": {}, \"records\": {}, \"termination_depth_m\": {}, \
+             \"mean_ic\": {}, \"lpi\": {}, \"lsn\": {}, \"settlement_mm\": {}, \
+             \"water_level_m\": {}}}}}",
+            geometry,
+            string(id),
+            data.height(),
+            number(termination),
+            number(mean_ic),
+            number(lpi),
+            number(lsn),
+            number(settlement),
+            number(*WATER_LEVEL)
+        );
+        features.push(feature);
+    }
+
+    Ok(format!(
+        "{{\n  \"type\": \"FeatureCollection\",\n  \"features\": [\n{}\n  ]\n}}\n",
+        features.join(",\n")
+    ))
+}
+
+/// Renders the GeoJSON FeatureCollection of several soundings and writes it
+/// to a file.
+pub fn write_geojson(
+    soundings: &[(&str, &ConicDataFrame)],
+    file_path: &str,
+) -> Result<(), CoreError> {
+    let geojson = render_geojson(soundings)?;
+    std::fs::write(file_path, geojson)?;
+
+    Ok(())
+}
+
+/// Reads sounding locations from a CSV file with `id`, `longitude` and
+/// `latitude` columns (WGS 84 degrees), keyed by sounding ID.
+pub fn read_locations(file_path: &str) -> Result<HashMap<String, Location>, CoreError> {
+    let data = CsvReadOptions::default()
+        .with_has_header(true)
+        .try_into_reader_with_file_path(Some(file_path.into()))?
+        .finish()
+        .map_err(|err| {
+            CoreError::InvalidData(format!(
+                "Failed to read locations file '{}': {}",
+                file_path, err
+            ))
+        })?;
+
+    let missing = |name: &str| {
+        CoreError::InvalidData(format!(
+            "Locations file '{}' has no '{}' column",
+            file_path, name
+        ))
+    };
+    let ids = data.column("id").map_err(|_| missing("id"))?.cast(&DataType::String)?;
+    let longitude = column_values(&data, "longitude")?
+        .ok_or_else(|| missing("longitude"))?;
+    let latitude = column_values(&data, "latitude")?
+        .ok_or_else(|| missing("latitude"))?;
+
+    let locations = ids
+        .str()?
+        .into_iter()
+        .zip(longitude)
+        .zip(latitude)
+        .filter_map(|((id, longitude), latitude)| {
+            Some((id?.to_string(), Location { longitude, latitude }))
+        })
+        .collect();
+
+    Ok(locations)
+}
+
+// JSON number, or null if not finite
+fn number(value: f64) -> String {
+    if value.is_finite() { value.to_string() } else { "null".to_string() }
+}
+
+// JSON string literal
+fn string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", ch as u32);
+            }
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -0,0 +1,265 @@
+use std::fmt::Write;
+use crate::kernel::{ConicDataFrame, CoreError, Provenance};
+use crate::kernel::profile::profile_scope;
+use crate::kernel::config::{COL_QT, COL_FS, COL_U2, COL_U0, COL_IC};
+use crate::math::sbt::sbt_layers;
+use super::{
+    IC_BOUNDARIES, column_values, depth_values, metadata_rows,
+    processing_parameters, summary_stats
+};
+
+const PLOT_WIDTH: f64 = 220.0;
+const PLOT_HEIGHT: f64 = 560.0;
+const PLOT_MARGIN: f64 = 40.0;
+
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h1 { margin-bottom: 0.2em; }
+h2 { border-bottom: 1px solid #ccc; padding-bottom: 0.2em; margin-top: 1.5em; }
+table { border-collapse: collapse; font-size: 0.9em; }
+th, td { border: 1px solid #ccc; padding: 0.25em 0.6em; text-align: right; }
+th { background: #f2f2f2; }
+td:first-child, th:first-child { text-align: left; }
+.plots { display: flex; flex-wrap: wrap; gap: 1em; }
+svg text { font-size: 11px; }
+footer { margin-top: 2em; color: #777; font-size: 0.8em; }
+";
+
+/// Renders a self-contained HTML report of a processed sounding.
+///
+/// The report contains the sounding metadata, the configured processing
+/// parameters, summary statistics of every numeric column, SVG depth
+/// profiles of qt, fs, u2 and Ic, and the SBT classification log, followed
+/// by a provenance footer. Profiles and the classification log are omitted
+/// for columns not present.
+pub fn render_html(
+    data: &ConicDataFrame,
+    sounding_id: &str,
+) -> Result<String, CoreError> {
+    profile_scope!("render_html");
+
+    let depth = depth_values(data)?;
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n");
+    html.push_str("<meta charset=\"utf-8\">\n");
+    let _ = writeln!(html, "<title>CPTu report - {}</title>", escape(sounding_id));
+    let _ = writeln!(html, "<style>\n{}</style>\n</head>\n<body>", STYLE);
+    let _ = writeln!(html, "<h1>CPTu report - {}</h1>", escape(sounding_id));
+
+    // metadata
+    html.push_str("<h2>Metadata</h2>\n<table>\n");
+    for (label, value) in metadata_rows(data, sounding_id)? {
+        push_row(&mut html, "td", &[escape(&label), escape(&value)]);
+    }
+    html.push_str("</table>\n");
+
+    // processing parameters
+    html.push_str("<h2>Processing parameters</h2>\n<table>\n");
+    for (label, value) in processing_parameters() {
+        push_row(&mut html, "td", &[escape(label), escape(&value)]);
+    }
+    html.push_str("</table>\n");
+
+    // summary statistics
+    html.push_str("<h2>Summary statistics</h2>\n<table>\n");
+    push_row(&mut html, "th", &[
+        "Column".into(), "Count".into(), "Min".into(), "Max".into(), "Mean".into()
+    ]);
+    for stats in summary_stats(data) {
+        push_row(&mut html, "td", &[
+            escape(&stats.name),
+            stats.count.to_string(),
+            format!("{:.3}", stats.min),
+            format!("{:.3}", stats.max),
+            format!("{:.3}", stats.mean),
+        ]);
+    }
+    html.push_str("</table>\n");
+
+    // profile plots
+    html.push_str("<h2>Profiles</h2>\n<div class=\"plots\">\n");
+    for name in [*COL_QT, *COL_FS, *COL_U2, *COL_IC] {
+        let Some(values) = column_values(data, name)? else { continue };
+
+        let mut series = vec![(values, "#1f5fa8")];
+        if name == *COL_U2
+            && let Some(u0) = column_values(data, *COL_U0)?
+        {
+            series.push((u0, "#c0392b"));
+        }
+        let guides: &[f64] = if name == *COL_IC { &IC_BOUNDARIES } else { &[] };
+
+        html.push_str(&svg_profile(name, &depth, &series, guides));
+    }
+    html.push_str("</div>\n");
+
+    // SBT classification log
+    if data.column(*COL_IC).is_ok() {
+        html.push_str("<h2>SBT classification log</h2>\n<table>\n");
+        push_row(&mut html, "th", &[
+            "Top (m)".into(), "Bottom (m)".into(), "Zone".into(),
+            "Description".into(), "Mean Ic".into(),
+        ]);
+        for layer in sbt_layers(data)? {
+            push_row(&mut html, "td", &[
+                format!("{:.3}", layer.top),
+                format!("{:.3}", layer.bottom),
+                layer.zone.number().to_string(),
+                escape(layer.zone.description()),
+                format!("{:.2}", layer.mean_ic),
+            ]);
+        }
+        html.push_str("</table>\n");
+    }
+
+    let _ = writeln!(
+        html,
+        "<footer>Generated by {}</footer>",
+        escape(&Provenance::now().describe())
+    );
+    html.push_str("</body>\n</html>\n");
+
+    Ok(html)
+}
+
+/// Renders the HTML report of a processed sounding and writes it to a file.
+pub fn write_html(
+    data: &ConicDataFrame,
+    file_path: &str,
+    sounding_id: &str,
+) -> Result<(), CoreError> {
+    let html = render_html(data, sounding_id)?;
+    std::fs::write(file_path, html)?;
+
+    Ok(())
+}
+
+/// Renders one or more series as an SVG depth profile with depth downwards.
+///
+/// Series are broken into separate polylines at missing values. `guides`
+/// are drawn as dashed vertical reference lines.
+fn svg_profile(
+    title: &str,
+    depth: &[f64],
+    series: &[(Vec<f64>, &str)],
+    guides: &[f64],
+) -> String {
+    let finite = |values: &[f64]| -> (f64, f64) {
+        values
+            .iter()
+            .filter(|value| value.is_finite())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &value| {
+                (min.min(value), max.max(value))
+            })
+    };
+
+    let (z_min, z_max) = finite(depth);
+    let (mut x_min, mut x_max) = series
+        .iter()
+        .map(|(values, _)| finite(values))
+        .chain(guides.iter().map(|&guide| (guide, guide)))
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), (lo, hi)| {
+            (min.min(lo), max.max(hi))
+        });
+    if !x_min.is_finite() || !x_max.is_finite() {
+        (x_min, x_max) = (0.0, 1.0);
+    }
+    if x_max <= x_min {
+        x_max = x_min + 1.0;
+    }
+    let z_span = if z_max > z_min { z_max - z_min } else { 1.0 };
+
+    let width = PLOT_WIDTH + 2.0 * PLOT_MARGIN;
+    let height = PLOT_HEIGHT + 2.0 * PLOT_MARGIN;
+    let to_x = |x: f64| PLOT_MARGIN + (x - x_min) / (x_max - x_min) * PLOT_WIDTH;
+    let to_y = |z: f64| PLOT_MARGIN + (z - z_min) / z_span * PLOT_HEIGHT;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" \
+         viewBox=\"0 0 {0} {1}\">",
+        width, height
+    );
+    let _ = writeln!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" \
+         stroke=\"#999\"/>",
+        PLOT_MARGIN, PLOT_MARGIN, PLOT_WIDTH, PLOT_HEIGHT
+    );
+    let _ = writeln!(
+        svg,
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+        width / 2.0, PLOT_MARGIN / 2.0, escape(title)
+    );
+
+    // axis limits
+    let bottom_y = height - PLOT_MARGIN / 2.0;
+    let labels = [
+        (PLOT_MARGIN, bottom_y, "start", format!("{:.2}", x_min)),
+        (width - PLOT_MARGIN, bottom_y, "end", format!("{:.2}", x_max)),
+        (PLOT_MARGIN - 4.0, PLOT_MARGIN + 4.0, "end", format!("{:.1}", z_min)),
+        (PLOT_MARGIN - 4.0, height - PLOT_MARGIN, "end", format!("{:.1}", z_max)),
+    ];
+    for (x, y, anchor, label) in labels {
+        let _ = writeln!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"{}\">{}</text>",
+            x, y, anchor, label
+        );
+    }
+
+    for &guide in guides {
+        let _ = writeln!(
+            svg,
+            "<line x1=\"{0:.1}\" y1=\"{1}\" x2=\"{0:.1}\" y2=\"{2}\" \
+             stroke=\"#bbb\" stroke-dasharray=\"4 3\"/>",
+            to_x(guide), PLOT_MARGIN, PLOT_MARGIN + PLOT_HEIGHT
+        );
+    }
+
+    for (values, color) in series {
+        let mut points = String::new();
+        let flush = |points: &mut String, svg: &mut String| {
+            if !points.is_empty() {
+                let _ = writeln!(
+                    svg,
+                    "<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"1\" \
+                     points=\"{}\"/>",
+                    color, points.trim_end()
+                );
+                points.clear();
+            }
+        };
+
+        for (&z, &value) in depth.iter().zip(values) {
+            if z.is_finite() && value.is_finite() {
+                let _ = write!(points, "{:.1},{:.1} ", to_x(value), to_y(z));
+            } else {
+                flush(&mut points, &mut svg);
+            }
+        }
+        flush(&mut points, &mut svg);
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Appends a table row with the given cell tag (`td` or `th`).
+fn push_row(html: &mut String, tag: &str, cells: &[String]) {
+    html.push_str("<tr>");
+    for cell in cells {
+        let _ = write!(html, "<{0}>{1}</{0}>", tag, cell);
+    }
+    html.push_str("</tr>\n");
+}
+
+/// Escapes text for inclusion in HTML content and attributes.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
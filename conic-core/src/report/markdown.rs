@@ -0,0 +1,118 @@
+use std::fmt::Write;
+use crate::kernel::{ConicDataFrame, CoreError, Provenance};
+use crate::kernel::config::{COL_QT, COL_IC};
+use crate::math::sbt::{SbtZone, sbt_layers};
+use crate::frame::qa::reliability_weights;
+use super::{column_values, depth_values};
+
+/// Depth intervals summarized in a Markdown table.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarkdownRows {
+    /// One row per SBT layer (consecutive records of the same zone).
+    Layers,
+    /// One row per depth bin of the given size (m).
+    DepthBins(f64),
+}
+
+/// Renders a Markdown table with mean qt, mean Ic and SBT zone per interval.
+///
+/// Intended for pasting into engineering memos and wikis; a provenance line
+/// follows the table. For depth bins, the zone is that of the bin mean Ic.
+/// Records flagged unreliable by `flag_clogging` are down-weighted. Requires
+/// the columns added by `add_behavior_cols`.
+pub fn render_markdown(
+    data: &ConicDataFrame,
+    rows: MarkdownRows,
+) -> Result<String, CoreError> {
+    let depth = depth_values(data)?;
+    let missing = |name: &str| {
+        CoreError::InvalidData(format!(
+            "Cannot render Markdown summary: missing column '{}'",
+            name
+        ))
+    };
+    let qt = column_values(data, *COL_QT)?.ok_or_else(|| missing(*COL_QT))?;
+    let ic = column_values(data, *COL_IC)?.ok_or_else(|| missing(*COL_IC))?;
+    let weights = reliability_weights(data)?;
+
+    let intervals: Vec<(f64, f64)> = match rows {
+        MarkdownRows::Layers => sbt_layers(data)?
+            .into_iter()
+            .map(|layer| (layer.top, layer.bottom))
+            .collect(),
+        MarkdownRows::DepthBins(step) => {
+            if step.is_nan() || step <= 0.0 {
+                return Err(CoreError::InvalidData(format!(
+                    "Cannot bin depth: step must be positive, got {}",
+                    step
+                )));
+            }
+
+            let mut indices: Vec<i64> = depth
+                .iter()
+                .filter(|z| z.is_finite())
+                .map(|z| (z / step).floor() as i64)
+                .collect();
+            indices.dedup();
+
+            // round limits to 3 decimal places to avoid floating point noise
+            let round = |value: f64| (value * 1000.0).round() / 1000.0;
+            indices
+                .into_iter()
+                .map(|i| (round(i as f64 * step), round((i + 1) as f64 * step)))
+                .collect()
+        }
+    };
+
+    let mut markdown = String::new();
+    markdown.push_str("| Top (m) | Bottom (m) | Mean qt (MPa) | Mean Ic | SBT |\n");
+    markdown.push_str("|--:|--:|--:|--:|:--|\n");
+
+    for (top, bottom) in intervals {
+        // layers include their bottom record, bins exclude it
+        let in_interval = |z: f64| match rows {
+            MarkdownRows::Layers => z >= top && z <= bottom,
+            MarkdownRows::DepthBins(_) => z >= top && z < bottom,
+        };
+        // records flagged unreliable are down-weighted
+        let mean = |values: &[f64]| {
+            let (sum, weight_sum) = depth
+                .iter()
+                .zip(values)
+                .zip(&weights)
+                .filter(|((z, value), _)| in_interval(**z) && value.is_finite())
+                .fold((0.0, 0.0), |(sum, weight_sum), ((_, value), weight)| {
+                    (sum + value * weight, weight_sum + weight)
+                });
+            if weight_sum > 0.0 { sum / weight_sum } else { f64::NAN }
+        };
+
+        let mean_ic = mean(&ic);
+        let zone = SbtZone::from_ic(mean_ic)
+            .map(|zone| zone.to_string())
+            .unwrap_or_else(|| "-".to_string());
+
+        let _ = writeln!(
+            markdown,
+            "| {:.3} | {:.3} | {:.2} | {:.2} | {} |",
+            top, bottom, mean(&qt), mean_ic, zone.replace('|', "\\|")
+        );
+    }
+
+    let _ = writeln!(markdown, "\n_Generated by {}_", Provenance::now().describe());
+
+    Ok(markdown)
+}
+
+/// Renders the Markdown summary of a processed sounding and writes it to a
+/// file.
+pub fn write_markdown(
+    data: &ConicDataFrame,
+    file_path: &str,
+    rows: MarkdownRows,
+) -> Result<(), CoreError> {
+    let markdown = render_markdown(data, rows)?;
+    std::fs::write(file_path, markdown)?;
+
+    Ok(())
+}
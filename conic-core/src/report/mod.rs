@@ -0,0 +1,131 @@
+pub mod geojson;
+pub mod html;
+pub mod markdown;
+#[cfg(feature = "pdf")]
+pub mod pdf;
+
+use polars::prelude::*;
+use crate::kernel::{ConicDataFrame, CoreError};
+use crate::kernel::config::{
+    COL_DEPTH, A_RATIO, GAMMA_S, GAMMA_W, P_REF, ROLLING, SMOOTHING, WATER_LEVEL,
+    MAX_ITER, TOLERANCE
+};
+
+/// Ic boundaries between SBT zones drawn on the Ic profile.
+pub const IC_BOUNDARIES: [f64; 5] = [1.31, 2.05, 2.60, 2.95, 3.60];
+
+/// Summary statistics of a numeric column.
+pub(crate) struct ColumnSummary {
+    pub name: String,
+    /// Number of finite values.
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+/// Computes count, min, max and mean of the finite values of every
+/// floating point column.
+pub(crate) fn summary_stats(data: &DataFrame) -> Vec<ColumnSummary> {
+    data.columns()
+        .iter()
+        .filter_map(|column| {
+            let values = column.f64().ok()?;
+
+            let finite: Vec<f64> = values
+                .into_iter()
+                .flatten()
+                .filter(|value| value.is_finite())
+                .collect();
+
+            let (min, max, mean) = if finite.is_empty() {
+                (f64::NAN, f64::NAN, f64::NAN)
+            } else {
+                (
+                    finite.iter().copied().fold(f64::INFINITY, f64::min),
+                    finite.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+                    finite.iter().sum::<f64>() / finite.len() as f64,
+                )
+            };
+
+            Some(ColumnSummary {
+                name: column.name().to_string(),
+                count: finite.len(),
+                min,
+                max,
+                mean,
+            })
+        })
+        .collect()
+}
+
+/// Returns the configured processing parameters as label-value pairs.
+pub(crate) fn processing_parameters() -> Vec<(&'static str, String)> {
+    vec![
+        ("Net area ratio a", format!("{}", *A_RATIO)),
+        ("Soil unit weight γs (kN/m³)", format!("{}", *GAMMA_S)),
+        ("Water unit weight γw (kN/m³)", format!("{}", *GAMMA_W)),
+        ("Water level (m)", format!("{}", *WATER_LEVEL)),
+        ("Reference pressure pa (kPa)", format!("{}", *P_REF)),
+        ("Rolling window (samples)", format!("{}", *ROLLING)),
+        ("Smoothing", format!("{:?}", *SMOOTHING)),
+        ("Max. iterations", format!("{}", *MAX_ITER)),
+        ("Tolerance", format!("{}", *TOLERANCE)),
+    ]
+}
+
+/// Returns the sounding metadata shown in reports as label-value pairs.
+pub(crate) fn metadata_rows(
+    data: &ConicDataFrame,
+    sounding_id: &str,
+) -> Result<Vec<(String, String)>, CoreError> {
+    let depth = depth_values(data)?;
+    let finite_depth = depth.iter().copied().filter(|z| z.is_finite());
+    let top = finite_depth.clone().fold(f64::INFINITY, f64::min);
+    let bottom = finite_depth.fold(f64::NEG_INFINITY, f64::max);
+
+    let mut rows = vec![
+        ("Sounding".to_string(), sounding_id.to_string()),
+        ("Records".to_string(), data.height().to_string()),
+        ("Depth range (m)".to_string(), format!("{:.3} - {:.3}", top, bottom)),
+    ];
+    for conversion in &data.metadata().conversions {
+        rows.push((
+            format!("Converted {}", conversion.column),
+            format!(
+                "{} from {} to {} (x{})",
+                conversion.source, conversion.from, conversion.to, conversion.factor
+            ),
+        ));
+    }
+
+    Ok(rows)
+}
+
+/// Returns the depth values of a sounding, failing if the column is missing.
+pub fn depth_values(data: &DataFrame) -> Result<Vec<f64>, CoreError> {
+    column_values(data, *COL_DEPTH)?.ok_or_else(|| {
+        CoreError::InvalidData(format!(
+            "Cannot render report: missing column '{}'",
+            *COL_DEPTH
+        ))
+    })
+}
+
+/// Returns the values of a column as f64 (missing values as NaN), or None if
+/// the column is not present.
+pub fn column_values(
+    data: &DataFrame,
+    name: &str,
+) -> Result<Option<Vec<f64>>, CoreError> {
+    let Ok(column) = data.column(name) else { return Ok(None) };
+
+    let values = column
+        .cast(&DataType::Float64)?
+        .f64()?
+        .into_iter()
+        .map(|value| value.unwrap_or(f64::NAN))
+        .collect();
+
+    Ok(Some(values))
+}
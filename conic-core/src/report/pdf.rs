@@ -0,0 +1,345 @@
+use std::fs::File;
+use std::io::BufWriter;
+use printpdf::{
+    BuiltinFont, Color, IndirectFontRef, Line, LineDashPattern, Mm,
+    PdfDocument, PdfDocumentReference, PdfLayerReference, Point, Rgb
+};
+use crate::kernel::{ConicDataFrame, CoreError, Provenance};
+use crate::kernel::profile::profile_scope;
+use crate::kernel::config::{COL_QT, COL_FS, COL_U2, COL_U0, COL_IC};
+use crate::frame::ascii::greek_spelling;
+use crate::math::sbt::sbt_layers;
+use super::{
+    IC_BOUNDARIES, column_values, depth_values, metadata_rows,
+    processing_parameters, summary_stats
+};
+
+// A4 portrait
+const PAGE_WIDTH: f64 = 210.0;
+const PAGE_HEIGHT: f64 = 297.0;
+const MARGIN: f64 = 18.0;
+const ROW_HEIGHT: f64 = 4.6;
+
+/// Writes a PDF report of a processed sounding.
+///
+/// Contains the same sections as the HTML report (metadata, processing
+/// parameters, summary statistics, depth profiles and SBT classification
+/// log) and provenance footer, laid out on A4 pages for factual report
+/// appendices. Uses the PDF
+/// built-in fonts, so Greek letters in column names are spelled out.
+pub fn write_pdf(
+    data: &ConicDataFrame,
+    file_path: &str,
+    sounding_id: &str,
+) -> Result<(), CoreError> {
+    profile_scope!("write_pdf");
+
+    let title = format!("CPTu report - {}", sounding_id);
+    let (doc, page, layer) = PdfDocument::new(
+        title.as_str(),
+        Mm(PAGE_WIDTH as f32),
+        Mm(PAGE_HEIGHT as f32),
+        "content",
+    );
+    let layer = doc.get_page(page).get_layer(layer);
+
+    let mut writer = PageWriter {
+        font: doc.add_builtin_font(BuiltinFont::Helvetica).map_err(pdf_err)?,
+        bold: doc.add_builtin_font(BuiltinFont::HelveticaBold).map_err(pdf_err)?,
+        doc,
+        layer,
+        y: PAGE_HEIGHT - MARGIN,
+    };
+
+    writer.text(&title, 16.0, MARGIN, true);
+    writer.y -= 4.0;
+
+    // metadata
+    writer.heading("Metadata");
+    for (label, value) in metadata_rows(data, sounding_id)? {
+        writer.row(&[label, value], &[MARGIN, MARGIN + 60.0], false);
+    }
+
+    // processing parameters
+    writer.heading("Processing parameters");
+    for (label, value) in processing_parameters() {
+        writer.row(&[label.to_string(), value], &[MARGIN, MARGIN + 60.0], false);
+    }
+
+    // summary statistics
+    let stat_columns = [
+        MARGIN, MARGIN + 60.0, MARGIN + 80.0, MARGIN + 105.0, MARGIN + 130.0
+    ];
+    writer.heading("Summary statistics");
+    writer.row(
+        &["Column", "Count", "Min", "Max", "Mean"].map(String::from),
+        &stat_columns,
+        true,
+    );
+    for stats in summary_stats(data) {
+        writer.row(
+            &[
+                stats.name,
+                stats.count.to_string(),
+                format!("{:.3}", stats.min),
+                format!("{:.3}", stats.max),
+                format!("{:.3}", stats.mean),
+            ],
+            &stat_columns,
+            false,
+        );
+    }
+
+    // SBT classification log
+    if data.column(*COL_IC).is_ok() {
+        let log_columns = [
+            MARGIN, MARGIN + 22.0, MARGIN + 44.0, MARGIN + 58.0, MARGIN + 150.0
+        ];
+        let log_header = ["Top (m)", "Bottom (m)", "Zone", "Description", "Mean Ic"];
+        writer.heading("SBT classification log");
+        writer.row(
+            &log_header.map(String::from),
+            &log_columns,
+            true,
+        );
+        for layer in sbt_layers(data)? {
+            writer.row(
+                &[
+                    format!("{:.3}", layer.top),
+                    format!("{:.3}", layer.bottom),
+                    layer.zone.number().to_string(),
+                    layer.zone.description().to_string(),
+                    format!("{:.2}", layer.mean_ic),
+                ],
+                &log_columns,
+                false,
+            );
+        }
+    }
+
+    // provenance footer of the tabular pages
+    writer.y -= ROW_HEIGHT;
+    writer.ensure_space(ROW_HEIGHT);
+    let footer = format!("Generated by {}", Provenance::now().describe());
+    writer.text(&footer, 7.0, MARGIN, false);
+
+    // profile plots on a page of their own
+    writer.new_page();
+    writer.heading("Profiles");
+    let depth = depth_values(data)?;
+    let panels: Vec<(&str, Vec<Vec<f64>>)> = [*COL_QT, *COL_FS, *COL_U2, *COL_IC]
+        .into_iter()
+        .filter_map(|name| {
+            let values = column_values(data, name).ok()??;
+            let mut series = vec![values];
+            if name == *COL_U2
+                && let Ok(Some(u0)) = column_values(data, *COL_U0)
+            {
+                series.push(u0);
+            }
+            Some((name, series))
+        })
+        .collect();
+
+    let gap = 6.0;
+    let panel_count = panels.len().max(1) as f64;
+    let panel_width =
+        (PAGE_WIDTH - 2.0 * MARGIN - gap * (panel_count - 1.0)) / panel_count;
+    let top = writer.y - 8.0;
+    let bottom = MARGIN + 6.0;
+    for (i, (name, series)) in panels.iter().enumerate() {
+        let left = MARGIN + i as f64 * (panel_width + gap);
+        let guides: &[f64] = if *name == *COL_IC { &IC_BOUNDARIES } else { &[] };
+        let frame = (left, top, panel_width, top - bottom);
+        writer.profile(name, &depth, series, guides, frame);
+    }
+
+    let file = File::create(file_path)?;
+    writer.doc.save(&mut BufWriter::new(file)).map_err(pdf_err)?;
+
+    Ok(())
+}
+
+/// Writes text top-down across pages, breaking pages when full.
+struct PageWriter {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    font: IndirectFontRef,
+    bold: IndirectFontRef,
+    /// Baseline of the next line (mm from the page bottom).
+    y: f64,
+}
+
+impl PageWriter {
+    fn new_page(&mut self) {
+        let (page, layer) = self.doc.add_page(
+            Mm(PAGE_WIDTH as f32),
+            Mm(PAGE_HEIGHT as f32),
+            "content",
+        );
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    fn ensure_space(&mut self, height: f64) {
+        if self.y - height < MARGIN {
+            self.new_page();
+        }
+    }
+
+    fn text(&mut self, text: &str, size: f64, x: f64, bold: bool) {
+        let font = if bold { &self.bold } else { &self.font };
+        self.layer.use_text(
+            pdf_text(text),
+            size as f32,
+            Mm(x as f32),
+            Mm(self.y as f32),
+            font,
+        );
+        self.y -= size * 0.3528 + 2.0;
+    }
+
+    fn heading(&mut self, text: &str) {
+        self.ensure_space(4.0 * ROW_HEIGHT);
+        self.y -= 3.0;
+        self.text(text, 12.0, MARGIN, true);
+    }
+
+    fn row(&mut self, cells: &[String], x_positions: &[f64], bold: bool) {
+        self.ensure_space(ROW_HEIGHT);
+        let font = if bold { &self.bold } else { &self.font };
+        for (cell, &x) in cells.iter().zip(x_positions) {
+            self.layer.use_text(
+                pdf_text(cell),
+                8.5,
+                Mm(x as f32),
+                Mm(self.y as f32),
+                font,
+            );
+        }
+        self.y -= ROW_HEIGHT;
+    }
+
+    /// Draws series against depth (downwards) inside the given box
+    /// (left, top, width, height), breaking lines at missing values.
+    fn profile(
+        &self,
+        title: &str,
+        depth: &[f64],
+        series: &[Vec<f64>],
+        guides: &[f64],
+        (left, top, width, height): (f64, f64, f64, f64),
+    ) {
+        let range = |values: &[f64]| {
+            values
+                .iter()
+                .filter(|value| value.is_finite())
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &value| {
+                    (min.min(value), max.max(value))
+                })
+        };
+
+        let (z_min, z_max) = range(depth);
+        let (mut x_min, mut x_max) = series
+            .iter()
+            .map(|values| range(values))
+            .chain(guides.iter().map(|&guide| (guide, guide)))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), (lo, hi)| {
+                (min.min(lo), max.max(hi))
+            });
+        if !x_min.is_finite() || !x_max.is_finite() {
+            (x_min, x_max) = (0.0, 1.0);
+        }
+        if x_max <= x_min {
+            x_max = x_min + 1.0;
+        }
+        let z_span = if z_max > z_min { z_max - z_min } else { 1.0 };
+
+        let to_point = |x: f64, z: f64| {
+            let x = left + (x - x_min) / (x_max - x_min) * width;
+            let y = top - (z - z_min) / z_span * height;
+            (Point::new(Mm(x as f32), Mm(y as f32)), false)
+        };
+        let label = |text: &str, x: f64, y: f64| {
+            self.layer.use_text(
+                pdf_text(text), 7.0, Mm(x as f32), Mm(y as f32), &self.font
+            );
+        };
+
+        // frame
+        self.layer.set_outline_color(gray(0.6));
+        self.layer.set_outline_thickness(0.5);
+        let corner = |x: f64, y: f64| (Point::new(Mm(x as f32), Mm(y as f32)), false);
+        self.layer.add_line(Line {
+            points: vec![
+                corner(left, top),
+                corner(left + width, top),
+                corner(left + width, top - height),
+                corner(left, top - height),
+            ],
+            is_closed: true,
+        });
+
+        label(title, left, top + 3.0);
+        label(&format!("{:.2}", x_min), left, top - height - 4.0);
+        label(&format!("{:.2}", x_max), left + width - 8.0, top - height - 4.0);
+        label(&format!("{:.1}", z_min), left + 1.0, top - 3.0);
+        label(&format!("{:.1}", z_max), left + 1.0, top - height + 1.5);
+
+        // zone boundaries
+        self.layer.set_line_dash_pattern(LineDashPattern {
+            dash_1: Some(2),
+            gap_1: Some(2),
+            ..Default::default()
+        });
+        for &guide in guides {
+            self.layer.add_line(Line {
+                points: vec![to_point(guide, z_min), to_point(guide, z_max)],
+                is_closed: false,
+            });
+        }
+        self.layer.set_line_dash_pattern(LineDashPattern::default());
+
+        let colors = [
+            Rgb::new(0.12, 0.37, 0.66, None),
+            Rgb::new(0.75, 0.22, 0.17, None),
+        ];
+        self.layer.set_outline_thickness(0.3);
+        for (values, color) in series.iter().zip(colors.iter().cycle()) {
+            self.layer.set_outline_color(Color::Rgb(color.clone()));
+
+            let mut points = Vec::new();
+            for (&z, &value) in depth.iter().zip(values) {
+                if z.is_finite() && value.is_finite() {
+                    points.push(to_point(value, z));
+                } else {
+                    let segment = std::mem::take(&mut points);
+                    if segment.len() > 1 {
+                        self.layer.add_line(Line { points: segment, is_closed: false });
+                    }
+                }
+            }
+            if points.len() > 1 {
+                self.layer.add_line(Line { points, is_closed: false });
+            }
+        }
+    }
+}
+
+fn gray(level: f32) -> Color {
+    Color::Rgb(Rgb::new(level, level, level, None))
+}
+
+/// Replaces characters the built-in fonts cannot encode.
+fn pdf_text(text: &str) -> String {
+    text.chars()
+        .map(|ch| match greek_spelling(ch) {
+            Some(spelled) => spelled.to_string(),
+            None => ch.to_string(),
+        })
+        .collect()
+}
+
+fn pdf_err(err: printpdf::Error) -> CoreError {
+    CoreError::InvalidData(format!("Failed to write PDF report: {}", err))
+}
@@ -0,0 +1,368 @@
+use conic_core::frame::project::ProjectLayer;
+use conic_core::kernel::config::COL_IC;
+use conic_core::report::IC_BOUNDARIES;
+use eframe::egui;
+use egui_plot::{
+    HLine, Legend, Line, LineStyle, Plot, PlotPoints, PlotResponse, PlotTransform,
+    VLine,
+};
+use crate::sounding::{
+    Profile, Sounding, Source, is_project, load, move_boundary, save_layers,
+};
+
+/// Group linking the depth axes and cursors of the profile plots.
+const DEPTH_AXIS: &str = "depth";
+
+/// Table of a project database read by default, as written by
+/// `conic process --sqlite`.
+const DEFAULT_TABLE: &str = "soundings";
+
+/// Largest distance (points) from the pointer at which a layer boundary is
+/// picked for dragging.
+const PICK_DISTANCE: f32 = 5.0;
+
+/// Viewer window: a path bar, the profiles of the open sounding and its
+/// editable layer table.
+pub struct ViewerApp {
+    /// Path typed in the toolbar.
+    path: String,
+    /// Table of the project database holding the soundings.
+    table: String,
+    /// Sounding IDs of the open project database.
+    sounding_ids: Vec<String>,
+    sounding: Option<Sounding>,
+    /// Layer whose top boundary was under the pointer in the last frame.
+    hovered: Option<usize>,
+    /// Layer whose top boundary is being dragged.
+    dragging: Option<usize>,
+    /// Whether the layers were edited since they were loaded or saved.
+    modified: bool,
+    /// Outcome of the last action, shown in the toolbar.
+    status: String,
+}
+
+impl ViewerApp {
+    /// Creates the viewer, opening `path` right away if given.
+    pub fn new(path: Option<String>) -> Self {
+        let mut app = Self {
+            path: path.clone().unwrap_or_default(),
+            table: DEFAULT_TABLE.to_string(),
+            sounding_ids: Vec::new(),
+            sounding: None,
+            hovered: None,
+            dragging: None,
+            modified: false,
+            status: String::new(),
+        };
+        if path.is_some() {
+            app.open();
+        }
+        app
+    }
+
+    /// Opens the typed path: the first sounding of a project database, or a
+    /// sounding file.
+    fn open(&mut self) {
+        if !is_project(&self.path) {
+            self.sounding_ids.clear();
+            self.load(Source::File(self.path.clone()));
+            return;
+        }
+
+        match conic_core::frame::project::sounding_ids(&self.path, &self.table) {
+            Ok(ids) => {
+                self.sounding_ids = ids;
+                match self.sounding_ids.first().cloned() {
+                    Some(sounding_id) => self.load_project(sounding_id),
+                    None => self.status = format!("No soundings in '{}'", self.table),
+                }
+            }
+            Err(err) => self.status = format!("Error: {}", err),
+        }
+    }
+
+    fn load_project(&mut self, sounding_id: String) {
+        self.load(Source::Project {
+            db_path: self.path.clone(),
+            table: self.table.clone(),
+            sounding_id,
+        });
+    }
+
+    /// Loads a sounding, keeping the current one on failure.
+    fn load(&mut self, source: Source) {
+        match load(&source) {
+            Ok(sounding) => {
+                self.status = match sounding.warnings.as_slice() {
+                    [] => format!(
+                        "{} records, {} layers",
+                        sounding.depth.len(),
+                        sounding.layers.len()
+                    ),
+                    warnings => format!("Warning: {}", warnings.join("; ")),
+                };
+                self.sounding = Some(sounding);
+                self.dragging = None;
+                self.modified = false;
+            }
+            Err(err) => self.status = format!("Error: {}", err),
+        }
+    }
+
+    fn save(&mut self) {
+        let Some(sounding) = &self.sounding else { return };
+
+        match save_layers(sounding) {
+            Ok(count) => {
+                self.status = format!("Saved {} layers of {}", count, sounding.id);
+                self.modified = false;
+            }
+            Err(err) => self.status = format!("Error: {}", err),
+        }
+    }
+
+    fn toolbar(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Sounding or project");
+            let response = ui.text_edit_singleline(&mut self.path);
+            let entered = response.lost_focus()
+                && ui.input(|input| input.key_pressed(egui::Key::Enter));
+            if is_project(&self.path) {
+                ui.label("Table");
+                ui.add(egui::TextEdit::singleline(&mut self.table).desired_width(80.0));
+            }
+            if ui.button("Open").clicked() || entered {
+                self.open();
+            }
+
+            if !self.sounding_ids.is_empty() {
+                let current = self
+                    .sounding
+                    .as_ref()
+                    .map(|sounding| sounding.id.clone())
+                    .unwrap_or_default();
+                let mut selected = current.clone();
+                egui::ComboBox::from_id_salt("sounding_id")
+                    .selected_text(&selected)
+                    .show_ui(ui, |ui| {
+                        for id in &self.sounding_ids {
+                            ui.selectable_value(&mut selected, id.clone(), id);
+                        }
+                    });
+                if selected != current {
+                    self.load_project(selected);
+                }
+            }
+
+            ui.label(&self.status);
+        });
+    }
+
+    fn layer_panel(&mut self, ui: &mut egui::Ui) {
+        let Some(sounding) = &mut self.sounding else { return };
+        let is_project = matches!(sounding.source, Source::Project { .. });
+
+        ui.heading(format!("{} layers", sounding.id));
+        let mut save = false;
+        ui.horizontal(|ui| {
+            let label = if self.modified { "Save layers *" } else { "Save layers" };
+            save = ui
+                .add_enabled(is_project, egui::Button::new(label))
+                .on_disabled_hover_text("Open the sounding from a project database")
+                .clicked();
+            if ui.button("Reset to SBT").clicked() {
+                sounding.layers = sounding.detected.clone();
+                self.modified = true;
+            }
+        });
+        ui.label("Drag a boundary in any profile to move it.");
+        ui.separator();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            if layer_table(ui, &mut sounding.layers) {
+                self.modified = true;
+            }
+        });
+
+        if save {
+            self.save();
+        }
+    }
+
+    /// Applies a drag on a plot to the layer boundaries and returns the
+    /// boundary under the pointer.
+    fn drag_boundary(&mut self, plot: &PlotResponse<()>) -> Option<usize> {
+        let sounding = self.sounding.as_mut()?;
+        let response = &plot.response;
+
+        let hovered = response.hover_pos().and_then(|pointer| {
+            boundary_at(&sounding.layers, &plot.transform, pointer)
+        });
+        if response.drag_started() {
+            self.dragging = hovered;
+        }
+        if let (Some(index), Some(pointer)) =
+            (self.dragging, response.interact_pointer_pos())
+            && response.dragged()
+        {
+            let depth = plot.transform.value_from_position(pointer).y;
+            move_boundary(&mut sounding.layers, index, depth);
+            self.modified = true;
+        }
+        if response.drag_stopped() {
+            self.dragging = None;
+        }
+
+        hovered
+    }
+}
+
+impl eframe::App for ViewerApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        egui::TopBottomPanel::top("toolbar").show(ctx, |ui| self.toolbar(ui));
+
+        if self.sounding.is_none() {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.centered_and_justified(|ui| {
+                    ui.label("Open a sounding file or a project database")
+                });
+            });
+            return;
+        }
+
+        egui::SidePanel::right("layers").show(ctx, |ui| self.layer_panel(ui));
+
+        // panning would fight the drag of a boundary picked in the last frame
+        let pan = self.hovered.is_none() && self.dragging.is_none();
+        let highlighted = self.dragging.or(self.hovered);
+        let plots = egui::CentralPanel::default()
+            .show(ctx, |ui| {
+                let Some(sounding) = &self.sounding else { return Vec::new() };
+                let panels = sounding.profiles.len().max(1) as f32;
+                let spacing = ui.spacing().item_spacing.x;
+                let width = (ui.available_width() - spacing * (panels - 1.0)) / panels;
+
+                ui.horizontal(|ui| {
+                    sounding
+                        .profiles
+                        .iter()
+                        .map(|profile| {
+                            profile_plot(ui, sounding, profile, width, pan, highlighted)
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .inner
+            })
+            .inner;
+
+        let mut hovered = None;
+        for plot in &plots {
+            hovered = self.drag_boundary(plot).or(hovered);
+        }
+        self.hovered = hovered;
+
+        if self.hovered.is_some() || self.dragging.is_some() {
+            ctx.set_cursor_icon(egui::CursorIcon::ResizeVertical);
+        }
+    }
+}
+
+/// Lists the depth range and editable label of every layer, returning true
+/// if a label was edited.
+fn layer_table(ui: &mut egui::Ui, layers: &mut [ProjectLayer]) -> bool {
+    let mut edited = false;
+
+    egui::Grid::new("layer_table").striped(true).show(ui, |ui| {
+        ui.strong("Top (m)");
+        ui.strong("Bottom (m)");
+        ui.strong("Label");
+        ui.end_row();
+
+        for layer in layers.iter_mut() {
+            ui.label(format!("{:.2}", layer.top));
+            ui.label(format!("{:.2}", layer.bottom));
+            edited |= ui.text_edit_singleline(&mut layer.label).changed();
+            ui.end_row();
+        }
+    });
+
+    edited
+}
+
+/// Returns the layer whose top boundary is closest to `pointer`, within
+/// `PICK_DISTANCE`. The top of the first layer is not a boundary.
+fn boundary_at(
+    layers: &[ProjectLayer],
+    transform: &PlotTransform,
+    pointer: egui::Pos2,
+) -> Option<usize> {
+    (1..layers.len())
+        .map(|index| {
+            let y = transform.position_from_point_y(layers[index].top);
+            (index, (y - pointer.y).abs())
+        })
+        .filter(|(_, distance)| *distance <= PICK_DISTANCE)
+        .min_by(|(_, first), (_, second)| first.total_cmp(second))
+        .map(|(index, _)| index)
+}
+
+/// Plots a profile against depth, increasing downwards, with the layer
+/// boundaries as horizontal guides and the zone limits on the Ic profile.
+fn profile_plot(
+    ui: &mut egui::Ui,
+    sounding: &Sounding,
+    profile: &Profile,
+    width: f32,
+    pan: bool,
+    highlighted: Option<usize>,
+) -> PlotResponse<()> {
+    let guide_color = ui.visuals().weak_text_color();
+    let active_color = ui.visuals().selection.stroke.color;
+
+    let mut plot = Plot::new(&profile.label)
+        .width(width)
+        .height(ui.available_height())
+        .invert_y(true)
+        .allow_drag(pan)
+        .link_axis(DEPTH_AXIS, [false, true])
+        .link_cursor(DEPTH_AXIS, [false, true])
+        .x_axis_label(profile.label.as_str())
+        .y_axis_label("Depth (m)");
+    if profile.series.len() > 1 {
+        plot = plot.legend(Legend::default());
+    }
+
+    plot.show(ui, |plot_ui| {
+        for (name, values) in &profile.series {
+            let points: PlotPoints = sounding
+                .depth
+                .iter()
+                .zip(values)
+                .filter(|(depth, value)| depth.is_finite() && value.is_finite())
+                .map(|(depth, value)| [*value, *depth])
+                .collect();
+            plot_ui.line(Line::new(name.as_str(), points));
+        }
+
+        // the first layer starts at the top of the sounding
+        for (index, layer) in sounding.layers.iter().enumerate().skip(1) {
+            let boundary = HLine::new("Layer boundary", layer.top);
+            let boundary = if highlighted == Some(index) {
+                boundary.color(active_color).width(2.5)
+            } else {
+                boundary.color(guide_color).style(LineStyle::dashed_loose())
+            };
+            plot_ui.hline(boundary);
+        }
+
+        if profile.label == *COL_IC {
+            for boundary in IC_BOUNDARIES {
+                plot_ui.vline(
+                    VLine::new("SBT zone", boundary)
+                        .color(guide_color)
+                        .style(LineStyle::dotted_dense()),
+                );
+            }
+        }
+    })
+}
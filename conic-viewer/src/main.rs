@@ -0,0 +1,21 @@
+mod app;
+mod sounding;
+
+/// Opens the viewer window, loading the sounding file or project database
+/// given as first argument.
+fn main() -> eframe::Result {
+    let path = std::env::args().nth(1);
+
+    let options = eframe::NativeOptions {
+        viewport: eframe::egui::ViewportBuilder::default()
+            .with_title("conic-viewer")
+            .with_inner_size([1200.0, 800.0]),
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "conic-viewer",
+        options,
+        Box::new(|_creation_context| Ok(Box::new(app::ViewerApp::new(path)))),
+    )
+}
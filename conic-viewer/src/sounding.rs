@@ -0,0 +1,208 @@
+use conic_core::frame::project::{self, ProjectLayer};
+use conic_core::kernel::config::{COL_FS, COL_IC, COL_QT, COL_U0, COL_U2};
+use conic_core::math::sbt::sbt_layers;
+use conic_core::prelude::*;
+use conic_core::report::{column_values, depth_values};
+
+/// File extensions of project databases written by `conic process --sqlite`.
+const PROJECT_EXTENSIONS: [&str; 3] = ["db", "sqlite", "sqlite3"];
+
+/// Where a sounding was loaded from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    /// Sounding file, processed like `conic process`.
+    File(String),
+    /// Processed sounding stored in a project database.
+    Project { db_path: String, table: String, sounding_id: String },
+}
+
+/// Profile plotted in its own panel, sharing the depth axis of the others.
+pub struct Profile {
+    /// Axis label, the configured column name.
+    pub label: String,
+    /// Plotted series as name-values pairs.
+    pub series: Vec<(String, Vec<f64>)>,
+}
+
+/// Processed sounding reduced to the series and layers shown by the viewer.
+pub struct Sounding {
+    pub source: Source,
+    /// Sounding ID in the project, or derived from the file name.
+    pub id: String,
+    /// Record depths (m).
+    pub depth: Vec<f64>,
+    /// The qt, fs, u2 and Ic profiles, in display order.
+    pub profiles: Vec<Profile>,
+    /// Layers shown and edited in the viewer.
+    pub layers: Vec<ProjectLayer>,
+    /// SBT layers detected from Ic, restored by a reset.
+    pub detected: Vec<ProjectLayer>,
+    /// Unit warnings raised while reading the file.
+    pub warnings: Vec<String>,
+}
+
+/// Returns true if `path` names a project database rather than a sounding
+/// file.
+pub fn is_project(path: &str) -> bool {
+    std::path::Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            PROJECT_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+        })
+}
+
+/// Loads a sounding from its source.
+///
+/// Files are cleaned with `clean_standard` and get the standard derived
+/// columns, like in `conic process`. Project soundings are already
+/// processed; their stored layers are shown if any, otherwise the detected
+/// SBT layers.
+pub fn load(source: &Source) -> Result<Sounding, CoreError> {
+    match source {
+        Source::File(path) => {
+            let data = read_auto(path)?;
+            let warnings = data
+                .check_units()?
+                .iter()
+                .map(|warning| warning.to_string())
+                .collect();
+
+            let data = data
+                .clean_standard()?
+                .add_derived_cols(Some(&OutputSelection::Standard))?;
+            let detected = detected_layers(&data)?;
+            let id = sounding_id(path);
+
+            sounding(source, &id, &data, &detected, detected.clone())
+                .map(|sounding| Sounding { warnings, ..sounding })
+        }
+        Source::Project { db_path, table, sounding_id } => {
+            let data = project::read_sounding(db_path, table, sounding_id)?;
+            let detected = detected_layers(&data)?;
+            let stored = project::read_layers(db_path, sounding_id)?;
+            let layers = if stored.is_empty() { detected.clone() } else { stored };
+
+            sounding(source, sounding_id, &data, &detected, layers)
+        }
+    }
+}
+
+/// Stores the layers of a project sounding in the project database.
+///
+/// # Errors
+///
+/// Returns `CoreError::InvalidData` for soundings loaded from a file, which
+/// have no project database to write to.
+pub fn save_layers(sounding: &Sounding) -> Result<usize, CoreError> {
+    match &sounding.source {
+        Source::Project { db_path, sounding_id, .. } => {
+            project::write_layers(db_path, sounding_id, &sounding.layers)
+        }
+        Source::File(path) => Err(CoreError::InvalidData(format!(
+            "Cannot save the layers of '{}': open it from a project database",
+            path
+        ))),
+    }
+}
+
+/// Moves the boundary between layer `index - 1` and layer `index` to
+/// `depth`, kept within the two layers.
+pub fn move_boundary(layers: &mut [ProjectLayer], index: usize, depth: f64) {
+    let (upper, lower) = (layers[index - 1].top, layers[index].bottom);
+    if !depth.is_finite() || upper > lower {
+        return;
+    }
+
+    let depth = depth.clamp(upper, lower);
+    layers[index - 1].bottom = depth;
+    layers[index].top = depth;
+}
+
+fn sounding(
+    source: &Source,
+    id: &str,
+    data: &ConicDataFrame,
+    detected: &[ProjectLayer],
+    layers: Vec<ProjectLayer>,
+) -> Result<Sounding, CoreError> {
+    let mut profiles = Vec::with_capacity(4);
+    for names in [
+        vec![*COL_QT],
+        vec![*COL_FS],
+        vec![*COL_U2, *COL_U0],
+        vec![*COL_IC],
+    ] {
+        let mut series = Vec::with_capacity(names.len());
+        for name in &names {
+            if let Some(values) = column_values(data, name)? {
+                series.push((name.to_string(), values));
+            }
+        }
+        if !series.is_empty() {
+            profiles.push(Profile { label: names[0].to_string(), series });
+        }
+    }
+
+    Ok(Sounding {
+        source: source.clone(),
+        id: id.to_string(),
+        depth: depth_values(data)?,
+        profiles,
+        layers,
+        detected: detected.to_vec(),
+        warnings: Vec::new(),
+    })
+}
+
+// soundings processed without Ic have no SBT layers to start from
+fn detected_layers(data: &ConicDataFrame) -> Result<Vec<ProjectLayer>, CoreError> {
+    if data.column(*COL_IC).is_err() {
+        return Ok(Vec::new());
+    }
+
+    Ok(sbt_layers(data)?.iter().map(ProjectLayer::from).collect())
+}
+
+/// Derives a sounding identifier from the file name.
+fn sounding_id(path: &str) -> String {
+    let file_name = std::path::Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string());
+
+    match file_name.split_once('.') {
+        Some((stem, _)) => stem.to_string(),
+        None => file_name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(top: f64, bottom: f64) -> ProjectLayer {
+        ProjectLayer { top, bottom, label: String::new() }
+    }
+
+    #[test]
+    fn boundaries_move_within_their_layers() {
+        let mut layers = vec![layer(0.0, 1.0), layer(1.02, 3.0), layer(3.02, 5.0)];
+
+        move_boundary(&mut layers, 1, 1.5);
+        assert_eq!((layers[0].bottom, layers[1].top), (1.5, 1.5));
+
+        move_boundary(&mut layers, 2, 9.0);
+        assert_eq!((layers[1].bottom, layers[2].top), (5.0, 5.0));
+
+        move_boundary(&mut layers, 1, -1.0);
+        assert_eq!((layers[0].bottom, layers[1].top), (0.0, 0.0));
+    }
+
+    #[test]
+    fn project_databases_are_told_apart_from_sounding_files() {
+        assert!(is_project("site/project.db"));
+        assert!(is_project("site/project.SQLITE"));
+        assert!(!is_project("site/CPT-01.csv"));
+    }
+}